@@ -11,6 +11,8 @@ use halo2_proofs::{
 use crate::utilities::UtilitiesInstructions;
 
 pub mod chip;
+#[cfg(test)]
+pub(crate) mod test_curve;
 
 /// The set of circuit instructions required to use the ECC gadgets.
 pub trait EccInstructions<C: CurveAffine>:
@@ -137,6 +139,19 @@ pub trait EccInstructions<C: CurveAffine>:
         base: &Self::NonIdentityPoint,
     ) -> Result<(Self::Point, Self::ScalarVar), Error>;
 
+    /// Performs variable-base multi-scalar multiplication, returning
+    /// `[scalar_1] base_1 + ... + [scalar_n] base_n` for the given `terms`.
+    ///
+    /// The default implementation multiplies each base separately using
+    /// [`EccInstructions::mul`], and sums the results using complete addition.
+    fn msm(
+        &self,
+        layouter: &mut impl Layouter<C::Base>,
+        terms: &[(Self::ScalarVar, Self::NonIdentityPoint)],
+    ) -> Result<Self::Point, Error> {
+        msm_by_mul(self, layouter, terms)
+    }
+
     /// Performs fixed-base scalar multiplication using a full-width scalar, returning `[scalar] base`.
     fn mul_fixed(
         &self,
@@ -165,6 +180,27 @@ pub trait EccInstructions<C: CurveAffine>:
     ) -> Result<Self::Point, Error>;
 }
 
+/// Computes a multi-scalar multiplication as a sum of separate scalar multiplications.
+fn msm_by_mul<C: CurveAffine, EccChip: EccInstructions<C>>(
+    chip: &EccChip,
+    layouter: &mut impl Layouter<C::Base>,
+    terms: &[(EccChip::ScalarVar, EccChip::NonIdentityPoint)],
+) -> Result<EccChip::Point, Error> {
+    let mut acc = None;
+    for (scalar, base) in terms {
+        let (term, _) = chip.mul(layouter, scalar, base)?;
+        acc = Some(match acc {
+            Some(acc) => chip.add(layouter, &acc, &term)?,
+            None => term,
+        });
+    }
+
+    match acc {
+        Some(acc) => Ok(acc),
+        None => chip.witness_point_from_constant(layouter, C::identity()),
+    }
+}
+
 /// Instructions that can be implemented for a curve whose base field fits into
 /// its scalar field.
 pub trait BaseFitsInScalarInstructions<C: CurveAffine>: EccInstructions<C> {
@@ -390,6 +426,9 @@ impl<C: CurveAffine, EccChip: EccInstructions<C> + Clone + Debug + Eq>
     }
 }
 
+/// A term `(scalar, base)` of a multi-scalar multiplication, for [`Point::msm`].
+pub type MsmTerm<C, EccChip> = (ScalarVar<C, EccChip>, NonIdentityPoint<C, EccChip>);
+
 /// A point on a specific elliptic curve.
 #[derive(Copy, Clone, Debug)]
 pub struct Point<C: CurveAffine, EccChip: EccInstructions<C> + Clone + Debug + Eq> {
@@ -462,6 +501,28 @@ impl<C: CurveAffine, EccChip: EccInstructions<C> + Clone + Debug + Eq> Point<C,
             })
     }
 
+    /// Returns `[scalar_1] base_1 + ... + [scalar_n] base_n` for the given `terms`.
+    ///
+    /// Depending on the `EccChip` implementation, this may share work between the
+    /// terms, rather than multiplying each base separately.
+    pub fn msm(
+        chip: EccChip,
+        mut layouter: impl Layouter<C::Base>,
+        terms: &[MsmTerm<C, EccChip>],
+    ) -> Result<Self, Error> {
+        let terms = terms
+            .iter()
+            .map(|(scalar, base)| {
+                assert_eq!(chip, scalar.chip);
+                assert_eq!(chip, base.chip);
+                (scalar.inner.clone(), base.inner.clone())
+            })
+            .collect::<Vec<_>>();
+
+        let point = chip.msm(&mut layouter, &terms);
+        point.map(|inner| Point { chip, inner })
+    }
+
     /// Returns `[sign] self`.
     /// This constrains `sign` to be in {-1, 1}.
     pub fn mul_sign(
@@ -621,7 +682,7 @@ impl<C: CurveAffine, EccChip: EccInstructions<C>> FixedPointShort<C, EccChip> {
 
 #[cfg(test)]
 pub(crate) mod tests {
-    use ff::PrimeField;
+    use ff::{Field, PrimeField};
     use group::{prime::PrimeCurveAffine, Curve, Group};
     use std::marker::PhantomData;
 
@@ -631,19 +692,25 @@ pub(crate) mod tests {
         plonk::{Circuit, ConstraintSystem, Error},
     };
     use lazy_static::lazy_static;
-    use pasta_curves::pallas;
+    use pasta_curves::{pallas, vesta};
 
     use super::{
         chip::{
-            find_zs_and_us, BaseFieldElem, EccChip, EccConfig, FixedPoint, FullScalar, ShortScalar,
-            H, NUM_WINDOWS, NUM_WINDOWS_SHORT,
+            base_to_scalar, find_zs_and_us, num_windows, BaseFieldElem, EccChip, EccConfig,
+            EccCurve, FixedPoint, FullScalar, ShortScalar, FIXED_BASE_WINDOW_SIZE, H, NUM_WINDOWS,
+            NUM_WINDOWS_SHORT,
         },
-        FixedPoints,
+        test_curve, FixedPoints,
     };
     use crate::{
+        sinsemilla::primitives as sinsemilla,
         test_circuits::test_utils::test_against_stored_circuit,
-        utilities::lookup_range_check::{
-            PallasLookupRangeCheck, PallasLookupRangeCheck4_5BConfig, PallasLookupRangeCheckConfig,
+        utilities::{
+            lookup_range_check::{
+                LookupRangeCheck, LookupRangeCheckConfig, PallasLookupRangeCheck,
+                PallasLookupRangeCheck4_5BConfig, PallasLookupRangeCheckConfig,
+            },
+            UtilitiesInstructions,
         },
     };
 
@@ -966,6 +1033,512 @@ pub(crate) mod tests {
         }
     }
 
+    /// A curve on which [`CurveEccCircuit`] exercises the ECC chip, using the curve's
+    /// generator as the fixed base.
+    pub(crate) trait TestCurve: EccCurve {
+        fn zs_and_us() -> &'static [(u64, [Self::Base; H])];
+        fn zs_and_us_short() -> &'static [(u64, [Self::Base; H])];
+    }
+
+    lazy_static! {
+        static ref VESTA_ZS_AND_US: Vec<(u64, [vesta::Base; H])> =
+            find_zs_and_us(vesta::Affine::generator(), NUM_WINDOWS).unwrap();
+        static ref VESTA_ZS_AND_US_SHORT: Vec<(u64, [vesta::Base; H])> =
+            find_zs_and_us(vesta::Affine::generator(), NUM_WINDOWS_SHORT).unwrap();
+        static ref TEST_CURVE_ZS_AND_US: Vec<(u64, [test_curve::Base; H])> = find_zs_and_us(
+            test_curve::Affine::generator(),
+            num_windows::<test_curve::Affine>(FIXED_BASE_WINDOW_SIZE)
+        )
+        .unwrap();
+        static ref TEST_CURVE_ZS_AND_US_SHORT: Vec<(u64, [test_curve::Base; H])> =
+            find_zs_and_us(test_curve::Affine::generator(), NUM_WINDOWS_SHORT).unwrap();
+    }
+
+    impl TestCurve for vesta::Affine {
+        fn zs_and_us() -> &'static [(u64, [vesta::Base; H])] {
+            &VESTA_ZS_AND_US
+        }
+
+        fn zs_and_us_short() -> &'static [(u64, [vesta::Base; H])] {
+            &VESTA_ZS_AND_US_SHORT
+        }
+    }
+
+    impl TestCurve for test_curve::Affine {
+        fn zs_and_us() -> &'static [(u64, [test_curve::Base; H])] {
+            &TEST_CURVE_ZS_AND_US
+        }
+
+        fn zs_and_us_short() -> &'static [(u64, [test_curve::Base; H])] {
+            &TEST_CURVE_ZS_AND_US_SHORT
+        }
+    }
+
+    #[derive(Debug, Default, Eq, PartialEq, Clone)]
+    pub(crate) struct GeneratorFixedBases<C>(PhantomData<C>);
+    #[derive(Debug, Default, Eq, PartialEq, Clone)]
+    pub(crate) struct GeneratorFullWidth<C>(PhantomData<C>);
+    #[derive(Debug, Default, Eq, PartialEq, Clone)]
+    pub(crate) struct GeneratorBaseField<C>(PhantomData<C>);
+    #[derive(Debug, Default, Eq, PartialEq, Clone)]
+    pub(crate) struct GeneratorShort<C>(PhantomData<C>);
+
+    pub(crate) type VestaFixedBases = GeneratorFixedBases<vesta::Affine>;
+    pub(crate) type VestaFullWidth = GeneratorFullWidth<vesta::Affine>;
+
+    fn us<C: TestCurve>(
+        zs_and_us: &[(u64, [C::Base; H])],
+    ) -> Vec<[<C::Base as PrimeField>::Repr; H]> {
+        zs_and_us
+            .iter()
+            .map(|(_, us)| us.map(|u| u.to_repr()))
+            .collect()
+    }
+
+    impl<C: TestCurve> FixedPoint<C> for GeneratorFullWidth<C> {
+        type FixedScalarKind = FullScalar;
+
+        fn generator(&self) -> C {
+            C::generator()
+        }
+
+        fn u(&self) -> Vec<[<C::Base as PrimeField>::Repr; H]> {
+            us::<C>(C::zs_and_us())
+        }
+
+        fn z(&self) -> Vec<u64> {
+            C::zs_and_us().iter().map(|(z, _)| *z).collect()
+        }
+    }
+
+    impl<C: TestCurve> FixedPoint<C> for GeneratorBaseField<C> {
+        type FixedScalarKind = BaseFieldElem;
+
+        fn generator(&self) -> C {
+            C::generator()
+        }
+
+        fn u(&self) -> Vec<[<C::Base as PrimeField>::Repr; H]> {
+            us::<C>(C::zs_and_us())
+        }
+
+        fn z(&self) -> Vec<u64> {
+            C::zs_and_us().iter().map(|(z, _)| *z).collect()
+        }
+    }
+
+    impl<C: TestCurve> FixedPoint<C> for GeneratorShort<C> {
+        type FixedScalarKind = ShortScalar;
+
+        fn generator(&self) -> C {
+            C::generator()
+        }
+
+        fn u(&self) -> Vec<[<C::Base as PrimeField>::Repr; H]> {
+            us::<C>(C::zs_and_us_short())
+        }
+
+        fn z(&self) -> Vec<u64> {
+            C::zs_and_us_short().iter().map(|(z, _)| *z).collect()
+        }
+    }
+
+    impl<C: TestCurve> FixedPoints<C> for GeneratorFixedBases<C> {
+        type FullScalar = GeneratorFullWidth<C>;
+        type ShortScalar = GeneratorShort<C>;
+        type Base = GeneratorBaseField<C>;
+    }
+
+    type TestLookup<C> = LookupRangeCheckConfig<<C as EccCurve>::NativeField, { sinsemilla::K }>;
+    type TestEccChip<C> = EccChip<GeneratorFixedBases<C>, TestLookup<C>, C>;
+
+    fn expect<C: TestCurve>(
+        chip: TestEccChip<C>,
+        mut layouter: impl Layouter<C::Base>,
+        result: &super::Point<C, TestEccChip<C>>,
+        expected: C::CurveExt,
+    ) -> Result<(), Error> {
+        let expected = super::Point::new(
+            chip,
+            layouter.namespace(|| "expected"),
+            Value::known(expected.to_affine()),
+        )?;
+        result.constrain_equal(layouter.namespace(|| "constrain result"), &expected)
+    }
+
+    /// Exercises the ECC chip instantiated over the curve `C`, checking each result
+    /// against the natively computed point.
+    struct CurveEccCircuit<C>(PhantomData<C>);
+
+    impl<C: TestCurve> Circuit<C::Base> for CurveEccCircuit<C> {
+        type Config = EccConfig<GeneratorFixedBases<C>, TestLookup<C>, C>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            CurveEccCircuit(PhantomData)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advices = [(); 10].map(|_| meta.advice_column());
+            let lookup_table = meta.lookup_table_column();
+            let lagrange_coeffs = [(); 8].map(|_| meta.fixed_column());
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let range_check = TestLookup::<C>::configure(meta, advices[9], lookup_table);
+            TestEccChip::<C>::configure(meta, advices, lagrange_coeffs, range_check)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            let chip = TestEccChip::<C>::construct(config.clone());
+            let column = config.advices[0];
+            config.lookup_config.load_range_check_table(&mut layouter)?;
+
+            let p_val = C::CurveExt::random(rand::rngs::OsRng);
+            let q_val = C::CurveExt::random(rand::rngs::OsRng);
+            let p = super::NonIdentityPoint::new(
+                chip.clone(),
+                layouter.namespace(|| "P"),
+                Value::known(p_val.to_affine()),
+            )?;
+            let q = super::NonIdentityPoint::new(
+                chip.clone(),
+                layouter.namespace(|| "Q"),
+                Value::known(q_val.to_affine()),
+            )?;
+
+            // Complete and incomplete addition.
+            let r = p.add(layouter.namespace(|| "P + Q"), &q)?;
+            expect(
+                chip.clone(),
+                layouter.namespace(|| "P + Q"),
+                &r,
+                p_val + q_val,
+            )?;
+            let r = p.add(layouter.namespace(|| "P + P"), &p)?;
+            expect(
+                chip.clone(),
+                layouter.namespace(|| "P + P"),
+                &r,
+                p_val.double(),
+            )?;
+            let r = p.add_incomplete(layouter.namespace(|| "P +' Q"), &q)?;
+            expect(
+                chip.clone(),
+                layouter.namespace(|| "P +' Q"),
+                &r.into(),
+                p_val + q_val,
+            )?;
+
+            // Variable-base scalar multiplication. Where the base field is larger than
+            // the scalar field (as on Vesta), -1 exercises the reduction of the scalar.
+            for scalar_val in [
+                C::Base::ZERO,
+                -C::Base::ONE,
+                C::Base::random(rand::rngs::OsRng),
+            ] {
+                let scalar = chip.load_private(
+                    layouter.namespace(|| "scalar"),
+                    column,
+                    Value::known(scalar_val),
+                )?;
+                let scalar = super::ScalarVar::from_base(
+                    chip.clone(),
+                    layouter.namespace(|| "ScalarVar from_base"),
+                    &scalar,
+                )?;
+                let (r, _) = p.mul(layouter.namespace(|| "[a]P"), scalar)?;
+                let expected = p_val * base_to_scalar::<C>(&scalar_val);
+                expect(chip.clone(), layouter.namespace(|| "[a]P"), &r, expected)?;
+            }
+
+            // Full-width fixed-base scalar multiplication.
+            {
+                let scalar_val = C::Scalar::random(rand::rngs::OsRng);
+                let scalar = super::ScalarFixed::new(
+                    chip.clone(),
+                    layouter.namespace(|| "fixed scalar"),
+                    Value::known(scalar_val),
+                )?;
+                let base =
+                    super::FixedPoint::from_inner(chip.clone(), GeneratorFullWidth::default());
+                let (r, _) = base.mul(layouter.namespace(|| "[a]B"), scalar)?;
+                expect(
+                    chip.clone(),
+                    layouter.namespace(|| "[a]B"),
+                    &r,
+                    C::generator() * scalar_val,
+                )?;
+            }
+
+            // Signed short fixed-base scalar multiplication.
+            {
+                let magnitude = chip.load_private(
+                    layouter.namespace(|| "magnitude"),
+                    column,
+                    Value::known(C::Base::from(0xABCD_EF01_2345_6789)),
+                )?;
+                let sign = chip.load_private(
+                    layouter.namespace(|| "sign"),
+                    column,
+                    Value::known(-C::Base::ONE),
+                )?;
+                let scalar = super::ScalarFixedShort::new(
+                    chip.clone(),
+                    layouter.namespace(|| "short scalar"),
+                    (magnitude, sign),
+                )?;
+                let base =
+                    super::FixedPointShort::from_inner(chip.clone(), GeneratorShort::default());
+                let (r, _) = base.mul(layouter.namespace(|| "[-m]B"), scalar)?;
+                let expected = C::generator() * -C::Scalar::from(0xABCD_EF01_2345_6789);
+                expect(chip.clone(), layouter.namespace(|| "[-m]B"), &r, expected)?;
+            }
+
+            // Fixed-base scalar multiplication with a base field element.
+            {
+                let scalar_val = -C::Base::ONE;
+                let scalar = chip.load_private(
+                    layouter.namespace(|| "base field scalar"),
+                    column,
+                    Value::known(scalar_val),
+                )?;
+                let base = super::FixedPointBaseField::from_inner(
+                    chip.clone(),
+                    GeneratorBaseField::default(),
+                );
+                let r = base.mul(layouter.namespace(|| "[a]B"), scalar)?;
+                let expected = C::generator() * base_to_scalar::<C>(&scalar_val);
+                expect(chip.clone(), layouter.namespace(|| "[a]B"), &r, expected)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn ecc_chip_vesta() {
+        let circuit = CurveEccCircuit::<vesta::Affine>(PhantomData);
+        let prover = MockProver::run(13, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn ecc_chip_254_bit_curve() {
+        let circuit = CurveEccCircuit::<test_curve::Affine>(PhantomData);
+        let prover = MockProver::run(13, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    /// Fixed bases for windows wider than 3 bits, which interpolate the y-coordinate
+    /// and so have no `z` or `u` values.
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    struct WideFixedBases;
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    struct WideFullWidth;
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    struct WideBaseField;
+    #[derive(Debug, Eq, PartialEq, Clone)]
+    struct WideShort;
+
+    macro_rules! impl_wide_fixed_point {
+        ($name:ident, $kind:ident) => {
+            impl FixedPoint<pallas::Affine> for $name {
+                type FixedScalarKind = $kind;
+
+                fn generator(&self) -> pallas::Affine {
+                    *BASE
+                }
+
+                fn u(&self) -> Vec<[[u8; 32]; H]> {
+                    vec![]
+                }
+
+                fn z(&self) -> Vec<u64> {
+                    vec![]
+                }
+            }
+        };
+    }
+    impl_wide_fixed_point!(WideFullWidth, FullScalar);
+    impl_wide_fixed_point!(WideBaseField, BaseFieldElem);
+    impl_wide_fixed_point!(WideShort, ShortScalar);
+
+    impl FixedPoints<pallas::Affine> for WideFixedBases {
+        type FullScalar = WideFullWidth;
+        type ShortScalar = WideShort;
+        type Base = WideBaseField;
+    }
+
+    type WideEccChip<const WINDOW_SIZE: usize> =
+        EccChip<WideFixedBases, PallasLookupRangeCheckConfig, pallas::Affine, WINDOW_SIZE>;
+
+    fn expect_wide<const WINDOW_SIZE: usize>(
+        chip: WideEccChip<WINDOW_SIZE>,
+        mut layouter: impl Layouter<pallas::Base>,
+        result: &super::Point<pallas::Affine, WideEccChip<WINDOW_SIZE>>,
+        expected: pallas::Point,
+    ) -> Result<(), Error> {
+        let expected = super::Point::new(
+            chip,
+            layouter.namespace(|| "expected"),
+            Value::known(expected.to_affine()),
+        )?;
+        result.constrain_equal(layouter.namespace(|| "constrain result"), &expected)
+    }
+
+    /// Exercises fixed-base scalar multiplication using `WINDOW_SIZE`-bit windows.
+    struct WideWindowCircuit<const WINDOW_SIZE: usize> {
+        short_magnitude: pallas::Base,
+    }
+
+    impl<const WINDOW_SIZE: usize> Circuit<pallas::Base> for WideWindowCircuit<WINDOW_SIZE> {
+        type Config =
+            EccConfig<WideFixedBases, PallasLookupRangeCheckConfig, pallas::Affine, WINDOW_SIZE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            WideWindowCircuit {
+                short_magnitude: self.short_magnitude,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 10].map(|_| meta.advice_column());
+            let lookup_table = meta.lookup_table_column();
+            let lagrange_coeffs: Vec<_> = (0..(1 << WINDOW_SIZE))
+                .map(|_| meta.fixed_column())
+                .collect();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let range_check =
+                PallasLookupRangeCheckConfig::configure(meta, advices[9], lookup_table);
+            WideEccChip::<WINDOW_SIZE>::configure(meta, advices, lagrange_coeffs, range_check)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let chip = WideEccChip::<WINDOW_SIZE>::construct(config.clone());
+            let column = config.advices[0];
+            config.lookup_config.load_range_check_table(&mut layouter)?;
+
+            // Full-width fixed-base scalar multiplication.
+            for scalar_val in [
+                pallas::Scalar::random(rand::rngs::OsRng),
+                pallas::Scalar::zero(),
+                -pallas::Scalar::one(),
+            ] {
+                let scalar = super::ScalarFixed::new(
+                    chip.clone(),
+                    layouter.namespace(|| "fixed scalar"),
+                    Value::known(scalar_val),
+                )?;
+                let base = super::FixedPoint::from_inner(chip.clone(), WideFullWidth);
+                let (r, _) = base.mul(layouter.namespace(|| "[a]B"), scalar)?;
+                expect_wide(
+                    chip.clone(),
+                    layouter.namespace(|| "[a]B"),
+                    &r,
+                    *BASE * scalar_val,
+                )?;
+            }
+
+            // Signed short fixed-base scalar multiplication.
+            for (sign_val, sign_scalar) in [
+                (pallas::Base::one(), pallas::Scalar::one()),
+                (-pallas::Base::one(), -pallas::Scalar::one()),
+            ] {
+                let magnitude = chip.load_private(
+                    layouter.namespace(|| "magnitude"),
+                    column,
+                    Value::known(self.short_magnitude),
+                )?;
+                let sign = chip.load_private(
+                    layouter.namespace(|| "sign"),
+                    column,
+                    Value::known(sign_val),
+                )?;
+                let scalar = super::ScalarFixedShort::new(
+                    chip.clone(),
+                    layouter.namespace(|| "short scalar"),
+                    (magnitude, sign),
+                )?;
+                let base = super::FixedPointShort::from_inner(chip.clone(), WideShort);
+                let (r, _) = base.mul(layouter.namespace(|| "[±m]B"), scalar)?;
+                let expected =
+                    *BASE * (base_to_scalar::<pallas::Affine>(&self.short_magnitude) * sign_scalar);
+                expect_wide(chip.clone(), layouter.namespace(|| "[±m]B"), &r, expected)?;
+            }
+
+            // Fixed-base scalar multiplication with a base field element. -1 has its MSB
+            // set, and so exercises the canonicity check.
+            for scalar_val in [
+                pallas::Base::random(rand::rngs::OsRng),
+                -pallas::Base::one(),
+            ] {
+                let scalar = chip.load_private(
+                    layouter.namespace(|| "base field scalar"),
+                    column,
+                    Value::known(scalar_val),
+                )?;
+                let base = super::FixedPointBaseField::from_inner(chip.clone(), WideBaseField);
+                let r = base.mul(layouter.namespace(|| "[a]B"), scalar)?;
+                let expected = *BASE * base_to_scalar::<pallas::Affine>(&scalar_val);
+                expect_wide(chip.clone(), layouter.namespace(|| "[a]B"), &r, expected)?;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn wide_window_circuit<const WINDOW_SIZE: usize>() {
+        for short_magnitude in [rand::random::<u64>() as u128, (1 << 64) - 1, 1 << 64] {
+            let circuit = WideWindowCircuit::<WINDOW_SIZE> {
+                short_magnitude: pallas::Base::from_u128(short_magnitude),
+            };
+            let prover = MockProver::run(13, &circuit, vec![]).unwrap();
+            if short_magnitude < 1 << 64 {
+                assert_eq!(prover.verify(), Ok(()));
+            } else {
+                assert!(prover.verify().is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn ecc_chip_window_size_4() {
+        wide_window_circuit::<4>();
+    }
+
+    #[test]
+    fn ecc_chip_window_size_5() {
+        wide_window_circuit::<5>();
+    }
+
+    #[test]
+    fn test_ecc_chip_window_size_4_against_stored_circuit() {
+        let circuit = WideWindowCircuit::<4> {
+            short_magnitude: pallas::Base::from(rand::random::<u64>()),
+        };
+        test_against_stored_circuit(circuit, "ecc_chip_window_size_4", 4672);
+    }
+
+    #[test]
+    fn test_ecc_chip_window_size_5_against_stored_circuit() {
+        let circuit = WideWindowCircuit::<5> {
+            short_magnitude: pallas::Base::from(rand::random::<u64>()),
+        };
+        test_against_stored_circuit(circuit, "ecc_chip_window_size_5", 6144);
+    }
+
     #[test]
     fn ecc_chip() {
         let k = 13;
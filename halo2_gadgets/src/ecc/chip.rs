@@ -1,19 +1,21 @@
 //! Chip implementations for the ECC gadgets.
 
 use super::{BaseFitsInScalarInstructions, EccInstructions, FixedPoints};
-use crate::utilities::{
-    lookup_range_check::{PallasLookupRangeCheck, PallasLookupRangeCheckConfig},
-    UtilitiesInstructions,
+use crate::{
+    sinsemilla::primitives as sinsemilla,
+    utilities::{
+        lookup_range_check::{LookupRangeCheck, PallasLookupRangeCheckConfig},
+        UtilitiesInstructions,
+    },
 };
 use arrayvec::ArrayVec;
 
-use ff::PrimeField;
-use group::prime::PrimeCurveAffine;
+use ff::{Field, PrimeField, PrimeFieldBits};
 use halo2_proofs::{
     circuit::{AssignedCell, Chip, Layouter, Value},
     plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Fixed},
 };
-use pasta_curves::{arithmetic::CurveAffine, pallas};
+use pasta_curves::{arithmetic::CurveAffine, pallas, vesta};
 
 use std::convert::TryInto;
 
@@ -29,50 +31,121 @@ pub use constants::*;
 // Exposed for Sinsemilla.
 pub(crate) use mul::incomplete::DoubleAndAdd;
 
+/// A curve supported by [`EccChip`].
+///
+/// The chip supports short Weierstrass curves $y^2 = x^3 + b$ whose base field is the
+/// circuit field. The base field modulus $p$ and scalar field modulus $q$ must have
+/// the same bit length $n \leq 255$, with $p = 2^{n-1} + t_p$ and $q = 2^{n-1} + t_q$
+/// for some $t_p, t_q < 2^{130}$. This holds for Pallas and Vesta ($n = 255$).
+///
+/// The window counts and overflow checks used by the chip are derived from $n$,
+/// $t_p$ and $t_q$. [`EccChip::configure`] panics if the curve does not meet these
+/// requirements.
+pub trait EccCurve:
+    CurveAffine<Base = <Self as EccCurve>::NativeField, ScalarExt = <Self as EccCurve>::ScalarField>
+{
+    /// The base field of the curve, which is also the circuit field.
+    type NativeField: PrimeFieldBits;
+    /// The scalar field of the curve.
+    type ScalarField: PrimeFieldBits;
+
+    /// Returns $t_q$ such that the scalar field modulus is $q = 2^{n-1} + t_q$, as an
+    /// element of the base field.
+    fn t_q() -> Self::Base {
+        modulus_offset::<Self::Scalar, Self::Base>()
+    }
+
+    /// Returns $t_p$ such that the base field modulus is $p = 2^{n-1} + t_p$.
+    fn t_p() -> Self::Base {
+        modulus_offset::<Self::Base, Self::Base>()
+    }
+}
+
+impl EccCurve for pallas::Affine {
+    type NativeField = pallas::Base;
+    type ScalarField = pallas::Scalar;
+}
+
+impl EccCurve for vesta::Affine {
+    type NativeField = vesta::Base;
+    type ScalarField = vesta::Scalar;
+}
+
+/// Returns $t$ such that the modulus of `F` is $2^{n-1} + t$, where $n$ is its bit
+/// length, as an element of `B`.
+///
+/// $t - 1$ is given by the low $n - 1$ bits of $-1 \in F$.
+fn modulus_offset<F: PrimeFieldBits, B: PrimeField>() -> B {
+    (-F::ONE)
+        .to_le_bits()
+        .iter()
+        .take(F::NUM_BITS as usize - 1)
+        .rev()
+        .fold(B::ZERO, |acc, bit| acc.double() + B::from(*bit as u64))
+        + B::ONE
+}
+
+/// Returns whether `value` is less than $2^{130}$.
+fn fits_in_130_bits<F: PrimeFieldBits>(value: F) -> bool {
+    value.to_le_bits().iter().skip(130).all(|bit| !*bit)
+}
+
+/// Interprets the canonical encoding of a base field element as an integer and
+/// reduces it into the scalar field.
+#[cfg(test)]
+pub(super) fn base_to_scalar<C: EccCurve>(base: &C::Base) -> C::Scalar {
+    base.to_le_bits()
+        .iter()
+        .rev()
+        .fold(C::Scalar::ZERO, |acc, bit| {
+            acc.double() + C::Scalar::from(*bit as u64)
+        })
+}
+
 /// A curve point represented in affine (x, y) coordinates, or the
 /// identity represented as (0, 0).
 /// Each coordinate is assigned to a cell.
 #[derive(Clone, Debug)]
-pub struct EccPoint {
+pub struct EccPoint<C: CurveAffine = pallas::Affine> {
     /// x-coordinate
     ///
     /// Stored as an `Assigned<F>` to enable batching inversions.
-    x: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+    x: AssignedCell<Assigned<C::Base>, C::Base>,
     /// y-coordinate
     ///
     /// Stored as an `Assigned<F>` to enable batching inversions.
-    y: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+    y: AssignedCell<Assigned<C::Base>, C::Base>,
 }
 
-impl EccPoint {
+impl<C: CurveAffine> EccPoint<C> {
     /// Constructs a point from its coordinates, without checking they are on the curve.
     ///
     /// This is an internal API that we only use where we know we have a valid curve point.
     pub(crate) fn from_coordinates_unchecked(
-        x: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
-        y: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+        x: AssignedCell<Assigned<C::Base>, C::Base>,
+        y: AssignedCell<Assigned<C::Base>, C::Base>,
     ) -> Self {
         EccPoint { x, y }
     }
 
     /// Returns the value of this curve point, if known.
-    pub fn point(&self) -> Value<pallas::Affine> {
+    pub fn point(&self) -> Value<C> {
         self.x.value().zip(self.y.value()).map(|(x, y)| {
             if x.is_zero_vartime() && y.is_zero_vartime() {
-                pallas::Affine::identity()
+                C::identity()
             } else {
-                pallas::Affine::from_xy(x.evaluate(), y.evaluate()).unwrap()
+                C::from_xy(x.evaluate(), y.evaluate()).unwrap()
             }
         })
     }
     /// The cell containing the affine short-Weierstrass x-coordinate,
     /// or 0 for the zero point.
-    pub fn x(&self) -> AssignedCell<pallas::Base, pallas::Base> {
+    pub fn x(&self) -> AssignedCell<C::Base, C::Base> {
         self.x.clone().evaluate()
     }
     /// The cell containing the affine short-Weierstrass y-coordinate,
     /// or 0 for the zero point.
-    pub fn y(&self) -> AssignedCell<pallas::Base, pallas::Base> {
+    pub fn y(&self) -> AssignedCell<C::Base, C::Base> {
         self.y.clone().evaluate()
     }
 
@@ -85,48 +158,48 @@ impl EccPoint {
 /// A non-identity point represented in affine (x, y) coordinates.
 /// Each coordinate is assigned to a cell.
 #[derive(Clone, Debug)]
-pub struct NonIdentityEccPoint {
+pub struct NonIdentityEccPoint<C: CurveAffine = pallas::Affine> {
     /// x-coordinate
     ///
     /// Stored as an `Assigned<F>` to enable batching inversions.
-    x: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+    x: AssignedCell<Assigned<C::Base>, C::Base>,
     /// y-coordinate
     ///
     /// Stored as an `Assigned<F>` to enable batching inversions.
-    y: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+    y: AssignedCell<Assigned<C::Base>, C::Base>,
 }
 
-impl NonIdentityEccPoint {
+impl<C: CurveAffine> NonIdentityEccPoint<C> {
     /// Constructs a point from its coordinates, without checking they are on the curve.
     ///
     /// This is an internal API that we only use where we know we have a valid non-identity
     /// curve point.
     pub(crate) fn from_coordinates_unchecked(
-        x: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
-        y: AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+        x: AssignedCell<Assigned<C::Base>, C::Base>,
+        y: AssignedCell<Assigned<C::Base>, C::Base>,
     ) -> Self {
         NonIdentityEccPoint { x, y }
     }
 
     /// Returns the value of this curve point, if known.
-    pub fn point(&self) -> Value<pallas::Affine> {
+    pub fn point(&self) -> Value<C> {
         self.x.value().zip(self.y.value()).map(|(x, y)| {
             assert!(!x.is_zero_vartime() && !y.is_zero_vartime());
-            pallas::Affine::from_xy(x.evaluate(), y.evaluate()).unwrap()
+            C::from_xy(x.evaluate(), y.evaluate()).unwrap()
         })
     }
     /// The cell containing the affine short-Weierstrass x-coordinate.
-    pub fn x(&self) -> AssignedCell<pallas::Base, pallas::Base> {
+    pub fn x(&self) -> AssignedCell<C::Base, C::Base> {
         self.x.clone().evaluate()
     }
     /// The cell containing the affine short-Weierstrass y-coordinate.
-    pub fn y(&self) -> AssignedCell<pallas::Base, pallas::Base> {
+    pub fn y(&self) -> AssignedCell<C::Base, C::Base> {
         self.y.clone().evaluate()
     }
 }
 
-impl From<NonIdentityEccPoint> for EccPoint {
-    fn from(non_id_point: NonIdentityEccPoint) -> Self {
+impl<C: CurveAffine> From<NonIdentityEccPoint<C>> for EccPoint<C> {
+    fn from(non_id_point: NonIdentityEccPoint<C>) -> Self {
         Self {
             x: non_id_point.x,
             y: non_id_point.y,
@@ -135,11 +208,15 @@ impl From<NonIdentityEccPoint> for EccPoint {
 }
 
 /// Configuration for [`EccChip`].
+///
+/// `WINDOW_SIZE` is the window size used for fixed-base scalar multiplication.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[allow(non_snake_case)]
 pub struct EccConfig<
-    FixedPoints: super::FixedPoints<pallas::Affine>,
-    Lookup: PallasLookupRangeCheck = PallasLookupRangeCheckConfig,
+    FixedPoints: super::FixedPoints<C>,
+    Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }> = PallasLookupRangeCheckConfig,
+    C: EccCurve = pallas::Affine,
+    const WINDOW_SIZE: usize = FIXED_BASE_WINDOW_SIZE,
 > {
     /// Advice columns needed by instructions in the ECC chip.
     pub advices: [Column<Advice>; 10],
@@ -151,14 +228,16 @@ pub struct EccConfig<
     add: add::Config,
 
     /// Variable-base scalar multiplication
-    mul: mul::Config<Lookup>,
+    mul: mul::Config<C, Lookup>,
+    /// Variable-base multi-scalar multiplication, if enabled
+    msm: Option<mul::msm::Config<C, Lookup>>,
 
     /// Fixed-base full-width scalar multiplication
-    mul_fixed_full: mul_fixed::full_width::Config<FixedPoints>,
+    mul_fixed_full: mul_fixed::full_width::Config<C, FixedPoints, WINDOW_SIZE>,
     /// Fixed-base signed short scalar multiplication
-    mul_fixed_short: mul_fixed::short::Config<FixedPoints>,
+    mul_fixed_short: mul_fixed::short::Config<C, FixedPoints, WINDOW_SIZE>,
     /// Fixed-base mul using a base field element as a scalar
-    mul_fixed_base_field: mul_fixed::base_field_elem::Config<FixedPoints, Lookup>,
+    mul_fixed_base_field: mul_fixed::base_field_elem::Config<C, FixedPoints, Lookup, WINDOW_SIZE>,
 
     /// Witness point
     witness_point: witness_point::Config,
@@ -171,8 +250,13 @@ pub struct EccConfig<
 ///
 /// This trait exists because of limitations around const generics.
 pub trait FixedScalarKind {
-    /// The number of windows that this scalar kind requires.
+    /// The number of windows that this scalar kind requires on Pallas and Vesta, for
+    /// the default [`FIXED_BASE_WINDOW_SIZE`]-bit windows.
     const NUM_WINDOWS: usize;
+
+    /// The number of `window_size`-bit windows that this scalar kind requires on the
+    /// curve `C`.
+    fn num_windows<C: CurveAffine>(window_size: usize) -> usize;
 }
 
 /// Type marker representing a full-width scalar for use in fixed-base scalar
@@ -181,6 +265,10 @@ pub trait FixedScalarKind {
 pub enum FullScalar {}
 impl FixedScalarKind for FullScalar {
     const NUM_WINDOWS: usize = NUM_WINDOWS;
+
+    fn num_windows<C: CurveAffine>(window_size: usize) -> usize {
+        num_windows::<C>(window_size)
+    }
 }
 
 /// Type marker representing a signed 64-bit scalar for use in fixed-base scalar
@@ -189,6 +277,10 @@ impl FixedScalarKind for FullScalar {
 pub enum ShortScalar {}
 impl FixedScalarKind for ShortScalar {
     const NUM_WINDOWS: usize = NUM_WINDOWS_SHORT;
+
+    fn num_windows<C: CurveAffine>(window_size: usize) -> usize {
+        num_windows_short(window_size)
+    }
 }
 
 /// Type marker representing a base field element being used as a scalar in fixed-base
@@ -197,6 +289,10 @@ impl FixedScalarKind for ShortScalar {
 pub enum BaseFieldElem {}
 impl FixedScalarKind for BaseFieldElem {
     const NUM_WINDOWS: usize = NUM_WINDOWS;
+
+    fn num_windows<C: CurveAffine>(window_size: usize) -> usize {
+        num_windows::<C>(window_size)
+    }
 }
 
 /// Returns information about a fixed point that is required by [`EccChip`].
@@ -206,6 +302,11 @@ impl FixedScalarKind for BaseFieldElem {
 /// - $z + y = u^2$ (some square in the field); and
 /// - $z - y$ is not a square.
 ///
+/// The $z$ and $u$ values, and [`FixedPoint::lagrange_coeffs`], are only used for the
+/// default [`FIXED_BASE_WINDOW_SIZE`]-bit windows. For wider windows, [`EccChip`] instead
+/// interpolates both coordinates using [`FixedPoint::wide_lagrange_coeffs`], and
+/// implementations may return empty vectors from [`FixedPoint::u`] and [`FixedPoint::z`].
+///
 /// TODO: When associated consts can be used as const generics, introduce a
 /// `const NUM_WINDOWS: usize` associated const, and return `NUM_WINDOWS`-sized
 /// arrays instead of `Vec`s.
@@ -224,23 +325,48 @@ pub trait FixedPoint<C: CurveAffine>: std::fmt::Debug + Eq + Clone {
 
     /// Returns the Lagrange coefficients for this fixed point.
     fn lagrange_coeffs(&self) -> Vec<[C::Base; H]> {
-        compute_lagrange_coeffs(self.generator(), Self::FixedScalarKind::NUM_WINDOWS)
+        compute_lagrange_coeffs(
+            self.generator(),
+            Self::FixedScalarKind::num_windows::<C>(FIXED_BASE_WINDOW_SIZE),
+        )
+    }
+
+    /// Returns the Lagrange coefficients interpolating the $x$- and $y$-coordinates
+    /// for this fixed point, using `window_size`-bit windows.
+    #[allow(clippy::type_complexity)]
+    fn wide_lagrange_coeffs(&self, window_size: usize) -> (Vec<Vec<C::Base>>, Vec<Vec<C::Base>>) {
+        let num_windows = Self::FixedScalarKind::num_windows::<C>(window_size);
+        (
+            compute_lagrange_coeffs_x(self.generator(), window_size, num_windows),
+            compute_lagrange_coeffs_y(self.generator(), window_size, num_windows),
+        )
     }
 }
 
 /// An [`EccInstructions`] chip that uses 10 advice columns.
+///
+/// Fixed-base scalar multiplication uses `WINDOW_SIZE`-bit windows, where
+/// `3 <= WINDOW_SIZE <= MAX_FIXED_BASE_WINDOW_SIZE`.
+/// Wider windows use fewer rows per multiplication, at the cost of higher-degree
+/// gates and more fixed columns.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct EccChip<
-    FixedPoints: super::FixedPoints<pallas::Affine>,
-    Lookup: PallasLookupRangeCheck = PallasLookupRangeCheckConfig,
+    FixedPoints: super::FixedPoints<C>,
+    Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }> = PallasLookupRangeCheckConfig,
+    C: EccCurve = pallas::Affine,
+    const WINDOW_SIZE: usize = FIXED_BASE_WINDOW_SIZE,
 > {
-    config: EccConfig<FixedPoints, Lookup>,
+    config: EccConfig<FixedPoints, Lookup, C, WINDOW_SIZE>,
 }
 
-impl<FixedPoints: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck>
-    Chip<pallas::Base> for EccChip<FixedPoints, Lookup>
+impl<
+        FixedPoints: super::FixedPoints<C>,
+        Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+        C: EccCurve,
+        const WINDOW_SIZE: usize,
+    > Chip<C::Base> for EccChip<FixedPoints, Lookup, C, WINDOW_SIZE>
 {
-    type Config = EccConfig<FixedPoints, Lookup>;
+    type Config = EccConfig<FixedPoints, Lookup, C, WINDOW_SIZE>;
     type Loaded = ();
 
     fn config(&self) -> &Self::Config {
@@ -252,32 +378,54 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeC
     }
 }
 
-impl<Fixed: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck>
-    UtilitiesInstructions<pallas::Base> for EccChip<Fixed, Lookup>
+impl<
+        Fixed: super::FixedPoints<C>,
+        Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+        C: EccCurve,
+        const WINDOW_SIZE: usize,
+    > UtilitiesInstructions<C::Base> for EccChip<Fixed, Lookup, C, WINDOW_SIZE>
 {
-    type Var = AssignedCell<pallas::Base, pallas::Base>;
+    type Var = AssignedCell<C::Base, C::Base>;
 }
 
-impl<FixedPoints: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck>
-    EccChip<FixedPoints, Lookup>
+impl<
+        FixedPoints: super::FixedPoints<C>,
+        Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+        C: EccCurve,
+        const WINDOW_SIZE: usize,
+    > EccChip<FixedPoints, Lookup, C, WINDOW_SIZE>
 {
     /// Reconstructs this chip from the given config.
-    pub fn construct(config: <Self as Chip<pallas::Base>>::Config) -> Self {
+    pub fn construct(config: <Self as Chip<C::Base>>::Config) -> Self {
         Self { config }
     }
 
     /// # Side effects
     ///
     /// All columns in `advices` will be equality-enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `WINDOW_SIZE` is not in `3..=MAX_FIXED_BASE_WINDOW_SIZE`, if
+    /// `lagrange_coeffs` does not contain exactly `1 << WINDOW_SIZE` columns, or if the
+    /// curve does not have $a = 0$ and moduli of the form required by [`EccCurve`].
     #[allow(non_snake_case)]
     pub fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+        meta: &mut ConstraintSystem<C::Base>,
         advices: [Column<Advice>; 10],
-        lagrange_coeffs: [Column<Fixed>; 8],
+        lagrange_coeffs: impl AsRef<[Column<Fixed>]>,
         range_check: Lookup,
-    ) -> <Self as Chip<pallas::Base>>::Config {
+    ) -> <Self as Chip<C::Base>>::Config {
+        assert!(bool::from(C::a().is_zero()));
+        assert_eq!(C::Base::NUM_BITS, C::Scalar::NUM_BITS);
+        // The overflow and canonicity checks split scalars at bit 130, below the
+        // top window of a fixed-base scalar.
+        assert!(C::Scalar::NUM_BITS <= 255);
+        assert!(C::Scalar::NUM_BITS as usize > 130 + WINDOW_SIZE);
+        assert!(fits_in_130_bits(C::t_q()) && fits_in_130_bits(C::t_p()));
+
         // Create witness point gate
-        let witness_point = witness_point::Config::configure(meta, advices[0], advices[1]);
+        let witness_point = witness_point::Config::configure::<C>(meta, advices[0], advices[1]);
         // Create incomplete point addition gate
         let add_incomplete =
             add_incomplete::Config::configure(meta, advices[0], advices[1], advices[2], advices[3]);
@@ -293,9 +441,9 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeC
 
         // Create config that is shared across short, base-field, and full-width
         // fixed-base scalar mul.
-        let mul_fixed = mul_fixed::Config::<FixedPoints>::configure(
+        let mul_fixed = mul_fixed::Config::<C, FixedPoints, WINDOW_SIZE>::configure(
             meta,
-            lagrange_coeffs,
+            lagrange_coeffs.as_ref().to_vec(),
             advices[4],
             advices[5],
             add,
@@ -303,27 +451,25 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeC
         );
 
         // Create gate that is only used in full-width fixed-base scalar mul.
-        let mul_fixed_full =
-            mul_fixed::full_width::Config::<FixedPoints>::configure(meta, mul_fixed.clone());
+        let mul_fixed_full = mul_fixed::full_width::Config::configure(meta, mul_fixed.clone());
 
         // Create gate that is only used in short fixed-base scalar mul.
-        let mul_fixed_short =
-            mul_fixed::short::Config::<FixedPoints>::configure(meta, mul_fixed.clone());
+        let mul_fixed_short = mul_fixed::short::Config::configure(meta, mul_fixed.clone());
 
         // Create gate that is only used in fixed-base mul using a base field element.
-        let mul_fixed_base_field =
-            mul_fixed::base_field_elem::Config::<FixedPoints, Lookup>::configure(
-                meta,
-                advices[6..9].try_into().unwrap(),
-                range_check,
-                mul_fixed,
-            );
+        let mul_fixed_base_field = mul_fixed::base_field_elem::Config::configure(
+            meta,
+            advices[6..9].try_into().unwrap(),
+            range_check,
+            mul_fixed,
+        );
 
         EccConfig {
             advices,
             add_incomplete,
             add,
             mul,
+            msm: None,
             mul_fixed_full,
             mul_fixed_short,
             mul_fixed_base_field,
@@ -331,26 +477,56 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeC
             lookup_config: range_check,
         }
     }
+
+    /// Configures the chip as in [`EccChip::configure`], and additionally creates the
+    /// gates used by [`EccInstructions::msm`] to multiply pairs of terms with a shared
+    /// double-and-add ladder.
+    ///
+    /// Without these gates, [`EccInstructions::msm`] multiplies each term separately.
+    ///
+    /// # Side effects
+    ///
+    /// All columns in `advices` will be equality-enabled.
+    pub fn configure_with_msm(
+        meta: &mut ConstraintSystem<C::Base>,
+        advices: [Column<Advice>; 10],
+        lagrange_coeffs: impl AsRef<[Column<Fixed>]>,
+        range_check: Lookup,
+    ) -> <Self as Chip<C::Base>>::Config {
+        let config = Self::configure(meta, advices, lagrange_coeffs, range_check);
+        let msm = mul::msm::Config::configure(
+            meta,
+            config.add,
+            config.witness_point,
+            config.mul,
+            advices,
+        );
+
+        EccConfig {
+            msm: Some(msm),
+            ..config
+        }
+    }
 }
 
 /// A full-width scalar used for fixed-base scalar multiplication.
-/// This is decomposed into 85 3-bit windows in little-endian order,
-/// i.e. `windows` = [k_0, k_1, ..., k_84] (for a 255-bit scalar)
+/// This is decomposed into 3-bit windows in little-endian order,
+/// e.g. `windows` = [k_0, k_1, ..., k_84] (for a 255-bit scalar)
 /// where `scalar = k_0 + k_1 * (2^3) + ... + k_84 * (2^3)^84` and
 /// each `k_i` is in the range [0..2^3).
 #[derive(Clone, Debug)]
-pub struct EccScalarFixed {
-    value: Value<pallas::Scalar>,
+pub struct EccScalarFixed<C: CurveAffine = pallas::Affine> {
+    value: Value<C::Scalar>,
     /// The circuit-assigned windows representing this scalar, or `None` if the scalar has
     /// not been used yet.
-    windows: Option<ArrayVec<AssignedCell<pallas::Base, pallas::Base>, { NUM_WINDOWS }>>,
+    windows: Option<Vec<AssignedCell<C::Base, C::Base>>>,
 }
 
 // TODO: Make V a `u64`
-type MagnitudeCell = AssignedCell<pallas::Base, pallas::Base>;
+type MagnitudeCell<F = pallas::Base> = AssignedCell<F, F>;
 // TODO: Make V an enum Sign { Positive, Negative }
-type SignCell = AssignedCell<pallas::Base, pallas::Base>;
-type MagnitudeSign = (MagnitudeCell, SignCell);
+type SignCell<F = pallas::Base> = AssignedCell<F, F>;
+type MagnitudeSign<F = pallas::Base> = (MagnitudeCell<F>, SignCell<F>);
 
 /// A signed short scalar used for fixed-base scalar multiplication.
 /// A short scalar must have magnitude in the range [0..2^64), with
@@ -365,13 +541,12 @@ type MagnitudeSign = (MagnitudeCell, SignCell);
 /// each `k_i` is in the range [0..2^3).
 /// k_21 must be a single bit, i.e. 0 or 1.
 #[derive(Clone, Debug)]
-pub struct EccScalarFixedShort {
-    magnitude: MagnitudeCell,
-    sign: SignCell,
+pub struct EccScalarFixedShort<C: CurveAffine = pallas::Affine> {
+    magnitude: MagnitudeCell<C::Base>,
+    sign: SignCell<C::Base>,
     /// The circuit-assigned running sum constraining this signed short scalar, or `None`
     /// if the scalar has not been used yet.
-    running_sum:
-        Option<ArrayVec<AssignedCell<pallas::Base, pallas::Base>, { NUM_WINDOWS_SHORT + 1 }>>,
+    running_sum: Option<ArrayVec<AssignedCell<C::Base, C::Base>, { NUM_WINDOWS_SHORT + 1 }>>,
 }
 
 /// A base field element used for fixed-base scalar multiplication.
@@ -380,18 +555,19 @@ pub struct EccScalarFixedShort {
 /// for element α = a_0 + (2^3) a_1 + ... + (2^{3(n-1)}) a_{n-1}.
 /// Each `a_i` is in the range [0..2^3).
 ///
-/// `running_sum` = [z_0, ..., z_85], where we expect z_85 = 0.
+/// `running_sum` = [z_0, ..., z_85] (for a 255-bit base field element), where we
+/// expect z_85 = 0.
 /// Since z_0 is initialized as the scalar α, we store it as
 /// `base_field_elem`.
 #[derive(Clone, Debug)]
-struct EccBaseFieldElemFixed {
-    base_field_elem: AssignedCell<pallas::Base, pallas::Base>,
-    running_sum: ArrayVec<AssignedCell<pallas::Base, pallas::Base>, { NUM_WINDOWS + 1 }>,
+struct EccBaseFieldElemFixed<C: CurveAffine> {
+    base_field_elem: AssignedCell<C::Base, C::Base>,
+    running_sum: Vec<AssignedCell<C::Base, C::Base>>,
 }
 
-impl EccBaseFieldElemFixed {
+impl<C: CurveAffine> EccBaseFieldElemFixed<C> {
     #![allow(dead_code)]
-    fn base_field_elem(&self) -> AssignedCell<pallas::Base, pallas::Base> {
+    fn base_field_elem(&self) -> AssignedCell<C::Base, C::Base> {
         self.base_field_elem.clone()
     }
 }
@@ -399,7 +575,7 @@ impl EccBaseFieldElemFixed {
 /// An enumeration of the possible types of scalars used in variable-base
 /// multiplication.
 #[derive(Clone, Debug)]
-pub enum ScalarVar {
+pub enum ScalarVar<C: CurveAffine = pallas::Affine> {
     /// An element of the elliptic curve's base field, that is used as a scalar
     /// in variable-base scalar mul.
     ///
@@ -413,32 +589,33 @@ pub enum ScalarVar {
     /// [4.2.3 Orchard Key Components][orchardkeycomponents].)
     ///
     /// [orchardkeycomponents]: https://zips.z.cash/protocol/protocol.pdf#orchardkeycomponents
-    BaseFieldElem(AssignedCell<pallas::Base, pallas::Base>),
+    BaseFieldElem(AssignedCell<C::Base, C::Base>),
     /// A full-width scalar. This is unimplemented for halo2_gadgets v0.1.0.
     FullWidth,
 }
 
-impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck>
-    EccInstructions<pallas::Affine> for EccChip<Fixed, Lookup>
+impl<
+        Fixed: FixedPoints<C>,
+        Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+        C: EccCurve,
+        const WINDOW_SIZE: usize,
+    > EccInstructions<C> for EccChip<Fixed, Lookup, C, WINDOW_SIZE>
 where
-    <Fixed as FixedPoints<pallas::Affine>>::Base:
-        FixedPoint<pallas::Affine, FixedScalarKind = BaseFieldElem>,
-    <Fixed as FixedPoints<pallas::Affine>>::FullScalar:
-        FixedPoint<pallas::Affine, FixedScalarKind = FullScalar>,
-    <Fixed as FixedPoints<pallas::Affine>>::ShortScalar:
-        FixedPoint<pallas::Affine, FixedScalarKind = ShortScalar>,
+    <Fixed as FixedPoints<C>>::Base: FixedPoint<C, FixedScalarKind = BaseFieldElem>,
+    <Fixed as FixedPoints<C>>::FullScalar: FixedPoint<C, FixedScalarKind = FullScalar>,
+    <Fixed as FixedPoints<C>>::ShortScalar: FixedPoint<C, FixedScalarKind = ShortScalar>,
 {
-    type ScalarFixed = EccScalarFixed;
-    type ScalarFixedShort = EccScalarFixedShort;
-    type ScalarVar = ScalarVar;
-    type Point = EccPoint;
-    type NonIdentityPoint = NonIdentityEccPoint;
-    type X = AssignedCell<pallas::Base, pallas::Base>;
+    type ScalarFixed = EccScalarFixed<C>;
+    type ScalarFixedShort = EccScalarFixedShort<C>;
+    type ScalarVar = ScalarVar<C>;
+    type Point = EccPoint<C>;
+    type NonIdentityPoint = NonIdentityEccPoint<C>;
+    type X = AssignedCell<C::Base, C::Base>;
     type FixedPoints = Fixed;
 
     fn constrain_equal(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
         a: &Self::Point,
         b: &Self::Point,
     ) -> Result<(), Error> {
@@ -455,8 +632,8 @@ where
 
     fn witness_point(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        value: Value<pallas::Affine>,
+        layouter: &mut impl Layouter<C::Base>,
+        value: Value<C>,
     ) -> Result<Self::Point, Error> {
         let config = self.config().witness_point;
         layouter.assign_region(
@@ -470,8 +647,8 @@ where
     /// affine coordinates.
     fn witness_point_from_constant(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        value: pallas::Affine,
+        layouter: &mut impl Layouter<C::Base>,
+        value: C,
     ) -> Result<Self::Point, Error> {
         let config = self.config().witness_point;
         layouter.assign_region(
@@ -482,8 +659,8 @@ where
 
     fn witness_point_non_id(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        value: Value<pallas::Affine>,
+        layouter: &mut impl Layouter<C::Base>,
+        value: Value<C>,
     ) -> Result<Self::NonIdentityPoint, Error> {
         let config = self.config().witness_point;
         layouter.assign_region(
@@ -494,8 +671,8 @@ where
 
     fn witness_scalar_var(
         &self,
-        _layouter: &mut impl Layouter<pallas::Base>,
-        _value: Value<pallas::Scalar>,
+        _layouter: &mut impl Layouter<C::Base>,
+        _value: Value<C::Scalar>,
     ) -> Result<Self::ScalarVar, Error> {
         // This is unimplemented for halo2_gadgets v0.1.0.
         todo!()
@@ -503,8 +680,8 @@ where
 
     fn witness_scalar_fixed(
         &self,
-        _layouter: &mut impl Layouter<pallas::Base>,
-        value: Value<pallas::Scalar>,
+        _layouter: &mut impl Layouter<C::Base>,
+        value: Value<C::Scalar>,
     ) -> Result<Self::ScalarFixed, Error> {
         Ok(EccScalarFixed {
             value,
@@ -515,8 +692,8 @@ where
 
     fn scalar_fixed_from_signed_short(
         &self,
-        _layouter: &mut impl Layouter<pallas::Base>,
-        (magnitude, sign): MagnitudeSign,
+        _layouter: &mut impl Layouter<C::Base>,
+        (magnitude, sign): MagnitudeSign<C::Base>,
     ) -> Result<Self::ScalarFixedShort, Error> {
         Ok(EccScalarFixedShort {
             magnitude,
@@ -527,13 +704,13 @@ where
     }
 
     fn extract_p<Point: Into<Self::Point> + Clone>(point: &Point) -> Self::X {
-        let point: EccPoint = (point.clone()).into();
+        let point: EccPoint<C> = (point.clone()).into();
         point.x()
     }
 
     fn add_incomplete(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
         a: &Self::NonIdentityPoint,
         b: &Self::NonIdentityPoint,
     ) -> Result<Self::NonIdentityPoint, Error> {
@@ -546,7 +723,7 @@ where
 
     fn add<A: Into<Self::Point> + Clone, B: Into<Self::Point> + Clone>(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
         a: &A,
         b: &B,
     ) -> Result<Self::Point, Error> {
@@ -563,8 +740,8 @@ where
     /// This constrains `sign` to be in {-1, 1}.
     fn mul_sign(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        sign: &AssignedCell<pallas::Base, pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
+        sign: &AssignedCell<C::Base, C::Base>,
         point: &Self::Point,
     ) -> Result<Self::Point, Error> {
         // Multiply point by sign, using the same gate as mul_fixed::short.
@@ -579,7 +756,7 @@ where
 
     fn mul(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
         scalar: &Self::ScalarVar,
         base: &Self::NonIdentityPoint,
     ) -> Result<(Self::Point, Self::ScalarVar), Error> {
@@ -596,11 +773,25 @@ where
         }
     }
 
+    fn msm(
+        &self,
+        layouter: &mut impl Layouter<C::Base>,
+        terms: &[(Self::ScalarVar, Self::NonIdentityPoint)],
+    ) -> Result<Self::Point, Error> {
+        match self.config().msm {
+            Some(config) if !terms.is_empty() => config.assign(
+                layouter.namespace(|| "variable-base multi-scalar mul"),
+                terms,
+            ),
+            _ => super::msm_by_mul(self, layouter, terms),
+        }
+    }
+
     fn mul_fixed(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
         scalar: &Self::ScalarFixed,
-        base: &<Self::FixedPoints as FixedPoints<pallas::Affine>>::FullScalar,
+        base: &<Self::FixedPoints as FixedPoints<C>>::FullScalar,
     ) -> Result<(Self::Point, Self::ScalarFixed), Error> {
         let config = self.config().mul_fixed_full.clone();
         config.assign(
@@ -612,9 +803,9 @@ where
 
     fn mul_fixed_short(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
+        layouter: &mut impl Layouter<C::Base>,
         scalar: &Self::ScalarFixedShort,
-        base: &<Self::FixedPoints as FixedPoints<pallas::Affine>>::ShortScalar,
+        base: &<Self::FixedPoints as FixedPoints<C>>::ShortScalar,
     ) -> Result<(Self::Point, Self::ScalarFixedShort), Error> {
         let config = self.config().mul_fixed_short.clone();
         config.assign(
@@ -626,9 +817,9 @@ where
 
     fn mul_fixed_base_field_elem(
         &self,
-        layouter: &mut impl Layouter<pallas::Base>,
-        base_field_elem: AssignedCell<pallas::Base, pallas::Base>,
-        base: &<Self::FixedPoints as FixedPoints<pallas::Affine>>::Base,
+        layouter: &mut impl Layouter<C::Base>,
+        base_field_elem: AssignedCell<C::Base, C::Base>,
+        base: &<Self::FixedPoints as FixedPoints<C>>::Base,
     ) -> Result<Self::Point, Error> {
         let config = self.config().mul_fixed_base_field.clone();
         config.assign(
@@ -639,19 +830,20 @@ where
     }
 }
 
-impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck>
-    BaseFitsInScalarInstructions<pallas::Affine> for EccChip<Fixed, Lookup>
+impl<
+        Fixed: FixedPoints<C>,
+        Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+        C: EccCurve,
+        const WINDOW_SIZE: usize,
+    > BaseFitsInScalarInstructions<C> for EccChip<Fixed, Lookup, C, WINDOW_SIZE>
 where
-    <Fixed as FixedPoints<pallas::Affine>>::Base:
-        FixedPoint<pallas::Affine, FixedScalarKind = BaseFieldElem>,
-    <Fixed as FixedPoints<pallas::Affine>>::FullScalar:
-        FixedPoint<pallas::Affine, FixedScalarKind = FullScalar>,
-    <Fixed as FixedPoints<pallas::Affine>>::ShortScalar:
-        FixedPoint<pallas::Affine, FixedScalarKind = ShortScalar>,
+    <Fixed as FixedPoints<C>>::Base: FixedPoint<C, FixedScalarKind = BaseFieldElem>,
+    <Fixed as FixedPoints<C>>::FullScalar: FixedPoint<C, FixedScalarKind = FullScalar>,
+    <Fixed as FixedPoints<C>>::ShortScalar: FixedPoint<C, FixedScalarKind = ShortScalar>,
 {
     fn scalar_var_from_base(
         &self,
-        _layouter: &mut impl Layouter<pallas::Base>,
+        _layouter: &mut impl Layouter<C::Base>,
         base: &Self::Var,
     ) -> Result<Self::ScalarVar, Error> {
         Ok(ScalarVar::BaseFieldElem(base.clone()))
@@ -6,7 +6,7 @@ use halo2_proofs::{
     plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
-use pasta_curves::pallas;
+use pasta_curves::arithmetic::CurveAffine;
 
 use std::collections::HashSet;
 
@@ -35,8 +35,8 @@ pub struct Config {
 
 impl Config {
     #[allow(clippy::too_many_arguments)]
-    pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+    pub(super) fn configure<F: PrimeField>(
+        meta: &mut ConstraintSystem<F>,
         x_p: Column<Advice>,
         y_p: Column<Advice>,
         x_qr: Column<Advice>,
@@ -74,7 +74,7 @@ impl Config {
         [self.x_qr, self.y_qr].into_iter().collect()
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate<F: PrimeField>(&self, meta: &mut ConstraintSystem<F>) {
         // https://p.z.cash/halo2-0.1:ecc-complete-addition
         meta.create_gate("complete addition", |meta| {
             let q_add = meta.query_selector(self.q_add);
@@ -112,9 +112,9 @@ impl Config {
             let if_delta = y_q_plus_y_p.clone() * delta;
 
             // Useful constants
-            let one = Expression::Constant(pallas::Base::one());
-            let two = Expression::Constant(pallas::Base::from(2));
-            let three = Expression::Constant(pallas::Base::from(3));
+            let one = Expression::Constant(F::ONE);
+            let two = Expression::Constant(F::from(2));
+            let three = Expression::Constant(F::from(3));
 
             // (x_q − x_p)⋅((x_q − x_p)⋅λ − (y_q−y_p)) = 0
             let poly1 = {
@@ -192,13 +192,13 @@ impl Config {
         });
     }
 
-    pub(super) fn assign_region(
+    pub(super) fn assign_region<C: CurveAffine>(
         &self,
-        p: &EccPoint,
-        q: &EccPoint,
+        p: &EccPoint<C>,
+        q: &EccPoint<C>,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<EccPoint, Error> {
+        region: &mut Region<'_, C::Base>,
+    ) -> Result<EccPoint<C>, Error> {
         // Enable `q_add` selector
         self.q_add.enable(region, offset)?;
 
@@ -255,9 +255,9 @@ impl Config {
                     } else {
                         if !y_p.is_zero_vartime() {
                             // 3(x_p)^2
-                            let three_x_p_sq = x_p.square() * pallas::Base::from(3);
+                            let three_x_p_sq = x_p.square() * C::Base::from(3);
                             // 1 / 2(y_p)
-                            let inv_two_y_p = y_p.invert() * pallas::Base::TWO_INV;
+                            let inv_two_y_p = y_p.invert() * C::Base::TWO_INV;
                             // λ = 3(x_p)^2 / 2(y_p)
                             three_x_p_sq * inv_two_y_p
                         } else {
@@ -1,12 +1,13 @@
 use std::collections::HashSet;
 
 use super::NonIdentityEccPoint;
+use group::ff::Field;
 use halo2_proofs::{
     circuit::Region,
     plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Selector},
     poly::Rotation,
 };
-use pasta_curves::pallas;
+use pasta_curves::arithmetic::CurveAffine;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Config {
@@ -22,8 +23,8 @@ pub struct Config {
 }
 
 impl Config {
-    pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+    pub(super) fn configure<F: Field>(
+        meta: &mut ConstraintSystem<F>,
         x_p: Column<Advice>,
         y_p: Column<Advice>,
         x_qr: Column<Advice>,
@@ -53,7 +54,7 @@ impl Config {
             .collect()
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate<F: Field>(&self, meta: &mut ConstraintSystem<F>) {
         // https://p.z.cash/halo2-0.1:ecc-incomplete-addition
         meta.create_gate("incomplete addition", |meta| {
             let q_add_incomplete = meta.query_selector(self.q_add_incomplete);
@@ -79,13 +80,13 @@ impl Config {
         });
     }
 
-    pub(super) fn assign_region(
+    pub(super) fn assign_region<C: CurveAffine>(
         &self,
-        p: &NonIdentityEccPoint,
-        q: &NonIdentityEccPoint,
+        p: &NonIdentityEccPoint<C>,
+        q: &NonIdentityEccPoint<C>,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<NonIdentityEccPoint, Error> {
+        region: &mut Region<'_, C::Base>,
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
         // Enable `q_add_incomplete` selector
         self.q_add_incomplete.enable(region, offset)?;
 
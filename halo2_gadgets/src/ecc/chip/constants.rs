@@ -8,103 +8,157 @@ use group::{
 use halo2_proofs::arithmetic::lagrange_interpolate;
 use pasta_curves::{arithmetic::CurveAffine, pallas};
 
-/// Window size for fixed-base scalar multiplication
+/// Default window size for fixed-base scalar multiplication
 pub const FIXED_BASE_WINDOW_SIZE: usize = 3;
 
+/// Largest window size supported for fixed-base scalar multiplication
+///
+/// For window sizes $w$ larger than [`FIXED_BASE_WINDOW_SIZE`], the y-coordinate of each
+/// window's point is interpolated in the same way as its x-coordinate, using a gate of
+/// degree $2^w + 1$ (33 for $w = 5$). This raises the degree of the whole circuit, and so
+/// the size of the extended domain used by the prover.
+pub const MAX_FIXED_BASE_WINDOW_SIZE: usize = 5;
+
 /// $2^{`FIXED_BASE_WINDOW_SIZE`}$
 pub const H: usize = 1 << FIXED_BASE_WINDOW_SIZE;
 
-/// Number of windows for a full-width scalar
+/// Number of windows for a full-width scalar on Pallas or Vesta
 pub const NUM_WINDOWS: usize =
     (pallas::Scalar::NUM_BITS as usize + FIXED_BASE_WINDOW_SIZE - 1) / FIXED_BASE_WINDOW_SIZE;
 
 /// Number of windows for a short signed scalar
-pub const NUM_WINDOWS_SHORT: usize =
-    (L_SCALAR_SHORT + FIXED_BASE_WINDOW_SIZE - 1) / FIXED_BASE_WINDOW_SIZE;
+pub const NUM_WINDOWS_SHORT: usize = num_windows_short(FIXED_BASE_WINDOW_SIZE);
+
+/// Number of windows for a full-width scalar on the curve `C`, using
+/// `window_size`-bit windows.
+pub fn num_windows<C: CurveAffine>(window_size: usize) -> usize {
+    (C::Scalar::NUM_BITS as usize + window_size - 1) / window_size
+}
+
+/// Number of windows for a short signed scalar, using `window_size`-bit windows.
+pub const fn num_windows_short(window_size: usize) -> usize {
+    (L_SCALAR_SHORT + window_size - 1) / window_size
+}
 
 /// $\ell_\mathsf{value}$
 /// Number of bits in an unsigned short scalar.
 pub(crate) const L_SCALAR_SHORT: usize = 64;
 
-/// The Pallas scalar field modulus is $q = 2^{254} + \mathsf{t_q}$.
-/// <https://github.com/zcash/pasta>
-pub(crate) const T_Q: u128 = 45560315531506369815346746415080538113;
-
-/// The Pallas base field modulus is $p = 2^{254} + \mathsf{t_p}$.
-/// <https://github.com/zcash/pasta>
-pub(crate) const T_P: u128 = 45560315531419706090280762371685220353;
-
-/// For each fixed base, we calculate its scalar multiples in three-bit windows.
-/// Each window will have $2^3 = 8$ points. The tables are computed as described in
+/// For each fixed base, we calculate its scalar multiples in `window_size`-bit windows.
+/// Each window will have $H = 2^w$ points. The tables are computed as described in
 /// [the Halo 2 book](https://zcash.github.io/halo2/design/gadgets/ecc/fixed-base-scalar-mul.html#load-fixed-base).
-fn compute_window_table<C: CurveAffine>(base: C, num_windows: usize) -> Vec<[C; H]> {
-    let mut window_table: Vec<[C; H]> = Vec::with_capacity(num_windows);
+fn compute_window_table<C: CurveAffine>(
+    base: C,
+    window_size: usize,
+    num_windows: usize,
+) -> Vec<Vec<C>> {
+    let h = 1 << window_size;
+    let mut window_table: Vec<Vec<C>> = Vec::with_capacity(num_windows);
 
     // Generate window table entries for all windows but the last.
-    // For these first `num_windows - 1` windows, we compute the multiple [(k+2)*(2^3)^w]B.
+    // For these first `num_windows - 1` windows, we compute the multiple [(k+2)*(H^w)]B.
     // Here, w ranges from [0..`num_windows - 1`)
     for w in 0..(num_windows - 1) {
         window_table.push(
-            (0..H)
+            (0..h)
                 .map(|k| {
-                    // scalar = (k+2)*(8^w)
+                    // scalar = (k+2)*(H^w)
                     let scalar = C::Scalar::from(k as u64 + 2)
-                        * C::Scalar::from(H as u64).pow(&[w as u64, 0, 0, 0]);
+                        * C::Scalar::from(h as u64).pow(&[w as u64, 0, 0, 0]);
                     (base * scalar).to_affine()
                 })
-                .collect::<ArrayVec<C, H>>()
-                .into_inner()
-                .unwrap(),
+                .collect(),
         );
     }
 
     // Generate window table entries for the last window, w = `num_windows - 1`.
-    // For the last window, we compute [k * (2^3)^w - sum]B, where sum is defined
-    // as sum = \sum_{j = 0}^{`num_windows - 2`} 2^{3j+1}
-    let sum = (0..(num_windows - 1)).fold(C::Scalar::ZERO, |acc, j| {
-        acc + C::Scalar::from(2).pow(&[FIXED_BASE_WINDOW_SIZE as u64 * j as u64 + 1, 0, 0, 0])
-    });
+    // For the last window, we compute [k * H^w - sum]B, where sum is defined
+    // as sum = \sum_{j = 0}^{`num_windows - 2`} 2^{`window_size`*j+1}
+    let sum = last_window_offset::<C>(window_size, num_windows);
     window_table.push(
-        (0..H)
+        (0..h)
             .map(|k| {
-                // scalar = k * (2^3)^w - sum, where w = `num_windows - 1`
+                // scalar = k * H^w - sum, where w = `num_windows - 1`
                 let scalar = C::Scalar::from(k as u64)
-                    * C::Scalar::from(H as u64).pow(&[(num_windows - 1) as u64, 0, 0, 0])
+                    * C::Scalar::from(h as u64).pow(&[(num_windows - 1) as u64, 0, 0, 0])
                     - sum;
                 (base * scalar).to_affine()
             })
-            .collect::<ArrayVec<C, H>>()
-            .into_inner()
-            .unwrap(),
+            .collect(),
     );
 
     window_table
 }
 
-/// For each window, we interpolate the $x$-coordinate.
-/// Here, we pre-compute and store the coefficients of the interpolation polynomial.
-pub fn compute_lagrange_coeffs<C: CurveAffine>(base: C, num_windows: usize) -> Vec<[C::Base; H]> {
-    // We are interpolating over the 3-bit window, k \in [0..8)
-    let points: Vec<_> = (0..H).map(|i| C::Base::from(i as u64)).collect();
+/// Returns $\sum_{j = 0}^{n - 2} 2^{wj + 1}$ for $n$ windows of $w$ bits, which is
+/// subtracted from the multiples in the last window.
+fn last_window_offset<C: CurveAffine>(window_size: usize, num_windows: usize) -> C::Scalar {
+    (0..(num_windows - 1)).fold(C::Scalar::ZERO, |acc, j| {
+        acc + C::Scalar::from(2).pow(&[window_size as u64 * j as u64 + 1, 0, 0, 0])
+    })
+}
 
-    let window_table = compute_window_table(base, num_windows);
+/// Interpolates the given coordinate of the points in each window of the window table
+/// for `base`, over $k \in [0..H)$.
+fn interpolate_window_table<C: CurveAffine>(
+    base: C,
+    window_size: usize,
+    num_windows: usize,
+    coordinate: impl Fn(&C) -> C::Base,
+) -> Vec<Vec<C::Base>> {
+    // We are interpolating over the window, k \in [0..H)
+    let points: Vec<_> = (0..(1 << window_size))
+        .map(|i| C::Base::from(i as u64))
+        .collect();
+
+    let window_table = compute_window_table(base, window_size, num_windows);
 
     window_table
         .iter()
         .map(|window_points| {
-            let x_window_points: Vec<_> = window_points
-                .iter()
-                .map(|point| *point.coordinates().unwrap().x())
-                .collect();
-            lagrange_interpolate(&points, &x_window_points)
-                .into_iter()
-                .collect::<ArrayVec<C::Base, H>>()
-                .into_inner()
-                .unwrap()
+            let window_coords: Vec<_> = window_points.iter().map(&coordinate).collect();
+            lagrange_interpolate(&points, &window_coords)
         })
         .collect()
 }
 
+/// For each window, we interpolate the $x$-coordinate.
+/// Here, we pre-compute and store the coefficients of the interpolation polynomial.
+pub fn compute_lagrange_coeffs<C: CurveAffine>(base: C, num_windows: usize) -> Vec<[C::Base; H]> {
+    compute_lagrange_coeffs_x(base, FIXED_BASE_WINDOW_SIZE, num_windows)
+        .into_iter()
+        .map(|coeffs| coeffs.try_into().unwrap())
+        .collect()
+}
+
+/// For each `window_size`-bit window, we interpolate the $x$-coordinate.
+///
+/// This is [`compute_lagrange_coeffs`] for windows of any supported size.
+pub fn compute_lagrange_coeffs_x<C: CurveAffine>(
+    base: C,
+    window_size: usize,
+    num_windows: usize,
+) -> Vec<Vec<C::Base>> {
+    interpolate_window_table(base, window_size, num_windows, |point| {
+        *point.coordinates().unwrap().x()
+    })
+}
+
+/// For each `window_size`-bit window, we interpolate the $y$-coordinate.
+///
+/// This is used instead of [`find_zs_and_us`] for windows wider than
+/// [`FIXED_BASE_WINDOW_SIZE`] bits, for which suitable $z$ values cannot
+/// practically be found.
+pub fn compute_lagrange_coeffs_y<C: CurveAffine>(
+    base: C,
+    window_size: usize,
+    num_windows: usize,
+) -> Vec<Vec<C::Base>> {
+    interpolate_window_table(base, window_size, num_windows, |point| {
+        *point.coordinates().unwrap().y()
+    })
+}
+
 /// For each window, $z$ is a field element such that for each point $(x, y)$ in the window:
 /// - $z + y = u^2$ (some square in the field); and
 /// - $z - y$ is not a square.
@@ -140,7 +194,7 @@ pub fn find_zs_and_us<C: CurveAffine>(
         })
     };
 
-    let window_table = compute_window_table(base, num_windows);
+    let window_table = compute_window_table(base, FIXED_BASE_WINDOW_SIZE, num_windows);
     window_table
         .iter()
         .map(|window_points| find_z_and_us(window_points))
@@ -154,7 +208,9 @@ pub fn find_zs_and_us<C: CurveAffine>(
 #[cfg(any(test, feature = "test-dependencies"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
 pub fn test_zs_and_us<C: CurveAffine>(base: C, z: &[u64], u: &[[[u8; 32]; H]], num_windows: usize) {
-    let window_table = compute_window_table(base, num_windows);
+    use group::ff::PrimeField;
+
+    let window_table = compute_window_table(base, FIXED_BASE_WINDOW_SIZE, num_windows);
 
     for ((u, z), window_points) in u.iter().zip(z.iter()).zip(window_table) {
         for (u, point) in u.iter().zip(window_points.iter()) {
@@ -173,6 +229,50 @@ pub fn test_zs_and_us<C: CurveAffine>(base: C, z: &[u64], u: &[[[u8; 32]; H]], n
 #[cfg(any(test, feature = "test-dependencies"))]
 #[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
 pub fn test_lagrange_coeffs<C: CurveAffine>(base: C, num_windows: usize) {
+    let lagrange_coeffs: Vec<Vec<_>> = compute_lagrange_coeffs(base, num_windows)
+        .iter()
+        .map(|coeffs| coeffs.to_vec())
+        .collect();
+    check_lagrange_coeffs(
+        base,
+        FIXED_BASE_WINDOW_SIZE,
+        num_windows,
+        |point| *point.coordinates().unwrap().x(),
+        &lagrange_coeffs,
+    );
+}
+
+/// Test that Lagrange interpolation coefficients reproduce the correct x- and
+/// y-coordinates for each fixed-base multiple in each `window_size`-bit window.
+#[cfg(any(test, feature = "test-dependencies"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-dependencies")))]
+pub fn test_lagrange_coeffs_xy<C: CurveAffine>(base: C, window_size: usize, num_windows: usize) {
+    check_lagrange_coeffs(
+        base,
+        window_size,
+        num_windows,
+        |point| *point.coordinates().unwrap().x(),
+        &compute_lagrange_coeffs_x(base, window_size, num_windows),
+    );
+    check_lagrange_coeffs(
+        base,
+        window_size,
+        num_windows,
+        |point| *point.coordinates().unwrap().y(),
+        &compute_lagrange_coeffs_y(base, window_size, num_windows),
+    );
+}
+
+/// Checks that `lagrange_coeffs` interpolate the given coordinate of each fixed-base
+/// multiple in each `window_size`-bit window.
+#[cfg(any(test, feature = "test-dependencies"))]
+fn check_lagrange_coeffs<C: CurveAffine>(
+    base: C,
+    window_size: usize,
+    num_windows: usize,
+    coordinate: impl Fn(&C) -> C::Base,
+    lagrange_coeffs: &[Vec<C::Base>],
+) {
     /// Evaluate y = f(x) given the coefficients of f(x)
     fn evaluate<C: CurveAffine>(x: u8, coeffs: &[C::Base]) -> C::Base {
         let x = C::Base::from(x as u64);
@@ -184,46 +284,35 @@ pub fn test_lagrange_coeffs<C: CurveAffine>(base: C, num_windows: usize) {
             .unwrap_or(C::Base::ZERO)
     }
 
-    let lagrange_coeffs = compute_lagrange_coeffs(base, num_windows);
-
-    // Check first 84 windows, i.e. `k_0, k_1, ..., k_83`
-    for (idx, coeffs) in lagrange_coeffs[0..(num_windows - 1)].iter().enumerate() {
-        // Test each three-bit chunk in this window.
-        for bits in 0..(H as u8) {
-            {
-                // Interpolate the x-coordinate using this window's coefficients
-                let interpolated_x = evaluate::<C>(bits, coeffs);
+    let h = 1u64 << window_size;
 
-                // Compute the actual x-coordinate of the multiple [(k+2)*(8^w)]B.
-                let point = base
-                    * C::Scalar::from(bits as u64 + 2)
-                    * C::Scalar::from(H as u64).pow(&[idx as u64, 0, 0, 0]);
-                let x = *point.to_affine().coordinates().unwrap().x();
+    // Check that the interpolated coordinate matches the actual one.
+    let check = |window: usize, bits: u8, point: C::CurveExt| {
+        let actual = coordinate(&point.to_affine());
+        assert_eq!(actual, evaluate::<C>(bits, &lagrange_coeffs[window]));
+    };
 
-                // Check that the interpolated x-coordinate matches the actual one.
-                assert_eq!(x, interpolated_x);
-            }
+    // Check all windows but the last, i.e. `k_0, k_1, ..., k_{n-2}`
+    for idx in 0..(num_windows - 1) {
+        // Test each chunk in this window.
+        for bits in 0..(h as u8) {
+            // Compute the actual multiple [(k+2)*(H^w)]B.
+            let point = base
+                * C::Scalar::from(bits as u64 + 2)
+                * C::Scalar::from(h).pow(&[idx as u64, 0, 0, 0]);
+            check(idx, bits, point);
         }
     }
 
     // Check last window.
-    for bits in 0..(H as u8) {
-        // Interpolate the x-coordinate using the last window's coefficients
-        let interpolated_x = evaluate::<C>(bits, &lagrange_coeffs[num_windows - 1]);
-
-        // Compute the actual x-coordinate of the multiple [k * (8^84) - offset]B,
-        // where offset = \sum_{j = 0}^{83} 2^{3j+1}
-        let offset = (0..(num_windows - 1)).fold(C::Scalar::ZERO, |acc, w| {
-            acc + C::Scalar::from(2).pow(&[FIXED_BASE_WINDOW_SIZE as u64 * w as u64 + 1, 0, 0, 0])
-        });
+    for bits in 0..(h as u8) {
+        // Compute the actual multiple [k * H^{n-1} - offset]B,
+        // where offset = \sum_{j = 0}^{n-2} 2^{wj+1}
+        let offset = last_window_offset::<C>(window_size, num_windows);
         let scalar = C::Scalar::from(bits as u64)
-            * C::Scalar::from(H as u64).pow(&[(num_windows - 1) as u64, 0, 0, 0])
+            * C::Scalar::from(h).pow(&[(num_windows - 1) as u64, 0, 0, 0])
             - offset;
-        let point = base * scalar;
-        let x = *point.to_affine().coordinates().unwrap().x();
-
-        // Check that the interpolated x-coordinate matches the actual one.
-        assert_eq!(x, interpolated_x);
+        check(num_windows - 1, bits, base * scalar);
     }
 }
 
@@ -234,7 +323,11 @@ mod tests {
     use pasta_curves::{arithmetic::CurveAffine, pallas};
     use proptest::prelude::*;
 
-    use super::{compute_window_table, find_zs_and_us, test_lagrange_coeffs, H, NUM_WINDOWS};
+    use super::{
+        compute_window_table, find_zs_and_us, num_windows, test_lagrange_coeffs,
+        test_lagrange_coeffs_xy, FIXED_BASE_WINDOW_SIZE, H, MAX_FIXED_BASE_WINDOW_SIZE,
+        NUM_WINDOWS,
+    };
 
     prop_compose! {
         /// Generate an arbitrary Pallas point.
@@ -256,6 +349,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn lagrange_coeffs_wide_windows() {
+        let base = pallas::Point::random(rand::rngs::OsRng).to_affine();
+        for window_size in FIXED_BASE_WINDOW_SIZE..=MAX_FIXED_BASE_WINDOW_SIZE {
+            test_lagrange_coeffs_xy(
+                base,
+                window_size,
+                num_windows::<pallas::Affine>(window_size),
+            );
+        }
+    }
+
     #[test]
     fn zs_and_us() {
         let base = pallas::Point::random(rand::rngs::OsRng);
@@ -264,7 +369,8 @@ mod tests {
                 .unwrap()
                 .into_iter()
                 .unzip();
-        let window_table = compute_window_table(base.to_affine(), NUM_WINDOWS);
+        let window_table =
+            compute_window_table(base.to_affine(), FIXED_BASE_WINDOW_SIZE, NUM_WINDOWS);
 
         for ((u, z), window_points) in u.iter().zip(z.iter()).zip(window_table) {
             for (u, point) in u.iter().zip(window_points.iter()) {
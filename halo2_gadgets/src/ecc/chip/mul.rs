@@ -1,10 +1,14 @@
-use super::{add, EccPoint, NonIdentityEccPoint, ScalarVar, T_Q};
-use crate::utilities::{
-    lookup_range_check::{PallasLookupRangeCheck, PallasLookupRangeCheckConfig},
-    {bool_check, ternary},
+use super::{add, EccCurve, EccPoint, NonIdentityEccPoint, ScalarVar};
+use crate::{
+    sinsemilla::primitives as sinsemilla,
+    utilities::{
+        lookup_range_check::LookupRangeCheck,
+        {bool_check, ternary},
+    },
 };
 use std::{
     convert::TryInto,
+    marker::PhantomData,
     ops::{Deref, Range},
 };
 
@@ -16,54 +20,61 @@ use halo2_proofs::{
 };
 use uint::construct_uint;
 
-use pasta_curves::pallas;
-
 mod complete;
 pub(super) mod incomplete;
+pub(super) mod msm;
 mod overflow;
 
 /// Number of bits for which complete addition needs to be used in variable-base
 /// scalar multiplication
 const NUM_COMPLETE_BITS: usize = 3;
 
-// Bits used in incomplete addition. k_{254} to k_{4} inclusive
-const INCOMPLETE_LEN: usize = pallas::Scalar::NUM_BITS as usize - 1 - NUM_COMPLETE_BITS;
+// Bits used in incomplete addition. k_{n-1} to k_{4} inclusive, for an n-bit scalar
+// field (k_{254} to k_{4} for Pallas).
+fn incomplete_len<C: EccCurve>() -> usize {
+    C::Scalar::NUM_BITS as usize - 1 - NUM_COMPLETE_BITS
+}
 
-// Bits k_{254} to k_{4} inclusive are used in incomplete addition.
-// The `hi` half is k_{254} to k_{130} inclusive (length 125 bits).
+// The `hi` half is k_{n-1} down to the midpoint (for Pallas, k_{254} to k_{130}
+// inclusive, length 125 bits).
 // (It is a coincidence that k_{130} matches the boundary of the
 // overflow check described in [the book](https://zcash.github.io/halo2/design/gadgets/ecc/var-base-scalar-mul.html#overflow-check).)
-const INCOMPLETE_HI_RANGE: Range<usize> = 0..INCOMPLETE_HI_LEN;
-const INCOMPLETE_HI_LEN: usize = INCOMPLETE_LEN / 2;
+fn incomplete_hi_range<C: EccCurve>() -> Range<usize> {
+    0..incomplete_len::<C>() / 2
+}
 
-// Bits k_{254} to k_{4} inclusive are used in incomplete addition.
-// The `lo` half is k_{129} to k_{4} inclusive (length 126 bits).
-const INCOMPLETE_LO_RANGE: Range<usize> = INCOMPLETE_HI_LEN..INCOMPLETE_LEN;
-const INCOMPLETE_LO_LEN: usize = INCOMPLETE_LEN - INCOMPLETE_HI_LEN;
+// The `lo` half is the rest, down to k_{4} (for Pallas, k_{129} to k_{4} inclusive,
+// length 126 bits).
+fn incomplete_lo_range<C: EccCurve>() -> Range<usize> {
+    incomplete_len::<C>() / 2..incomplete_len::<C>()
+}
 
 // Bits k_{3} to k_{1} inclusive are used in complete addition.
 // Bit k_{0} is handled separately.
-const COMPLETE_RANGE: Range<usize> = INCOMPLETE_LEN..(INCOMPLETE_LEN + NUM_COMPLETE_BITS);
+fn complete_range<C: EccCurve>() -> Range<usize> {
+    incomplete_len::<C>()..(incomplete_len::<C>() + NUM_COMPLETE_BITS)
+}
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub struct Config<Lookup: PallasLookupRangeCheck = PallasLookupRangeCheckConfig> {
+pub struct Config<C: EccCurve, Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>> {
     // Selector used to check switching logic on LSB
     q_mul_lsb: Selector,
     // Configuration used in complete addition
     add_config: add::Config,
     // Configuration used for `hi` bits of the scalar
-    hi_config: incomplete::Config<INCOMPLETE_HI_LEN>,
+    hi_config: incomplete::Config,
     // Configuration used for `lo` bits of the scalar
-    lo_config: incomplete::Config<INCOMPLETE_LO_LEN>,
+    lo_config: incomplete::Config,
     // Configuration used for complete addition part of double-and-add algorithm
     complete_config: complete::Config,
     // Configuration used to check for overflow
-    overflow_config: overflow::Config<Lookup>,
+    overflow_config: overflow::Config<C, Lookup>,
+    _marker: PhantomData<C>,
 }
 
-impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
+impl<C: EccCurve, Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>> Config<C, Lookup> {
     pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+        meta: &mut ConstraintSystem<C::Base>,
         add_config: add::Config,
         lookup_config: Lookup,
         advices: [Column<Advice>; 10],
@@ -85,6 +96,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
             lo_config,
             complete_config,
             overflow_config,
+            _marker: PhantomData,
         };
 
         config.create_gate(meta);
@@ -126,7 +138,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
         config
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
         // If `lsb` is 0, (x, y) = (x_p, -y_p). If `lsb` is 1, (x, y) = (0,0).
         // https://p.z.cash/halo2-0.1:ecc-var-mul-lsb-gate?partial
         meta.create_gate("LSB check", |meta| {
@@ -141,7 +153,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
 
             //    z_0 = 2 * z_1 + k_0
             // => k_0 = z_0 - 2 * z_1
-            let lsb = z_0 - z_1 * pallas::Base::from(2);
+            let lsb = z_0 - z_1 * C::Base::from(2);
 
             let bool_check = bool_check(lsb.clone());
 
@@ -163,25 +175,25 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
 
     pub(super) fn assign(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        alpha: AssignedCell<pallas::Base, pallas::Base>,
-        base: &NonIdentityEccPoint,
-    ) -> Result<(EccPoint, ScalarVar), Error> {
-        let (result, zs): (EccPoint, Vec<Z<pallas::Base>>) = layouter.assign_region(
+        mut layouter: impl Layouter<C::Base>,
+        alpha: AssignedCell<C::Base, C::Base>,
+        base: &NonIdentityEccPoint<C>,
+    ) -> Result<(EccPoint<C>, ScalarVar<C>), Error> {
+        let (result, zs): (EccPoint<C>, Vec<Z<C::Base>>) = layouter.assign_region(
             || "variable-base scalar mul",
             |mut region| {
                 let offset = 0;
 
                 // Case `base` into an `EccPoint` for later use.
-                let base_point: EccPoint = base.clone().into();
+                let base_point: EccPoint<C> = base.clone().into();
 
                 // Decompose `k = alpha + t_q` bitwise (big-endian bit order).
-                let bits = decompose_for_scalar_mul(alpha.value());
+                let bits = decompose_for_scalar_mul::<C>(alpha.value());
 
                 // Define ranges for each part of the algorithm.
-                let bits_incomplete_hi = &bits[INCOMPLETE_HI_RANGE];
-                let bits_incomplete_lo = &bits[INCOMPLETE_LO_RANGE];
-                let lsb = bits[pallas::Scalar::NUM_BITS as usize - 1];
+                let bits_incomplete_hi = &bits[incomplete_hi_range::<C>()];
+                let bits_incomplete_lo = &bits[incomplete_lo_range::<C>()];
+                let lsb = bits[C::Scalar::NUM_BITS as usize - 1];
 
                 // Initialize the accumulator `acc = [2]base` using complete addition.
                 let acc =
@@ -201,7 +213,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
                     || "z_init = 0",
                     self.hi_config.z,
                     offset,
-                    pallas::Base::zero(),
+                    C::Base::ZERO,
                 )?);
 
                 // Double-and-add (incomplete addition) for the `hi` half of the scalar decomposition
@@ -229,15 +241,15 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
                 // Then, the final assignment of double-and-add was made on row + offset + 1.
                 // Outside of incomplete addition, we must account for these offset increases by adding
                 // 2 to the incomplete addition length.
-                assert!(INCOMPLETE_LO_RANGE.len() >= INCOMPLETE_HI_RANGE.len());
-                let offset = offset + INCOMPLETE_LO_RANGE.len() + 2;
+                assert!(incomplete_lo_range::<C>().len() >= incomplete_hi_range::<C>().len());
+                let offset = offset + incomplete_lo_range::<C>().len() + 2;
 
                 // Complete addition
                 let (acc, zs_complete) = {
                     let z = zs_incomplete_lo.last().expect("should not be empty");
                     // Bits used in complete addition. k_{3} to k_{1} inclusive
                     // The LSB k_{0} is handled separately.
-                    let bits_complete = &bits[COMPLETE_RANGE];
+                    let bits_complete = &bits[complete_range::<C>()];
                     self.complete_config.assign_region(
                         &mut region,
                         offset,
@@ -250,7 +262,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
                 };
 
                 // Each iteration of the complete addition uses two rows.
-                let offset = offset + COMPLETE_RANGE.len() * 2;
+                let offset = offset + NUM_COMPLETE_BITS * 2;
 
                 // Process the least significant bit
                 let z_1 = zs_complete.last().unwrap().clone();
@@ -259,12 +271,11 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
                 #[cfg(test)]
                 // Check that the correct multiple is obtained.
                 {
+                    use super::base_to_scalar;
                     use group::Curve;
 
                     let base = base.point();
-                    let alpha = alpha
-                        .value()
-                        .map(|alpha| pallas::Scalar::from_repr(alpha.to_repr()).unwrap());
+                    let alpha = alpha.value().map(base_to_scalar::<C>);
                     let real_mul = base.zip(alpha).map(|(base, alpha)| base * alpha);
                     let result = result.point();
 
@@ -281,7 +292,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
                         .chain(zs_complete.into_iter())
                         .chain(Some(z_0))
                         .collect::<Vec<_>>();
-                    assert_eq!(zs.len(), pallas::Scalar::NUM_BITS as usize + 1);
+                    assert_eq!(zs.len(), C::Scalar::NUM_BITS as usize + 1);
 
                     // This reverses zs to give us [z_0, z_1, ..., z_{254}, z_{255}].
                     zs.reverse();
@@ -320,13 +331,13 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
     /// [Specification](https://p.z.cash/halo2-0.1:ecc-var-mul-lsb-gate?partial).
     fn process_lsb(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        base: &NonIdentityEccPoint,
-        acc: EccPoint,
-        z_1: Z<pallas::Base>,
+        base: &NonIdentityEccPoint<C>,
+        acc: EccPoint<C>,
+        z_1: Z<C::Base>,
         lsb: Value<bool>,
-    ) -> Result<(EccPoint, Z<pallas::Base>), Error> {
+    ) -> Result<(EccPoint<C>, Z<C::Base>), Error> {
         // Enforce switching logic on LSB using a custom gate
         self.q_mul_lsb.enable(region, offset)?;
 
@@ -334,8 +345,8 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
         // Assign z_0 = 2⋅z_1 + k_0
         let z_0 = {
             let z_0_val = z_1.value().zip(lsb).map(|(z_1, lsb)| {
-                let lsb = pallas::Base::from(lsb as u64);
-                z_1 * pallas::Base::from(2) + lsb
+                let lsb = C::Base::from(lsb as u64);
+                *z_1 * C::Base::from(2) + lsb
             });
             let z_0_cell = region.assign_advice(
                 || "z_0",
@@ -418,18 +429,18 @@ impl<F: Field> Deref for Z<F> {
 // https://p.z.cash/halo2-0.1:ecc-var-mul-witness-scalar?partial
 #[allow(clippy::assign_op_pattern)]
 #[allow(clippy::ptr_offset_with_cast)]
-fn decompose_for_scalar_mul(scalar: Value<&pallas::Base>) -> Vec<Value<bool>> {
+fn decompose_for_scalar_mul<C: EccCurve>(scalar: Value<&C::Base>) -> Vec<Value<bool>> {
     construct_uint! {
         struct U256(4);
     }
 
     let bitstring = scalar.map(|scalar| {
         // We use `k = scalar + t_q` in the double-and-add algorithm, where
-        // the scalar field `F_q = 2^254 + t_q`.
+        // the scalar field `F_q = 2^{n-1} + t_q` for an n-bit modulus.
         // Note that the addition `scalar + t_q` is not reduced.
         //
-        let scalar = U256::from_little_endian(&scalar.to_repr());
-        let t_q = U256::from_little_endian(&T_Q.to_le_bytes());
+        let scalar = U256::from_little_endian(scalar.to_repr().as_ref());
+        let t_q = U256::from_little_endian(C::t_q().to_repr().as_ref());
         let k = scalar + t_q;
 
         // Little-endian bit representation of `k`.
@@ -441,14 +452,14 @@ fn decompose_for_scalar_mul(scalar: Value<&pallas::Base>) -> Vec<Value<bool>> {
                 .flat_map(|byte| (0..8).map(move |shift| (byte >> shift) % 2 == 1))
         };
 
-        // Take the first 255 bits.
+        // Take the first n bits.
         bitstring
-            .take(pallas::Scalar::NUM_BITS as usize)
+            .take(C::Scalar::NUM_BITS as usize)
             .collect::<Vec<_>>()
     });
 
     // Transpose.
-    let mut bitstring = bitstring.transpose_vec(pallas::Scalar::NUM_BITS as usize);
+    let mut bitstring = bitstring.transpose_vec(C::Scalar::NUM_BITS as usize);
     // Reverse to get the big-endian bit representation.
     bitstring.reverse();
     bitstring
@@ -1,5 +1,5 @@
 use super::super::{add, EccPoint};
-use super::{COMPLETE_RANGE, X, Y, Z};
+use super::{NUM_COMPLETE_BITS, X, Y, Z};
 use crate::utilities::{bool_check, ternary};
 
 use halo2_proofs::{
@@ -8,7 +8,8 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
-use pasta_curves::pallas;
+use group::ff::PrimeField;
+use pasta_curves::arithmetic::CurveAffine;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct Config {
@@ -21,8 +22,8 @@ pub struct Config {
 }
 
 impl Config {
-    pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+    pub(super) fn configure<F: PrimeField>(
+        meta: &mut ConstraintSystem<F>,
         z_complete: Column<Advice>,
         add_config: add::Config,
     ) -> Self {
@@ -43,7 +44,7 @@ impl Config {
     /// This is used to check the bits used in complete addition, since the incomplete
     /// addition gate (controlled by `q_mul`) already checks scalar decomposition for
     /// the other bits.
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate<F: PrimeField>(&self, meta: &mut ConstraintSystem<F>) {
         // | y_p | z_complete |
         // --------------------
         // | y_p | z_{i + 1}  |
@@ -60,7 +61,7 @@ impl Config {
                 let z_next = meta.query_advice(self.z_complete, Rotation::next());
 
                 // k_{i} = z_{i} - 2⋅z_{i+1}
-                let k = z_next - Expression::Constant(pallas::Base::from(2)) * z_prev;
+                let k = z_next - Expression::Constant(F::from(2)) * z_prev;
                 // (k_i) ⋅ (1 - k_i) = 0
                 let bool_check = bool_check(k.clone());
 
@@ -84,22 +85,22 @@ impl Config {
     #[allow(clippy::type_complexity)]
     #[allow(non_snake_case)]
     #[allow(clippy::too_many_arguments)]
-    pub(super) fn assign_region(
+    pub(super) fn assign_region<C: CurveAffine>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
         bits: &[Value<bool>],
-        base: &EccPoint,
-        x_a: X<pallas::Base>,
-        y_a: Y<pallas::Base>,
-        z: Z<pallas::Base>,
-    ) -> Result<(EccPoint, Vec<Z<pallas::Base>>), Error> {
+        base: &EccPoint<C>,
+        x_a: X<C::Base>,
+        y_a: Y<C::Base>,
+        z: Z<C::Base>,
+    ) -> Result<(EccPoint<C>, Vec<Z<C::Base>>), Error> {
         // Make sure we have the correct number of bits for the complete addition
         // part of variable-base scalar mul.
-        assert_eq!(bits.len(), COMPLETE_RANGE.len());
+        assert_eq!(bits.len(), NUM_COMPLETE_BITS);
 
         // Enable selectors for complete range
-        for row in 0..COMPLETE_RANGE.len() {
+        for row in 0..NUM_COMPLETE_BITS {
             // Each iteration uses 2 rows (two complete additions)
             let row = 2 * row;
             // Check scalar decomposition for each iteration. Since the gate enabled by
@@ -123,7 +124,7 @@ impl Config {
         };
 
         // Store interstitial running sum `z`s in vector
-        let mut zs: Vec<Z<pallas::Base>> = Vec::with_capacity(bits.len());
+        let mut zs: Vec<Z<C::Base>> = Vec::with_capacity(bits.len());
 
         // Complete addition
         for (iter, k) in bits.iter().enumerate() {
@@ -139,8 +140,8 @@ impl Config {
             // Update `z`.
             z = {
                 // z_next = z_cur * 2 + k_next
-                let z_val = z.value() * Value::known(pallas::Base::from(2))
-                    + k.map(|k| pallas::Base::from(k as u64));
+                let z_val = z.value().copied() * Value::known(C::Base::from(2))
+                    + k.map(|k| C::Base::from(k as u64));
                 let z_cell =
                     region.assign_advice(|| "z", self.z_complete, row + offset + 2, || z_val)?;
                 Z(z_cell)
@@ -10,7 +10,7 @@ use halo2_proofs::{
     },
     poly::Rotation,
 };
-use pasta_curves::pallas;
+use pasta_curves::arithmetic::CurveAffine;
 
 /// A helper struct for implementing single-row double-and-add using incomplete addition.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -27,11 +27,11 @@ pub(crate) struct DoubleAndAdd {
 
 impl DoubleAndAdd {
     /// Derives the expression `x_r = lambda_1^2 - x_a - x_p`.
-    pub(crate) fn x_r(
+    pub(crate) fn x_r<F: PrimeField>(
         &self,
-        meta: &mut VirtualCells<pallas::Base>,
+        meta: &mut VirtualCells<F>,
         rotation: Rotation,
-    ) -> Expression<pallas::Base> {
+    ) -> Expression<F> {
         let x_a = meta.query_advice(self.x_a, rotation);
         let x_p = meta.query_advice(self.x_p, rotation);
         let lambda_1 = meta.query_advice(self.lambda_1, rotation);
@@ -43,11 +43,11 @@ impl DoubleAndAdd {
     /// Note that this is missing the factor of `1/2`; the Sinsemilla constraints factor
     /// it out, so we leave it up to the caller to handle it.
     #[allow(non_snake_case)]
-    pub(crate) fn Y_A(
+    pub(crate) fn Y_A<F: PrimeField>(
         &self,
-        meta: &mut VirtualCells<pallas::Base>,
+        meta: &mut VirtualCells<F>,
         rotation: Rotation,
-    ) -> Expression<pallas::Base> {
+    ) -> Expression<F> {
         let x_a = meta.query_advice(self.x_a, rotation);
         let lambda_1 = meta.query_advice(self.lambda_1, rotation);
         let lambda_2 = meta.query_advice(self.lambda_2, rotation);
@@ -56,7 +56,7 @@ impl DoubleAndAdd {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub(crate) struct Config<const NUM_BITS: usize> {
+pub(crate) struct Config {
     // Selector constraining the first row of incomplete addition.
     pub(super) q_mul_1: Selector,
     // Selector constraining the main loop of incomplete addition.
@@ -71,9 +71,9 @@ pub(crate) struct Config<const NUM_BITS: usize> {
     pub(super) y_p: Column<Advice>,
 }
 
-impl<const NUM_BITS: usize> Config<NUM_BITS> {
-    pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+impl Config {
+    pub(super) fn configure<F: PrimeField>(
+        meta: &mut ConstraintSystem<F>,
         z: Column<Advice>,
         x_a: Column<Advice>,
         x_p: Column<Advice>,
@@ -104,23 +104,22 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
     }
 
     // Gate for incomplete addition part of variable-base scalar multiplication.
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate<F: PrimeField>(&self, meta: &mut ConstraintSystem<F>) {
         // Closure to compute x_{R,i} = λ_{1,i}^2 - x_{A,i} - x_{P,i}
-        let x_r = |meta: &mut VirtualCells<pallas::Base>, rotation: Rotation| {
+        let x_r = |meta: &mut VirtualCells<F>, rotation: Rotation| {
             self.double_and_add.x_r(meta, rotation)
         };
 
         // Closure to compute y_{A,i} = (λ_{1,i} + λ_{2,i}) * (x_{A,i} - x_{R,i}) / 2
-        let y_a = |meta: &mut VirtualCells<pallas::Base>, rotation: Rotation| {
-            self.double_and_add.Y_A(meta, rotation) * pallas::Base::TWO_INV
+        let y_a = |meta: &mut VirtualCells<F>, rotation: Rotation| {
+            self.double_and_add.Y_A(meta, rotation) * F::TWO_INV
         };
 
         // Constraints used for q_mul_{2, 3} == 1
         // https://p.z.cash/halo2-0.1:ecc-var-mul-incomplete-main-loop?partial
         // https://p.z.cash/halo2-0.1:ecc-var-mul-incomplete-last-row?partial
-        let for_loop = |meta: &mut VirtualCells<pallas::Base>,
-                        y_a_next: Expression<pallas::Base>| {
-            let one = Expression::Constant(pallas::Base::one());
+        let for_loop = |meta: &mut VirtualCells<F>, y_a_next: Expression<F>| {
+            let one = Expression::Constant(F::ONE);
 
             // z_i
             let z_cur = meta.query_advice(self.z, Rotation::cur());
@@ -144,13 +143,13 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
             // The current bit in the scalar decomposition, k_i = z_i - 2⋅z_{i+1}.
             // Recall that we assigned the cumulative variable `z_i` in descending order,
             // i from n down to 0. So z_{i+1} corresponds to the `z_prev` query.
-            let k = z_cur - z_prev * pallas::Base::from(2);
+            let k = z_cur - z_prev * F::from(2);
             // Check booleanity of decomposition.
             let bool_check = bool_check(k.clone());
 
             // λ_{1,i}⋅(x_{A,i} − x_{P,i}) − y_{A,i} + (2k_i - 1) y_{P,i} = 0
             let gradient_1 = lambda1_cur * (x_a_cur.clone() - x_p_cur) - y_a_cur.clone()
-                + (k * pallas::Base::from(2) - one) * y_p_cur;
+                + (k * F::from(2) - one) * y_p_cur;
 
             // λ_{2,i}^2 − x_{A,i-1} − x_{R,i} − x_{A,i} = 0
             let secant_line = lambda2_cur.clone().square()
@@ -225,16 +224,16 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
     /// the boundary between halves.
     /// Returns (x, y, z).
     #[allow(clippy::type_complexity)]
-    pub(super) fn double_and_add(
+    pub(super) fn double_and_add<C: CurveAffine>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        base: &NonIdentityEccPoint,
+        base: &NonIdentityEccPoint<C>,
         bits: &[Value<bool>],
-        acc: (X<pallas::Base>, Y<pallas::Base>, Z<pallas::Base>),
-    ) -> Result<(X<pallas::Base>, Y<pallas::Base>, Vec<Z<pallas::Base>>), Error> {
-        // Check that we have the correct number of bits for this double-and-add.
-        assert_eq!(bits.len(), NUM_BITS);
+        acc: (X<C::Base>, Y<C::Base>, Z<C::Base>),
+    ) -> Result<(X<C::Base>, Y<C::Base>, Vec<Z<C::Base>>), Error> {
+        // The double-and-add uses one row per bit of the scalar.
+        let num_bits = bits.len();
 
         // Handle exceptional cases
         let (x_p, y_p) = (base.x.value().cloned(), base.y.value().cloned());
@@ -258,12 +257,12 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
 
             let offset = offset + 1;
             // q_mul_2 = 1 on all rows after offset 0, excluding the last row.
-            for idx in 0..(NUM_BITS - 1) {
+            for idx in 0..(num_bits - 1) {
                 self.q_mul_2.enable(region, offset + idx)?;
             }
 
             // q_mul_3 = 1 on the last row.
-            self.q_mul_3.enable(region, offset + NUM_BITS - 1)?;
+            self.q_mul_3.enable(region, offset + num_bits - 1)?;
         }
 
         // Initialise double-and-add
@@ -292,7 +291,7 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
         let offset = offset + 1;
 
         // Initialise vector to store all interstitial `z` running sum values.
-        let mut zs: Vec<Z<pallas::Base>> = Vec::with_capacity(bits.len());
+        let mut zs: Vec<Z<C::Base>> = Vec::with_capacity(bits.len());
 
         // Incomplete addition
         for (row, k) in bits.iter().enumerate() {
@@ -301,7 +300,7 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
             let z_val = z
                 .value()
                 .zip(k.as_ref())
-                .map(|(z_val, k)| pallas::Base::from(2) * z_val + pallas::Base::from(*k as u64));
+                .map(|(z_val, k)| C::Base::from(2) * z_val + C::Base::from(*k as u64));
             z = region.assign_advice(|| "z", self.z, row + offset, || z_val)?;
             zs.push(Z(z.clone()));
 
@@ -340,7 +339,7 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
                     .zip(x_a.value())
                     .zip(x_r)
                     .map(|(((lambda1, y_a), x_a), x_r)| {
-                        y_a * pallas::Base::from(2) * (x_a - x_r).invert() - lambda1
+                        y_a * C::Base::from(2) * (x_a - x_r).invert() - lambda1
                     });
             region.assign_advice(
                 || "lambda2",
@@ -365,7 +364,7 @@ impl<const NUM_BITS: usize> Config<NUM_BITS> {
         let y_a = region.assign_advice(
             || "y_a",
             self.double_and_add.lambda_1,
-            offset + NUM_BITS,
+            offset + num_bits,
             || y_a,
         )?;
 
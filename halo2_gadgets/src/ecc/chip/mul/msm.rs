@@ -0,0 +1,1195 @@
+//! Variable-base multi-scalar multiplication.
+//!
+//! Terms are multiplied in pairs using Shamir's trick. For a pair $[\alpha] P + [\beta] Q$,
+//! both scalars are decomposed exactly as in variable-base scalar multiplication, and a
+//! single double-and-add ladder processes a bit of each scalar per row:
+//!
+//! $$A_{i-1} = [2] A_i + [2k_{\alpha,i} - 1] P + [2k_{\beta,i} - 1] Q = [2] A_i \pm T_i,$$
+//!
+//! where $T_i$ is $P + Q$ if $k_{\alpha,i} = k_{\beta,i}$, and $P - Q$ otherwise. This uses
+//! one row of incomplete addition per bit for the pair, instead of one row per bit for
+//! each term. A merged double-and-add row costs the same as an addition row, so nothing
+//! further would be saved by sharing the accumulator between pairs; instead, the results
+//! of each pair (and of an unpaired final term, which uses ordinary variable-base scalar
+//! multiplication) are summed using complete addition.
+//!
+//! As for a single scalar, the final bits of both scalars are processed using complete
+//! addition, and each scalar's decomposition is subjected to the overflow check.
+//!
+//! Unlike a single ladder, the joint ladder would reach an exceptional case of
+//! incomplete addition whenever the discrete logarithm relation between $P$ and $Q$ is
+//! known (for example when $Q = \pm P$, for which $P + Q$ or $P - Q$ is the identity).
+//! To avoid this without making the layout depend on the witnessed values, the ladder is
+//! offset by a fixed point $S$, whose discrete logarithm relative to any other point is
+//! unknown. Each row adds $T_i + S$ instead of $T_i$, using a table of $(P \pm Q) + S$ and
+//! $(P \pm Q) - S$, and the accumulator starts from $[2]((P + Q) + S)$, so that after
+//! $j$ rows it is offset by $[3 \cdot 2^j - 1] S$. Reaching an exceptional case would
+//! then reveal a discrete logarithm relation between $S$ and the inputs. Each row also
+//! constrains the x-coordinates involved in both of its additions to be distinct, so
+//! that the ladder cannot be used to prove an incorrect result. The final offset is a
+//! constant, which is removed using complete addition before the bits using complete
+//! addition are processed.
+
+use super::{
+    super::{add, witness_point, EccCurve, EccPoint, NonIdentityEccPoint, ScalarVar},
+    complete_range, decompose_for_scalar_mul, incomplete_len, NUM_COMPLETE_BITS, X, Y, Z,
+};
+use crate::{
+    sinsemilla::primitives as sinsemilla,
+    utilities::{bool_check, lookup_range_check::LookupRangeCheck, ternary},
+};
+
+use ff::{Field, PrimeField};
+use group::Curve;
+use halo2_proofs::{
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+use pasta_curves::arithmetic::CurveExt;
+
+/// The personalization of the fixed point by which the joint double-and-add is offset.
+const OFFSET_PERSONALIZATION: &str = "halo2_gadgets:msm";
+
+/// The x- and y-coordinates of the accumulator, and the running sums of both scalars.
+type Accumulator<F> = (X<F>, Y<F>, [Vec<Z<F>>; 2]);
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Config<C: EccCurve, Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>> {
+    // Selector constraining the first row of the joint double-and-add.
+    q_msm_1: Selector,
+    // Selectors constraining the main loop of the joint double-and-add, on even and odd
+    // rows respectively.
+    q_msm_2: [Selector; 2],
+    // Selectors constraining the last row of the joint double-and-add, if it is an even
+    // or odd row respectively.
+    q_msm_3: [Selector; 2],
+    // Selector used to select `±base` for the bits using complete addition.
+    q_msm_complete: Selector,
+    // Selector used to select `-base` or the identity for the least significant bits.
+    q_msm_lsb: Selector,
+    // Cumulative sums used to decompose the two scalars.
+    z: [Column<Advice>; 2],
+    // x-coordinate of the accumulator in each double-and-add iteration.
+    x_a: Column<Advice>,
+    // lambda1 in each double-and-add iteration.
+    lambda_1: Column<Advice>,
+    // lambda2 in each double-and-add iteration.
+    lambda_2: Column<Advice>,
+    // Inverse witnessing that each double-and-add iteration is not exceptional.
+    inv: Column<Advice>,
+    // Coordinates of (P + Q) + S and (P - Q) + S on odd rows, and of (P + Q) - S and
+    // (P - Q) - S on even rows.
+    table: [Column<Advice>; 4],
+    // z_{i+1} and z_i for the bits using complete addition.
+    z_complete: [Column<Advice>; 2],
+    // Coordinates of the base for the bits using complete addition.
+    base: [Column<Advice>; 2],
+    // Configuration used in complete addition
+    add_config: add::Config,
+    // Configuration used to witness P - Q, and the offset
+    witness_point: witness_point::Config,
+    // Configuration used for an unpaired term, and for the overflow check
+    mul_config: super::Config<C, Lookup>,
+}
+
+impl<C: EccCurve, Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>> Config<C, Lookup> {
+    pub(crate) fn configure(
+        meta: &mut ConstraintSystem<C::Base>,
+        add_config: add::Config,
+        witness_point: witness_point::Config,
+        mul_config: super::Config<C, Lookup>,
+        advices: [Column<Advice>; 10],
+    ) -> Self {
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = Self {
+            q_msm_1: meta.selector(),
+            q_msm_2: [meta.selector(), meta.selector()],
+            q_msm_3: [meta.selector(), meta.selector()],
+            q_msm_complete: meta.selector(),
+            q_msm_lsb: meta.selector(),
+            z: [advices[0], advices[1]],
+            x_a: advices[2],
+            lambda_1: advices[3],
+            lambda_2: advices[4],
+            inv: advices[5],
+            table: [advices[6], advices[7], advices[8], advices[9]],
+            z_complete: [advices[4], advices[5]],
+            base: [advices[6], advices[7]],
+            add_config,
+            witness_point,
+            mul_config,
+        };
+
+        // The selected base is assigned on the row after the selection, into the input
+        // columns of the complete addition that uses it. The selection row is also the
+        // row on which the previous complete addition assigns its output.
+        let add_config_outputs = config.add_config.output_columns();
+        for column in config.z_complete.iter().chain(config.base.iter()) {
+            assert!(
+                !add_config_outputs.contains(column),
+                "selection columns cannot overlap with complete addition outputs."
+            );
+        }
+
+        config.create_gate(meta);
+
+        config
+    }
+
+    /// Returns the bits `(k_α, k_β)` decomposed on the row at `rotation`.
+    fn bits(
+        &self,
+        meta: &mut VirtualCells<C::Base>,
+        rotation: Rotation,
+    ) -> [Expression<C::Base>; 2] {
+        self.z.map(|z| {
+            // k_i = z_i - 2⋅z_{i+1}
+            let z_cur = meta.query_advice(z, rotation);
+            let z_prev = meta.query_advice(z, Rotation(rotation.0 - 1));
+            z_cur - z_prev * C::Base::from(2)
+        })
+    }
+
+    /// Returns the rotations, relative to a row of the given parity, of the rows on
+    /// which `(P ± Q) + S` and `(P ± Q) - S` are assigned.
+    fn table_rotations(parity: usize) -> (Rotation, Rotation) {
+        if parity == 1 {
+            (Rotation::cur(), Rotation::next())
+        } else {
+            (Rotation::next(), Rotation::cur())
+        }
+    }
+
+    /// Derives the point `T + S = [2k_α - 1] P + [2k_β - 1] Q + S` added on the row at
+    /// `rotation`, which has the given parity.
+    fn t(
+        &self,
+        meta: &mut VirtualCells<C::Base>,
+        rotation: Rotation,
+        parity: usize,
+    ) -> (Expression<C::Base>, Expression<C::Base>) {
+        let one = Expression::Constant(C::Base::ONE);
+        let [k_alpha, k_beta] = self.bits(meta, rotation);
+        let (plus, minus) = Self::table_rotations(parity);
+        let mut query_table = |table_rotation: Rotation| {
+            self.table
+                .map(|column| meta.query_advice(column, Rotation(rotation.0 + table_rotation.0)))
+        };
+        let [x_sum_plus, y_sum_plus, x_diff_plus, y_diff_plus] = query_table(plus);
+        let [x_sum_minus, y_sum_minus, x_diff_minus, y_diff_minus] = query_table(minus);
+
+        // T + S is
+        // - (P + Q) + S if k_α = k_β = 1,
+        // - (P - Q) + S if k_α = 1 and k_β = 0,
+        // - -((P + Q) - S) if k_α = k_β = 0,
+        // - -((P - Q) - S) if k_α = 0 and k_β = 1.
+        let both = k_alpha.clone() * k_beta.clone();
+        let alpha_only = k_alpha.clone() * (one.clone() - k_beta.clone());
+        let neither = (one.clone() - k_alpha.clone()) * (one.clone() - k_beta.clone());
+        let beta_only = (one - k_alpha) * k_beta;
+
+        let x_t = both.clone() * x_sum_plus
+            + alpha_only.clone() * x_diff_plus
+            + neither.clone() * x_sum_minus
+            + beta_only.clone() * x_diff_minus;
+        let y_t = both * y_sum_plus + alpha_only * y_diff_plus
+            - neither * y_sum_minus
+            - beta_only * y_diff_minus;
+
+        (x_t, y_t)
+    }
+
+    /// Derives the expression `x_r = lambda_1^2 - x_a - x_t`.
+    fn x_r(
+        &self,
+        meta: &mut VirtualCells<C::Base>,
+        rotation: Rotation,
+        parity: usize,
+    ) -> Expression<C::Base> {
+        let x_a = meta.query_advice(self.x_a, rotation);
+        let lambda_1 = meta.query_advice(self.lambda_1, rotation);
+        let (x_t, _) = self.t(meta, rotation, parity);
+        lambda_1.square() - x_a - x_t
+    }
+
+    /// Derives the expression `y_a = (lambda_1 + lambda_2) * (x_a - x_r) / 2`.
+    fn y_a(
+        &self,
+        meta: &mut VirtualCells<C::Base>,
+        rotation: Rotation,
+        parity: usize,
+    ) -> Expression<C::Base> {
+        let x_a = meta.query_advice(self.x_a, rotation);
+        let lambda_1 = meta.query_advice(self.lambda_1, rotation);
+        let lambda_2 = meta.query_advice(self.lambda_2, rotation);
+        (lambda_1 + lambda_2) * (x_a - self.x_r(meta, rotation, parity)) * C::Base::TWO_INV
+    }
+
+    fn create_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
+        // Constraints used for q_msm_{2, 3} == 1 on a row of the given parity
+        let for_loop = |meta: &mut VirtualCells<C::Base>,
+                        parity: usize,
+                        y_a_next: Expression<C::Base>| {
+            let one = Expression::Constant(C::Base::ONE);
+
+            // x_{A,i}
+            let x_a_cur = meta.query_advice(self.x_a, Rotation::cur());
+            // x_{A,i-1}
+            let x_a_next = meta.query_advice(self.x_a, Rotation::next());
+            // λ_{1,i}
+            let lambda1_cur = meta.query_advice(self.lambda_1, Rotation::cur());
+            // λ_{2,i}
+            let lambda2_cur = meta.query_advice(self.lambda_2, Rotation::cur());
+            let inv = meta.query_advice(self.inv, Rotation::cur());
+
+            let [k_alpha, k_beta] = self.bits(meta, Rotation::cur());
+            let (x_t, y_t) = self.t(meta, Rotation::cur(), parity);
+            let x_r = self.x_r(meta, Rotation::cur(), parity);
+            let y_a_cur = self.y_a(meta, Rotation::cur(), parity);
+
+            // λ_{1,i}⋅(x_{A,i} − x_{T,i}) − y_{A,i} + y_{T,i} = 0
+            let gradient_1 = lambda1_cur * (x_a_cur.clone() - x_t.clone()) - y_a_cur.clone() + y_t;
+
+            // λ_{2,i}^2 − x_{A,i-1} − x_{R,i} − x_{A,i} = 0
+            let secant_line =
+                lambda2_cur.clone().square() - x_a_next.clone() - x_r.clone() - x_a_cur.clone();
+
+            // λ_{2,i}⋅(x_{A,i} − x_{A,i-1}) − y_{A,i} − y_{A,i-1} = 0
+            let gradient_2 = lambda2_cur * (x_a_cur.clone() - x_a_next) - y_a_cur - y_a_next;
+
+            // (x_{A,i} − x_{T,i})⋅(x_{A,i} − x_{R,i})⋅inv_i = 1, which rules out both
+            // exceptional cases of incomplete addition.
+            let non_exceptional = (x_a_cur.clone() - x_t) * (x_a_cur - x_r) * inv - one;
+
+            vec![
+                ("bool_check k_alpha", bool_check(k_alpha)),
+                ("bool_check k_beta", bool_check(k_beta)),
+                ("gradient_1", gradient_1),
+                ("secant_line", secant_line),
+                ("gradient_2", gradient_2),
+                ("non_exceptional", non_exceptional),
+            ]
+        };
+
+        meta.create_gate("q_msm_1 == 1 checks", |meta| {
+            let q_msm_1 = meta.query_selector(self.q_msm_1);
+            let one = Expression::Constant(C::Base::ONE);
+
+            // The first row of the double-and-add is odd.
+            let y_a_next = self.y_a(meta, Rotation::next(), 1);
+            let y_a_witnessed = meta.query_advice(self.lambda_1, Rotation::cur());
+
+            // None of the points in the table, which are assigned on the following two
+            // rows, is the identity (0, 0). This also means that none of them has a
+            // y-coordinate of zero.
+            let y_table = [1, 3]
+                .into_iter()
+                .flat_map(|i| {
+                    [Rotation::next(), Rotation(2)]
+                        .map(|rotation| meta.query_advice(self.table[i], rotation))
+                })
+                .fold(one.clone(), |acc, y| acc * y);
+            let inv = meta.query_advice(self.inv, Rotation::cur());
+
+            Constraints::with_selector(
+                q_msm_1,
+                [
+                    ("init y_a", y_a_witnessed - y_a_next),
+                    ("non-identity table", y_table * inv - one),
+                ],
+            )
+        });
+
+        for (parity, (loop_name, last_name)) in [
+            (
+                "q_msm_2 == 1 checks on even rows",
+                "q_msm_3 == 1 checks on even rows",
+            ),
+            (
+                "q_msm_2 == 1 checks on odd rows",
+                "q_msm_3 == 1 checks on odd rows",
+            ),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            meta.create_gate(loop_name, |meta| {
+                let q_msm_2 = meta.query_selector(self.q_msm_2[parity]);
+
+                let y_a_next = self.y_a(meta, Rotation::next(), 1 - parity);
+
+                // The table remains constant throughout the double-and-add.
+                let table_check = self
+                    .table
+                    .iter()
+                    .map(|column| {
+                        meta.query_advice(*column, Rotation::cur())
+                            - meta.query_advice(*column, Rotation(2))
+                    })
+                    .collect::<Vec<_>>();
+
+                Constraints::with_selector(
+                    q_msm_2,
+                    ["x_sum_check", "y_sum_check", "x_diff_check", "y_diff_check"]
+                        .into_iter()
+                        .zip(table_check)
+                        .chain(for_loop(meta, parity, y_a_next)),
+                )
+            });
+
+            meta.create_gate(last_name, |meta| {
+                let q_msm_3 = meta.query_selector(self.q_msm_3[parity]);
+                let y_a_final = meta.query_advice(self.lambda_1, Rotation::next());
+                Constraints::with_selector(q_msm_3, for_loop(meta, parity, y_a_final))
+            });
+        }
+
+        // | z_{i+1} | z_i | base_x | base_y |
+        // | x_p     | y_p |
+        let select = |meta: &mut VirtualCells<C::Base>| {
+            let z_prev = meta.query_advice(self.z_complete[0], Rotation::cur());
+            let z_cur = meta.query_advice(self.z_complete[1], Rotation::cur());
+            let base_x = meta.query_advice(self.base[0], Rotation::cur());
+            let base_y = meta.query_advice(self.base[1], Rotation::cur());
+            let x_p = meta.query_advice(self.add_config.x_p, Rotation::next());
+            let y_p = meta.query_advice(self.add_config.y_p, Rotation::next());
+
+            // k_i = z_i - 2⋅z_{i+1}
+            let k = z_cur - z_prev * C::Base::from(2);
+            (k, base_x, base_y, x_p, y_p)
+        };
+
+        meta.create_gate("select ±base for complete bits of msm", |meta| {
+            let q_msm_complete = meta.query_selector(self.q_msm_complete);
+            let (k, base_x, base_y, x_p, y_p) = select(meta);
+
+            // k_i = 0 => (x_p, y_p) = (base_x, -base_y)
+            // k_i = 1 => (x_p, y_p) = (base_x, base_y)
+            let x_switch = x_p - base_x;
+            let y_switch = ternary(k.clone(), y_p.clone() - base_y.clone(), y_p + base_y);
+
+            Constraints::with_selector(
+                q_msm_complete,
+                [
+                    ("bool_check", bool_check(k)),
+                    ("x_switch", x_switch),
+                    ("y_switch", y_switch),
+                ],
+            )
+        });
+
+        meta.create_gate("select -base or identity for LSB of msm", |meta| {
+            let q_msm_lsb = meta.query_selector(self.q_msm_lsb);
+            let (k, base_x, base_y, x_p, y_p) = select(meta);
+
+            // k_0 = 0 => (x_p, y_p) = (base_x, -base_y)
+            // k_0 = 1 => (x_p, y_p) = (0, 0)
+            let lsb_x = ternary(k.clone(), x_p.clone(), x_p - base_x);
+            let lsb_y = ternary(k.clone(), y_p.clone(), y_p + base_y);
+
+            Constraints::with_selector(
+                q_msm_lsb,
+                [
+                    ("bool_check", bool_check(k)),
+                    ("lsb_x", lsb_x),
+                    ("lsb_y", lsb_y),
+                ],
+            )
+        });
+    }
+
+    pub(crate) fn assign(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        terms: &[(ScalarVar<C>, NonIdentityEccPoint<C>)],
+    ) -> Result<EccPoint<C>, Error> {
+        // Full-width scalars are not supported by variable-base scalar multiplication.
+        let terms = terms
+            .iter()
+            .map(|(scalar, base)| match scalar {
+                ScalarVar::BaseFieldElem(scalar) => Ok((scalar.clone(), base)),
+                ScalarVar::FullWidth => Err(Error::Synthesis),
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut acc: Option<EccPoint<C>> = None;
+        for (i, pair) in terms.chunks(2).enumerate() {
+            let result = match pair {
+                [(alpha, p), (beta, q)] => self.assign_pair(
+                    layouter.namespace(|| format!("terms {} and {}", 2 * i, 2 * i + 1)),
+                    (alpha.clone(), p),
+                    (beta.clone(), q),
+                )?,
+                [(alpha, p)] => {
+                    self.mul_config
+                        .assign(
+                            layouter.namespace(|| format!("term {}", 2 * i)),
+                            alpha.clone(),
+                            p,
+                        )?
+                        .0
+                }
+                _ => unreachable!(),
+            };
+
+            acc = Some(match acc {
+                Some(acc) => self.add(layouter.namespace(|| "accumulate terms"), &acc, &result)?,
+                None => result,
+            });
+        }
+
+        Ok(acc.expect("terms is not empty"))
+    }
+
+    /// Returns `a + b`, using complete addition.
+    fn add(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        a: &EccPoint<C>,
+        b: &EccPoint<C>,
+    ) -> Result<EccPoint<C>, Error> {
+        layouter.assign_region(
+            || "complete addition",
+            |mut region| self.add_config.assign_region(a, b, 0, &mut region),
+        )
+    }
+
+    /// Returns the fixed point `S` by which the joint double-and-add is offset.
+    fn offset() -> C::CurveExt {
+        C::CurveExt::hash_to_curve(OFFSET_PERSONALIZATION)(b"S")
+    }
+
+    /// Returns `[alpha] P + [beta] Q`.
+    #[allow(non_snake_case)]
+    fn assign_pair(
+        &self,
+        mut layouter: impl Layouter<C::Base>,
+        (alpha, P): (AssignedCell<C::Base, C::Base>, &NonIdentityEccPoint<C>),
+        (beta, Q): (AssignedCell<C::Base, C::Base>, &NonIdentityEccPoint<C>),
+    ) -> Result<EccPoint<C>, Error> {
+        let bases = [P, Q];
+        let bits = [
+            decompose_for_scalar_mul::<C>(alpha.value()),
+            decompose_for_scalar_mul::<C>(beta.value()),
+        ];
+        let offset = Self::offset();
+
+        // P + Q, using complete addition.
+        let sum = layouter.assign_region(
+            || "P + Q",
+            |mut region| {
+                self.add_config
+                    .assign_region(&P.clone().into(), &Q.clone().into(), 0, &mut region)
+            },
+        )?;
+
+        // P - Q is witnessed, and constrained by checking that (P - Q) + Q = P. Both
+        // P + Q and P - Q are the identity if Q = -P or Q = P respectively.
+        let diff = layouter.assign_region(
+            || "P - Q",
+            |mut region| {
+                let value = P
+                    .point()
+                    .zip(Q.point())
+                    .map(|(p, q)| (p.to_curve() - q).to_affine());
+                let diff = self.witness_point.point(value, 0, &mut region)?;
+
+                let p = self
+                    .add_config
+                    .assign_region(&diff, &Q.clone().into(), 1, &mut region)?;
+                region.constrain_equal(p.x().cell(), P.x().cell())?;
+                region.constrain_equal(p.y().cell(), P.y().cell())?;
+
+                Ok(diff)
+            },
+        )?;
+
+        // (P ± Q) + S and (P ± Q) - S, using complete addition.
+        let (plus, minus) = layouter.assign_region(
+            || "(P ± Q) ± S",
+            |mut region| {
+                let s = self
+                    .witness_point
+                    .constant_point(offset.to_affine(), 0, &mut region)?;
+                let neg_s =
+                    self.witness_point
+                        .constant_point((-offset).to_affine(), 1, &mut region)?;
+
+                // Each complete addition uses two rows.
+                let mut row = 2;
+                let mut add = |a: &EccPoint<C>, b: &EccPoint<C>| {
+                    let result = self.add_config.assign_region(a, b, row, &mut region);
+                    row += 2;
+                    result
+                };
+                let plus = [add(&sum, &s)?, add(&diff, &s)?];
+                let minus = [add(&sum, &neg_s)?, add(&diff, &neg_s)?];
+                Ok((plus, minus))
+            },
+        )?;
+
+        // Initialize the accumulator `acc = [2]((P + Q) + S)` using complete addition.
+        let acc = layouter.assign_region(
+            || "[2]((P + Q) + S)",
+            |mut region| {
+                self.add_config
+                    .assign_region(&plus[0], &plus[0], 0, &mut region)
+            },
+        )?;
+
+        // Joint double-and-add (incomplete addition) for bits k_{n-1} to k_{4}.
+        let (x_a, y_a, zs_incomplete) = layouter.assign_region(
+            || "joint double-and-add",
+            |mut region| {
+                self.double_and_add(
+                    &mut region,
+                    (&plus, &minus),
+                    [
+                        &bits[0][..incomplete_len::<C>()],
+                        &bits[1][..incomplete_len::<C>()],
+                    ],
+                    &acc,
+                )
+            },
+        )?;
+
+        // Remove the offset `[3⋅2^n - 1] S` accumulated over the n rows of the joint
+        // double-and-add, using complete addition.
+        let acc = layouter.assign_region(
+            || "remove offset",
+            |mut region| {
+                let rows = C::Scalar::from(2).pow_vartime([incomplete_len::<C>() as u64]);
+                let accumulated = offset * (C::Scalar::from(3) * rows - C::Scalar::ONE);
+                let neg_accumulated = self.witness_point.constant_point(
+                    (-accumulated).to_affine(),
+                    0,
+                    &mut region,
+                )?;
+
+                let acc = EccPoint::from_coordinates_unchecked(x_a.0.clone(), y_a.0.clone());
+                self.add_config
+                    .assign_region(&acc, &neg_accumulated, 1, &mut region)
+            },
+        )?;
+
+        // Complete addition for bits k_{3} to k_{0}.
+        let (result, zs_complete) = layouter.assign_region(
+            || "complete bits",
+            |mut region| {
+                self.complete(
+                    &mut region,
+                    bases,
+                    [
+                        &bits[0][complete_range::<C>()],
+                        &bits[1][complete_range::<C>()],
+                    ],
+                    [
+                        bits[0][C::Scalar::NUM_BITS as usize - 1],
+                        bits[1][C::Scalar::NUM_BITS as usize - 1],
+                    ],
+                    acc.clone(),
+                    [
+                        zs_incomplete[0].last().unwrap().clone(),
+                        zs_incomplete[1].last().unwrap().clone(),
+                    ],
+                )
+            },
+        )?;
+
+        #[cfg(test)]
+        // Check that the correct linear combination is obtained.
+        {
+            use super::super::base_to_scalar;
+
+            let alpha_val = alpha.value().map(base_to_scalar::<C>);
+            let beta_val = beta.value().map(base_to_scalar::<C>);
+            let real_msm = P
+                .point()
+                .zip(alpha_val)
+                .zip(Q.point().zip(beta_val))
+                .map(|((p, alpha), (q, beta))| (p * alpha + q * beta).to_affine());
+
+            real_msm
+                .zip(result.point())
+                .assert_if_known(|(real_msm, result)| real_msm == result);
+        }
+
+        for (i, (scalar, (zs_incomplete, zs_complete))) in [alpha, beta]
+            .into_iter()
+            .zip(zs_incomplete.into_iter().zip(zs_complete))
+            .enumerate()
+        {
+            // This reverses zs to give us [z_0, z_1, ..., z_{254}, z_{255}].
+            let zs = zs_incomplete
+                .into_iter()
+                .chain(zs_complete)
+                .rev()
+                .collect::<Vec<_>>();
+            assert_eq!(zs.len(), C::Scalar::NUM_BITS as usize + 1);
+
+            self.mul_config.overflow_config.overflow_check(
+                layouter.namespace(|| format!("overflow check {}", i)),
+                scalar,
+                &zs,
+            )?;
+        }
+
+        Ok(result)
+    }
+
+    /// Processes the bits in the incomplete addition range of both scalars.
+    ///
+    /// ```text
+    /// |  z_α  |  z_β  |  x_a  |  λ1  |  λ2  | inv |          table           |
+    /// ------------------------------------------------------------------------
+    /// |   0   |   0   |       | y_a  |      | inv |                          | q_msm_1
+    /// | z_254 | z_254 | x_a   |  λ1  |  λ2  | inv | (P + Q) + S, (P - Q) + S | q_msm_2[1]
+    /// | z_253 | z_253 | x_a   |  λ1  |  λ2  | inv | (P + Q) - S, (P - Q) - S | q_msm_2[0]
+    /// |  ...  |  ...  |  ...  | ...  | ...  | ... |           ...            | q_msm_2[..]
+    /// |  z_4  |  z_4  | x_a   |  λ1  |  λ2  | inv |           ...            | q_msm_3[..]
+    /// |       |       | x_a   | y_a  |      |     |           ...            |
+    /// ```
+    ///
+    /// Returns the accumulator, and the running sums `[z_255, ..., z_4]` of each scalar.
+    fn double_and_add(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        (plus, minus): (&[EccPoint<C>; 2], &[EccPoint<C>; 2]),
+        bits: [&[Value<bool>]; 2],
+        acc: &EccPoint<C>,
+    ) -> Result<Accumulator<C::Base>, Error> {
+        let num_bits = incomplete_len::<C>();
+        assert!(bits.iter().all(|bits| bits.len() == num_bits));
+
+        // The coordinates of the points in the table, in the order in which they are
+        // assigned.
+        fn table<C: EccCurve>(
+            points: &[EccPoint<C>; 2],
+        ) -> [&AssignedCell<Assigned<C::Base>, C::Base>; 4] {
+            [&points[0].x, &points[0].y, &points[1].x, &points[1].y]
+        }
+        let table_values = |points: &[EccPoint<C>; 2]| -> Value<[Assigned<C::Base>; 4]> {
+            let [x_s, y_s, x_d, y_d] = table(points).map(|cell| cell.value().cloned());
+            x_s.zip(y_s)
+                .zip(x_d.zip(y_d))
+                .map(|((x_s, y_s), (x_d, y_d))| [x_s, y_s, x_d, y_d])
+        };
+        let plus_values = table_values(plus);
+        let minus_values = table_values(minus);
+
+        // Handle exceptional cases
+        plus_values
+            .zip(minus_values)
+            .error_if_known_and(|(plus, minus)| {
+                // One of the points in the table is the identity
+                [plus[1], plus[3], minus[1], minus[3]]
+                    .iter()
+                    .any(|y| y.is_zero_vartime())
+            })?;
+
+        // Set q_msm values
+        {
+            self.q_msm_1.enable(region, 0)?;
+            for row in 1..num_bits {
+                self.q_msm_2[row % 2].enable(region, row)?;
+            }
+            self.q_msm_3[num_bits % 2].enable(region, num_bits)?;
+        }
+
+        // Initialise the running sums for both scalars to zero.
+        let mut zs: [Vec<Z<C::Base>>; 2] = [vec![], vec![]];
+        for (zs, z) in zs.iter_mut().zip(self.z) {
+            let z_init =
+                region.assign_advice_from_constant(|| "z_init = 0", z, 0, C::Base::ZERO)?;
+            zs.push(Z(z_init));
+        }
+
+        let mut x_a = acc.x.copy_advice(|| "starting x_a", region, self.x_a, 1)?;
+        let mut y_a = acc
+            .y
+            .copy_advice(|| "starting y_a", region, self.lambda_1, 0)?
+            .value()
+            .cloned();
+
+        let inv = plus_values
+            .zip(minus_values)
+            .map(|(plus, minus)| (plus[1] * plus[3] * minus[1] * minus[3]).invert());
+        region.assign_advice(|| "inv", self.inv, 0, || inv)?;
+
+        // Copy the table into the first two rows of the double-and-add, and assign it to
+        // the others.
+        for (row, points) in [(1, plus), (2, minus)] {
+            for (cell, column) in table(points).into_iter().zip(self.table) {
+                cell.copy_advice(|| "table", region, column, row)?;
+            }
+        }
+        for row in 3..=num_bits + 1 {
+            let values = if row % 2 == 1 {
+                plus_values
+            } else {
+                minus_values
+            };
+            for (i, column) in self.table.into_iter().enumerate() {
+                region.assign_advice(|| "table", column, row, || values.map(|values| values[i]))?;
+            }
+        }
+
+        for (idx, (k_alpha, k_beta)) in bits[0].iter().zip(bits[1].iter()).enumerate() {
+            let row = idx + 1;
+
+            // z_{i} = 2 * z_{i+1} + k_i
+            for ((zs, column), k) in zs.iter_mut().zip(self.z).zip([k_alpha, k_beta]) {
+                let z_val = zs
+                    .last()
+                    .unwrap()
+                    .value()
+                    .zip(k.as_ref())
+                    .map(|(z, k)| C::Base::from(2) * z + C::Base::from(*k as u64));
+                let z = region.assign_advice(|| "z", column, row, || z_val)?;
+                zs.push(Z(z));
+            }
+
+            // T + S = (P ± Q) + S if k_α is set, and -((P ± Q) - S) otherwise, where
+            // P ± Q is P + Q if the bits are equal, and P - Q otherwise.
+            let t = k_alpha.zip(*k_beta).zip(plus_values.zip(minus_values)).map(
+                |((k_alpha, k_beta), (plus, minus))| {
+                    let i = if k_alpha == k_beta { 0 } else { 2 };
+                    if k_alpha {
+                        (plus[i], plus[i + 1])
+                    } else {
+                        (minus[i], -minus[i + 1])
+                    }
+                },
+            );
+            let (x_t, y_t) = (t.map(|t| t.0), t.map(|t| t.1));
+
+            // Compute and assign λ1⋅(x_A − x_T) = y_A − y_T
+            let lambda1 = y_a
+                .zip(y_t)
+                .zip(x_a.value())
+                .zip(x_t)
+                .map(|(((y_a, y_t), x_a), x_t)| (y_a - y_t) * (*x_a - x_t).invert());
+            region.assign_advice(|| "lambda1", self.lambda_1, row, || lambda1)?;
+
+            // x_R = λ1^2 - x_A - x_T
+            let x_r = lambda1
+                .zip(x_a.value())
+                .zip(x_t)
+                .map(|((lambda1, x_a), x_t)| lambda1.square() - x_a - x_t);
+
+            // Handle exceptional cases
+            x_a.value()
+                .zip(x_t)
+                .zip(x_r)
+                .error_if_known_and(|((x_a, x_t), x_r)| {
+                    // x_A = x_T or x_A = x_R
+                    **x_a == *x_t || **x_a == *x_r
+                })?;
+
+            // λ2 = (2(y_A) / (x_A - x_R)) - λ1
+            let lambda2 =
+                lambda1
+                    .zip(y_a)
+                    .zip(x_a.value())
+                    .zip(x_r)
+                    .map(|(((lambda1, y_a), x_a), x_r)| {
+                        y_a * C::Base::from(2) * (*x_a - x_r).invert() - lambda1
+                    });
+            region.assign_advice(|| "lambda2", self.lambda_2, row, || lambda2)?;
+
+            let inv = x_a
+                .value()
+                .zip(x_t)
+                .zip(x_r)
+                .map(|((x_a, x_t), x_r)| ((*x_a - x_t) * (*x_a - x_r)).invert());
+            region.assign_advice(|| "inv", self.inv, row, || inv)?;
+
+            // Compute and assign `x_a` for the next row
+            let x_a_new = lambda2.square() - x_a.value() - x_r;
+            y_a = lambda2 * (x_a.value() - x_a_new) - y_a;
+            x_a = region.assign_advice(|| "x_a", self.x_a, row + 1, || x_a_new)?;
+        }
+
+        // Witness final y_a
+        let y_a = region.assign_advice(|| "y_a", self.lambda_1, num_bits + 1, || y_a)?;
+
+        Ok((X(x_a), Y(y_a), zs))
+    }
+
+    /// Processes the bits in the complete addition range of both scalars, followed by
+    /// their least significant bits.
+    ///
+    /// Each bit in the complete addition range uses five rows:
+    /// ```text
+    /// | x_p | y_p | x_qr  | y_qr  | complete addition | z_{i+1} | z_i | base_x | base_y |
+    /// -------------------------------------------------------------------------------------
+    /// |     |     | acc_x | acc_y |                   | z_{i+1} | z_i |  P_x   |  P_y   | q_msm_complete
+    /// | U_x | U_y | acc_x | acc_y |  acc + U          |
+    /// |acc_x|acc_y|acc+U_x|acc+U_y|  acc + (acc + U)  |
+    /// |     |     | acc_x | acc_y |                   | z_{i+1} | z_i |  Q_x   |  Q_y   | q_msm_complete
+    /// | V_x | V_y | acc_x | acc_y |  acc + V          |
+    /// ```
+    ///
+    /// Returns the result, and the running sums `[z_3, ..., z_0]` of each scalar.
+    #[allow(clippy::type_complexity)]
+    fn complete(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        bases: [&NonIdentityEccPoint<C>; 2],
+        bits: [&[Value<bool>]; 2],
+        lsb: [Value<bool>; 2],
+        mut acc: EccPoint<C>,
+        mut z: [Z<C::Base>; 2],
+    ) -> Result<(EccPoint<C>, [Vec<Z<C::Base>>; 2]), Error> {
+        assert!(bits.iter().all(|bits| bits.len() == NUM_COMPLETE_BITS));
+
+        let mut zs: [Vec<Z<C::Base>>; 2] = [vec![], vec![]];
+        let mut offset = 0;
+
+        for (k_alpha, k_beta) in bits[0].iter().zip(bits[1].iter()) {
+            // acc = (acc + U) + acc, where U = ±P
+            let (u, z_alpha) = self.select(region, offset, false, bases[0], &z[0], *k_alpha)?;
+            zs[0].push(z_alpha.clone());
+            z[0] = z_alpha;
+            let tmp_acc = self
+                .add_config
+                .assign_region(&u, &acc, offset + 1, region)?;
+            acc = self
+                .add_config
+                .assign_region(&acc, &tmp_acc, offset + 2, region)?;
+
+            // acc = acc + V, where V = ±Q
+            let (v, z_beta) = self.select(region, offset + 3, false, bases[1], &z[1], *k_beta)?;
+            zs[1].push(z_beta.clone());
+            z[1] = z_beta;
+            acc = self
+                .add_config
+                .assign_region(&v, &acc, offset + 4, region)?;
+
+            offset += 5;
+        }
+
+        // acc = acc + U, where U = -base if the LSB is not set, and the identity otherwise.
+        for (i, (base, lsb)) in bases.into_iter().zip(lsb).enumerate() {
+            let (u, z_0) = self.select(region, offset, true, base, &z[i], lsb)?;
+            zs[i].push(z_0);
+            acc = self
+                .add_config
+                .assign_region(&u, &acc, offset + 1, region)?;
+
+            offset += 2;
+        }
+
+        Ok((acc, zs))
+    }
+
+    /// Selects the point added to the accumulator for bit `k_i` of a scalar, given
+    /// `z_{i+1}`. Returns the selected point, assigned on the row after `offset`, and
+    /// `z_i`.
+    fn select(
+        &self,
+        region: &mut Region<'_, C::Base>,
+        offset: usize,
+        is_lsb: bool,
+        base: &NonIdentityEccPoint<C>,
+        z_prev: &Z<C::Base>,
+        k: Value<bool>,
+    ) -> Result<(EccPoint<C>, Z<C::Base>), Error> {
+        if is_lsb {
+            self.q_msm_lsb.enable(region, offset)?;
+        } else {
+            self.q_msm_complete.enable(region, offset)?;
+        }
+
+        z_prev.copy_advice(|| "z_{i+1}", region, self.z_complete[0], offset)?;
+        let z_val = z_prev
+            .value()
+            .zip(k)
+            .map(|(z, k)| C::Base::from(2) * z + C::Base::from(k as u64));
+        let z = Z(region.assign_advice(|| "z_i", self.z_complete[1], offset, || z_val)?);
+
+        base.x
+            .copy_advice(|| "base_x", region, self.base[0], offset)?;
+        base.y
+            .copy_advice(|| "base_y", region, self.base[1], offset)?;
+
+        let (x_b, y_b) = (base.x.value().cloned(), base.y.value().cloned());
+        let point = x_b.zip(y_b).zip(k).map(|((x, y), k)| match (is_lsb, k) {
+            // The identity.
+            (true, true) => (Assigned::Zero, Assigned::Zero),
+            // -base
+            (_, false) => (x, -y),
+            // base
+            (false, true) => (x, y),
+        });
+
+        let x = region.assign_advice(
+            || "x_p",
+            self.add_config.x_p,
+            offset + 1,
+            || point.map(|p| p.0),
+        )?;
+        let y = region.assign_advice(
+            || "y_p",
+            self.add_config.y_p,
+            offset + 1,
+            || point.map(|p| p.1),
+        )?;
+
+        Ok((EccPoint::from_coordinates_unchecked(x, y), z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use group::{ff::Field, Curve, Group};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{self, Advice, Circuit, Column, ConstraintSystem, Error},
+        poly::commitment::Params,
+    };
+    use pasta_curves::{pallas, vesta};
+    use rand::rngs::OsRng;
+
+    use crate::{
+        ecc::{
+            chip::{base_to_scalar, EccChip, EccConfig},
+            tests::TestFixedBases,
+            NonIdentityPoint, Point, ScalarVar,
+        },
+        test_circuits::test_utils::Proof,
+        utilities::{
+            lookup_range_check::{LookupRangeCheck, PallasLookupRangeCheckConfig},
+            UtilitiesInstructions,
+        },
+    };
+
+    type Chip = EccChip<TestFixedBases>;
+
+    /// A term of a multi-scalar multiplication, as a scalar and a base.
+    type Term = (Value<pallas::Base>, Value<pallas::Affine>);
+
+    /// Returns a term with the given scalar and base.
+    fn term(scalar: pallas::Base, base: pallas::Affine) -> Term {
+        (Value::known(scalar), Value::known(base))
+    }
+
+    /// Witnesses the given terms, and checks that their multi-scalar multiplication is
+    /// the natively computed point.
+    fn msm(
+        chip: &Chip,
+        mut layouter: impl Layouter<pallas::Base>,
+        column: Column<Advice>,
+        terms: &[Term],
+    ) -> Result<(), Error> {
+        let terms_var = terms
+            .iter()
+            .map(|(scalar, base)| {
+                let scalar = chip.load_private(layouter.namespace(|| "scalar"), column, *scalar)?;
+                let scalar = ScalarVar::from_base(
+                    chip.clone(),
+                    layouter.namespace(|| "ScalarVar from_base"),
+                    &scalar,
+                )?;
+                let base =
+                    NonIdentityPoint::new(chip.clone(), layouter.namespace(|| "base"), *base)?;
+                Ok((scalar, base))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let result = Point::msm(chip.clone(), layouter.namespace(|| "msm"), &terms_var)?;
+
+        let expected = terms.iter().fold(
+            Value::known(pallas::Point::identity()),
+            |acc, (scalar, base)| {
+                acc.zip(scalar.zip(*base)).map(|(acc, (scalar, base))| {
+                    acc + base * base_to_scalar::<pallas::Affine>(&scalar)
+                })
+            },
+        );
+        let expected = Point::new(
+            chip.clone(),
+            layouter.namespace(|| "expected"),
+            expected.map(|expected| expected.to_affine()),
+        )?;
+        result.constrain_equal(layouter.namespace(|| "constrain result"), &expected)
+    }
+
+    /// Configures the chip, with the gates for multi-scalar multiplication if `with_msm`
+    /// is set.
+    fn configure(
+        meta: &mut ConstraintSystem<pallas::Base>,
+        with_msm: bool,
+    ) -> EccConfig<TestFixedBases> {
+        let advices = [(); 10].map(|_| meta.advice_column());
+        let lookup_table = meta.lookup_table_column();
+        let lagrange_coeffs = [(); 8].map(|_| meta.fixed_column());
+        let constants = meta.fixed_column();
+        meta.enable_constant(constants);
+
+        let range_check = PallasLookupRangeCheckConfig::configure(meta, advices[9], lookup_table);
+        if with_msm {
+            Chip::configure_with_msm(meta, advices, lagrange_coeffs, range_check)
+        } else {
+            Chip::configure(meta, advices, lagrange_coeffs, range_check)
+        }
+    }
+
+    struct MsmCircuit<const WITH_MSM: bool>;
+
+    impl<const WITH_MSM: bool> Circuit<pallas::Base> for MsmCircuit<WITH_MSM> {
+        type Config = EccConfig<TestFixedBases>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MsmCircuit
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            configure(meta, WITH_MSM)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let chip = Chip::construct(config.clone());
+            let column = config.advices[0];
+            config.lookup_config.load_range_check_table(&mut layouter)?;
+
+            let random_point = || pallas::Point::random(OsRng).to_affine();
+            let random_scalar = || pallas::Base::random(OsRng);
+            let p = random_point();
+
+            // A pair of random terms.
+            msm(
+                &chip,
+                layouter.namespace(|| "[a]P + [b]Q"),
+                column,
+                &[
+                    term(random_scalar(), p),
+                    term(random_scalar(), random_point()),
+                ],
+            )?;
+
+            // Scalars at the edges of the range, and an unpaired term.
+            msm(
+                &chip,
+                layouter.namespace(|| "[0]P + [-1]Q + [c]R"),
+                column,
+                &[
+                    term(pallas::Base::zero(), p),
+                    term(-pallas::Base::one(), random_point()),
+                    term(random_scalar(), random_point()),
+                ],
+            )?;
+
+            // A pair of zero scalars, whose result is the identity.
+            msm(
+                &chip,
+                layouter.namespace(|| "[0]P + [0]Q"),
+                column,
+                &[
+                    term(pallas::Base::zero(), p),
+                    term(pallas::Base::zero(), random_point()),
+                ],
+            )?;
+
+            // No terms.
+            msm(&chip, layouter.namespace(|| "no terms"), column, &[])?;
+
+            // Bases with a known discrete logarithm relation.
+            for terms in exceptional_terms() {
+                msm(
+                    &chip,
+                    layouter.namespace(|| "exceptional terms"),
+                    column,
+                    &terms,
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Returns `[a]P + [b](-P)` and `[a]P + [b]P + [c]P`, for which `P + Q` or `P - Q`
+    /// is the identity in the first pair of terms.
+    fn exceptional_terms() -> [Vec<Term>; 2] {
+        let p = pallas::Point::random(OsRng).to_affine();
+        let random_scalar = || pallas::Base::random(OsRng);
+        [
+            vec![term(random_scalar(), p), term(random_scalar(), -p)],
+            vec![
+                term(random_scalar(), p),
+                term(random_scalar(), p),
+                term(random_scalar(), p),
+            ],
+        ]
+    }
+
+    /// A circuit computing a single multi-scalar multiplication of the given terms.
+    struct TermsCircuit(Vec<Term>);
+
+    impl Circuit<pallas::Base> for TermsCircuit {
+        type Config = EccConfig<TestFixedBases>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            TermsCircuit(vec![(Value::unknown(), Value::unknown()); self.0.len()])
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            configure(meta, true)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            let chip = Chip::construct(config.clone());
+            let column = config.advices[0];
+            config.lookup_config.load_range_check_table(&mut layouter)?;
+            msm(&chip, layouter.namespace(|| "msm"), column, &self.0)
+        }
+    }
+
+    #[test]
+    fn msm_chip() {
+        let prover = MockProver::run(13, &MsmCircuit::<true>, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn msm_chip_without_msm_gates() {
+        let prover = MockProver::run(13, &MsmCircuit::<false>, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn msm_chip_exceptional_proofs() {
+        const K: u32 = 11;
+        let params: Params<vesta::Affine> = Params::new(K);
+
+        for terms in exceptional_terms() {
+            // The keys do not depend on the witnessed terms.
+            let circuit = TermsCircuit(terms);
+            let vk = plonk::keygen_vk(&params, &circuit.without_witnesses()).unwrap();
+
+            let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            let proof = Proof::create(&vk, &params, circuit).unwrap();
+            assert!(proof.verify(&vk, &params).is_ok());
+        }
+    }
+}
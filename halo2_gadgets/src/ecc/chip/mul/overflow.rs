@@ -1,33 +1,32 @@
-use super::{T_Q, Z};
+use super::super::EccCurve;
+use super::Z;
 use crate::{
-    sinsemilla::primitives as sinsemilla,
-    utilities::lookup_range_check::{PallasLookupRangeCheck, PallasLookupRangeCheckConfig},
+    sinsemilla::primitives as sinsemilla, utilities::lookup_range_check::LookupRangeCheck,
 };
 
-use group::ff::PrimeField;
+use group::ff::{Field, PrimeField};
 use halo2_proofs::circuit::AssignedCell;
 use halo2_proofs::{
     circuit::Layouter,
     plonk::{Advice, Assigned, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
-use pasta_curves::pallas;
-
-use std::iter;
+use std::{iter, marker::PhantomData};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub struct Config<Lookup: PallasLookupRangeCheck = PallasLookupRangeCheckConfig> {
+pub struct Config<C: EccCurve, Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>> {
     // Selector to check z_0 = alpha + t_q (mod p)
     q_mul_overflow: Selector,
     // 10-bit lookup table
     lookup_config: Lookup,
     // Advice columns
     advices: [Column<Advice>; 3],
+    _marker: PhantomData<C>,
 }
 
-impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
+impl<C: EccCurve, Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>> Config<C, Lookup> {
     pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+        meta: &mut ConstraintSystem<C::Base>,
         lookup_config: Lookup,
         advices: [Column<Advice>; 3],
     ) -> Self {
@@ -39,6 +38,7 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
             q_mul_overflow: meta.selector(),
             lookup_config,
             advices,
+            _marker: PhantomData,
         };
 
         config.create_gate(meta);
@@ -46,21 +46,27 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
         config
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
         // https://p.z.cash/halo2-0.1:ecc-var-mul-overflow
         meta.create_gate("overflow checks", |meta| {
             let q_mul_overflow = meta.query_selector(self.q_mul_overflow);
 
+            // The scalar field modulus is q = 2^{n-1} + t_q, for an n-bit modulus
+            // (n = 255 for Pallas).
+            let n = C::Scalar::NUM_BITS as u64;
+
             // Constant expressions
-            let one = Expression::Constant(pallas::Base::one());
-            let two_pow_124 = Expression::Constant(pallas::Base::from_u128(1 << 124));
-            let two_pow_130 =
-                two_pow_124.clone() * Expression::Constant(pallas::Base::from_u128(1 << 6));
+            let one = Expression::Constant(C::Base::ONE);
+            // 2^{n-131} (2^124 for Pallas)
+            let two_pow_n_minus_131 = Expression::Constant(C::Base::from(2).pow([n - 131]));
+            let two_pow_130 = two_pow_n_minus_131.clone()
+                * Expression::Constant(C::Base::from(2).pow([130 - (n - 131)]));
 
             let z_0 = meta.query_advice(self.advices[0], Rotation::prev());
             let z_130 = meta.query_advice(self.advices[0], Rotation::cur());
             let eta = meta.query_advice(self.advices[0], Rotation::next());
 
+            // The most significant bit k_{n-1} (k_254 for Pallas).
             let k_254 = meta.query_advice(self.advices[1], Rotation::prev());
             let alpha = meta.query_advice(self.advices[1], Rotation::cur());
 
@@ -70,15 +76,14 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
             let s = meta.query_advice(self.advices[2], Rotation::cur());
             let s_check = s - (alpha.clone() + k_254.clone() * two_pow_130);
 
-            // q = 2^254 + t_q is the Pallas scalar field modulus.
             // We cast t_q into the base field to check alpha + t_q (mod p).
-            let t_q = Expression::Constant(pallas::Base::from_u128(T_Q));
+            let t_q = Expression::Constant(C::t_q());
 
             // z_0 - alpha - t_q = 0 (mod p)
             let recovery = z_0 - alpha - t_q;
 
-            // k_254 * (z_130 - 2^124) = 0
-            let lo_zero = k_254.clone() * (z_130.clone() - two_pow_124);
+            // k_254 * (z_130 - 2^{n-131}) = 0
+            let lo_zero = k_254.clone() * (z_130.clone() - two_pow_n_minus_131);
 
             // k_254 * s_minus_lo_130 = 0
             let s_minus_lo_130_check = k_254.clone() * s_minus_lo_130.clone();
@@ -100,20 +105,23 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
 
     pub(super) fn overflow_check(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        alpha: AssignedCell<pallas::Base, pallas::Base>,
-        zs: &[Z<pallas::Base>], // [z_0, z_1, ..., z_{254}, z_{255}]
+        mut layouter: impl Layouter<C::Base>,
+        alpha: AssignedCell<C::Base, C::Base>,
+        zs: &[Z<C::Base>], // [z_0, z_1, ..., z_{n-1}, z_n]
     ) -> Result<(), Error> {
+        // The most significant bit of k is k_{n-1} = z_{n-1} (k_254 for Pallas).
+        let msb = C::Scalar::NUM_BITS as usize - 1;
+
         // s = alpha + k_254 ⋅ 2^130 is witnessed here, and then copied into
         // the decomposition as well as the overflow check gate.
         // In the overflow check gate, we check that s is properly derived
         // from alpha and k_254.
         let s = {
-            let k_254 = zs[254].clone();
+            let k_254 = zs[msb].clone();
             let s_val = alpha
                 .value()
                 .zip(k_254.value())
-                .map(|(alpha, k_254)| alpha + k_254 * pallas::Base::from_u128(1 << 65).square());
+                .map(|(alpha, k_254)| *alpha + *k_254 * C::Base::from_u128(1 << 65).square());
 
             layouter.assign_region(
                 || "s = alpha + k_254 ⋅ 2^130",
@@ -158,8 +166,8 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
                     )?;
                 }
 
-                // Copy `k_254` = z_254
-                zs[254].copy_advice(|| "copy k_254", &mut region, self.advices[1], offset)?;
+                // Copy `k_254` = z_{n-1}
+                zs[msb].copy_advice(|| "copy k_254", &mut region, self.advices[1], offset)?;
 
                 // Copy original alpha
                 alpha.copy_advice(
@@ -189,9 +197,9 @@ impl<Lookup: PallasLookupRangeCheck> Config<Lookup> {
 
     fn s_minus_lo_130(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        s: AssignedCell<pallas::Base, pallas::Base>,
-    ) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
+        mut layouter: impl Layouter<C::Base>,
+        s: AssignedCell<C::Base, C::Base>,
+    ) -> Result<AssignedCell<C::Base, C::Base>, Error> {
         // Number of k-bit words we can use in the lookup decomposition.
         let num_words = 130 / sinsemilla::K;
         assert!(num_words * sinsemilla::K == 130);
@@ -1,6 +1,7 @@
 use super::{
-    add, add_incomplete, EccBaseFieldElemFixed, EccScalarFixed, EccScalarFixedShort, FixedPoint,
-    NonIdentityEccPoint, FIXED_BASE_WINDOW_SIZE, H,
+    add, add_incomplete, EccBaseFieldElemFixed, EccCurve, EccScalarFixed, EccScalarFixedShort,
+    FixedPoint, FixedScalarKind, NonIdentityEccPoint, FIXED_BASE_WINDOW_SIZE,
+    MAX_FIXED_BASE_WINDOW_SIZE,
 };
 use crate::utilities::decompose_running_sum::RunningSumConfig;
 
@@ -18,27 +19,18 @@ use halo2_proofs::{
     },
     poly::Rotation,
 };
-use lazy_static::lazy_static;
-use pasta_curves::{arithmetic::CurveAffine, pallas};
 
 pub mod base_field_elem;
 pub mod full_width;
 pub mod short;
 
-lazy_static! {
-    static ref TWO_SCALAR: pallas::Scalar = pallas::Scalar::from(2);
-    // H = 2^3 (3-bit window)
-    static ref H_SCALAR: pallas::Scalar = pallas::Scalar::from(H as u64);
-    static ref H_BASE: pallas::Base = pallas::Base::from(H as u64);
-}
-
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Config<FixedPoints: super::FixedPoints<pallas::Affine>> {
-    running_sum_config: RunningSumConfig<pallas::Base, FIXED_BASE_WINDOW_SIZE>,
+pub struct Config<C: EccCurve, FixedPoints: super::FixedPoints<C>, const WINDOW_SIZE: usize> {
+    running_sum_config: RunningSumConfig<C::Base, WINDOW_SIZE>,
     // The fixed Lagrange interpolation coefficients for `x_p`.
-    lagrange_coeffs: [Column<Fixed>; H],
-    // The fixed `z` for each window such that `y + z = u^2`.
-    fixed_z: Column<Fixed>,
+    lagrange_coeffs: Vec<Column<Fixed>>,
+    // The fixed columns used to check `y_p`.
+    y_check: YCheck,
     // Decomposition of an `n-1`-bit scalar into `k`-bit windows:
     // a = a_0 + 2^k(a_1) + 2^{2k}(a_2) + ... + 2^{(n-1)k}(a_{n-1})
     window: Column<Advice>,
@@ -51,26 +43,70 @@ pub struct Config<FixedPoints: super::FixedPoints<pallas::Affine>> {
     _marker: PhantomData<FixedPoints>,
 }
 
-impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
+/// The check used to constrain the $y$-coordinate of each window's multiple of the
+/// fixed base.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum YCheck {
+    /// Check that `y + z = u^2`, where `z` is fixed for each window and `u` is witnessed.
+    ///
+    /// Suitable `z` values can only practically be found for windows of at most
+    /// `FIXED_BASE_WINDOW_SIZE` bits.
+    ZsAndUs {
+        // The fixed `z` for each window such that `y + z = u^2`.
+        fixed_z: Column<Fixed>,
+    },
+    /// Interpolate `y` in the same way as `x`.
+    Interpolated {
+        // The fixed Lagrange interpolation coefficients for `y_p`.
+        lagrange_coeffs: Vec<Column<Fixed>>,
+    },
+}
+
+impl<C: EccCurve, FixedPoints: super::FixedPoints<C>, const WINDOW_SIZE: usize>
+    Config<C, FixedPoints, WINDOW_SIZE>
+{
+    /// The number of values each window can take, i.e. `2^WINDOW_SIZE`.
+    const H: usize = 1 << WINDOW_SIZE;
+
     #[allow(clippy::too_many_arguments)]
     pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
-        lagrange_coeffs: [Column<Fixed>; H],
+        meta: &mut ConstraintSystem<C::Base>,
+        lagrange_coeffs: Vec<Column<Fixed>>,
         window: Column<Advice>,
         u: Column<Advice>,
         add_config: add::Config,
         add_incomplete_config: add_incomplete::Config,
     ) -> Self {
+        assert!(
+            (FIXED_BASE_WINDOW_SIZE..=MAX_FIXED_BASE_WINDOW_SIZE).contains(&WINDOW_SIZE),
+            "Unsupported fixed-base window size."
+        );
+        assert_eq!(
+            lagrange_coeffs.len(),
+            Self::H,
+            "Fixed-base scalar multiplication requires 2^WINDOW_SIZE Lagrange coefficient columns."
+        );
+
         meta.enable_equality(window);
         meta.enable_equality(u);
 
         let q_running_sum = meta.selector();
         let running_sum_config = RunningSumConfig::configure(meta, q_running_sum, window);
 
+        let y_check = if WINDOW_SIZE <= FIXED_BASE_WINDOW_SIZE {
+            YCheck::ZsAndUs {
+                fixed_z: meta.fixed_column(),
+            }
+        } else {
+            YCheck::Interpolated {
+                lagrange_coeffs: (0..Self::H).map(|_| meta.fixed_column()).collect(),
+            }
+        };
+
         let config = Self {
             running_sum_config,
             lagrange_coeffs,
-            fixed_z: meta.fixed_column(),
+            y_check,
             window,
             u,
             add_config,
@@ -110,9 +146,9 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
     /// helpers, which decompose the scalar using a running sum.
     ///
     /// This gate is not used in the mul_fixed::full_width helper, since the full-width
-    /// scalar is witnessed directly as three-bit windows instead of being decomposed
+    /// scalar is witnessed directly as `WINDOW_SIZE`-bit windows instead of being decomposed
     /// via a running sum.
-    fn running_sum_coords_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn running_sum_coords_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
         meta.create_gate("Running sum coordinates check", |meta| {
             let q_mul_fixed_running_sum =
                 meta.query_selector(self.running_sum_config.q_range_check());
@@ -120,9 +156,9 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
             let z_cur = meta.query_advice(self.window, Rotation::cur());
             let z_next = meta.query_advice(self.window, Rotation::next());
 
-            //    z_{i+1} = (z_i - a_i) / 2^3
-            // => a_i = z_i - z_{i+1} * 2^3
-            let word = z_cur - z_next * pallas::Base::from(H as u64);
+            //    z_{i+1} = (z_i - a_i) / 2^w
+            // => a_i = z_i - z_{i+1} * 2^w
+            let word = z_cur - z_next * C::Base::from(Self::H as u64);
 
             Constraints::with_selector(q_mul_fixed_running_sum, self.coords_check(meta, word))
         });
@@ -132,91 +168,133 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
     #[allow(clippy::op_ref)]
     fn coords_check(
         &self,
-        meta: &mut VirtualCells<'_, pallas::Base>,
-        window: Expression<pallas::Base>,
-    ) -> Vec<(&'static str, Expression<pallas::Base>)> {
+        meta: &mut VirtualCells<'_, C::Base>,
+        window: Expression<C::Base>,
+    ) -> Vec<(&'static str, Expression<C::Base>)> {
         let y_p = meta.query_advice(self.add_config.y_p, Rotation::cur());
         let x_p = meta.query_advice(self.add_config.x_p, Rotation::cur());
-        let z = meta.query_fixed(self.fixed_z);
-        let u = meta.query_advice(self.u, Rotation::cur());
 
-        let window_pow: Vec<Expression<pallas::Base>> = (0..H)
+        match &self.y_check {
+            YCheck::ZsAndUs { fixed_z } => {
+                let z = meta.query_fixed(*fixed_z);
+                let u = meta.query_advice(self.u, Rotation::cur());
+
+                let interpolated_x = Self::interpolate(meta, &window, &self.lagrange_coeffs);
+
+                // Check interpolation of x-coordinate
+                let x_check = interpolated_x - x_p.clone();
+                // Check that `y + z = u^2`, where `z` is fixed and `u`, `y` are witnessed
+                let y_check = u.square() - y_p.clone() - z;
+                // Check that (x, y) is on the curve
+                let on_curve =
+                    y_p.square() - x_p.clone().square() * x_p - Expression::Constant(C::b());
+
+                vec![
+                    ("check x", x_check),
+                    ("check y", y_check),
+                    ("on-curve", on_curve),
+                ]
+            }
+            YCheck::Interpolated { lagrange_coeffs } => {
+                let interpolated_x = Self::interpolate(meta, &window, &self.lagrange_coeffs);
+                let interpolated_y = Self::interpolate(meta, &window, lagrange_coeffs);
+
+                // Both coordinates are interpolated from the window table, so (x, y) is
+                // necessarily on the curve.
+                vec![
+                    ("check x", interpolated_x - x_p),
+                    ("check y", interpolated_y - y_p),
+                ]
+            }
+        }
+    }
+
+    /// Evaluates the polynomial with the given fixed coefficients at `window`.
+    fn interpolate(
+        meta: &mut VirtualCells<'_, C::Base>,
+        window: &Expression<C::Base>,
+        coeffs: &[Column<Fixed>],
+    ) -> Expression<C::Base> {
+        let window_pow: Vec<Expression<C::Base>> = (0..Self::H)
             .map(|pow| {
-                (0..pow).fold(Expression::Constant(pallas::Base::one()), |acc, _| {
+                (0..pow).fold(Expression::Constant(C::Base::ONE), |acc, _| {
                     acc * window.clone()
                 })
             })
             .collect();
 
-        let interpolated_x = window_pow.iter().zip(self.lagrange_coeffs.iter()).fold(
-            Expression::Constant(pallas::Base::zero()),
+        window_pow.iter().zip(coeffs.iter()).fold(
+            Expression::Constant(C::Base::ZERO),
             |acc, (window_pow, coeff)| acc + (window_pow.clone() * meta.query_fixed(*coeff)),
-        );
-
-        // Check interpolation of x-coordinate
-        let x_check = interpolated_x - x_p.clone();
-        // Check that `y + z = u^2`, where `z` is fixed and `u`, `y` are witnessed
-        let y_check = u.square() - y_p.clone() - z;
-        // Check that (x, y) is on the curve
-        let on_curve =
-            y_p.square() - x_p.clone().square() * x_p - Expression::Constant(pallas::Affine::b());
-
-        vec![
-            ("check x", x_check),
-            ("check y", y_check),
-            ("on-curve", on_curve),
-        ]
+        )
     }
 
     #[allow(clippy::type_complexity)]
-    fn assign_region_inner<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    fn assign_region_inner<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        scalar: &ScalarFixed,
+        scalar: &ScalarFixed<C>,
         base: &F,
         coords_check_toggle: Selector,
-    ) -> Result<(NonIdentityEccPoint, NonIdentityEccPoint), Error> {
+    ) -> Result<(NonIdentityEccPoint<C>, NonIdentityEccPoint<C>), Error> {
+        let num_windows = F::FixedScalarKind::num_windows::<C>(WINDOW_SIZE);
+
         // Assign fixed columns for given fixed base
-        self.assign_fixed_constants::<F, NUM_WINDOWS>(region, offset, base, coords_check_toggle)?;
+        self.assign_fixed_constants(region, offset, num_windows, base, coords_check_toggle)?;
 
         // Initialize accumulator
-        let acc = self.initialize_accumulator::<F, NUM_WINDOWS>(region, offset, base, scalar)?;
+        let acc = self.initialize_accumulator(region, offset, num_windows, base, scalar)?;
 
         // Process all windows excluding least and most significant windows
-        let acc = self.add_incomplete::<F, NUM_WINDOWS>(region, offset, acc, base, scalar)?;
+        let acc = self.add_incomplete(region, offset, num_windows, acc, base, scalar)?;
 
         // Process most significant window
-        let mul_b = self.process_msb::<F, NUM_WINDOWS>(region, offset, base, scalar)?;
+        let mul_b = self.process_msb(region, offset, num_windows, base, scalar)?;
 
         Ok((acc, mul_b))
     }
 
     /// [Specification](https://p.z.cash/halo2-0.1:ecc-fixed-mul-load-base).
-    fn assign_fixed_constants<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    fn assign_fixed_constants<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
+        num_windows: usize,
         base: &F,
         coords_check_toggle: Selector,
     ) -> Result<(), Error> {
         let mut constants = None;
         let build_constants = || {
-            let lagrange_coeffs = base.lagrange_coeffs();
-            assert_eq!(lagrange_coeffs.len(), NUM_WINDOWS);
-
-            let z = base.z();
-            assert_eq!(z.len(), NUM_WINDOWS);
-
-            (lagrange_coeffs, z)
+            let (lagrange_coeffs, z, lagrange_coeffs_y) = match self.y_check {
+                YCheck::ZsAndUs { .. } => {
+                    let lagrange_coeffs: Vec<Vec<C::Base>> = base
+                        .lagrange_coeffs()
+                        .iter()
+                        .map(|coeffs| coeffs.to_vec())
+                        .collect();
+                    let z = base.z();
+                    assert_eq!(z.len(), num_windows);
+                    (lagrange_coeffs, z, vec![])
+                }
+                YCheck::Interpolated { .. } => {
+                    let (lagrange_coeffs, lagrange_coeffs_y) =
+                        base.wide_lagrange_coeffs(WINDOW_SIZE);
+                    assert_eq!(lagrange_coeffs_y.len(), num_windows);
+                    (lagrange_coeffs, vec![], lagrange_coeffs_y)
+                }
+            };
+            assert_eq!(lagrange_coeffs.len(), num_windows);
+
+            (lagrange_coeffs, z, lagrange_coeffs_y)
         };
 
         // Assign fixed columns for given fixed base
-        for window in 0..NUM_WINDOWS {
+        for window in 0..num_windows {
             coords_check_toggle.enable(region, window + offset)?;
 
             // Assign x-coordinate Lagrange interpolation coefficients
-            for k in 0..H {
+            for (k, coeff) in self.lagrange_coeffs.iter().enumerate() {
                 region.assign_fixed(
                     || {
                         format!(
@@ -224,7 +302,7 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
                             window, k
                         )
                     },
-                    self.lagrange_coeffs[k],
+                    *coeff,
                     window + offset,
                     || {
                         if constants.as_ref().is_none() {
@@ -236,34 +314,57 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
                 )?;
             }
 
-            // Assign z-values for each window
-            region.assign_fixed(
-                || format!("z-value for window: {:?}", window),
-                self.fixed_z,
-                window + offset,
-                || {
-                    let z = &constants.as_ref().unwrap().1;
-                    Value::known(pallas::Base::from(z[window]))
-                },
-            )?;
+            match &self.y_check {
+                YCheck::ZsAndUs { fixed_z } => {
+                    // Assign z-values for each window
+                    region.assign_fixed(
+                        || format!("z-value for window: {:?}", window),
+                        *fixed_z,
+                        window + offset,
+                        || {
+                            let z = &constants.as_ref().unwrap().1;
+                            Value::known(C::Base::from(z[window]))
+                        },
+                    )?;
+                }
+                YCheck::Interpolated { lagrange_coeffs } => {
+                    // Assign y-coordinate Lagrange interpolation coefficients
+                    for (k, coeff) in lagrange_coeffs.iter().enumerate() {
+                        region.assign_fixed(
+                            || {
+                                format!(
+                                    "y Lagrange interpolation coeff for window: {:?}, k: {:?}",
+                                    window, k
+                                )
+                            },
+                            *coeff,
+                            window + offset,
+                            || {
+                                let lagrange_coeffs_y = &constants.as_ref().unwrap().2;
+                                Value::known(lagrange_coeffs_y[window][k])
+                            },
+                        )?;
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
     /// Assigns the values used to process a window.
-    fn process_window<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    #[allow(clippy::too_many_arguments)]
+    fn process_window<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
+        num_windows: usize,
         w: usize,
         k_usize: Value<usize>,
-        window_scalar: Value<pallas::Scalar>,
+        window_scalar: Value<C::Scalar>,
         base: &F,
-    ) -> Result<NonIdentityEccPoint, Error> {
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
         let base_value = base.generator();
-        let base_u = base.u();
-        assert_eq!(base_u.len(), NUM_WINDOWS);
 
         // Compute [window_scalar]B
         let mul_b = {
@@ -272,7 +373,7 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
 
             let x = mul_b.map(|mul_b| {
                 let x = *mul_b.x();
-                assert!(x != pallas::Base::zero());
+                assert!(x != C::Base::ZERO);
                 x.into()
             });
             let x = region.assign_advice(
@@ -284,7 +385,7 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
 
             let y = mul_b.map(|mul_b| {
                 let y = *mul_b.y();
-                assert!(y != pallas::Base::zero());
+                assert!(y != C::Base::ZERO);
                 y.into()
             });
             let y = region.assign_advice(
@@ -298,55 +399,62 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
         };
 
         // Assign u = (y_p + z_w).sqrt()
-        let u_val = k_usize.map(|k| pallas::Base::from_repr(base_u[w][k]).unwrap());
-        region.assign_advice(|| "u", self.u, offset + w, || u_val)?;
+        if let YCheck::ZsAndUs { .. } = self.y_check {
+            let base_u = base.u();
+            assert_eq!(base_u.len(), num_windows);
+
+            let u_val = k_usize.map(|k| C::Base::from_repr(base_u[w][k]).unwrap());
+            region.assign_advice(|| "u", self.u, offset + w, || u_val)?;
+        }
 
         Ok(mul_b)
     }
 
-    fn initialize_accumulator<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    fn initialize_accumulator<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
+        num_windows: usize,
         base: &F,
-        scalar: &ScalarFixed,
-    ) -> Result<NonIdentityEccPoint, Error> {
+        scalar: &ScalarFixed<C>,
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
         // Recall that the message at each window `w` is represented as
-        // `m_w = [(k_w + 2) ⋅ 8^w]B`.
+        // `m_w = [(k_w + 2) ⋅ H^w]B`.
         // When `w = 0`, we have `m_0 = [(k_0 + 2)]B`.
         let w = 0;
-        let k0 = scalar.windows_field()[0];
-        let k0_usize = scalar.windows_usize()[0];
-        self.process_lower_bits::<_, NUM_WINDOWS>(region, offset, w, k0, k0_usize, base)
+        let k0 = scalar.windows_field(WINDOW_SIZE)[0];
+        let k0_usize = scalar.windows_usize(WINDOW_SIZE)[0];
+        self.process_lower_bits(region, offset, num_windows, w, k0, k0_usize, base)
     }
 
-    fn add_incomplete<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    fn add_incomplete<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        mut acc: NonIdentityEccPoint,
+        num_windows: usize,
+        mut acc: NonIdentityEccPoint<C>,
         base: &F,
-        scalar: &ScalarFixed,
-    ) -> Result<NonIdentityEccPoint, Error> {
-        let scalar_windows_field = scalar.windows_field();
-        let scalar_windows_usize = scalar.windows_usize();
-        assert_eq!(scalar_windows_field.len(), NUM_WINDOWS);
+        scalar: &ScalarFixed<C>,
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
+        let scalar_windows_field = scalar.windows_field(WINDOW_SIZE);
+        let scalar_windows_usize = scalar.windows_usize(WINDOW_SIZE);
+        assert_eq!(scalar_windows_field.len(), num_windows);
 
         for (w, (k, k_usize)) in scalar_windows_field
             .into_iter()
             .zip(scalar_windows_usize)
             .enumerate()
             // The MSB is processed separately.
-            .take(NUM_WINDOWS - 1)
+            .take(num_windows - 1)
             // Skip k_0 (already processed).
             .skip(1)
         {
-            // Compute [(k_w + 2) ⋅ 8^w]B
+            // Compute [(k_w + 2) ⋅ H^w]B
             //
             // This assigns the coordinates of the returned point into the input cells for
             // the incomplete addition gate, which will then copy them into themselves.
             let mul_b =
-                self.process_lower_bits::<_, NUM_WINDOWS>(region, offset, w, k, k_usize, base)?;
+                self.process_lower_bits(region, offset, num_windows, w, k, k_usize, base)?;
 
             // Add to the accumulator.
             //
@@ -360,44 +468,51 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
     }
 
     /// Assigns the values used to process a window that does not contain the MSB.
-    fn process_lower_bits<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    #[allow(clippy::too_many_arguments)]
+    fn process_lower_bits<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
+        num_windows: usize,
         w: usize,
-        k: Value<pallas::Scalar>,
+        k: Value<C::Scalar>,
         k_usize: Value<usize>,
         base: &F,
-    ) -> Result<NonIdentityEccPoint, Error> {
-        // `scalar = [(k_w + 2) ⋅ 8^w]
-        let scalar = k.map(|k| (k + *TWO_SCALAR) * (*H_SCALAR).pow(&[w as u64, 0, 0, 0]));
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
+        // `scalar = [(k_w + 2) ⋅ H^w]
+        let scalar =
+            k.map(|k| (k + C::Scalar::from(2)) * C::Scalar::from(Self::H as u64).pow([w as u64]));
 
-        self.process_window::<_, NUM_WINDOWS>(region, offset, w, k_usize, scalar, base)
+        self.process_window(region, offset, num_windows, w, k_usize, scalar, base)
     }
 
     /// Assigns the values used to process the window containing the MSB.
-    fn process_msb<F: FixedPoint<pallas::Affine>, const NUM_WINDOWS: usize>(
+    fn process_msb<F: FixedPoint<C>>(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
+        num_windows: usize,
         base: &F,
-        scalar: &ScalarFixed,
-    ) -> Result<NonIdentityEccPoint, Error> {
-        let k_usize = scalar.windows_usize()[NUM_WINDOWS - 1];
+        scalar: &ScalarFixed<C>,
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
+        let k_usize = scalar.windows_usize(WINDOW_SIZE)[num_windows - 1];
 
-        // offset_acc = \sum_{j = 0}^{NUM_WINDOWS - 2} 2^{FIXED_BASE_WINDOW_SIZE*j + 1}
-        let offset_acc = (0..(NUM_WINDOWS - 1)).fold(pallas::Scalar::zero(), |acc, w| {
-            acc + (*TWO_SCALAR).pow(&[FIXED_BASE_WINDOW_SIZE as u64 * w as u64 + 1, 0, 0, 0])
+        // offset_acc = \sum_{j = 0}^{num_windows - 2} 2^{WINDOW_SIZE*j + 1}
+        let offset_acc = (0..(num_windows - 1)).fold(C::Scalar::ZERO, |acc, w| {
+            acc + C::Scalar::from(2).pow([WINDOW_SIZE as u64 * w as u64 + 1])
         });
 
-        // `scalar = [k * 8^(NUM_WINDOWS - 1) - offset_acc]`.
-        let scalar = scalar.windows_field()[scalar.windows_field().len() - 1]
-            .map(|k| k * (*H_SCALAR).pow(&[(NUM_WINDOWS - 1) as u64, 0, 0, 0]) - offset_acc);
+        // `scalar = [k * H^(num_windows - 1) - offset_acc]`.
+        let windows_field = scalar.windows_field(WINDOW_SIZE);
+        let scalar = windows_field[windows_field.len() - 1].map(|k| {
+            k * C::Scalar::from(Self::H as u64).pow([(num_windows - 1) as u64]) - offset_acc
+        });
 
-        self.process_window::<_, NUM_WINDOWS>(
+        self.process_window(
             region,
             offset,
-            NUM_WINDOWS - 1,
+            num_windows,
+            num_windows - 1,
             k_usize,
             scalar,
             base,
@@ -405,51 +520,57 @@ impl<FixedPoints: super::FixedPoints<pallas::Affine>> Config<FixedPoints> {
     }
 }
 
-enum ScalarFixed {
-    FullWidth(EccScalarFixed),
-    Short(EccScalarFixedShort),
-    BaseFieldElem(EccBaseFieldElemFixed),
+enum ScalarFixed<C: EccCurve> {
+    FullWidth(EccScalarFixed<C>),
+    Short(EccScalarFixedShort<C>),
+    BaseFieldElem(EccBaseFieldElemFixed<C>),
 }
 
-impl From<&EccScalarFixed> for ScalarFixed {
-    fn from(scalar_fixed: &EccScalarFixed) -> Self {
+impl<C: EccCurve> From<&EccScalarFixed<C>> for ScalarFixed<C> {
+    fn from(scalar_fixed: &EccScalarFixed<C>) -> Self {
         Self::FullWidth(scalar_fixed.clone())
     }
 }
 
-impl From<&EccScalarFixedShort> for ScalarFixed {
-    fn from(scalar_fixed: &EccScalarFixedShort) -> Self {
+impl<C: EccCurve> From<&EccScalarFixedShort<C>> for ScalarFixed<C> {
+    fn from(scalar_fixed: &EccScalarFixedShort<C>) -> Self {
         Self::Short(scalar_fixed.clone())
     }
 }
 
-impl From<&EccBaseFieldElemFixed> for ScalarFixed {
-    fn from(base_field_elem: &EccBaseFieldElemFixed) -> Self {
+impl<C: EccCurve> From<&EccBaseFieldElemFixed<C>> for ScalarFixed<C> {
+    fn from(base_field_elem: &EccBaseFieldElemFixed<C>) -> Self {
         Self::BaseFieldElem(base_field_elem.clone())
     }
 }
 
-impl ScalarFixed {
+impl<C: EccCurve> ScalarFixed<C> {
     /// The scalar decomposition was done in the base field. For computation
-    /// outside the circuit, we now convert them back into the scalar field.
+    /// outside the circuit, we now convert the windows back into the scalar field.
     ///
     /// This function does not require that the base field fits inside the scalar field,
     /// because the window size fits into either field.
-    fn windows_field(&self) -> Vec<Value<pallas::Scalar>> {
-        let running_sum_to_windows = |zs: Vec<AssignedCell<pallas::Base, pallas::Base>>| {
+    fn windows_field(&self, window_size: usize) -> Vec<Value<C::Scalar>> {
+        self.windows_usize(window_size)
+            .into_iter()
+            .map(|window| window.map(|window| C::Scalar::from(window as u64)))
+            .collect()
+    }
+
+    /// The scalar decomposition is guaranteed to be in `window_size`-bit windows, so we
+    /// construct `usize` indices from the lowest `window_size` bits of each window field
+    /// element for convenient indexing into `u`-values.
+    fn windows_usize(&self, window_size: usize) -> Vec<Value<usize>> {
+        let running_sum_to_windows = |zs: Vec<AssignedCell<C::Base, C::Base>>| {
             (0..(zs.len() - 1))
                 .map(|idx| {
-                    let z_cur = zs[idx].value();
-                    let z_next = zs[idx + 1].value();
-                    let word = z_cur - z_next * Value::known(*H_BASE);
-                    // This assumes that the endianness of the encodings of pallas::Base
-                    // and pallas::Scalar are the same. They happen to be, but we need to
-                    // be careful if this is generalised.
-                    word.map(|word| pallas::Scalar::from_repr(word.to_repr()).unwrap())
+                    let z_cur = zs[idx].value().copied();
+                    let z_next = zs[idx + 1].value().copied();
+                    z_cur - z_next * Value::known(C::Base::from(1 << window_size))
                 })
                 .collect::<Vec<_>>()
         };
-        match self {
+        let windows = match self {
             Self::BaseFieldElem(scalar) => running_sum_to_windows(scalar.running_sum.to_vec()),
             Self::Short(scalar) => running_sum_to_windows(
                 scalar
@@ -463,22 +584,11 @@ impl ScalarFixed {
                 .as_ref()
                 .expect("EccScalarFixed has been witnessed")
                 .iter()
-                .map(|bits| {
-                    // This assumes that the endianness of the encodings of pallas::Base
-                    // and pallas::Scalar are the same. They happen to be, but we need to
-                    // be careful if this is generalised.
-                    bits.value()
-                        .map(|value| pallas::Scalar::from_repr(value.to_repr()).unwrap())
-                })
+                .map(|window| window.value().cloned())
                 .collect::<Vec<_>>(),
-        }
-    }
+        };
 
-    /// The scalar decomposition is guaranteed to be in three-bit windows, so we construct
-    /// `usize` indices from the lowest three bits of each window field element for
-    /// convenient indexing into `u`-values.
-    fn windows_usize(&self) -> Vec<Value<usize>> {
-        self.windows_field()
+        windows
             .iter()
             .map(|window| {
                 window.map(|window| {
@@ -486,7 +596,7 @@ impl ScalarFixed {
                         .to_le_bits()
                         .iter()
                         .by_vals()
-                        .take(FIXED_BASE_WINDOW_SIZE)
+                        .take(window_size)
                         .rev()
                         .fold(0, |acc, b| 2 * acc + usize::from(b))
                 })
@@ -1,39 +1,44 @@
-use super::super::{EccBaseFieldElemFixed, EccPoint, FixedPoints, NUM_WINDOWS, T_P};
-use super::H_BASE;
+use super::super::{
+    constants::num_windows, EccBaseFieldElemFixed, EccCurve, EccPoint, FixedPoints,
+};
 
-use crate::utilities::{
-    bitrange_subset, bool_check,
-    lookup_range_check::{PallasLookupRangeCheck, PallasLookupRangeCheckConfig},
-    range_check,
+use crate::{
+    sinsemilla::primitives as sinsemilla,
+    utilities::{bitrange_subset, bool_check, lookup_range_check::LookupRangeCheck, range_check},
 };
 
-use group::ff::PrimeField;
+use group::ff::{Field, PrimeField};
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter},
     plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
-use pasta_curves::pallas;
-
-use std::convert::TryInto;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Config<
-    Fixed: FixedPoints<pallas::Affine>,
-    Lookup: PallasLookupRangeCheck = PallasLookupRangeCheckConfig,
+    C: EccCurve,
+    Fixed: FixedPoints<C>,
+    Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+    const WINDOW_SIZE: usize,
 > {
     q_mul_fixed_base_field: Selector,
     canon_advices: [Column<Advice>; 3],
     lookup_config: Lookup,
-    super_config: super::Config<Fixed>,
+    super_config: super::Config<C, Fixed, WINDOW_SIZE>,
 }
 
-impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<Fixed, Lookup> {
+impl<
+        C: EccCurve,
+        Fixed: FixedPoints<C>,
+        Lookup: LookupRangeCheck<C::NativeField, { sinsemilla::K }>,
+        const WINDOW_SIZE: usize,
+    > Config<C, Fixed, Lookup, WINDOW_SIZE>
+{
     pub(crate) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+        meta: &mut ConstraintSystem<C::Base>,
         canon_advices: [Column<Advice>; 3],
         lookup_config: Lookup,
-        super_config: super::Config<Fixed>,
+        super_config: super::Config<C, Fixed, WINDOW_SIZE>,
     ) -> Self {
         for advice in canon_advices.iter() {
             meta.enable_equality(*advice);
@@ -59,94 +64,143 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
         config
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    /// Returns the index `n - 1` of the running sum element holding the top window of α,
+    /// for `n` windows (84 for 3-bit windows on Pallas).
+    fn z_top_idx() -> usize {
+        num_windows::<C>(WINDOW_SIZE) - 1
+    }
+
+    /// Returns the index of the MSB α_2 of α (254 on Pallas).
+    fn msb_idx() -> usize {
+        C::Base::NUM_BITS as usize - 1
+    }
+
+    /// Returns the number of bits of α below its top window, which form α_0
+    /// (252 bits for 3-bit windows on Pallas).
+    fn alpha_0_num_bits() -> usize {
+        WINDOW_SIZE * Self::z_top_idx()
+    }
+
+    /// Returns `(j, r)` such that bit 130 of α is bit `r` of window `j`
+    /// (`(43, 1)` for 3-bit windows).
+    fn bit_130_window() -> (usize, usize) {
+        (130 / WINDOW_SIZE, 130 % WINDOW_SIZE)
+    }
+
+    /// Returns the index of the first running sum element that starts at or above
+    /// bit 130 of α (44 for 3-bit windows).
+    fn z_hi_idx() -> usize {
+        match Self::bit_130_window() {
+            (j, 0) => j,
+            (j, _) => j + 1,
+        }
+    }
+
+    fn create_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
+        let alpha_0_num_bits = Self::alpha_0_num_bits();
+        // α_1 is the top window of α, excluding its MSB.
+        let alpha_1_num_bits = Self::msb_idx() - alpha_0_num_bits;
+        let (_, r) = Self::bit_130_window();
+        let z_hi_idx = Self::z_hi_idx();
+
         // Check that the base field element is canonical.
         // https://p.z.cash/halo2-0.1:ecc-fixed-mul-base-canonicity
         meta.create_gate("Canonicity checks", |meta| {
             let q_mul_fixed_base_field = meta.query_selector(self.q_mul_fixed_base_field);
 
             let alpha = meta.query_advice(self.canon_advices[0], Rotation::prev());
-            // The last three bits of α.
-            let z_84_alpha = meta.query_advice(self.canon_advices[2], Rotation::prev());
+            // The top window of α (the last three bits, for 3-bit windows).
+            let z_top_alpha = meta.query_advice(self.canon_advices[2], Rotation::prev());
 
             // Decompose α into three pieces, in little-endian order:
-            //            α = α_0 (252 bits)  || α_1 (2 bits) || α_2 (1 bit).
+            //            α = α_0 || α_1 || α_2 (1 bit).
+            // For 3-bit windows on Pallas, α_0 is 252 bits and α_1 is 2 bits.
             //
             // α_0 is derived, not witnessed.
             let alpha_0 = {
-                let two_pow_252 = pallas::Base::from_u128(1 << 126).square();
-                alpha - (z_84_alpha.clone() * two_pow_252)
+                let two_pow_alpha_0 = C::Base::from(2).pow([alpha_0_num_bits as u64]);
+                alpha - (z_top_alpha.clone() * two_pow_alpha_0)
             };
             let alpha_1 = meta.query_advice(self.canon_advices[1], Rotation::cur());
             let alpha_2 = meta.query_advice(self.canon_advices[2], Rotation::cur());
 
             let alpha_0_prime = meta.query_advice(self.canon_advices[0], Rotation::cur());
             let z_13_alpha_0_prime = meta.query_advice(self.canon_advices[0], Rotation::next());
-            let z_44_alpha = meta.query_advice(self.canon_advices[1], Rotation::next());
-            let z_43_alpha = meta.query_advice(self.canon_advices[2], Rotation::next());
+            let z_hi_alpha = meta.query_advice(self.canon_advices[1], Rotation::next());
+            // The running sum element below z_hi is only needed if bit 130 of α is
+            // not on a window boundary.
+            let z_j_alpha =
+                (r > 0).then(|| meta.query_advice(self.canon_advices[2], Rotation::next()));
 
             let decomposition_checks = {
-                // Range-constrain α_1 to be 2 bits
-                let alpha_1_range_check = range_check(alpha_1.clone(), 1 << 2);
+                // Range-constrain α_1
+                let alpha_1_range_check = range_check(alpha_1.clone(), 1 << alpha_1_num_bits);
                 // Boolean-constrain α_2
                 let alpha_2_range_check = bool_check(alpha_2.clone());
-                // Check that α_1 + 2^2 α_2 = z_84_alpha
-                let z_84_alpha_check = z_84_alpha.clone()
-                    - (alpha_1.clone() + alpha_2.clone() * pallas::Base::from(1 << 2));
+                // Check that α_1 + 2^{alpha_1_num_bits} α_2 = z_top_alpha
+                let z_top_alpha_check = z_top_alpha.clone()
+                    - (alpha_1.clone() + alpha_2.clone() * C::Base::from(1 << alpha_1_num_bits));
 
                 std::iter::empty()
                     .chain(Some(("alpha_1_range_check", alpha_1_range_check)))
                     .chain(Some(("alpha_2_range_check", alpha_2_range_check)))
-                    .chain(Some(("z_84_alpha_check", z_84_alpha_check)))
+                    .chain(Some(("z_top_alpha_check", z_top_alpha_check)))
             };
 
             // Check α_0_prime = α_0 + 2^130 - t_p
             let alpha_0_prime_check = {
-                let two_pow_130 = Expression::Constant(pallas::Base::from_u128(1 << 65).square());
-                let t_p = Expression::Constant(pallas::Base::from_u128(T_P));
+                let two_pow_130 = Expression::Constant(C::Base::from_u128(1 << 65).square());
+                let t_p = Expression::Constant(C::t_p());
                 alpha_0_prime - (alpha_0 + two_pow_130 - t_p)
             };
 
-            // We want to enforce canonicity of a 255-bit base field element, α.
-            // That is, we want to check that 0 ≤ α < p, where p is Pallas base
-            // field modulus p = 2^254 + t_p
-            //                 = 2^254 + 45560315531419706090280762371685220353.
-            // Note that t_p < 2^130.
+            // We want to enforce canonicity of an n-bit base field element, α.
+            // That is, we want to check that 0 ≤ α < p, where p is the base field
+            // modulus p = 2^{n-1} + t_p (for Pallas, n = 255 and p = 2^254 +
+            // 45560315531419706090280762371685220353). Note that t_p < 2^130.
             //
             // α has been decomposed into three pieces in little-endian order:
-            //            α = α_0 (252 bits)  || α_1 (2 bits) || α_2 (1 bit).
-            //              = α_0 + 2^252 α_1 + 2^254 α_2.
+            //            α = α_0 || α_1 || α_2 (1 bit).
+            //              = α_0 + 2^{alpha_0_num_bits} α_1 + 2^{n-1} α_2.
             //
             // If the MSB α_2 = 1, then:
             //      - α_2 = 1 => α_1 = 0, and
             //      - α_2 = 1 => α_0 < t_p. To enforce this:
             //          - α_2 = 1 => 0 ≤ α_0 < 2^130
-            //                - alpha_0_hi_120 = 0 (constrain α_0 to be 132 bits)
-            //                - a_43 = 0 or 1 (constrain α_0[130..=131] to be 0)
+            //                - alpha_0_hi = 0 (constrain α_0 to be below the window
+            //                  boundary z_hi; for 3-bit windows, 132 bits)
+            //                - if 130 is not a multiple of the window size, a_j < 2^r
+            //                  (for 3-bit windows, a_43 = 0 or 1, constraining
+            //                  α_0[130..=131] to be 0)
             //          - α_2 = 1 => 0 ≤ α_0 + 2^130 - t_p < 2^130
             //                    => 13 ten-bit lookups of α_0 + 2^130 - t_p
             //                    => z_13_alpha_0_prime = 0
             //
             let canon_checks = {
-                // alpha_0_hi_120 = z_44 - 2^120 z_84
-                let alpha_0_hi_120 = {
-                    let two_pow_120 =
-                        Expression::Constant(pallas::Base::from_u128(1 << 60).square());
-                    z_44_alpha.clone() - z_84_alpha * two_pow_120
+                // alpha_0_hi = z_hi - 2^{alpha_0_num_bits - w * hi} z_top
+                // (alpha_0_hi_120 = z_44 - 2^120 z_84, for 3-bit windows)
+                let alpha_0_hi = {
+                    let two_pow_hi = Expression::Constant(
+                        C::Base::from(2).pow([(alpha_0_num_bits - WINDOW_SIZE * z_hi_idx) as u64]),
+                    );
+                    z_hi_alpha.clone() - z_top_alpha * two_pow_hi
                 };
-                // a_43 = z_43 - (2^3)z_44
-                let a_43 = z_43_alpha - z_44_alpha * *H_BASE;
+                // a_j = z_j - (2^w) z_hi
+                let a_j_check = z_j_alpha.map(|z_j_alpha| {
+                    let a_j = z_j_alpha - z_hi_alpha * C::Base::from(1 << WINDOW_SIZE);
+                    (
+                        "MSB = 1 => a_j < 2^r",
+                        alpha_2.clone() * range_check(a_j, 1 << r),
+                    )
+                });
 
                 std::iter::empty()
                     .chain(Some(("MSB = 1 => alpha_1 = 0", alpha_2.clone() * alpha_1)))
                     .chain(Some((
-                        "MSB = 1 => alpha_0_hi_120 = 0",
-                        alpha_2.clone() * alpha_0_hi_120,
-                    )))
-                    .chain(Some((
-                        "MSB = 1 => a_43 = 0 or 1",
-                        alpha_2.clone() * bool_check(a_43),
+                        "MSB = 1 => alpha_0_hi = 0",
+                        alpha_2.clone() * alpha_0_hi,
                     )))
+                    .chain(a_j_check)
                     .chain(Some((
                         "MSB = 1 => z_13_alpha_0_prime = 0",
                         alpha_2 * z_13_alpha_0_prime,
@@ -164,12 +218,12 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
 
     pub fn assign(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        scalar: AssignedCell<pallas::Base, pallas::Base>,
-        base: &<Fixed as FixedPoints<pallas::Affine>>::Base,
-    ) -> Result<EccPoint, Error>
+        mut layouter: impl Layouter<C::Base>,
+        scalar: AssignedCell<C::Base, C::Base>,
+        base: &<Fixed as FixedPoints<C>>::Base,
+    ) -> Result<EccPoint<C>, Error>
     where
-        <Fixed as FixedPoints<pallas::Affine>>::Base: super::super::FixedPoint<pallas::Affine>,
+        <Fixed as FixedPoints<C>>::Base: super::super::FixedPoint<C>,
     {
         let (scalar, acc, mul_b) = layouter.assign_region(
             || "Base-field elem fixed-base mul (incomplete addition)",
@@ -183,16 +237,16 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
                         offset,
                         scalar.clone(),
                         true,
-                        pallas::Base::NUM_BITS as usize,
-                        NUM_WINDOWS,
+                        C::Base::NUM_BITS as usize,
+                        num_windows::<C>(WINDOW_SIZE),
                     )?;
                     EccBaseFieldElemFixed {
                         base_field_elem: running_sum[0].clone(),
-                        running_sum: (*running_sum).as_slice().try_into().unwrap(),
+                        running_sum: running_sum.to_vec(),
                     }
                 };
 
-                let (acc, mul_b) = self.super_config.assign_region_inner::<_, NUM_WINDOWS>(
+                let (acc, mul_b) = self.super_config.assign_region_inner(
                     &mut region,
                     offset,
                     &(&scalar).into(),
@@ -220,13 +274,10 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
         #[cfg(test)]
         // Check that the correct multiple is obtained.
         {
-            use super::super::FixedPoint;
+            use super::super::{base_to_scalar, FixedPoint};
             use group::Curve;
 
-            let scalar = &scalar
-                .base_field_elem()
-                .value()
-                .map(|scalar| pallas::Scalar::from_repr(scalar.to_repr()).unwrap());
+            let scalar = &scalar.base_field_elem().value().map(base_to_scalar::<C>);
             let real_mul = scalar.map(|scalar| base.generator() * scalar);
             let result = result.point();
 
@@ -235,15 +286,15 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
                 .assert_if_known(|(real_mul, result)| &real_mul.to_affine() == result);
         }
 
-        // We want to enforce canonicity of a 255-bit base field element, α.
-        // That is, we want to check that 0 ≤ α < p, where p is Pallas base
-        // field modulus p = 2^254 + t_p
-        //                 = 2^254 + 45560315531419706090280762371685220353.
-        // Note that t_p < 2^130.
+        // We want to enforce canonicity of an n-bit base field element, α.
+        // That is, we want to check that 0 ≤ α < p, where p is the base field
+        // modulus p = 2^{n-1} + t_p (for Pallas, n = 255 and p = 2^254 +
+        // 45560315531419706090280762371685220353). Note that t_p < 2^130.
         //
         // α has been decomposed into three pieces in little-endian order:
-        //            α = α_0 (252 bits)  || α_1 (2 bits) || α_2 (1 bit).
-        //              = α_0 + 2^252 α_1 + 2^254 α_2.
+        //            α = α_0 || α_1 || α_2 (1 bit).
+        //              = α_0 + 2^{alpha_0_num_bits} α_1 + 2^{n-1} α_2.
+        // For 3-bit windows on Pallas, α_0 is 252 bits and α_1 is 2 bits.
         //
         // If the MSB α_2 = 1, then:
         //      - α_2 = 1 => α_1 = 0, and
@@ -254,25 +305,28 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
         //                => 13 ten-bit lookups of α_0 + 2^130 - t_p
         //                => z_13_alpha_0_prime = 0
         //
+        let alpha_0_num_bits = Self::alpha_0_num_bits();
+        let msb_idx = Self::msb_idx();
+        let (j, r) = Self::bit_130_window();
         let (alpha, running_sum) = (scalar.base_field_elem, &scalar.running_sum);
-        let z_43_alpha = running_sum[43].clone();
-        let z_44_alpha = running_sum[44].clone();
-        let z_84_alpha = running_sum[84].clone();
+        let z_j_alpha = running_sum[j].clone();
+        let z_hi_alpha = running_sum[Self::z_hi_idx()].clone();
+        let z_top_alpha = running_sum[Self::z_top_idx()].clone();
 
-        // α_0 = α - z_84_alpha * 2^252
+        // α_0 = α - z_top_alpha * 2^{alpha_0_num_bits}
         let alpha_0 = alpha
             .value()
-            .zip(z_84_alpha.value())
-            .map(|(alpha, z_84_alpha)| {
-                let two_pow_252 = pallas::Base::from_u128(1 << 126).square();
-                alpha - z_84_alpha * two_pow_252
+            .zip(z_top_alpha.value())
+            .map(|(alpha, z_top_alpha)| {
+                let two_pow_alpha_0 = C::Base::from(2).pow([alpha_0_num_bits as u64]);
+                *alpha - *z_top_alpha * two_pow_alpha_0
             });
 
         let (alpha_0_prime, z_13_alpha_0_prime) = {
             // alpha_0_prime = alpha + 2^130 - t_p.
             let alpha_0_prime = alpha_0.map(|alpha_0| {
-                let two_pow_130 = pallas::Base::from_u128(1 << 65).square();
-                let t_p = pallas::Base::from_u128(T_P);
+                let two_pow_130 = C::Base::from_u128(1 << 65).square();
+                let t_p = C::t_p();
                 alpha_0 + two_pow_130 - t_p
             });
             let zs = self.lookup_config.witness_check(
@@ -299,9 +353,9 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
                     // Copy α
                     alpha.copy_advice(|| "Copy α", &mut region, self.canon_advices[0], offset)?;
 
-                    // z_84_alpha = the top three bits of alpha.
-                    z_84_alpha.copy_advice(
-                        || "Copy z_84_alpha",
+                    // z_top_alpha = the top window of alpha.
+                    z_top_alpha.copy_advice(
+                        || "Copy z_top_alpha",
                         &mut region,
                         self.canon_advices[2],
                         offset,
@@ -321,21 +375,25 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
                     )?;
 
                     // Decompose α into three pieces,
-                    //     α = α_0 (252 bits)  || α_1 (2 bits) || α_2 (1 bit).
+                    //     α = α_0 || α_1 || α_2 (1 bit).
                     // We only need to witness α_1 and α_2. α_0 is derived in the gate.
-                    // Witness α_1 = α[252..=253]
-                    let alpha_1 = alpha.value().map(|alpha| bitrange_subset(alpha, 252..254));
+                    // Witness α_1 = α[alpha_0_num_bits..msb_idx]
+                    let alpha_1 = alpha
+                        .value()
+                        .map(|alpha| bitrange_subset(alpha, alpha_0_num_bits..msb_idx));
                     region.assign_advice(
-                        || "α_1 = α[252..=253]",
+                        || format!("α_1 = α[{}..{}]", alpha_0_num_bits, msb_idx),
                         self.canon_advices[1],
                         offset,
                         || alpha_1,
                     )?;
 
-                    // Witness the MSB α_2 = α[254]
-                    let alpha_2 = alpha.value().map(|alpha| bitrange_subset(alpha, 254..255));
+                    // Witness the MSB α_2 = α[msb_idx]
+                    let alpha_2 = alpha
+                        .value()
+                        .map(|alpha| bitrange_subset(alpha, msb_idx..msb_idx + 1));
                     region.assign_advice(
-                        || "α_2 = α[254]",
+                        || format!("α_2 = α[{}]", msb_idx),
                         self.canon_advices[2],
                         offset,
                         || alpha_2,
@@ -353,21 +411,23 @@ impl<Fixed: FixedPoints<pallas::Affine>, Lookup: PallasLookupRangeCheck> Config<
                         offset,
                     )?;
 
-                    // Copy z_44_alpha
-                    z_44_alpha.copy_advice(
-                        || "Copy z_44_alpha",
+                    // Copy z_hi_alpha
+                    z_hi_alpha.copy_advice(
+                        || "Copy z_hi_alpha",
                         &mut region,
                         self.canon_advices[1],
                         offset,
                     )?;
 
-                    // Copy z_43_alpha
-                    z_43_alpha.copy_advice(
-                        || "Copy z_43_alpha",
-                        &mut region,
-                        self.canon_advices[2],
-                        offset,
-                    )?;
+                    // Copy z_j_alpha, if bit 130 of α is not on a window boundary.
+                    if r > 0 {
+                        z_j_alpha.copy_advice(
+                            || "Copy z_j_alpha",
+                            &mut region,
+                            self.canon_advices[2],
+                            offset,
+                        )?;
+                    }
                 }
 
                 Ok(())
@@ -1,25 +1,23 @@
-use super::super::{EccPoint, EccScalarFixed, FixedPoints, FIXED_BASE_WINDOW_SIZE, H, NUM_WINDOWS};
+use super::super::{constants::num_windows, EccCurve, EccPoint, EccScalarFixed, FixedPoints};
 
 use crate::utilities::{decompose_word, range_check};
-use arrayvec::ArrayVec;
 use ff::PrimeField;
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region, Value},
     plonk::{ConstraintSystem, Constraints, Error, Selector},
     poly::Rotation,
 };
-use pasta_curves::pallas;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Config<Fixed: FixedPoints<pallas::Affine>> {
+pub struct Config<C: EccCurve, Fixed: FixedPoints<C>, const WINDOW_SIZE: usize> {
     q_mul_fixed_full: Selector,
-    super_config: super::Config<Fixed>,
+    super_config: super::Config<C, Fixed, WINDOW_SIZE>,
 }
 
-impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
+impl<C: EccCurve, Fixed: FixedPoints<C>, const WINDOW_SIZE: usize> Config<C, Fixed, WINDOW_SIZE> {
     pub(crate) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
-        super_config: super::Config<Fixed>,
+        meta: &mut ConstraintSystem<C::Base>,
+        super_config: super::Config<C, Fixed, WINDOW_SIZE>,
     ) -> Self {
         let config = Self {
             q_mul_fixed_full: meta.selector(),
@@ -31,8 +29,8 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
         config
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
-        // Check that each window `k` is within 3 bits
+    fn create_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
+        // Check that each window `k` is within `WINDOW_SIZE` bits
         // https://p.z.cash/halo2-0.1:ecc-fixed-mul-full-word
         meta.create_gate("Full-width fixed-base scalar mul", |meta| {
             let q_mul_fixed_full = meta.query_selector(self.q_mul_fixed_full);
@@ -43,25 +41,26 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
                 self.super_config
                     .coords_check(meta, window.clone())
                     .into_iter()
-                    // Constrain each window to a 3-bit value:
-                    // window * (1 - window) * ... * (7 - window)
-                    .chain(Some(("window range check", range_check(window, H)))),
+                    // Constrain each window to a `WINDOW_SIZE`-bit value:
+                    // window * (1 - window) * ... * ((H - 1) - window)
+                    .chain(Some((
+                        "window range check",
+                        range_check(window, 1 << WINDOW_SIZE),
+                    ))),
             )
         });
     }
 
-    /// Witnesses the given scalar as `NUM_WINDOWS` 3-bit windows.
+    /// Witnesses the given scalar as `WINDOW_SIZE`-bit windows.
     ///
     /// The scalar is allowed to be non-canonical.
     fn witness(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        scalar: Value<pallas::Scalar>,
-    ) -> Result<EccScalarFixed, Error> {
-        let windows = self.decompose_scalar_fixed::<{ pallas::Scalar::NUM_BITS as usize }>(
-            scalar, offset, region,
-        )?;
+        scalar: Value<C::Scalar>,
+    ) -> Result<EccScalarFixed<C>, Error> {
+        let windows = self.decompose_scalar_fixed(scalar, offset, region)?;
 
         Ok(EccScalarFixed {
             value: scalar,
@@ -69,37 +68,39 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
         })
     }
 
-    /// Witnesses the given scalar as `NUM_WINDOWS` 3-bit windows.
+    /// Witnesses the given scalar as `WINDOW_SIZE`-bit windows.
     ///
     /// The scalar is allowed to be non-canonical.
-    fn decompose_scalar_fixed<const SCALAR_NUM_BITS: usize>(
+    #[allow(clippy::type_complexity)]
+    fn decompose_scalar_fixed(
         &self,
-        scalar: Value<pallas::Scalar>,
+        scalar: Value<C::Scalar>,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<ArrayVec<AssignedCell<pallas::Base, pallas::Base>, NUM_WINDOWS>, Error> {
+        region: &mut Region<'_, C::Base>,
+    ) -> Result<Vec<AssignedCell<C::Base, C::Base>>, Error> {
+        let num_windows = num_windows::<C>(WINDOW_SIZE);
+
         // Enable `q_mul_fixed_full` selector
-        for idx in 0..NUM_WINDOWS {
+        for idx in 0..num_windows {
             self.q_mul_fixed_full.enable(region, offset + idx)?;
         }
 
         // Decompose scalar into `k-bit` windows
         let scalar_windows: Value<Vec<u8>> = scalar.map(|scalar| {
-            decompose_word::<pallas::Scalar>(&scalar, SCALAR_NUM_BITS, FIXED_BASE_WINDOW_SIZE)
+            decompose_word::<C::Scalar>(&scalar, C::Scalar::NUM_BITS as usize, WINDOW_SIZE)
         });
 
-        // Transpose `Value<Vec<u8>>` into `Vec<Value<pallas::Base>>`.
+        // Transpose `Value<Vec<u8>>` into `Vec<Value<C::Base>>`.
         let scalar_windows = scalar_windows
             .map(|windows| {
                 windows
                     .into_iter()
-                    .map(|window| pallas::Base::from(window as u64))
+                    .map(|window| C::Base::from(window as u64))
             })
-            .transpose_vec(NUM_WINDOWS);
+            .transpose_vec(num_windows);
 
         // Store the scalar decomposition
-        let mut windows: ArrayVec<AssignedCell<pallas::Base, pallas::Base>, NUM_WINDOWS> =
-            ArrayVec::new();
+        let mut windows = Vec::with_capacity(num_windows);
         for (idx, window) in scalar_windows.into_iter().enumerate() {
             let window_cell = region.assign_advice(
                 || format!("k[{:?}]", offset + idx),
@@ -115,13 +116,12 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
 
     pub fn assign(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        scalar: &EccScalarFixed,
-        base: &<Fixed as FixedPoints<pallas::Affine>>::FullScalar,
-    ) -> Result<(EccPoint, EccScalarFixed), Error>
+        mut layouter: impl Layouter<C::Base>,
+        scalar: &EccScalarFixed<C>,
+        base: &<Fixed as FixedPoints<C>>::FullScalar,
+    ) -> Result<(EccPoint<C>, EccScalarFixed<C>), Error>
     where
-        <Fixed as FixedPoints<pallas::Affine>>::FullScalar:
-            super::super::FixedPoint<pallas::Affine>,
+        <Fixed as FixedPoints<C>>::FullScalar: super::super::FixedPoint<C>,
     {
         let (scalar, acc, mul_b) = layouter.assign_region(
             || "Full-width fixed-base mul (incomplete addition)",
@@ -134,7 +134,7 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
                     Some(_) => todo!("unimplemented for halo2_gadgets v0.1.0"),
                 }?;
 
-                let (acc, mul_b) = self.super_config.assign_region_inner::<_, NUM_WINDOWS>(
+                let (acc, mul_b) = self.super_config.assign_region_inner(
                     &mut region,
                     offset,
                     &(&scalar).into(),
@@ -1,26 +1,29 @@
 use std::convert::TryInto;
 
-use super::super::{EccPoint, EccScalarFixedShort, FixedPoints, L_SCALAR_SHORT, NUM_WINDOWS_SHORT};
-use crate::{ecc::chip::MagnitudeSign, utilities::bool_check};
+use super::super::{
+    constants::num_windows_short, EccCurve, EccPoint, EccScalarFixedShort, FixedPoints,
+    L_SCALAR_SHORT,
+};
+use crate::{ecc::chip::MagnitudeSign, utilities::range_check};
 
+use group::ff::Field;
 use halo2_proofs::{
     circuit::{AssignedCell, Layouter, Region},
     plonk::{ConstraintSystem, Constraints, Error, Expression, Selector},
     poly::Rotation,
 };
-use pasta_curves::pallas;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Config<Fixed: FixedPoints<pallas::Affine>> {
+pub struct Config<C: EccCurve, Fixed: FixedPoints<C>, const WINDOW_SIZE: usize> {
     // Selector used for fixed-base scalar mul with short signed exponent.
     q_mul_fixed_short: Selector,
-    super_config: super::Config<Fixed>,
+    super_config: super::Config<C, Fixed, WINDOW_SIZE>,
 }
 
-impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
+impl<C: EccCurve, Fixed: FixedPoints<C>, const WINDOW_SIZE: usize> Config<C, Fixed, WINDOW_SIZE> {
     pub(crate) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
-        super_config: super::Config<Fixed>,
+        meta: &mut ConstraintSystem<C::Base>,
+        super_config: super::Config<C, Fixed, WINDOW_SIZE>,
     ) -> Self {
         let config = Self {
             q_mul_fixed_short: meta.selector(),
@@ -32,7 +35,7 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
         config
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
+    fn create_gate(&self, meta: &mut ConstraintSystem<C::Base>) {
         // Gate contains the following constraints:
         // - https://p.z.cash/halo2-0.1:ecc-fixed-mul-short-msb
         // - https://p.z.cash/halo2-0.1:ecc-fixed-mul-short-conditional-neg
@@ -40,14 +43,15 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
             let q_mul_fixed_short = meta.query_selector(self.q_mul_fixed_short);
             let y_p = meta.query_advice(self.super_config.add_config.y_p, Rotation::cur());
             let y_a = meta.query_advice(self.super_config.add_config.y_qr, Rotation::cur());
-            // z_21 = k_21
+            // z_{n-1} = k_{n-1}
             let last_window = meta.query_advice(self.super_config.u, Rotation::cur());
             let sign = meta.query_advice(self.super_config.window, Rotation::cur());
 
-            let one = Expression::Constant(pallas::Base::one());
+            let one = Expression::Constant(C::Base::ONE);
 
-            // Check that last window is either 0 or 1.
-            let last_window_check = bool_check(last_window);
+            // Check that last window is within the bits remaining of the 64-bit magnitude.
+            // For 3-bit windows, this checks that it is either 0 or 1.
+            let last_window_check = range_check(last_window, 1 << Self::last_window_num_bits());
             // Check that sign is either 1 or -1.
             let sign_check = sign.clone().square() - one;
 
@@ -76,16 +80,22 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
         });
     }
 
-    /// Constraints `magnitude` to be at most 66 bits.
+    /// The number of bits of the magnitude that are in the last window.
+    fn last_window_num_bits() -> usize {
+        L_SCALAR_SHORT - WINDOW_SIZE * (num_windows_short(WINDOW_SIZE) - 1)
+    }
+
+    /// Constraints `magnitude` to be at most `WINDOW_SIZE * num_windows_short(WINDOW_SIZE)`
+    /// bits (66 bits for 3-bit windows).
     ///
-    /// The final window is separately constrained to be a single bit, which completes the
-    /// 64-bit range constraint.
+    /// The final window is separately constrained to the remaining bits (a single bit for
+    /// 3-bit windows), which completes the 64-bit range constraint.
     fn decompose(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        magnitude_sign: MagnitudeSign,
-    ) -> Result<EccScalarFixedShort, Error> {
+        magnitude_sign: MagnitudeSign<C::Base>,
+    ) -> Result<EccScalarFixedShort<C>, Error> {
         let (magnitude, sign) = magnitude_sign;
 
         // Decompose magnitude
@@ -95,7 +105,7 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
             magnitude.clone(),
             true,
             L_SCALAR_SHORT,
-            NUM_WINDOWS_SHORT,
+            num_windows_short(WINDOW_SIZE),
         )?;
 
         Ok(EccScalarFixedShort {
@@ -107,13 +117,12 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
 
     pub fn assign(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        scalar: &EccScalarFixedShort,
-        base: &<Fixed as FixedPoints<pallas::Affine>>::ShortScalar,
-    ) -> Result<(EccPoint, EccScalarFixedShort), Error>
+        mut layouter: impl Layouter<C::Base>,
+        scalar: &EccScalarFixedShort<C>,
+        base: &<Fixed as FixedPoints<C>>::ShortScalar,
+    ) -> Result<(EccPoint<C>, EccScalarFixedShort<C>), Error>
     where
-        <Fixed as FixedPoints<pallas::Affine>>::ShortScalar:
-            super::super::FixedPoint<pallas::Affine>,
+        <Fixed as FixedPoints<C>>::ShortScalar: super::super::FixedPoint<C>,
     {
         let (scalar, acc, mul_b) = layouter.assign_region(
             || "Short fixed-base mul (incomplete addition)",
@@ -130,15 +139,13 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
                     Some(_) => todo!("unimplemented for halo2_gadgets v0.1.0"),
                 }?;
 
-                let (acc, mul_b) = self
-                    .super_config
-                    .assign_region_inner::<_, NUM_WINDOWS_SHORT>(
-                        &mut region,
-                        offset,
-                        &(&scalar).into(),
-                        base,
-                        self.super_config.running_sum_config.q_range_check(),
-                    )?;
+                let (acc, mul_b) = self.super_config.assign_region_inner(
+                    &mut region,
+                    offset,
+                    &(&scalar).into(),
+                    base,
+                    self.super_config.running_sum_config.q_range_check(),
+                )?;
 
                 Ok((scalar, acc, mul_b))
             },
@@ -171,12 +178,13 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
                 // Copy last window to `u` column.
                 // (Although the last window is not a `u` value; we are copying it into the `u`
                 // column because there is an available cell there.)
-                let z_21 = scalar.running_sum.as_ref().unwrap()[21].clone();
-                z_21.copy_advice(|| "last_window", &mut region, self.super_config.u, offset)?;
+                let last_window = num_windows_short(WINDOW_SIZE) - 1;
+                let z_last = scalar.running_sum.as_ref().unwrap()[last_window].clone();
+                z_last.copy_advice(|| "last_window", &mut region, self.super_config.u, offset)?;
 
                 // Conditionally negate `y`-coordinate
                 let y_val = sign.value().and_then(|sign| {
-                    if sign == &-pallas::Base::one() {
+                    if sign == &-C::Base::ONE {
                         -magnitude_mul.y.value()
                     } else {
                         magnitude_mul.y.value().cloned()
@@ -205,8 +213,8 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
         // Invalid values result in constraint failures which are
         // tested at the circuit-level.
         {
-            use super::super::FixedPoint;
-            use group::{ff::PrimeField, Curve};
+            use super::super::{base_to_scalar, FixedPoint};
+            use group::{ff::PrimeFieldBits, Curve};
 
             scalar
                 .magnitude
@@ -215,19 +223,19 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
                 .zip(result.point())
                 .assert_if_known(|((magnitude, sign), result)| {
                     let magnitude_is_valid =
-                        magnitude <= &&pallas::Base::from(0xFFFF_FFFF_FFFF_FFFFu64);
-                    let sign_is_valid = sign.square() == pallas::Base::one();
+                        magnitude.to_le_bits().iter().skip(64).all(|bit| !*bit);
+                    let sign_is_valid = sign.square() == C::Base::ONE;
                     // Only check the result if the magnitude and sign are valid.
                     !(magnitude_is_valid && sign_is_valid) || {
                         let scalar = {
-                            // Move magnitude from base field into scalar field (which always fits
-                            // for Pallas).
-                            let magnitude = pallas::Scalar::from_repr(magnitude.to_repr()).unwrap();
+                            // Move magnitude from base field into scalar field (which always fits,
+                            // as it is at most 64 bits).
+                            let magnitude = base_to_scalar::<C>(magnitude);
 
-                            let sign = if sign == &&pallas::Base::one() {
-                                pallas::Scalar::one()
+                            let sign = if sign == &&C::Base::ONE {
+                                C::Scalar::ONE
                             } else {
-                                -pallas::Scalar::one()
+                                -C::Scalar::ONE
                             };
 
                             magnitude * sign
@@ -246,10 +254,10 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
     /// This constrains `sign` to be in {-1, 1}.
     pub(crate) fn assign_scalar_sign(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        sign: &AssignedCell<pallas::Base, pallas::Base>,
-        point: &EccPoint,
-    ) -> Result<EccPoint, Error> {
+        mut layouter: impl Layouter<C::Base>,
+        sign: &AssignedCell<C::Base, C::Base>,
+        point: &EccPoint<C>,
+    ) -> Result<EccPoint<C>, Error> {
         let signed_point = layouter.assign_region(
             || "Signed point",
             |mut region| {
@@ -263,7 +271,7 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
                     || "u=0",
                     self.super_config.u,
                     offset,
-                    pallas::Base::zero(),
+                    C::Base::ZERO,
                 )?;
 
                 // Copy sign to `window` column
@@ -279,7 +287,7 @@ impl<Fixed: FixedPoints<pallas::Affine>> Config<Fixed> {
 
                 // Conditionally negate y-coordinate according to the value of sign
                 let signed_y_val = sign.value().and_then(|sign| {
-                    if sign == &-pallas::Base::one() {
+                    if sign == &-C::Base::ONE {
                         -point.y.value()
                     } else {
                         point.y.value().cloned()
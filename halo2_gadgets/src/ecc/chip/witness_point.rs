@@ -1,6 +1,6 @@
 use super::{EccPoint, NonIdentityEccPoint};
 
-use group::prime::PrimeCurveAffine;
+use group::ff::Field;
 
 use halo2_proofs::{
     circuit::{AssignedCell, Region, Value},
@@ -10,12 +10,9 @@ use halo2_proofs::{
     },
     poly::Rotation,
 };
-use pasta_curves::{arithmetic::CurveAffine, pallas};
+use pasta_curves::arithmetic::CurveAffine;
 
-type Coordinates = (
-    AssignedCell<Assigned<pallas::Base>, pallas::Base>,
-    AssignedCell<Assigned<pallas::Base>, pallas::Base>,
-);
+type Coordinates<F> = (AssignedCell<Assigned<F>, F>, AssignedCell<Assigned<F>, F>);
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Config {
@@ -28,8 +25,8 @@ pub struct Config {
 }
 
 impl Config {
-    pub(super) fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+    pub(super) fn configure<C: CurveAffine>(
+        meta: &mut ConstraintSystem<C::Base>,
         x: Column<Advice>,
         y: Column<Advice>,
     ) -> Self {
@@ -40,25 +37,26 @@ impl Config {
             y,
         };
 
-        config.create_gate(meta);
+        config.create_gate::<C>(meta);
 
         config
     }
 
-    fn create_gate(&self, meta: &mut ConstraintSystem<pallas::Base>) {
-        let curve_eqn = |meta: &mut VirtualCells<pallas::Base>| {
+    fn create_gate<C: CurveAffine>(&self, meta: &mut ConstraintSystem<C::Base>) {
+        let curve_eqn = |meta: &mut VirtualCells<C::Base>| {
             let x = meta.query_advice(self.x, Rotation::cur());
             let y = meta.query_advice(self.y, Rotation::cur());
 
             // y^2 = x^3 + b
-            y.square() - (x.clone().square() * x) - Expression::Constant(pallas::Affine::b())
+            y.square() - (x.clone().square() * x) - Expression::Constant(C::b())
         };
 
         // https://p.z.cash/halo2-0.1:ecc-witness-point
         meta.create_gate("witness point", |meta| {
             // Check that the point being witnessed is either:
             // - the identity, which is mapped to (0, 0) in affine coordinates; or
-            // - a valid curve point y^2 = x^3 + b, where b = 5 in the Pallas equation
+            // - a valid curve point y^2 = x^3 + b, where b = 5 in the Pallas and Vesta
+            //   equations
 
             let q_point = meta.query_selector(self.q_point);
             let x = meta.query_advice(self.x, Rotation::cur());
@@ -77,7 +75,7 @@ impl Config {
         // https://p.z.cash/halo2-0.1:ecc-witness-non-identity-point
         meta.create_gate("witness non-identity point", |meta| {
             // Check that the point being witnessed is a valid curve point y^2 = x^3 + b,
-            // where b = 5 in the Pallas equation
+            // where b = 5 in the Pallas and Vesta equations
 
             let q_point_non_id = meta.query_selector(self.q_point_non_id);
 
@@ -85,12 +83,12 @@ impl Config {
         });
     }
 
-    fn assign_xy(
+    fn assign_xy<F: Field>(
         &self,
-        value: Value<(Assigned<pallas::Base>, Assigned<pallas::Base>)>,
+        value: Value<(Assigned<F>, Assigned<F>)>,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<Coordinates, Error> {
+        region: &mut Region<'_, F>,
+    ) -> Result<Coordinates<F>, Error> {
         // Assign `x` value
         let x_val = value.map(|value| value.0);
         let x_var = region.assign_advice(|| "x", self.x, offset, || x_val)?;
@@ -102,12 +100,12 @@ impl Config {
         Ok((x_var, y_var))
     }
 
-    fn assign_xy_from_constant(
+    fn assign_xy_from_constant<F: Field>(
         &self,
-        value: (Assigned<pallas::Base>, Assigned<pallas::Base>),
+        value: (Assigned<F>, Assigned<F>),
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<Coordinates, Error> {
+        region: &mut Region<'_, F>,
+    ) -> Result<Coordinates<F>, Error> {
         // Assign `x` value
         let x_var = region.assign_advice_from_constant(|| "x", self.x, offset, value.0)?;
 
@@ -118,18 +116,18 @@ impl Config {
     }
 
     /// Assigns a point that can be the identity.
-    pub(super) fn point(
+    pub(super) fn point<C: CurveAffine>(
         &self,
-        value: Value<pallas::Affine>,
+        value: Value<C>,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<EccPoint, Error> {
+        region: &mut Region<'_, C::Base>,
+    ) -> Result<EccPoint<C>, Error> {
         // Enable `q_point` selector
         self.q_point.enable(region, offset)?;
 
         let value = value.map(|value| {
             // Map the identity to (0, 0).
-            if value == pallas::Affine::identity() {
+            if value == C::identity() {
                 (Assigned::Zero, Assigned::Zero)
             } else {
                 let value = value.coordinates().unwrap();
@@ -142,16 +140,16 @@ impl Config {
     }
 
     /// Assigns a constant point that can be the identity.
-    pub(super) fn constant_point(
+    pub(super) fn constant_point<C: CurveAffine>(
         &self,
-        value: pallas::Affine,
+        value: C,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<EccPoint, Error> {
+        region: &mut Region<'_, C::Base>,
+    ) -> Result<EccPoint<C>, Error> {
         // Enable `q_point` selector
         self.q_point.enable(region, offset)?;
 
-        let value = if value == pallas::Affine::identity() {
+        let value = if value == C::identity() {
             // Map the identity to (0, 0).
             (Assigned::Zero, Assigned::Zero)
         } else {
@@ -164,17 +162,17 @@ impl Config {
     }
 
     /// Assigns a non-identity point.
-    pub(super) fn point_non_id(
+    pub(super) fn point_non_id<C: CurveAffine>(
         &self,
-        value: Value<pallas::Affine>,
+        value: Value<C>,
         offset: usize,
-        region: &mut Region<'_, pallas::Base>,
-    ) -> Result<NonIdentityEccPoint, Error> {
+        region: &mut Region<'_, C::Base>,
+    ) -> Result<NonIdentityEccPoint<C>, Error> {
         // Enable `q_point_non_id` selector
         self.q_point_non_id.enable(region, offset)?;
 
         // Return an error if the point is the identity.
-        value.error_if_known_and(|value| value == &pallas::Affine::identity())?;
+        value.error_if_known_and(|value| value == &C::identity())?;
 
         let value = value.map(|value| {
             let value = value.coordinates().unwrap();
@@ -188,6 +186,7 @@ impl Config {
 
 #[cfg(test)]
 pub mod tests {
+    use group::prime::PrimeCurveAffine;
     use halo2_proofs::circuit::Layouter;
     use pasta_curves::pallas;
 
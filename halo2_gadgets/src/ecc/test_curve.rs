@@ -0,0 +1,636 @@
+//! A 254-bit prime-order curve, used to test the ECC chip on a curve other than Pallas
+//! and Vesta.
+//!
+//! The curve is $E: y^2 = x^3 + 3$ over $\mathbb{F}_p$, with prime order $q$, where
+//! $p = 2^{253} + t_p$ and $q = 2^{253} + t_q$ for $t_p, t_q < 2^{128}$. As $3$ is not
+//! a square in $\mathbb{F}_p$, no point has $x = 0$, and the identity is represented
+//! in affine coordinates as $(0, 0)$.
+//!
+//! The implementation is straightforward rather than constant-time, and is only
+//! intended for tests.
+
+#![allow(clippy::derived_hash_with_manual_eq)]
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use ff::{Field, PrimeField, WithSmallOrderMulGroup};
+use group::{
+    prime::{PrimeCurve, PrimeCurveAffine, PrimeGroup},
+    Curve, Group, GroupEncoding,
+};
+use pasta_curves::arithmetic::{Coordinates, CurveAffine, CurveExt};
+use rand::RngCore;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+use super::chip::EccCurve;
+
+pub(crate) use base::Base;
+pub(crate) use scalar::Scalar;
+
+// Each field is derived in its own module, as the derive macro defines module-level
+// constants.
+mod base {
+    use ff::{PrimeField, WithSmallOrderMulGroup};
+
+    /// The base field of the curve.
+    #[derive(PrimeField)]
+    #[PrimeFieldModulus = "14474011154664524427946373126085988481778129298083959413406318020498665566527"]
+    #[PrimeFieldGenerator = "3"]
+    #[PrimeFieldReprEndianness = "little"]
+    pub(crate) struct Base([u64; 4]);
+
+    impl WithSmallOrderMulGroup<3> for Base {
+        // 3447499218813430606147042341397581106219495552336265854011280267726907732146,
+        // in Montgomery form.
+        const ZETA: Self = Base([
+            0xbb02df6140267ac6,
+            0xac956f7fe557bfdc,
+            0x86c27187aa506b88,
+            0x11f13da73acb4e2f,
+        ]);
+    }
+}
+
+mod scalar {
+    use ff::{PrimeField, WithSmallOrderMulGroup};
+
+    /// The scalar field of the curve.
+    #[derive(PrimeField)]
+    #[PrimeFieldModulus = "14474011154664524427946373126085988481969963593068232880195596304892602647009"]
+    #[PrimeFieldGenerator = "13"]
+    #[PrimeFieldReprEndianness = "little"]
+    pub(crate) struct Scalar([u64; 4]);
+
+    impl WithSmallOrderMulGroup<3> for Scalar {
+        // 1632097446003342835052034913541848768115956214658359325626430597545668497039,
+        // in Montgomery form.
+        const ZETA: Self = Scalar([
+            0x152fc89cf7efc18e,
+            0x2477741197e76e6e,
+            0x98b4d870a59075cc,
+            0x1bc83d5923cd0a02,
+        ]);
+    }
+}
+
+/// A point on the curve, in Jacobian coordinates. The identity has $z = 0$.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Point {
+    x: Base,
+    y: Base,
+    z: Base,
+}
+
+/// A point on the curve, in affine coordinates. The identity is $(0, 0)$.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Affine {
+    x: Base,
+    y: Base,
+}
+
+impl Point {
+    fn double_point(&self) -> Self {
+        // dbl-2009-l, for a = 0.
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = b.square();
+        let d = ((self.x + b).square() - a - c).double();
+        let e = a.double() + a;
+        let x = e.square() - d.double();
+        let y = e * (d - x) - c.double().double().double();
+        let z = (self.y * self.z).double();
+        Point { x, y, z }
+    }
+
+    fn add_point(&self, other: &Self) -> Self {
+        if self.is_identity_vartime() {
+            return *other;
+        }
+        if other.is_identity_vartime() {
+            return *self;
+        }
+
+        // add-2007-bl
+        let z1z1 = self.z.square();
+        let z2z2 = other.z.square();
+        let u1 = self.x * z2z2;
+        let u2 = other.x * z1z1;
+        let s1 = self.y * other.z * z2z2;
+        let s2 = other.y * self.z * z1z1;
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double_point()
+            } else {
+                Point::default()
+            };
+        }
+        let h = u2 - u1;
+        let i = h.double().square();
+        let j = h * i;
+        let r = (s2 - s1).double();
+        let v = u1 * i;
+        let x = r.square() - j - v.double();
+        let y = r * (v - x) - (s1 * j).double();
+        let z = ((self.z + other.z).square() - z1z1 - z2z2) * h;
+        Point { x, y, z }
+    }
+
+    fn mul_scalar(&self, scalar: &Scalar) -> Self {
+        scalar
+            .to_repr()
+            .as_ref()
+            .iter()
+            .rev()
+            .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+            .fold(Point::default(), |acc, bit| {
+                let acc = acc.double_point();
+                if bit {
+                    acc.add_point(self)
+                } else {
+                    acc
+                }
+            })
+    }
+
+    fn is_identity_vartime(&self) -> bool {
+        self.z.is_zero_vartime()
+    }
+}
+
+impl Affine {
+    fn is_identity_vartime(&self) -> bool {
+        self.x.is_zero_vartime() && self.y.is_zero_vartime()
+    }
+}
+
+impl From<Affine> for Point {
+    fn from(p: Affine) -> Self {
+        if p.is_identity_vartime() {
+            Point::default()
+        } else {
+            Point {
+                x: p.x,
+                y: p.y,
+                z: Base::ONE,
+            }
+        }
+    }
+}
+
+impl From<Point> for Affine {
+    fn from(p: Point) -> Self {
+        if p.is_identity_vartime() {
+            Affine::default()
+        } else {
+            let z_inv = p.z.invert().unwrap();
+            let z_inv2 = z_inv.square();
+            Affine {
+                x: p.x * z_inv2,
+                y: p.y * z_inv2 * z_inv,
+            }
+        }
+    }
+}
+
+impl PartialEq for Point {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.is_identity_vartime(), other.is_identity_vartime()) {
+            (true, true) => true,
+            (false, false) => {
+                let z1z1 = self.z.square();
+                let z2z2 = other.z.square();
+                self.x * z2z2 == other.x * z1z1
+                    && self.y * z2z2 * other.z == other.y * z1z1 * self.z
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Point {}
+
+impl ConstantTimeEq for Point {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        Choice::from((self == other) as u8)
+    }
+}
+
+impl ConstantTimeEq for Affine {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        Choice::from((self == other) as u8)
+    }
+}
+
+impl ConditionallySelectable for Point {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Point {
+            x: Base::conditional_select(&a.x, &b.x, choice),
+            y: Base::conditional_select(&a.y, &b.y, choice),
+            z: Base::conditional_select(&a.z, &b.z, choice),
+        }
+    }
+}
+
+impl ConditionallySelectable for Affine {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Affine {
+            x: Base::conditional_select(&a.x, &b.x, choice),
+            y: Base::conditional_select(&a.y, &b.y, choice),
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point { y: -self.y, ..self }
+    }
+}
+
+impl Neg for Affine {
+    type Output = Affine;
+
+    fn neg(self) -> Affine {
+        Affine { y: -self.y, ..self }
+    }
+}
+
+macro_rules! impl_add_sub {
+    ($lhs:ty, $rhs:ty) => {
+        impl Add<$rhs> for $lhs {
+            type Output = Point;
+
+            fn add(self, rhs: $rhs) -> Point {
+                Point::from(self).add_point(&Point::from(rhs))
+            }
+        }
+
+        impl<'r> Add<&'r $rhs> for $lhs {
+            type Output = Point;
+
+            fn add(self, rhs: &'r $rhs) -> Point {
+                self + *rhs
+            }
+        }
+
+        impl Sub<$rhs> for $lhs {
+            type Output = Point;
+
+            fn sub(self, rhs: $rhs) -> Point {
+                self + (-rhs)
+            }
+        }
+
+        impl<'r> Sub<&'r $rhs> for $lhs {
+            type Output = Point;
+
+            fn sub(self, rhs: &'r $rhs) -> Point {
+                self - *rhs
+            }
+        }
+    };
+}
+
+impl_add_sub!(Point, Point);
+impl_add_sub!(Point, Affine);
+impl_add_sub!(Affine, Affine);
+
+macro_rules! impl_assign_ops {
+    ($rhs:ty) => {
+        impl AddAssign<$rhs> for Point {
+            fn add_assign(&mut self, rhs: $rhs) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<'r> AddAssign<&'r $rhs> for Point {
+            fn add_assign(&mut self, rhs: &'r $rhs) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl SubAssign<$rhs> for Point {
+            fn sub_assign(&mut self, rhs: $rhs) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<'r> SubAssign<&'r $rhs> for Point {
+            fn sub_assign(&mut self, rhs: &'r $rhs) {
+                *self = *self - rhs;
+            }
+        }
+    };
+}
+
+impl_assign_ops!(Point);
+impl_assign_ops!(Affine);
+
+impl Mul<Scalar> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: Scalar) -> Point {
+        self.mul_scalar(&rhs)
+    }
+}
+
+impl<'r> Mul<&'r Scalar> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: &'r Scalar) -> Point {
+        self.mul_scalar(rhs)
+    }
+}
+
+impl Mul<Scalar> for Affine {
+    type Output = Point;
+
+    fn mul(self, rhs: Scalar) -> Point {
+        Point::from(self).mul_scalar(&rhs)
+    }
+}
+
+impl<'r> Mul<&'r Scalar> for Affine {
+    type Output = Point;
+
+    fn mul(self, rhs: &'r Scalar) -> Point {
+        Point::from(self).mul_scalar(rhs)
+    }
+}
+
+impl MulAssign<Scalar> for Point {
+    fn mul_assign(&mut self, rhs: Scalar) {
+        *self = *self * rhs;
+    }
+}
+
+impl<'r> MulAssign<&'r Scalar> for Point {
+    fn mul_assign(&mut self, rhs: &'r Scalar) {
+        *self = *self * rhs;
+    }
+}
+
+impl Sum for Point {
+    fn sum<I: Iterator<Item = Point>>(iter: I) -> Point {
+        iter.fold(Point::default(), |acc, p| acc + p)
+    }
+}
+
+impl<'a> Sum<&'a Point> for Point {
+    fn sum<I: Iterator<Item = &'a Point>>(iter: I) -> Point {
+        iter.fold(Point::default(), |acc, p| acc + p)
+    }
+}
+
+impl Group for Point {
+    type Scalar = Scalar;
+
+    fn random(rng: impl RngCore) -> Self {
+        Point::generator() * Scalar::random(rng)
+    }
+
+    fn identity() -> Self {
+        Point::default()
+    }
+
+    fn generator() -> Self {
+        Affine::generator().into()
+    }
+
+    fn is_identity(&self) -> Choice {
+        Choice::from(self.is_identity_vartime() as u8)
+    }
+
+    fn double(&self) -> Self {
+        self.double_point()
+    }
+}
+
+/// Encodes a point as the little-endian encoding of its $x$-coordinate, with the
+/// parity of its $y$-coordinate in the most significant bit. The identity is encoded
+/// as zero.
+fn to_bytes(p: &Affine) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes.copy_from_slice(p.x.to_repr().as_ref());
+    if !p.is_identity_vartime() && p.y.is_odd().into() {
+        bytes[31] |= 0x80;
+    }
+    bytes
+}
+
+fn from_bytes(bytes: &[u8; 32]) -> CtOption<Affine> {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return CtOption::new(Affine::default(), Choice::from(1));
+    }
+    let mut x_repr = <Base as PrimeField>::Repr::default();
+    x_repr.as_mut().copy_from_slice(bytes);
+    x_repr.as_mut()[31] &= 0x7f;
+    let y_is_odd = Choice::from(bytes[31] >> 7);
+    Base::from_repr(x_repr).and_then(|x| {
+        (x.square() * x + Point::b()).sqrt().map(|y| Affine {
+            x,
+            y: Base::conditional_select(&y, &-y, y.is_odd() ^ y_is_odd),
+        })
+    })
+}
+
+impl GroupEncoding for Point {
+    type Repr = [u8; 32];
+
+    fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        from_bytes(bytes).map(Point::from)
+    }
+
+    fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        Self::from_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Self::Repr {
+        to_bytes(&self.to_affine())
+    }
+}
+
+impl GroupEncoding for Affine {
+    type Repr = [u8; 32];
+
+    fn from_bytes(bytes: &Self::Repr) -> CtOption<Self> {
+        from_bytes(bytes)
+    }
+
+    fn from_bytes_unchecked(bytes: &Self::Repr) -> CtOption<Self> {
+        from_bytes(bytes)
+    }
+
+    fn to_bytes(&self) -> Self::Repr {
+        to_bytes(self)
+    }
+}
+
+impl Curve for Point {
+    type AffineRepr = Affine;
+
+    fn to_affine(&self) -> Affine {
+        (*self).into()
+    }
+}
+
+impl PrimeGroup for Point {}
+
+impl PrimeCurve for Point {
+    type Affine = Affine;
+}
+
+impl PrimeCurveAffine for Affine {
+    type Scalar = Scalar;
+    type Curve = Point;
+
+    fn identity() -> Self {
+        Affine::default()
+    }
+
+    fn generator() -> Self {
+        Affine {
+            x: Base::ONE,
+            y: Base::from(2),
+        }
+    }
+
+    fn is_identity(&self) -> Choice {
+        Choice::from(self.is_identity_vartime() as u8)
+    }
+
+    fn to_curve(&self) -> Point {
+        (*self).into()
+    }
+}
+
+impl CurveExt for Point {
+    type ScalarExt = Scalar;
+    type Base = Base;
+    type AffineExt = Affine;
+
+    const CURVE_ID: &'static str = "halo2_gadgets-test-254";
+
+    fn endo(&self) -> Self {
+        Point {
+            x: self.x * Base::ZETA,
+            ..*self
+        }
+    }
+
+    fn jacobian_coordinates(&self) -> (Base, Base, Base) {
+        (self.x, self.y, self.z)
+    }
+
+    /// Hashes to the curve by incrementing a candidate $x$-coordinate until it is on
+    /// the curve.
+    fn hash_to_curve<'a>(domain_prefix: &'a str) -> Box<dyn Fn(&[u8]) -> Self + 'a> {
+        Box::new(move |message| {
+            let mut hasher = DefaultHasher::new();
+            (domain_prefix, message).hash(&mut hasher);
+            let mut x = Base::from(hasher.finish());
+            loop {
+                if let Some(y) = Option::<Base>::from((x.square() * x + Self::b()).sqrt()) {
+                    return Affine { x, y }.into();
+                }
+                x += Base::ONE;
+            }
+        })
+    }
+
+    fn is_on_curve(&self) -> Choice {
+        let z6 = self.z.square().square() * self.z.square();
+        Choice::from(
+            (self.is_identity_vartime()
+                || self.y.square() == self.x.square() * self.x + z6 * Self::b()) as u8,
+        )
+    }
+
+    fn a() -> Base {
+        Base::ZERO
+    }
+
+    fn b() -> Base {
+        Base::from(3)
+    }
+
+    fn new_jacobian(x: Base, y: Base, z: Base) -> CtOption<Self> {
+        let p = Point { x, y, z };
+        CtOption::new(p, p.is_on_curve())
+    }
+}
+
+impl CurveAffine for Affine {
+    type ScalarExt = Scalar;
+    type Base = Base;
+    type CurveExt = Point;
+
+    fn coordinates(&self) -> CtOption<Coordinates<Self>> {
+        Coordinates::from_xy(self.x, self.y)
+    }
+
+    fn from_xy(x: Base, y: Base) -> CtOption<Self> {
+        let p = Affine { x, y };
+        CtOption::new(p, p.is_on_curve())
+    }
+
+    fn is_on_curve(&self) -> Choice {
+        Choice::from(
+            (self.is_identity_vartime() || self.y.square() == self.x.square() * self.x + Self::b())
+                as u8,
+        )
+    }
+
+    fn a() -> Base {
+        Base::ZERO
+    }
+
+    fn b() -> Base {
+        Base::from(3)
+    }
+}
+
+impl EccCurve for Affine {
+    type NativeField = Base;
+    type ScalarField = Scalar;
+}
+
+#[test]
+fn curve_arithmetic() {
+    let g = Point::generator();
+    let a = Scalar::random(rand::rngs::OsRng);
+    let b = Scalar::random(rand::rngs::OsRng);
+
+    assert!(bool::from(Affine::generator().is_on_curve()));
+    assert_eq!(g * (a + b), g * a + g * b);
+    assert_eq!(g.double(), g + g);
+    assert_eq!(g * -Scalar::ONE, -g);
+    assert!(bool::from((g * a - g * a).is_identity()));
+
+    // The generator has order q.
+    assert!(bool::from((g * -Scalar::ONE + g).is_identity()));
+
+    let p = (g * a).to_affine();
+    assert_eq!(Affine::from_bytes(&p.to_bytes()).unwrap(), p);
+    assert_eq!((-p).to_bytes()[..31], p.to_bytes()[..31]);
+
+    // The moduli have the form required by the chip.
+    assert_eq!(Base::NUM_BITS, 254);
+    assert_eq!(Scalar::NUM_BITS, 254);
+    assert_eq!(
+        Affine::t_q(),
+        Base::from_u128(311215509863162375263398303903461442017)
+    );
+    assert_eq!(
+        Affine::t_p(),
+        Base::from_u128(119381214878888908474120019509524361535)
+    );
+    assert_eq!(Base::ZETA.pow([3]), Base::ONE);
+    assert_eq!(Scalar::ZETA.pow([3]), Scalar::ONE);
+}
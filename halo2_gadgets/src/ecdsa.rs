@@ -0,0 +1,204 @@
+//! ECDSA signature verification over curves with foreign base and scalar fields, such
+//! as secp256k1 and P-256.
+
+use std::fmt::Debug;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::Error,
+};
+
+use crate::foreign_field::{FieldElement, FieldInstructions, ForeignField, U256};
+use primitives::AffinePoint;
+
+pub mod chip;
+pub mod primitives;
+
+/// A prime-order short Weierstrass curve $y^2 = x^3 + Ax + B$, whose base and scalar
+/// fields are both foreign to the circuit.
+pub trait ForeignCurve: Clone + Copy + Debug + Eq {
+    /// The field over which the curve is defined.
+    type Base: ForeignField;
+    /// The scalar field of the curve, whose modulus is the order of the group.
+    type Scalar: ForeignField;
+
+    /// The coefficient $A$ of the curve equation.
+    const A: U256;
+    /// The coefficient $B$ of the curve equation.
+    const B: U256;
+    /// The generator of the group.
+    const GENERATOR: AffinePoint;
+}
+
+/// The set of circuit instructions required to use the ECDSA gadgets.
+pub trait EcdsaInstructions<F: PrimeField, C: ForeignCurve>: Chip<F> + Clone + Debug + Eq {
+    /// The chip used for arithmetic in the scalar field of the curve.
+    type ScalarChip: FieldInstructions<F, C::Scalar>;
+    /// Variable representing a non-identity point on the curve.
+    type Point: Clone + Debug;
+
+    /// Returns the chip used for arithmetic in the scalar field of the curve.
+    fn scalar_chip(&self) -> Self::ScalarChip;
+
+    /// Witnesses the given point as a private input to the circuit, constraining it to
+    /// be on the curve.
+    fn witness_point(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: Value<AffinePoint>,
+    ) -> Result<Self::Point, Error>;
+
+    /// Returns `a + b`.
+    ///
+    /// This is incomplete addition: the circuit is unsatisfiable if `a = ±b`.
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Point,
+        b: &Self::Point,
+    ) -> Result<Self::Point, Error>;
+
+    /// Returns `[scalar] point`.
+    ///
+    /// The circuit is unsatisfiable if the result is the identity, and may be
+    /// unsatisfiable with negligible probability for random scalars.
+    fn mul(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        scalar: &<Self::ScalarChip as FieldInstructions<F, C::Scalar>>::Element,
+        point: &Self::Point,
+    ) -> Result<Self::Point, Error>;
+
+    /// Returns `[scalar] G`, where `G` is the generator of the curve.
+    ///
+    /// The circuit is unsatisfiable if the result is the identity, and may be
+    /// unsatisfiable with negligible probability for random scalars.
+    fn mul_generator(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        scalar: &<Self::ScalarChip as FieldInstructions<F, C::Scalar>>::Element,
+    ) -> Result<Self::Point, Error>;
+
+    /// Returns the x-coordinate of `point`, reduced modulo the order of the group.
+    fn x_mod_n(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        point: &Self::Point,
+    ) -> Result<<Self::ScalarChip as FieldInstructions<F, C::Scalar>>::Element, Error>;
+}
+
+/// An element of the scalar field of the curve `C`.
+pub type Scalar<F, C, EcdsaChip> = FieldElement<
+    F,
+    <C as ForeignCurve>::Scalar,
+    <EcdsaChip as EcdsaInstructions<F, C>>::ScalarChip,
+>;
+
+/// An ECDSA signature.
+#[derive(Debug)]
+pub struct Signature<F: PrimeField, C: ForeignCurve, EcdsaChip: EcdsaInstructions<F, C>> {
+    r: Scalar<F, C, EcdsaChip>,
+    s: Scalar<F, C, EcdsaChip>,
+}
+
+impl<F: PrimeField, C: ForeignCurve, EcdsaChip: EcdsaInstructions<F, C>>
+    Signature<F, C, EcdsaChip>
+{
+    /// Witnesses the given signature.
+    ///
+    /// This returns [`Error::Witness`] if either component of the signature is known and
+    /// not less than the order of the group.
+    pub fn new(
+        chip: EcdsaChip::ScalarChip,
+        mut layouter: impl Layouter<F>,
+        value: Value<primitives::Signature>,
+    ) -> Result<Self, Error> {
+        let r = Scalar::<F, C, EcdsaChip>::new(
+            chip.clone(),
+            layouter.namespace(|| "r"),
+            value.map(|signature| signature.r),
+        )?;
+        let s = Scalar::<F, C, EcdsaChip>::new(
+            chip,
+            layouter.namespace(|| "s"),
+            value.map(|signature| signature.s),
+        )?;
+        Ok(Signature { r, s })
+    }
+
+    /// Returns the `r` component of this signature.
+    pub fn r(&self) -> &Scalar<F, C, EcdsaChip> {
+        &self.r
+    }
+
+    /// Returns the `s` component of this signature.
+    pub fn s(&self) -> &Scalar<F, C, EcdsaChip> {
+        &self.s
+    }
+}
+
+/// An ECDSA public key.
+#[derive(Debug)]
+pub struct PublicKey<F: PrimeField, C: ForeignCurve, EcdsaChip: EcdsaInstructions<F, C>> {
+    chip: EcdsaChip,
+    inner: EcdsaChip::Point,
+}
+
+impl<F: PrimeField, C: ForeignCurve, EcdsaChip: EcdsaInstructions<F, C>>
+    PublicKey<F, C, EcdsaChip>
+{
+    /// Witnesses the given public key, constraining it to be on the curve.
+    pub fn new(
+        chip: EcdsaChip,
+        mut layouter: impl Layouter<F>,
+        value: Value<AffinePoint>,
+    ) -> Result<Self, Error> {
+        let inner = chip.witness_point(&mut layouter, value);
+        inner.map(|inner| PublicKey { chip, inner })
+    }
+
+    /// Returns the inner point.
+    pub fn inner(&self) -> &EcdsaChip::Point {
+        &self.inner
+    }
+
+    /// Constrains `signature` to be a valid signature by this key over `msg_hash`.
+    ///
+    /// `msg_hash` is the hash of the message, truncated to the bit length of the group
+    /// order and reduced modulo the group order.
+    pub fn verify_prehashed(
+        &self,
+        mut layouter: impl Layouter<F>,
+        msg_hash: &Scalar<F, C, EcdsaChip>,
+        signature: &Signature<F, C, EcdsaChip>,
+    ) -> Result<(), Error> {
+        let scalar_chip = self.chip.scalar_chip();
+
+        // Inverting r and s constrains both to be non-zero.
+        signature.r.inv(layouter.namespace(|| "r^-1"))?;
+        let w = signature.s.inv(layouter.namespace(|| "w = s^-1"))?;
+
+        // R = [z w] G + [r w] Q
+        let u_1 = msg_hash.mul(layouter.namespace(|| "u_1 = z w"), &w)?;
+        let u_2 = signature.r.mul(layouter.namespace(|| "u_2 = r w"), &w)?;
+        let u_1_g = self
+            .chip
+            .mul_generator(&mut layouter.namespace(|| "[u_1] G"), u_1.inner())?;
+        let u_2_q = self.chip.mul(
+            &mut layouter.namespace(|| "[u_2] Q"),
+            u_2.inner(),
+            &self.inner,
+        )?;
+        let big_r = self
+            .chip
+            .add(&mut layouter.namespace(|| "R"), &u_1_g, &u_2_q)?;
+
+        // Check that r = x(R) mod n.
+        let x = self
+            .chip
+            .x_mod_n(&mut layouter.namespace(|| "x(R) mod n"), &big_r)?;
+        Scalar::<F, C, EcdsaChip>::from_inner(scalar_chip, x)
+            .constrain_equal(layouter.namespace(|| "r = x(R) mod n"), &signature.r)
+    }
+}
@@ -0,0 +1,951 @@
+//! Chip implementation of ECDSA verification over curves with foreign fields.
+
+use std::convert::TryInto;
+
+use super::{
+    primitives::{self, AffinePoint},
+    EcdsaInstructions, ForeignCurve,
+};
+use crate::{
+    foreign_field::{
+        add_mod,
+        chip::{ForeignFieldChip, ForeignFieldConfig, ForeignFieldElement, LIMB_BITS, NUM_LIMBS},
+        inv_mod, mul_mod, FieldInstructions, ForeignField, U256,
+    },
+    sinsemilla::primitives as sinsemilla,
+    utilities::{
+        decompose_running_sum::RunningSumConfig,
+        lookup_range_check::{LookupRangeCheck, LookupRangeCheckConfig},
+    },
+};
+
+use ff::{Field, PrimeFieldBits};
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// The number of bits in each window of a scalar.
+pub const WINDOW_NUM_BITS: usize = 2;
+
+/// The number of points in each window table.
+const H: usize = 1 << WINDOW_NUM_BITS;
+
+/// A point on the curve `C` other than the identity, whose coordinates are elements of
+/// the foreign field `C::Base`.
+#[derive(Clone, Debug)]
+pub struct ForeignPoint<F: Field, C: ForeignCurve> {
+    x: ForeignFieldElement<F, C::Base>,
+    y: ForeignFieldElement<F, C::Base>,
+}
+
+impl<F: Field, C: ForeignCurve> ForeignPoint<F, C> {
+    /// Returns the x-coordinate of this point.
+    pub fn x(&self) -> &ForeignFieldElement<F, C::Base> {
+        &self.x
+    }
+
+    /// Returns the y-coordinate of this point.
+    pub fn y(&self) -> &ForeignFieldElement<F, C::Base> {
+        &self.y
+    }
+
+    /// Returns the value of this point, if known.
+    pub fn value(&self) -> Value<AffinePoint> {
+        self.x
+            .value()
+            .zip(self.y.value())
+            .map(|(x, y)| AffinePoint { x, y })
+    }
+}
+
+/// A window of a scalar, represented as consecutive outputs `(z_i, z_{i+1})` of a running
+/// sum decomposition. The window is `z_i - 2^WINDOW_NUM_BITS * z_{i+1}`.
+type Window<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+/// Configuration for the [`EcdsaChip`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EcdsaConfig<
+    F: PrimeFieldBits,
+    C: ForeignCurve,
+    Lookup: LookupRangeCheck<F, { sinsemilla::K }> = LookupRangeCheckConfig<F, { sinsemilla::K }>,
+> {
+    q_select: Selector,
+    /// Advice columns used by the chip.
+    pub advices: [Column<Advice>; 2 * NUM_LIMBS],
+    running_sum: RunningSumConfig<F, WINDOW_NUM_BITS>,
+    base_config: ForeignFieldConfig<F, C::Base, Lookup>,
+    scalar_config: ForeignFieldConfig<F, C::Scalar, Lookup>,
+}
+
+/// An [`EcdsaInstructions`] chip for the curve `C`.
+///
+/// Point arithmetic is performed with affine formulae over a [`ForeignFieldChip`] for
+/// the base field of the curve. Scalar multiplications use windows of
+/// [`WINDOW_NUM_BITS`] bits, selected from a table of points by a Lagrange interpolation
+/// gate:
+/// - [`EcdsaInstructions::mul_generator`] uses constant tables of multiples of the
+///   generator, so that it requires no doublings. The tables are assigned once, by
+///   [`EcdsaChip::load`].
+/// - [`EcdsaInstructions::mul`] recodes the scalar into odd signed digits, and uses a
+///   table of odd multiples of the point computed in the circuit.
+#[derive(Clone, Debug)]
+pub struct EcdsaChip<
+    F: PrimeFieldBits,
+    C: ForeignCurve,
+    Lookup: LookupRangeCheck<F, { sinsemilla::K }> = LookupRangeCheckConfig<F, { sinsemilla::K }>,
+> {
+    config: EcdsaConfig<F, C, Lookup>,
+    generator_tables: GeneratorTables<F, C>,
+}
+
+/// The tables of multiples of the generator used by
+/// [`EcdsaInstructions::mul_generator`], as assigned by [`EcdsaChip::load`].
+pub type GeneratorTables<F, C> = Vec<[ForeignPoint<F, C>; H]>;
+
+// The loaded tables are determined by the config, so chips are compared by their configs.
+impl<F: PrimeFieldBits, C: ForeignCurve, Lookup: LookupRangeCheck<F, { sinsemilla::K }>> PartialEq
+    for EcdsaChip<F, C, Lookup>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config
+    }
+}
+
+impl<F: PrimeFieldBits, C: ForeignCurve, Lookup: LookupRangeCheck<F, { sinsemilla::K }>> Eq
+    for EcdsaChip<F, C, Lookup>
+{
+}
+
+impl<F: PrimeFieldBits, C: ForeignCurve, Lookup: LookupRangeCheck<F, { sinsemilla::K }>> Chip<F>
+    for EcdsaChip<F, C, Lookup>
+{
+    type Config = EcdsaConfig<F, C, Lookup>;
+    type Loaded = GeneratorTables<F, C>;
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &self.generator_tables
+    }
+}
+
+impl<F: PrimeFieldBits, C: ForeignCurve, Lookup: LookupRangeCheck<F, { sinsemilla::K }>>
+    EcdsaChip<F, C, Lookup>
+{
+    /// Reconstructs this chip from the given config and the generator tables loaded by
+    /// [`EcdsaChip::load`].
+    pub fn construct(
+        config: EcdsaConfig<F, C, Lookup>,
+        generator_tables: GeneratorTables<F, C>,
+    ) -> Self {
+        Self {
+            config,
+            generator_tables,
+        }
+    }
+
+    /// Assigns the tables of multiples of the generator used by
+    /// [`EcdsaInstructions::mul_generator`].
+    ///
+    /// The tables should be loaded once per circuit, and shared by every chip
+    /// constructed from `config`.
+    pub fn load(
+        config: &EcdsaConfig<F, C, Lookup>,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<GeneratorTables<F, C>, Error> {
+        let base_chip = ForeignFieldChip::construct(config.base_config.clone());
+        generator_tables::<C>()
+            .into_iter()
+            .map(|table| {
+                let table = table
+                    .iter()
+                    .map(|entry| {
+                        Ok(ForeignPoint {
+                            x: base_chip.constant(layouter, entry.x)?,
+                            y: base_chip.constant(layouter, entry.y)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                Ok(table.try_into().unwrap())
+            })
+            .collect()
+    }
+
+    /// Configures the chip.
+    ///
+    /// # Side effects
+    ///
+    /// All columns in `advices` will be equality-enabled. The circuit must also have a
+    /// fixed column enabled for constants.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 2 * NUM_LIMBS],
+        range_check: Lookup,
+    ) -> EcdsaConfig<F, C, Lookup> {
+        // Windows must not straddle limbs.
+        assert_eq!(LIMB_BITS % WINDOW_NUM_BITS, 0);
+
+        let base_config = ForeignFieldChip::configure(meta, advices, range_check);
+        let scalar_config = ForeignFieldChip::configure(meta, advices, range_check);
+        let q_range_check = meta.selector();
+        let running_sum = RunningSumConfig::configure(meta, q_range_check, advices[0]);
+
+        let config = EcdsaConfig {
+            q_select: meta.selector(),
+            advices,
+            running_sum,
+            base_config,
+            scalar_config,
+        };
+        config.create_select_gate(meta);
+        config
+    }
+
+    fn base_chip(&self) -> ForeignFieldChip<F, C::Base, Lookup> {
+        ForeignFieldChip::construct(self.config.base_config.clone())
+    }
+
+    /// Returns `-point`.
+    fn neg(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        point: &ForeignPoint<F, C>,
+    ) -> Result<ForeignPoint<F, C>, Error> {
+        Ok(ForeignPoint {
+            x: point.x.clone(),
+            y: self.base_chip().neg(layouter, &point.y)?,
+        })
+    }
+
+    /// Returns `[2] point`.
+    fn double(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        point: &ForeignPoint<F, C>,
+    ) -> Result<ForeignPoint<F, C>, Error> {
+        let base_chip = self.base_chip();
+        let ForeignPoint { x, y } = point;
+
+        // λ = (3 x^2 + A) / (2 y)
+        let x_squared = base_chip.mul(layouter, x, x)?;
+        let numerator = base_chip.add(layouter, &x_squared, &x_squared)?;
+        let mut numerator = base_chip.add(layouter, &numerator, &x_squared)?;
+        if !C::A.is_zero() {
+            let a = base_chip.constant(layouter, C::A)?;
+            numerator = base_chip.add(layouter, &numerator, &a)?;
+        }
+        let two_y = base_chip.add(layouter, y, y)?;
+        // The curve has prime order, so it has no points with y = 0. The denominator is
+        // therefore non-zero for any point on the curve.
+        let lambda = base_chip.div_unchecked(layouter, &numerator, &two_y)?;
+
+        // x_r = λ^2 - 2 x
+        let lambda_squared = base_chip.mul(layouter, &lambda, &lambda)?;
+        let x_r = base_chip.sub(layouter, &lambda_squared, x)?;
+        let x_r = base_chip.sub(layouter, &x_r, x)?;
+
+        // y_r = λ (x - x_r) - y
+        let dx = base_chip.sub(layouter, x, &x_r)?;
+        let y_r = base_chip.mul(layouter, &lambda, &dx)?;
+        let y_r = base_chip.sub(layouter, &y_r, y)?;
+
+        Ok(ForeignPoint { x: x_r, y: y_r })
+    }
+
+    /// Decomposes `scalar` into little-endian windows of [`WINDOW_NUM_BITS`] bits each.
+    fn decompose(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        scalar: &ForeignFieldElement<F, C::Scalar>,
+    ) -> Result<Vec<Window<F>>, Error> {
+        let num_bits = C::Scalar::MODULUS.bits();
+        let mut windows = vec![];
+        for (i, limb) in scalar.limbs().iter().enumerate() {
+            // Scalars are canonical, so the top limb has fewer bits than the others.
+            let limb_bits = num_bits.saturating_sub(i * LIMB_BITS).min(LIMB_BITS);
+            if limb_bits == 0 {
+                continue;
+            }
+            let num_windows = (limb_bits + WINDOW_NUM_BITS - 1) / WINDOW_NUM_BITS;
+            let zs = layouter.assign_region(
+                || format!("decompose limb {}", i),
+                |mut region| {
+                    self.config.running_sum.copy_decompose(
+                        &mut region,
+                        0,
+                        limb.clone(),
+                        true,
+                        limb_bits,
+                        num_windows,
+                    )
+                },
+            )?;
+            windows.extend(zs.windows(2).map(|zs| (zs[0].clone(), zs[1].clone())));
+        }
+        Ok(windows)
+    }
+
+    /// Returns `candidates[window]`.
+    fn select(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        window: &Window<F>,
+        candidates: &[ForeignPoint<F, C>; H],
+    ) -> Result<ForeignPoint<F, C>, Error> {
+        let config = &self.config;
+        let (z_cur, z_next) = window;
+        let index = z_cur.value().zip(z_next.value()).map(|(z_cur, z_next)| {
+            let window = *z_cur - *z_next * F::from(H as u64);
+            (0..H)
+                .find(|k| window == F::from(*k as u64))
+                .expect("window is range-checked")
+        });
+
+        layouter.assign_region(
+            || "select point",
+            |mut region| {
+                let mut select_coordinate =
+                    |offset: usize,
+                     coordinates: &[&ForeignFieldElement<F, C::Base>]|
+                     -> Result<ForeignFieldElement<F, C::Base>, Error> {
+                        let mut limbs = vec![];
+                        for i in 0..NUM_LIMBS {
+                            let row = offset + i;
+                            config.q_select.enable(&mut region, row)?;
+                            z_cur.copy_advice(|| "z_cur", &mut region, config.advices[0], row)?;
+                            z_next.copy_advice(|| "z_next", &mut region, config.advices[1], row)?;
+                            let mut values = Value::known(vec![]);
+                            for (k, coordinate) in coordinates.iter().enumerate() {
+                                let limb = coordinate.limbs()[i].copy_advice(
+                                    || format!("candidate {}", k),
+                                    &mut region,
+                                    config.advices[2 + k],
+                                    row,
+                                )?;
+                                values = values.zip(limb.value()).map(|(mut values, limb)| {
+                                    values.push(*limb);
+                                    values
+                                });
+                            }
+                            limbs.push(region.assign_advice(
+                                || "selected",
+                                config.advices[2 + H],
+                                row,
+                                || {
+                                    values
+                                        .clone()
+                                        .zip(index)
+                                        .map(|(values, index)| values[index])
+                                },
+                            )?);
+                        }
+
+                        let values =
+                            coordinates
+                                .iter()
+                                .fold(Value::known(vec![]), |values, coordinate| {
+                                    values.zip(coordinate.value()).map(|(mut values, value)| {
+                                        values.push(value);
+                                        values
+                                    })
+                                });
+                        // The selected limbs are equal to the limbs of one of the
+                        // candidates, which are canonical.
+                        Ok(ForeignFieldElement::from_canonical_limbs(
+                            limbs.try_into().unwrap(),
+                            values.zip(index).map(|(values, index)| values[index]),
+                        ))
+                    };
+
+                let xs = candidates.iter().map(|point| &point.x).collect::<Vec<_>>();
+                let ys = candidates.iter().map(|point| &point.y).collect::<Vec<_>>();
+                let x = select_coordinate(0, &xs)?;
+                let y = select_coordinate(NUM_LIMBS, &ys)?;
+                Ok(ForeignPoint { x, y })
+            },
+        )
+    }
+}
+
+impl<F: PrimeFieldBits, C: ForeignCurve, Lookup: LookupRangeCheck<F, { sinsemilla::K }>>
+    EcdsaConfig<F, C, Lookup>
+{
+    fn create_select_gate(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("select window", |meta| {
+            let q_select = meta.query_selector(self.q_select);
+            let z_cur = meta.query_advice(self.advices[0], Rotation::cur());
+            let z_next = meta.query_advice(self.advices[1], Rotation::cur());
+            let candidates = (0..H)
+                .map(|k| meta.query_advice(self.advices[2 + k], Rotation::cur()))
+                .collect::<Vec<_>>();
+            let selected = meta.query_advice(self.advices[2 + H], Rotation::cur());
+
+            // The window is range-constrained by the running sum decomposition.
+            let window = z_cur - z_next * F::from(H as u64);
+
+            // Interpolate the candidates at the window, using the Lagrange basis
+            // polynomials over {0, ..., H - 1}.
+            let interpolated = candidates.into_iter().enumerate().fold(
+                Expression::Constant(F::ZERO),
+                |acc, (k, candidate)| {
+                    let (numerator, denominator) = (0..H).filter(|j| *j != k).fold(
+                        (Expression::Constant(F::ONE), F::ONE),
+                        |(numerator, denominator), j| {
+                            let j = F::from(j as u64);
+                            (
+                                numerator * (window.clone() - Expression::Constant(j)),
+                                denominator * (F::from(k as u64) - j),
+                            )
+                        },
+                    );
+                    acc + numerator * denominator.invert().unwrap() * candidate
+                },
+            );
+
+            Constraints::with_selector(q_select, Some(("select", interpolated - selected)))
+        });
+    }
+}
+
+/// Returns the number of windows in the decomposition of a scalar of the curve `C`.
+fn num_windows<C: ForeignCurve>() -> usize {
+    (0..NUM_LIMBS)
+        .map(|i| {
+            let limb_bits = C::Scalar::MODULUS
+                .bits()
+                .saturating_sub(i * LIMB_BITS)
+                .min(LIMB_BITS);
+            (limb_bits + WINDOW_NUM_BITS - 1) / WINDOW_NUM_BITS
+        })
+        .sum()
+}
+
+/// Returns the tables of multiples of the generator used by
+/// [`EcdsaInstructions::mul_generator`].
+///
+/// Window `w` of the scalar, with value `k`, selects `[(k + 2) * H^w] G`. The offsets
+/// ensure that no table entry is the identity, and that the partial sums of all but the
+/// last window are distinct from the entries they are added to. The sum of the offsets
+/// is subtracted from the entries of the last window.
+fn generator_tables<C: ForeignCurve>() -> Vec<[AffinePoint; H]> {
+    let mut base = Some(C::GENERATOR);
+    let mut offset = None;
+    let mut tables = (0..num_windows::<C>())
+        .map(|_| {
+            let two_base = primitives::add::<C>(base, base);
+            offset = primitives::add::<C>(offset, two_base);
+
+            let mut entry = two_base;
+            let table = [(); H].map(|_| {
+                let current = entry;
+                entry = primitives::add::<C>(entry, base);
+                current
+            });
+
+            for _ in 0..WINDOW_NUM_BITS {
+                base = primitives::add::<C>(base, base);
+            }
+            table
+        })
+        .collect::<Vec<_>>();
+
+    let neg_offset = offset.map(|offset| primitives::neg::<C>(&offset));
+    let last = tables.last_mut().unwrap();
+    *last = last.map(|entry| primitives::add::<C>(entry, neg_offset));
+
+    tables
+        .into_iter()
+        .map(|table| table.map(|entry| entry.expect("table entries are not the identity")))
+        .collect()
+}
+
+impl<F: PrimeFieldBits, C: ForeignCurve, Lookup: LookupRangeCheck<F, { sinsemilla::K }>>
+    EcdsaInstructions<F, C> for EcdsaChip<F, C, Lookup>
+{
+    type ScalarChip = ForeignFieldChip<F, C::Scalar, Lookup>;
+    type Point = ForeignPoint<F, C>;
+
+    fn scalar_chip(&self) -> Self::ScalarChip {
+        ForeignFieldChip::construct(self.config.scalar_config.clone())
+    }
+
+    fn witness_point(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: Value<AffinePoint>,
+    ) -> Result<Self::Point, Error> {
+        let base_chip = self.base_chip();
+        let x = base_chip.witness(layouter, value.map(|point| point.x))?;
+        let y = base_chip.witness(layouter, value.map(|point| point.y))?;
+
+        // Constrain y^2 = x^3 + A x + B.
+        let y_squared = base_chip.mul(layouter, &y, &y)?;
+        let x_squared = base_chip.mul(layouter, &x, &x)?;
+        let x_cubed = base_chip.mul(layouter, &x_squared, &x)?;
+        let mut rhs = x_cubed;
+        if !C::A.is_zero() {
+            let a = base_chip.constant(layouter, C::A)?;
+            let a_x = base_chip.mul(layouter, &a, &x)?;
+            rhs = base_chip.add(layouter, &rhs, &a_x)?;
+        }
+        let b = base_chip.constant(layouter, C::B)?;
+        let rhs = base_chip.add(layouter, &rhs, &b)?;
+        base_chip.constrain_equal(layouter, &y_squared, &rhs)?;
+
+        Ok(ForeignPoint { x, y })
+    }
+
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Point,
+        b: &Self::Point,
+    ) -> Result<Self::Point, Error> {
+        let base_chip = self.base_chip();
+
+        // λ = (y_b - y_a) / (x_b - x_a), which constrains x_a != x_b.
+        let dy = base_chip.sub(layouter, &b.y, &a.y)?;
+        let dx = base_chip.sub(layouter, &b.x, &a.x)?;
+        let lambda = base_chip.div(layouter, &dy, &dx)?;
+
+        // x_r = λ^2 - x_a - x_b
+        let lambda_squared = base_chip.mul(layouter, &lambda, &lambda)?;
+        let x_r = base_chip.sub(layouter, &lambda_squared, &a.x)?;
+        let x_r = base_chip.sub(layouter, &x_r, &b.x)?;
+
+        // y_r = λ (x_a - x_r) - y_a
+        let dx = base_chip.sub(layouter, &a.x, &x_r)?;
+        let y_r = base_chip.mul(layouter, &lambda, &dx)?;
+        let y_r = base_chip.sub(layouter, &y_r, &a.y)?;
+
+        Ok(ForeignPoint { x: x_r, y: y_r })
+    }
+
+    fn mul(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        scalar: &ForeignFieldElement<F, C::Scalar>,
+        point: &Self::Point,
+    ) -> Result<Self::Point, Error> {
+        let scalar_chip = self.scalar_chip();
+        let n = C::Scalar::MODULUS;
+
+        // We use the signed digits d_i = 2 k_i - (H - 1), where k_i are the windows of
+        // s = (scalar + (H - 1) c) / 2 mod n, and c = sum_i H^i. Then
+        //     sum_i d_i H^i = 2 s - (H - 1) c = scalar (mod n).
+        // The digits are odd, so no table entry is the identity.
+        let c = (0..num_windows::<C>()).fold((U256::zero(), U256::one()), |(c, power), _| {
+            (add_mod(c, power, n), mul_mod(power, U256::from(H), n))
+        });
+        let shift = (0..(H - 1)).fold(U256::zero(), |shift, _| add_mod(shift, c.0, n));
+        let shift = scalar_chip.constant(layouter, shift)?;
+        let half = scalar_chip.constant(layouter, inv_mod(U256::from(2u64), n))?;
+        let recoded = scalar_chip.add(layouter, scalar, &shift)?;
+        let recoded = scalar_chip.mul(layouter, &recoded, &half)?;
+        let windows = self.decompose(layouter, &recoded)?;
+
+        // Compute the odd multiples [1] P, [3] P, ..., [H - 1] P.
+        let two_point = self.double(layouter, point)?;
+        let mut odd_multiples = vec![point.clone()];
+        for _ in 1..(H / 2) {
+            let next = self.add(layouter, odd_multiples.last().unwrap(), &two_point)?;
+            odd_multiples.push(next);
+        }
+        // The table entry for window k is [2 k - (H - 1)] P.
+        let mut table = odd_multiples
+            .iter()
+            .rev()
+            .map(|multiple| self.neg(layouter, multiple))
+            .collect::<Result<Vec<_>, _>>()?;
+        table.extend(odd_multiples);
+        let table: [_; H] = table.try_into().unwrap();
+
+        // Evaluate the digits from the most significant window down, using Horner's rule.
+        // The accumulator is an even multiple of P before each addition, and so cannot
+        // collide with a table entry except with negligible probability.
+        let (top, rest) = windows.split_last().unwrap();
+        let mut acc = self.select(layouter, top, &table)?;
+        for window in rest.iter().rev() {
+            for _ in 0..WINDOW_NUM_BITS {
+                acc = self.double(layouter, &acc)?;
+            }
+            let entry = self.select(layouter, window, &table)?;
+            acc = self.add(layouter, &acc, &entry)?;
+        }
+        Ok(acc)
+    }
+
+    fn mul_generator(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        scalar: &ForeignFieldElement<F, C::Scalar>,
+    ) -> Result<Self::Point, Error> {
+        let windows = self.decompose(layouter, scalar)?;
+
+        let mut acc: Option<Self::Point> = None;
+        for (window, table) in windows.iter().zip(self.loaded()) {
+            let entry = self.select(layouter, window, table)?;
+            acc = Some(match acc {
+                None => entry,
+                Some(acc) => self.add(layouter, &acc, &entry)?,
+            });
+        }
+        Ok(acc.unwrap())
+    }
+
+    fn x_mod_n(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        point: &Self::Point,
+    ) -> Result<ForeignFieldElement<F, C::Scalar>, Error> {
+        self.scalar_chip()
+            .reduce_from(layouter.namespace(|| "x mod n"), &point.x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{num_windows, EcdsaChip, EcdsaConfig, ForeignPoint, H, WINDOW_NUM_BITS};
+    use crate::{
+        ecdsa::{
+            primitives::{self, AffinePoint, Secp256k1, P256},
+            EcdsaInstructions, ForeignCurve, PublicKey, Scalar, Signature,
+        },
+        foreign_field::{FieldInstructions, ForeignField, U256},
+        utilities::lookup_range_check::{LookupRangeCheck, LookupRangeCheckConfig},
+    };
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pasta_curves::pallas;
+    use rand::{rngs::OsRng, RngCore};
+    use std::convert::TryInto;
+
+    struct MyCircuit<C: ForeignCurve> {
+        public_key: Value<AffinePoint>,
+        msg_hash: Value<U256>,
+        signature: Value<primitives::Signature>,
+        _marker: std::marker::PhantomData<C>,
+    }
+
+    impl<C: ForeignCurve> Circuit<pallas::Base> for MyCircuit<C> {
+        type Config = EcdsaConfig<pallas::Base, C>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                public_key: Value::unknown(),
+                msg_hash: Value::unknown(),
+                signature: Value::unknown(),
+                _marker: std::marker::PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 8].map(|_| meta.advice_column());
+            let running_sum = meta.advice_column();
+            let table_idx = meta.lookup_table_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let range_check = LookupRangeCheckConfig::configure(meta, running_sum, table_idx);
+            EcdsaChip::configure(meta, advices, range_check)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .lookup_config
+                .load_range_check_table(&mut layouter)?;
+            let generator_tables = EcdsaChip::load(&config, &mut layouter)?;
+            let chip = EcdsaChip::construct(config, generator_tables);
+
+            let public_key = PublicKey::new(
+                chip.clone(),
+                layouter.namespace(|| "public key"),
+                self.public_key,
+            )?;
+            let msg_hash = Scalar::<_, C, EcdsaChip<_, C>>::new(
+                chip.scalar_chip(),
+                layouter.namespace(|| "msg hash"),
+                self.msg_hash,
+            )?;
+            let signature = Signature::new(
+                chip.scalar_chip(),
+                layouter.namespace(|| "signature"),
+                self.signature,
+            )?;
+            public_key.verify_prehashed(layouter.namespace(|| "verify"), &msg_hash, &signature)
+        }
+    }
+
+    fn random_scalar<C: ForeignCurve>() -> U256 {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        U256::from_little_endian(&bytes) % C::Scalar::MODULUS
+    }
+
+    /// Returns a circuit verifying a random signature, with the signed message hash
+    /// replaced by `msg_hash` if given.
+    fn circuit<C: ForeignCurve>(msg_hash: Option<U256>) -> MyCircuit<C> {
+        let secret_key = random_scalar::<C>();
+        let signed_hash = random_scalar::<C>();
+        let signature =
+            primitives::sign_prehashed::<C>(secret_key, signed_hash, random_scalar::<C>()).unwrap();
+        MyCircuit {
+            public_key: Value::known(primitives::public_key::<C>(secret_key).unwrap()),
+            msg_hash: Value::known(msg_hash.unwrap_or(signed_hash)),
+            signature: Value::known(signature),
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    // Verifying a signature takes around 2^19 rows, so these tests are slow and are
+    // only run on request, with `cargo test -- --ignored`.
+    const K: u32 = 20;
+
+    #[test]
+    #[ignore]
+    fn ecdsa_secp256k1() {
+        let prover = MockProver::run(K, &circuit::<Secp256k1>(None), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A signature over a different message hash is rejected.
+        let prover = MockProver::run(K, &circuit::<Secp256k1>(Some(U256::one())), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    #[ignore]
+    fn ecdsa_p256() {
+        let prover = MockProver::run(K, &circuit::<P256>(None), vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    /// A foreign-field point operation tested by [`GadgetCircuit`].
+    #[derive(Clone, Copy)]
+    enum Gadget {
+        Add,
+        Double,
+        /// Selects from the table `[P, 2P, 3P, 4P]` at the given window of the scalar.
+        Select(usize),
+        /// Reduces the x-coordinate `P.x` modulo the group order. `P` is not checked to
+        /// be on the curve, so that the coordinate can exceed the group order.
+        XModN,
+    }
+
+    /// Checks that a single gadget maps `p` and `q` (or `scalar`) to `expected`, which
+    /// is the x-coordinate of the output for [`Gadget::XModN`].
+    struct GadgetCircuit<C: ForeignCurve> {
+        gadget: Gadget,
+        p: Value<AffinePoint>,
+        q: Value<AffinePoint>,
+        scalar: Value<U256>,
+        expected: Value<AffinePoint>,
+        _marker: std::marker::PhantomData<C>,
+    }
+
+    impl<C: ForeignCurve> Circuit<pallas::Base> for GadgetCircuit<C> {
+        type Config = EcdsaConfig<pallas::Base, C>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            GadgetCircuit {
+                gadget: self.gadget,
+                p: Value::unknown(),
+                q: Value::unknown(),
+                scalar: Value::unknown(),
+                expected: Value::unknown(),
+                _marker: std::marker::PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            MyCircuit::<C>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config
+                .base_config
+                .lookup_config
+                .load_range_check_table(&mut layouter)?;
+            // None of the gadgets use the generator tables.
+            let chip = EcdsaChip::construct(config, vec![]);
+            let base_chip = chip.base_chip();
+            let layouter = &mut layouter;
+
+            let result = match self.gadget {
+                Gadget::Add => {
+                    let p = chip.witness_point(layouter, self.p)?;
+                    let q = chip.witness_point(layouter, self.q)?;
+                    chip.add(layouter, &p, &q)?
+                }
+                Gadget::Double => {
+                    let p = chip.witness_point(layouter, self.p)?;
+                    chip.double(layouter, &p)?
+                }
+                Gadget::Select(window) => {
+                    let multiples = self.p.map(|p| {
+                        [1u64, 2, 3, 4].map(|k| primitives::mul::<C>(U256::from(k), &p).unwrap())
+                    });
+                    let table = (0..H)
+                        .map(|k| {
+                            chip.witness_point(layouter, multiples.map(|multiples| multiples[k]))
+                        })
+                        .collect::<Result<Vec<_>, Error>>()?;
+
+                    let scalar = chip.scalar_chip().witness(layouter, self.scalar)?;
+                    let windows = chip.decompose(layouter, &scalar)?;
+                    assert_eq!(windows.len(), num_windows::<C>());
+                    chip.select(layouter, &windows[window], &table.try_into().unwrap())?
+                }
+                Gadget::XModN => {
+                    let p = ForeignPoint {
+                        x: base_chip.witness(layouter, self.p.map(|p| p.x))?,
+                        y: base_chip.witness(layouter, self.p.map(|p| p.y))?,
+                    };
+                    let x = chip.x_mod_n(layouter, &p)?;
+                    let expected = chip
+                        .scalar_chip()
+                        .witness(layouter, self.expected.map(|expected| expected.x))?;
+                    return chip.scalar_chip().constrain_equal(layouter, &x, &expected);
+                }
+            };
+
+            let expected = chip.witness_point(layouter, self.expected)?;
+            base_chip.constrain_equal(layouter, &result.x, &expected.x)?;
+            base_chip.constrain_equal(layouter, &result.y, &expected.y)
+        }
+    }
+
+    fn random_point<C: ForeignCurve>() -> AffinePoint {
+        primitives::public_key::<C>(random_scalar::<C>()).unwrap()
+    }
+
+    /// Returns whether `gadget` maps `p`, `q` and `scalar` to `expected`.
+    fn check_gadget<C: ForeignCurve>(
+        gadget: Gadget,
+        p: AffinePoint,
+        q: AffinePoint,
+        scalar: U256,
+        expected: AffinePoint,
+    ) -> bool {
+        let circuit = GadgetCircuit::<C> {
+            gadget,
+            p: Value::known(p),
+            q: Value::known(q),
+            scalar: Value::known(scalar),
+            expected: Value::known(expected),
+            _marker: std::marker::PhantomData,
+        };
+        let prover = MockProver::run(GADGET_K, &circuit, vec![]).unwrap();
+        prover.verify().is_ok()
+    }
+
+    // The largest circuit, which witnesses a table of four points and decomposes the
+    // scalar into windows, fits in 2^13 rows.
+    const GADGET_K: u32 = 13;
+
+    fn ecdsa_gadgets<C: ForeignCurve>() {
+        let p = random_point::<C>();
+        let q = random_point::<C>();
+        let sum = primitives::add::<C>(Some(p), Some(q)).unwrap();
+        let double = primitives::add::<C>(Some(p), Some(p)).unwrap();
+
+        assert!(check_gadget::<C>(Gadget::Add, p, q, U256::zero(), sum));
+        assert!(check_gadget::<C>(
+            Gadget::Double,
+            p,
+            q,
+            U256::zero(),
+            double
+        ));
+
+        // Window i of the scalar selects [k_i + 1] P.
+        let scalar = random_scalar::<C>();
+        let last = num_windows::<C>() - 1;
+        for window in [0, 1, last] {
+            let k = (scalar >> (window * WINDOW_NUM_BITS)).low_u64() % (H as u64);
+            let expected = primitives::mul::<C>(U256::from(k + 1), &p).unwrap();
+            assert!(check_gadget::<C>(
+                Gadget::Select(window),
+                p,
+                q,
+                scalar,
+                expected
+            ));
+        }
+
+        // The largest x-coordinate is not a canonical scalar.
+        let x = C::Base::MODULUS - U256::one();
+        assert!(x >= C::Scalar::MODULUS);
+        let p = AffinePoint { x, y: U256::zero() };
+        let expected = AffinePoint {
+            x: x % C::Scalar::MODULUS,
+            y: U256::zero(),
+        };
+        assert!(check_gadget::<C>(
+            Gadget::XModN,
+            p,
+            q,
+            U256::zero(),
+            expected
+        ));
+    }
+
+    #[test]
+    fn ecdsa_gadgets_secp256k1() {
+        ecdsa_gadgets::<Secp256k1>();
+    }
+
+    #[test]
+    fn ecdsa_gadgets_p256() {
+        ecdsa_gadgets::<P256>();
+    }
+
+    #[test]
+    fn ecdsa_gadgets_wrong_result() {
+        let p = random_point::<Secp256k1>();
+        let q = random_point::<Secp256k1>();
+        let double = primitives::add::<Secp256k1>(Some(p), Some(p)).unwrap();
+
+        // P + Q is not [2] P.
+        assert!(!check_gadget::<Secp256k1>(
+            Gadget::Add,
+            p,
+            q,
+            U256::zero(),
+            double
+        ));
+
+        // The x-coordinate must be reduced to exactly x mod n.
+        let x = <Secp256k1 as ForeignCurve>::Base::MODULUS - U256::one();
+        let p = AffinePoint { x, y: U256::zero() };
+        let wrong = AffinePoint {
+            x: x % <Secp256k1 as ForeignCurve>::Scalar::MODULUS + U256::one(),
+            y: U256::zero(),
+        };
+        assert!(!check_gadget::<Secp256k1>(
+            Gadget::XModN,
+            p,
+            q,
+            U256::zero(),
+            wrong
+        ));
+    }
+}
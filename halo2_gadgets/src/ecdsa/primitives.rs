@@ -0,0 +1,341 @@
+//! Native implementation of ECDSA, and the curves supported by the gadget.
+
+use super::ForeignCurve;
+use crate::foreign_field::{add_mod, inv_mod, mul_mod, sub_mod, ForeignField, U256};
+
+/// A point on a short Weierstrass curve other than the identity, in affine coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AffinePoint {
+    /// The x-coordinate of the point.
+    pub x: U256,
+    /// The y-coordinate of the point.
+    pub y: U256,
+}
+
+/// An ECDSA signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    /// The x-coordinate of the nonce commitment, reduced modulo the group order.
+    pub r: U256,
+    /// The signature proof.
+    pub s: U256,
+}
+
+/// The base field of secp256k1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256k1Base;
+
+impl ForeignField for Secp256k1Base {
+    const MODULUS: U256 = U256([
+        0xFFFF_FFFE_FFFF_FC2F,
+        0xFFFF_FFFF_FFFF_FFFF,
+        0xFFFF_FFFF_FFFF_FFFF,
+        0xFFFF_FFFF_FFFF_FFFF,
+    ]);
+}
+
+/// The scalar field of secp256k1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256k1Scalar;
+
+impl ForeignField for Secp256k1Scalar {
+    const MODULUS: U256 = U256([
+        0xBFD2_5E8C_D036_4141,
+        0xBAAE_DCE6_AF48_A03B,
+        0xFFFF_FFFF_FFFF_FFFE,
+        0xFFFF_FFFF_FFFF_FFFF,
+    ]);
+}
+
+/// The secp256k1 curve, $y^2 = x^3 + 7$.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Secp256k1;
+
+impl ForeignCurve for Secp256k1 {
+    type Base = Secp256k1Base;
+    type Scalar = Secp256k1Scalar;
+
+    const A: U256 = U256([0, 0, 0, 0]);
+    const B: U256 = U256([7, 0, 0, 0]);
+    const GENERATOR: AffinePoint = AffinePoint {
+        x: U256([
+            0x59F2_815B_16F8_1798,
+            0x029B_FCDB_2DCE_28D9,
+            0x55A0_6295_CE87_0B07,
+            0x79BE_667E_F9DC_BBAC,
+        ]),
+        y: U256([
+            0x9C47_D08F_FB10_D4B8,
+            0xFD17_B448_A685_5419,
+            0x5DA4_FBFC_0E11_08A8,
+            0x483A_DA77_26A3_C465,
+        ]),
+    };
+}
+
+/// The base field of P-256.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256Base;
+
+impl ForeignField for P256Base {
+    const MODULUS: U256 = U256([
+        0xFFFF_FFFF_FFFF_FFFF,
+        0x0000_0000_FFFF_FFFF,
+        0x0000_0000_0000_0000,
+        0xFFFF_FFFF_0000_0001,
+    ]);
+}
+
+/// The scalar field of P-256.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256Scalar;
+
+impl ForeignField for P256Scalar {
+    const MODULUS: U256 = U256([
+        0xF3B9_CAC2_FC63_2551,
+        0xBCE6_FAAD_A717_9E84,
+        0xFFFF_FFFF_FFFF_FFFF,
+        0xFFFF_FFFF_0000_0000,
+    ]);
+}
+
+/// The NIST P-256 curve, $y^2 = x^3 - 3x + b$.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct P256;
+
+impl ForeignCurve for P256 {
+    type Base = P256Base;
+    type Scalar = P256Scalar;
+
+    const A: U256 = U256([
+        0xFFFF_FFFF_FFFF_FFFC,
+        0x0000_0000_FFFF_FFFF,
+        0x0000_0000_0000_0000,
+        0xFFFF_FFFF_0000_0001,
+    ]);
+    const B: U256 = U256([
+        0x3BCE_3C3E_27D2_604B,
+        0x651D_06B0_CC53_B0F6,
+        0xB3EB_BD55_7698_86BC,
+        0x5AC6_35D8_AA3A_93E7,
+    ]);
+    const GENERATOR: AffinePoint = AffinePoint {
+        x: U256([
+            0xF4A1_3945_D898_C296,
+            0x7703_7D81_2DEB_33A0,
+            0xF8BC_E6E5_63A4_40F2,
+            0x6B17_D1F2_E12C_4247,
+        ]),
+        y: U256([
+            0xCBB6_4068_37BF_51F5,
+            0x2BCE_3357_6B31_5ECE,
+            0x8EE7_EB4A_7C0F_9E16,
+            0x4FE3_42E2_FE1A_7F9B,
+        ]),
+    };
+}
+
+/// Returns whether `point` is on the curve `C`.
+pub fn is_on_curve<C: ForeignCurve>(point: &AffinePoint) -> bool {
+    let p = C::Base::MODULUS;
+    let (x, y) = (point.x, point.y);
+    if x >= p || y >= p {
+        return false;
+    }
+    let x_cubed = mul_mod(mul_mod(x, x, p), x, p);
+    let rhs = add_mod(add_mod(x_cubed, mul_mod(C::A, x, p), p), C::B, p);
+    mul_mod(y, y, p) == rhs
+}
+
+/// Returns `-point`.
+pub fn neg<C: ForeignCurve>(point: &AffinePoint) -> AffinePoint {
+    AffinePoint {
+        x: point.x,
+        y: sub_mod(U256::zero(), point.y, C::Base::MODULUS),
+    }
+}
+
+/// Returns `a + b`, where `None` represents the identity.
+pub fn add<C: ForeignCurve>(a: Option<AffinePoint>, b: Option<AffinePoint>) -> Option<AffinePoint> {
+    let p = C::Base::MODULUS;
+    let (a, b) = match (a, b) {
+        (None, b) => return b,
+        (a, None) => return a,
+        (Some(a), Some(b)) => (a, b),
+    };
+
+    let lambda = if a.x != b.x {
+        // λ = (y_b - y_a) / (x_b - x_a)
+        mul_mod(sub_mod(b.y, a.y, p), inv_mod(sub_mod(b.x, a.x, p), p), p)
+    } else if a.y == b.y && !a.y.is_zero() {
+        // λ = (3 x_a^2 + A) / (2 y_a)
+        let x_squared = mul_mod(a.x, a.x, p);
+        let numerator = add_mod(
+            add_mod(add_mod(x_squared, x_squared, p), x_squared, p),
+            C::A,
+            p,
+        );
+        mul_mod(numerator, inv_mod(add_mod(a.y, a.y, p), p), p)
+    } else {
+        // a = -b
+        return None;
+    };
+
+    let x = sub_mod(sub_mod(mul_mod(lambda, lambda, p), a.x, p), b.x, p);
+    let y = sub_mod(mul_mod(lambda, sub_mod(a.x, x, p), p), a.y, p);
+    Some(AffinePoint { x, y })
+}
+
+/// Returns `[scalar] point`, where `None` represents the identity.
+pub fn mul<C: ForeignCurve>(scalar: U256, point: &AffinePoint) -> Option<AffinePoint> {
+    (0..scalar.bits()).rev().fold(None, |acc, i| {
+        let acc = add::<C>(acc, acc);
+        if scalar.bit(i) {
+            add::<C>(acc, Some(*point))
+        } else {
+            acc
+        }
+    })
+}
+
+/// Returns the public key corresponding to `secret_key`, or `None` if the secret key is
+/// not in the range `[1, n)`.
+pub fn public_key<C: ForeignCurve>(secret_key: U256) -> Option<AffinePoint> {
+    if secret_key.is_zero() || secret_key >= C::Scalar::MODULUS {
+        return None;
+    }
+    mul::<C>(secret_key, &C::GENERATOR)
+}
+
+/// Signs the message hash `msg_hash` with `secret_key` and the given `nonce`.
+///
+/// `msg_hash` is the hash of the message, already truncated to the bit length of the
+/// group order. Returns `None` if the nonce does not produce a valid signature, in
+/// which case the signer must choose another nonce.
+pub fn sign_prehashed<C: ForeignCurve>(
+    secret_key: U256,
+    msg_hash: U256,
+    nonce: U256,
+) -> Option<Signature> {
+    let n = C::Scalar::MODULUS;
+    let big_r = public_key::<C>(nonce)?;
+    let r = big_r.x % n;
+    let z = msg_hash % n;
+    // s = k^-1 (z + r d)
+    let s = mul_mod(
+        inv_mod(nonce, n),
+        add_mod(z, mul_mod(r, secret_key, n), n),
+        n,
+    );
+    if r.is_zero() || s.is_zero() {
+        None
+    } else {
+        Some(Signature { r, s })
+    }
+}
+
+/// Verifies a signature over the message hash `msg_hash`.
+///
+/// `msg_hash` is the hash of the message, already truncated to the bit length of the
+/// group order.
+pub fn verify_prehashed<C: ForeignCurve>(
+    public_key: &AffinePoint,
+    msg_hash: U256,
+    signature: &Signature,
+) -> bool {
+    let n = C::Scalar::MODULUS;
+    let Signature { r, s } = *signature;
+    if !is_on_curve::<C>(public_key) || r.is_zero() || r >= n || s.is_zero() || s >= n {
+        return false;
+    }
+
+    let w = inv_mod(s, n);
+    let u1 = mul_mod(msg_hash % n, w, n);
+    let u2 = mul_mod(r, w, n);
+    match add::<C>(mul::<C>(u1, &C::GENERATOR), mul::<C>(u2, public_key)) {
+        Some(big_r) => big_r.x % n == r,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        add, is_on_curve, mul, neg, public_key, sign_prehashed, verify_prehashed, AffinePoint,
+        Secp256k1, P256,
+    };
+    use crate::{
+        ecdsa::ForeignCurve,
+        foreign_field::{add_mod, ForeignField, U256},
+    };
+
+    use rand::{rngs::OsRng, RngCore};
+
+    fn random_scalar<C: ForeignCurve>() -> U256 {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        U256::from_little_endian(&bytes) % C::Scalar::MODULUS
+    }
+
+    fn check_group<C: ForeignCurve>() {
+        let g = C::GENERATOR;
+        assert!(is_on_curve::<C>(&g));
+        assert_eq!(mul::<C>(C::Scalar::MODULUS, &g), None);
+        assert_eq!(
+            mul::<C>(C::Scalar::MODULUS - U256::one(), &g),
+            Some(neg::<C>(&g))
+        );
+
+        let a = random_scalar::<C>();
+        let b = random_scalar::<C>();
+        let sum = add_mod(a, b, C::Scalar::MODULUS);
+        assert_eq!(
+            add::<C>(mul::<C>(a, &g), mul::<C>(b, &g)),
+            mul::<C>(sum, &g)
+        );
+    }
+
+    fn check_signature<C: ForeignCurve>() {
+        let secret_key = random_scalar::<C>();
+        let pk = public_key::<C>(secret_key).unwrap();
+        let msg_hash = random_scalar::<C>();
+        let signature = sign_prehashed::<C>(secret_key, msg_hash, random_scalar::<C>()).unwrap();
+
+        assert!(verify_prehashed::<C>(&pk, msg_hash, &signature));
+        assert!(!verify_prehashed::<C>(
+            &pk,
+            msg_hash + U256::one(),
+            &signature
+        ));
+        assert!(!verify_prehashed::<C>(&neg::<C>(&pk), msg_hash, &signature));
+    }
+
+    #[test]
+    fn secp256k1() {
+        check_group::<Secp256k1>();
+        check_signature::<Secp256k1>();
+
+        // 2G, from the SEC 2 generator.
+        assert_eq!(
+            public_key::<Secp256k1>(U256::from(2u64)),
+            Some(AffinePoint {
+                x: U256::from_str_radix(
+                    "C6047F9441ED7D6D3045406E95C07CD85C778E4B8CEF3CA7ABAC09B95C709EE5",
+                    16
+                )
+                .unwrap(),
+                y: U256::from_str_radix(
+                    "1AE168FEA63DC339A3C58419466CEAEEF7F632653266D0E1236431A950CFE52A",
+                    16
+                )
+                .unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn p256() {
+        check_group::<P256>();
+        check_signature::<P256>();
+    }
+}
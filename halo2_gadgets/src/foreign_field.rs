@@ -0,0 +1,288 @@
+//! Arithmetic over prime fields other than the circuit's native field.
+//!
+//! Elements of a foreign field are represented in the circuit as several limbs of native
+//! field elements. This allows, for example, operating on the coordinates of secp256k1 or
+//! P-256 points inside a circuit over one of the Pasta fields.
+
+use std::fmt::Debug;
+
+use ff::PrimeField;
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::Error,
+};
+
+pub mod chip;
+
+#[allow(clippy::assign_op_pattern)]
+#[allow(clippy::ptr_offset_with_cast)]
+mod integers {
+    use uint::construct_uint;
+
+    construct_uint! {
+        /// A 256-bit unsigned integer, used for the values of foreign field elements.
+        pub struct U256(4);
+    }
+
+    construct_uint! {
+        /// A 512-bit unsigned integer, wide enough to hold the product of two [`U256`]s.
+        pub(crate) struct U512(8);
+    }
+}
+pub use integers::U256;
+pub(crate) use integers::U512;
+
+impl From<U256> for U512 {
+    fn from(value: U256) -> Self {
+        let mut words = [0; 8];
+        words[..4].copy_from_slice(&value.0);
+        U512(words)
+    }
+}
+
+impl U512 {
+    /// Returns the low 256 bits of this integer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this integer does not fit in 256 bits.
+    pub(crate) fn low_u256(self) -> U256 {
+        assert!(self.0[4..].iter().all(|word| *word == 0));
+        U256(self.0[..4].try_into().unwrap())
+    }
+}
+
+/// Returns `(a + b) mod m`, for `a, b < m`.
+pub(crate) fn add_mod(a: U256, b: U256, m: U256) -> U256 {
+    (U512::from(a) + U512::from(b))
+        .div_mod(m.into())
+        .1
+        .low_u256()
+}
+
+/// Returns `(a - b) mod m`, for `a, b < m`.
+pub(crate) fn sub_mod(a: U256, b: U256, m: U256) -> U256 {
+    if a >= b {
+        a - b
+    } else {
+        m - (b - a)
+    }
+}
+
+/// Returns `(a * b) mod m`.
+pub(crate) fn mul_mod(a: U256, b: U256, m: U256) -> U256 {
+    (U512::from(a) * U512::from(b))
+        .div_mod(m.into())
+        .1
+        .low_u256()
+}
+
+/// Returns `a^-1 mod m` for prime `m`, or zero if `a` is zero.
+pub(crate) fn inv_mod(a: U256, m: U256) -> U256 {
+    // a^(m - 2) = a^-1 by Fermat's little theorem.
+    let exp = m - U256::from(2u64);
+    (0..exp.bits()).rev().fold(U256::one(), |acc, i| {
+        let acc = mul_mod(acc, acc, m);
+        if exp.bit(i) {
+            mul_mod(acc, a, m)
+        } else {
+            acc
+        }
+    })
+}
+
+/// A prime field other than the circuit's native field, identified by its modulus.
+pub trait ForeignField: Clone + Copy + Debug + Eq {
+    /// The modulus of the field, which must be an odd prime.
+    const MODULUS: U256;
+}
+
+/// The set of circuit instructions required to use the [`FieldElement`] gadget.
+///
+/// All results are fully reduced modulo `P::MODULUS`.
+pub trait FieldInstructions<F: PrimeField, P: ForeignField>: Chip<F> + Clone + Debug + Eq {
+    /// Variable representing an element of the foreign field.
+    type Element: Clone + Debug;
+
+    /// Witnesses the given element as a private input to the circuit.
+    ///
+    /// This returns [`Error::Witness`] if the value is known and not less than the
+    /// modulus.
+    fn witness(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: Value<U256>,
+    ) -> Result<Self::Element, Error>;
+
+    /// Assigns the given constant element.
+    ///
+    /// This returns [`Error::Witness`] if the value is not less than the modulus.
+    fn constant(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: U256,
+    ) -> Result<Self::Element, Error>;
+
+    /// Constrains `a` to be equal in value to `b`.
+    fn constrain_equal(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<(), Error>;
+
+    /// Returns `a + b`.
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error>;
+
+    /// Returns `a - b`.
+    fn sub(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error>;
+
+    /// Returns `-a`.
+    fn neg(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+    ) -> Result<Self::Element, Error>;
+
+    /// Returns `a * b`.
+    fn mul(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error>;
+
+    /// Returns `a^-1`, constraining `a` to be non-zero.
+    ///
+    /// This returns [`Error::Witness`] if `a` is known to be zero.
+    fn inv(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+    ) -> Result<Self::Element, Error>;
+
+    /// Returns `a / b`, constraining `b` to be non-zero.
+    ///
+    /// This returns [`Error::Witness`] if `b` is known to be zero.
+    fn div(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error>;
+}
+
+/// An element of the foreign field `P`.
+#[derive(Clone, Debug)]
+pub struct FieldElement<F: PrimeField, P: ForeignField, FieldChip: FieldInstructions<F, P>> {
+    chip: FieldChip,
+    inner: FieldChip::Element,
+}
+
+impl<F: PrimeField, P: ForeignField, FieldChip: FieldInstructions<F, P>>
+    FieldElement<F, P, FieldChip>
+{
+    /// Witnesses the given element.
+    pub fn new(
+        chip: FieldChip,
+        mut layouter: impl Layouter<F>,
+        value: Value<U256>,
+    ) -> Result<Self, Error> {
+        let inner = chip.witness(&mut layouter, value);
+        inner.map(|inner| FieldElement { chip, inner })
+    }
+
+    /// Assigns the given constant element.
+    pub fn new_from_constant(
+        chip: FieldChip,
+        mut layouter: impl Layouter<F>,
+        value: U256,
+    ) -> Result<Self, Error> {
+        let inner = chip.constant(&mut layouter, value);
+        inner.map(|inner| FieldElement { chip, inner })
+    }
+
+    /// Returns the inner element.
+    pub fn inner(&self) -> &FieldChip::Element {
+        &self.inner
+    }
+
+    /// Wraps the given element (obtained directly from an instruction) in a gadget.
+    pub fn from_inner(chip: FieldChip, inner: FieldChip::Element) -> Self {
+        FieldElement { chip, inner }
+    }
+
+    /// Constrains this element to be equal in value to another element.
+    pub fn constrain_equal(
+        &self,
+        mut layouter: impl Layouter<F>,
+        other: &Self,
+    ) -> Result<(), Error> {
+        assert_eq!(self.chip, other.chip);
+        self.chip
+            .constrain_equal(&mut layouter, &self.inner, &other.inner)
+    }
+
+    /// Returns `self + other`.
+    pub fn add(&self, mut layouter: impl Layouter<F>, other: &Self) -> Result<Self, Error> {
+        assert_eq!(self.chip, other.chip);
+        self.chip
+            .add(&mut layouter, &self.inner, &other.inner)
+            .map(|inner| self.wrap(inner))
+    }
+
+    /// Returns `self - other`.
+    pub fn sub(&self, mut layouter: impl Layouter<F>, other: &Self) -> Result<Self, Error> {
+        assert_eq!(self.chip, other.chip);
+        self.chip
+            .sub(&mut layouter, &self.inner, &other.inner)
+            .map(|inner| self.wrap(inner))
+    }
+
+    /// Returns `-self`.
+    pub fn neg(&self, mut layouter: impl Layouter<F>) -> Result<Self, Error> {
+        self.chip
+            .neg(&mut layouter, &self.inner)
+            .map(|inner| self.wrap(inner))
+    }
+
+    /// Returns `self * other`.
+    pub fn mul(&self, mut layouter: impl Layouter<F>, other: &Self) -> Result<Self, Error> {
+        assert_eq!(self.chip, other.chip);
+        self.chip
+            .mul(&mut layouter, &self.inner, &other.inner)
+            .map(|inner| self.wrap(inner))
+    }
+
+    /// Returns `self^-1`, constraining `self` to be non-zero.
+    pub fn inv(&self, mut layouter: impl Layouter<F>) -> Result<Self, Error> {
+        self.chip
+            .inv(&mut layouter, &self.inner)
+            .map(|inner| self.wrap(inner))
+    }
+
+    /// Returns `self / other`, constraining `other` to be non-zero.
+    pub fn div(&self, mut layouter: impl Layouter<F>, other: &Self) -> Result<Self, Error> {
+        assert_eq!(self.chip, other.chip);
+        self.chip
+            .div(&mut layouter, &self.inner, &other.inner)
+            .map(|inner| self.wrap(inner))
+    }
+
+    fn wrap(&self, inner: FieldChip::Element) -> Self {
+        FieldElement {
+            chip: self.chip.clone(),
+            inner,
+        }
+    }
+}
@@ -0,0 +1,1031 @@
+//! Chip implementation for foreign field arithmetic.
+
+use std::convert::TryInto;
+use std::marker::PhantomData;
+
+use super::{add_mod, inv_mod, mul_mod, sub_mod, FieldInstructions, ForeignField, U256, U512};
+use crate::{
+    sinsemilla::primitives as sinsemilla,
+    utilities::lookup_range_check::{LookupRangeCheck, LookupRangeCheckConfig},
+};
+
+use ff::{Field, PrimeFieldBits};
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+/// The number of limbs used to represent a foreign field element.
+pub const NUM_LIMBS: usize = 4;
+
+/// The number of bits in each limb.
+///
+/// This is a multiple of the lookup table size, so that each limb can be range-checked
+/// with a whole number of lookups.
+pub const LIMB_BITS: usize = 70;
+
+/// The number of lookup words in a limb.
+const LIMB_WORDS: usize = LIMB_BITS / sinsemilla::K;
+
+/// The number of carries in the multiplication gate, one between each pair of adjacent
+/// columns of the schoolbook product.
+const NUM_MUL_CARRIES: usize = 2 * NUM_LIMBS - 2;
+
+/// Each column of the product `a * b - q * p - r` is the sum of at most [`NUM_LIMBS`]
+/// products of limbs, so it has magnitude less than $2^{2 \cdot LIMB\_BITS + 2}$ and the
+/// carries out of it have magnitude less than $2^{LIMB\_BITS + 3}$. We offset the carries
+/// by this amount so that they can be range-checked as unsigned integers.
+const MUL_CARRY_OFFSET_BITS: usize = LIMB_BITS + 3;
+
+/// The number of lookup words in an offset multiplication carry.
+const MUL_CARRY_WORDS: usize = (MUL_CARRY_OFFSET_BITS + 1 + sinsemilla::K - 1) / sinsemilla::K;
+
+/// An element of the foreign field `P`, represented as [`NUM_LIMBS`] little-endian limbs
+/// of [`LIMB_BITS`] bits each.
+///
+/// Every element produced by [`ForeignFieldChip`] has range-checked limbs and is
+/// canonical, i.e. its value is less than `P::MODULUS`.
+#[derive(Clone, Debug)]
+pub struct ForeignFieldElement<F: Field, P: ForeignField> {
+    limbs: [AssignedCell<F, F>; NUM_LIMBS],
+    value: Value<U256>,
+    _marker: PhantomData<P>,
+}
+
+impl<F: Field, P: ForeignField> ForeignFieldElement<F, P> {
+    /// The cells containing the limbs of this element, in little-endian order.
+    pub fn limbs(&self) -> &[AssignedCell<F, F>; NUM_LIMBS] {
+        &self.limbs
+    }
+
+    /// Returns the value of this element, if known.
+    pub fn value(&self) -> Value<U256> {
+        self.value
+    }
+
+    /// Wraps limbs that are already known to be range-checked and canonical, such as
+    /// limbs copied from another element.
+    pub(crate) fn from_canonical_limbs(
+        limbs: [AssignedCell<F, F>; NUM_LIMBS],
+        value: Value<U256>,
+    ) -> Self {
+        ForeignFieldElement {
+            limbs,
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Splits `value` into little-endian limbs.
+fn to_limbs<F: PrimeFieldBits>(value: U256) -> [F; NUM_LIMBS] {
+    let mask = (1u128 << LIMB_BITS) - 1;
+    let mut limbs = [F::ZERO; NUM_LIMBS];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        *limb = F::from_u128((value >> (i * LIMB_BITS)).low_u128() & mask);
+    }
+    limbs
+}
+
+/// Returns `a^-1 mod P`, or [`Error::Witness`] if `a` is zero.
+fn inv_non_zero<P: ForeignField>(a: U256) -> Result<U256, Error> {
+    if a.is_zero() {
+        Err(Error::Witness("cannot invert zero".into()))
+    } else {
+        Ok(inv_mod(a, P::MODULUS))
+    }
+}
+
+/// An input to one of the chip's gates.
+enum Operand<'a, F: Field> {
+    /// Limbs that have already been assigned, along with their value.
+    Assigned(&'a [AssignedCell<F, F>; NUM_LIMBS], Value<U256>),
+    /// A constant.
+    Constant(U256),
+    /// A value to be witnessed.
+    Witness(Value<U256>),
+}
+
+impl<'a, F: PrimeFieldBits> Operand<'a, F> {
+    fn value(&self) -> Value<U256> {
+        match self {
+            Operand::Assigned(_, value) | Operand::Witness(value) => *value,
+            Operand::Constant(value) => Value::known(*value),
+        }
+    }
+
+    /// Assigns this operand to `columns` at `offset`.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        columns: &[Column<Advice>],
+        offset: usize,
+    ) -> Result<[AssignedCell<F, F>; NUM_LIMBS], Error> {
+        let limbs = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match self {
+                Operand::Assigned(limbs, _) => {
+                    limbs[i].copy_advice(|| format!("limb {}", i), region, *column, offset)
+                }
+                Operand::Constant(value) => region.assign_advice_from_constant(
+                    || format!("limb {}", i),
+                    *column,
+                    offset,
+                    to_limbs::<F>(*value)[i],
+                ),
+                Operand::Witness(value) => region.assign_advice(
+                    || format!("limb {}", i),
+                    *column,
+                    offset,
+                    || value.map(|value| to_limbs::<F>(value)[i]),
+                ),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(limbs.try_into().unwrap())
+    }
+}
+
+/// Configuration for the [`ForeignFieldChip`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForeignFieldConfig<
+    F: PrimeFieldBits,
+    P: ForeignField,
+    Lookup: LookupRangeCheck<F, { sinsemilla::K }> = LookupRangeCheckConfig<F, { sinsemilla::K }>,
+> {
+    q_mul: Selector,
+    q_add: Selector,
+    /// Advice columns used by the chip.
+    pub advices: [Column<Advice>; 2 * NUM_LIMBS],
+    /// Lookup range check used to constrain limbs and carries.
+    pub lookup_config: Lookup,
+    _marker: PhantomData<(F, P)>,
+}
+
+/// A [`FieldInstructions`] chip for the foreign field `P`, using `2 * NUM_LIMBS` advice
+/// columns and a lookup range check.
+///
+/// The chip uses two gates:
+/// - a multiplication gate, which constrains `a * b = q * p + r` over the integers by
+///   computing the schoolbook product limb by limb, with signed carries between columns;
+/// - an addition gate, which constrains `x + y = z + q * p` over the integers for a bit
+///   `q`. This is also used to show that an element `x` is canonical, by witnessing
+///   `d = p - 1 - x` with range-checked limbs and constraining `x + d = p - 1`.
+///
+/// Each gate row holds whole elements, and every witnessed limb and multiplication carry
+/// is range-checked with the lookup argument.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForeignFieldChip<
+    F: PrimeFieldBits,
+    P: ForeignField,
+    Lookup: LookupRangeCheck<F, { sinsemilla::K }> = LookupRangeCheckConfig<F, { sinsemilla::K }>,
+> {
+    config: ForeignFieldConfig<F, P, Lookup>,
+}
+
+impl<F: PrimeFieldBits, P: ForeignField, Lookup: LookupRangeCheck<F, { sinsemilla::K }>> Chip<F>
+    for ForeignFieldChip<F, P, Lookup>
+{
+    type Config = ForeignFieldConfig<F, P, Lookup>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeFieldBits, P: ForeignField, Lookup: LookupRangeCheck<F, { sinsemilla::K }>>
+    ForeignFieldChip<F, P, Lookup>
+{
+    /// Reconstructs this chip from the given config.
+    pub fn construct(config: ForeignFieldConfig<F, P, Lookup>) -> Self {
+        Self { config }
+    }
+
+    /// Configures the chip.
+    ///
+    /// # Side effects
+    ///
+    /// All columns in `advices` will be equality-enabled. The circuit must also have a
+    /// fixed column enabled for constants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the native field is too small for the carries of the multiplication gate
+    /// to be computed without wrapping around.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 2 * NUM_LIMBS],
+        range_check: Lookup,
+    ) -> ForeignFieldConfig<F, P, Lookup> {
+        // Each equation of the multiplication gate is, as an integer, bounded in magnitude
+        // by 2^(LIMB_BITS + MUL_CARRY_WORDS * K + 1). It must not wrap around the native
+        // modulus.
+        assert!(LIMB_BITS + MUL_CARRY_WORDS * sinsemilla::K + 2 < F::CAPACITY as usize);
+        assert!(P::MODULUS.bit(0) && P::MODULUS > U256::one());
+
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = ForeignFieldConfig {
+            q_mul: meta.selector(),
+            q_add: meta.selector(),
+            advices,
+            lookup_config: range_check,
+            _marker: PhantomData,
+        };
+        config.create_mul_gate(meta);
+        config.create_add_gate(meta);
+        config
+    }
+
+    /// Returns `x mod p`, where `x` is an element of another foreign field.
+    ///
+    /// This is useful when `x` is an element of a field that is close in size to `P`,
+    /// such as the base and scalar fields of an elliptic curve.
+    pub fn reduce_from<Q: ForeignField>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &ForeignFieldElement<F, Q>,
+    ) -> Result<ForeignFieldElement<F, P>, Error> {
+        let r = x.value.map(|x| x.div_mod(P::MODULUS).1);
+        let (_, _, r_cells) = self.mul_gate(
+            &mut layouter,
+            Operand::Assigned(&x.limbs, x.value),
+            Operand::Constant(U256::one()),
+            Operand::Witness(r),
+        )?;
+        self.canonical(&mut layouter, r_cells, r)
+    }
+
+    /// Returns `a / b`, without constraining `b` to be non-zero.
+    ///
+    /// If `b` is zero, the result is unconstrained when `a` is zero, and unsatisfiable
+    /// otherwise. Callers must ensure that `a` and `b` cannot both be zero.
+    pub(crate) fn div_unchecked(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &ForeignFieldElement<F, P>,
+        b: &ForeignFieldElement<F, P>,
+    ) -> Result<ForeignFieldElement<F, P>, Error> {
+        let b_inv = b.value.try_map(inv_non_zero::<P>)?;
+
+        // Constrain (a / b) * b = q * p + a.
+        let quotient = a
+            .value
+            .zip(b_inv)
+            .map(|(a, b_inv)| mul_mod(a, b_inv, P::MODULUS));
+        let (quotient_limbs, _, _) = self.mul_gate(
+            layouter,
+            Operand::Witness(quotient),
+            Operand::Assigned(&b.limbs, b.value),
+            Operand::Assigned(&a.limbs, a.value),
+        )?;
+        self.canonical(layouter, quotient_limbs, quotient)
+    }
+
+    /// Constrains `a * b = q * p + r` over the integers, witnessing the quotient `q`.
+    ///
+    /// Returns the assigned limbs of `a`, `b` and `r`. Witnessed operands are not
+    /// range-checked by this method.
+    #[allow(clippy::type_complexity)]
+    fn mul_gate(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: Operand<'_, F>,
+        b: Operand<'_, F>,
+        r: Operand<'_, F>,
+    ) -> Result<
+        (
+            [AssignedCell<F, F>; NUM_LIMBS],
+            [AssignedCell<F, F>; NUM_LIMBS],
+            [AssignedCell<F, F>; NUM_LIMBS],
+        ),
+        Error,
+    > {
+        let config = &self.config;
+        let q = a.value().zip(b.value()).zip(r.value()).map(|((a, b), r)| {
+            let (q, rem) =
+                (U512::from(a) * U512::from(b) - U512::from(r)).div_mod(P::MODULUS.into());
+            assert!(rem.is_zero());
+            q.low_u256()
+        });
+
+        let (a, b, q, r, carries) = layouter.assign_region(
+            || "foreign field mul",
+            |mut region| {
+                config.q_mul.enable(&mut region, 0)?;
+
+                let (lo, hi) = config.advices.split_at(NUM_LIMBS);
+                let a = a.assign(&mut region, lo, 0)?;
+                let b = b.assign(&mut region, hi, 0)?;
+                let q = Operand::Witness(q).assign(&mut region, lo, 1)?;
+                let r = r.assign(&mut region, hi, 1)?;
+
+                let carries = mul_carries::<F, P>(&a, &b, &q, &r);
+                let carries = carries
+                    .iter()
+                    .enumerate()
+                    .map(|(k, carry)| {
+                        region.assign_advice(
+                            || format!("carry {}", k),
+                            config.advices[k],
+                            2,
+                            || *carry,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                Ok((a, b, q, r, carries))
+            },
+        )?;
+
+        self.range_check_limbs(layouter, &q)?;
+        for carry in carries {
+            config.lookup_config.copy_check(
+                layouter.namespace(|| "carry"),
+                carry,
+                MUL_CARRY_WORDS,
+                true,
+            )?;
+        }
+
+        Ok((a, b, r))
+    }
+
+    /// Constrains `x + y = z + q * p` over the integers, where the bit `q` is witnessed if
+    /// `allow_overflow` is set, and is zero otherwise.
+    ///
+    /// Returns the assigned limbs of `x`, `y` and `z`. Witnessed operands are not
+    /// range-checked by this method.
+    #[allow(clippy::type_complexity)]
+    fn add_gate(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        x: Operand<'_, F>,
+        y: Operand<'_, F>,
+        z: Operand<'_, F>,
+        allow_overflow: bool,
+    ) -> Result<
+        (
+            [AssignedCell<F, F>; NUM_LIMBS],
+            [AssignedCell<F, F>; NUM_LIMBS],
+            [AssignedCell<F, F>; NUM_LIMBS],
+        ),
+        Error,
+    > {
+        let config = &self.config;
+        let q = x.value().zip(y.value()).map(|(x, y)| {
+            if U512::from(x) + U512::from(y) >= P::MODULUS.into() {
+                F::ONE
+            } else {
+                F::ZERO
+            }
+        });
+
+        layouter.assign_region(
+            || "foreign field add",
+            |mut region| {
+                config.q_add.enable(&mut region, 0)?;
+
+                let (lo, hi) = config.advices.split_at(NUM_LIMBS);
+                let x = x.assign(&mut region, lo, 0)?;
+                let y = y.assign(&mut region, hi, 0)?;
+                let z = z.assign(&mut region, lo, 1)?;
+                let q = if allow_overflow {
+                    region.assign_advice(|| "q", config.advices[NUM_LIMBS], 1, || q)?
+                } else {
+                    region.assign_advice_from_constant(
+                        || "q",
+                        config.advices[NUM_LIMBS],
+                        1,
+                        F::ZERO,
+                    )?
+                };
+
+                // Carry out of limb k: c_k = (x_k + y_k - z_k - q p_k + c_{k-1}) / 2^LIMB_BITS
+                let p = to_limbs::<F>(P::MODULUS);
+                let inv_two_pow_limb = F::from_u128(1 << LIMB_BITS).invert().unwrap();
+                let mut carry = Value::known(F::ZERO);
+                for k in 0..(NUM_LIMBS - 1) {
+                    carry = x[k]
+                        .value()
+                        .zip(y[k].value())
+                        .zip(z[k].value())
+                        .zip(q.value())
+                        .zip(carry)
+                        .map(|((((x, y), z), q), carry)| {
+                            (*x + y - z - *q * p[k] + carry) * inv_two_pow_limb
+                        });
+                    region.assign_advice(
+                        || format!("carry {}", k),
+                        config.advices[NUM_LIMBS + 1 + k],
+                        1,
+                        || carry,
+                    )?;
+                }
+
+                Ok((x, y, z))
+            },
+        )
+    }
+
+    /// Range-checks the given limbs to [`LIMB_BITS`] bits each.
+    fn range_check_limbs(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        limbs: &[AssignedCell<F, F>; NUM_LIMBS],
+    ) -> Result<(), Error> {
+        for limb in limbs.iter() {
+            self.config.lookup_config.copy_check(
+                layouter.namespace(|| "limb"),
+                limb.clone(),
+                LIMB_WORDS,
+                true,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Range-checks the limbs of a newly witnessed element and constrains its value to be
+    /// less than the modulus.
+    fn canonical(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        limbs: [AssignedCell<F, F>; NUM_LIMBS],
+        value: Value<U256>,
+    ) -> Result<ForeignFieldElement<F, P>, Error> {
+        self.range_check_limbs(layouter, &limbs)?;
+
+        // x < p if and only if d = p - 1 - x is non-negative, which we show by
+        // range-checking the limbs of d and constraining x + d = p - 1.
+        let p_minus_one = P::MODULUS - U256::one();
+        let d = value.map(|x| p_minus_one - x);
+        let (_, d_limbs, _) = self.add_gate(
+            layouter,
+            Operand::Assigned(&limbs, value),
+            Operand::Witness(d),
+            Operand::Constant(p_minus_one),
+            false,
+        )?;
+        self.range_check_limbs(layouter, &d_limbs)?;
+
+        Ok(ForeignFieldElement {
+            limbs,
+            value,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Computes the offset carries for the multiplication gate from the assigned limbs.
+fn mul_carries<F: PrimeFieldBits, P: ForeignField>(
+    a: &[AssignedCell<F, F>; NUM_LIMBS],
+    b: &[AssignedCell<F, F>; NUM_LIMBS],
+    q: &[AssignedCell<F, F>; NUM_LIMBS],
+    r: &[AssignedCell<F, F>; NUM_LIMBS],
+) -> Vec<Value<F>> {
+    let values = |limbs: &[AssignedCell<F, F>; NUM_LIMBS]| {
+        limbs.iter().fold(Value::known(vec![]), |acc, limb| {
+            acc.zip(limb.value()).map(|(mut acc, limb)| {
+                acc.push(*limb);
+                acc
+            })
+        })
+    };
+    let p = to_limbs::<F>(P::MODULUS);
+    let inv_two_pow_limb = F::from_u128(1 << LIMB_BITS).invert().unwrap();
+    let offset = F::from_u128(1 << MUL_CARRY_OFFSET_BITS);
+
+    let carries = values(a)
+        .zip(values(b))
+        .zip(values(q))
+        .zip(values(r))
+        .map(|(((a, b), q), r)| {
+            let mut carry = F::ZERO;
+            (0..NUM_MUL_CARRIES)
+                .map(|k| {
+                    let column = mul_column(k, &a, &b, &q, &p, &r);
+                    carry = (column + carry) * inv_two_pow_limb;
+                    carry + offset
+                })
+                .collect::<Vec<_>>()
+        });
+    carries.transpose_vec(NUM_MUL_CARRIES)
+}
+
+/// Returns column `k` of the schoolbook product `a * b - q * p - r`.
+fn mul_column<T>(k: usize, a: &[T], b: &[T], q: &[T], p: &[T], r: &[T]) -> T
+where
+    T: Clone + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>,
+{
+    let lo = k.saturating_sub(NUM_LIMBS - 1);
+    let hi = k.min(NUM_LIMBS - 1);
+    let mut column = a[lo].clone() * b[k - lo].clone() - q[lo].clone() * p[k - lo].clone();
+    for i in (lo + 1)..=hi {
+        column = column + a[i].clone() * b[k - i].clone() - q[i].clone() * p[k - i].clone();
+    }
+    if k < NUM_LIMBS {
+        column = column - r[k].clone();
+    }
+    column
+}
+
+impl<F: PrimeFieldBits, P: ForeignField, Lookup: LookupRangeCheck<F, { sinsemilla::K }>>
+    ForeignFieldConfig<F, P, Lookup>
+{
+    fn create_mul_gate(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("foreign field mul", |meta| {
+            let q_mul = meta.query_selector(self.q_mul);
+            let limbs = |meta: &mut halo2_proofs::plonk::VirtualCells<F>, start, rotation| {
+                self.advices[start..start + NUM_LIMBS]
+                    .iter()
+                    .map(|column| meta.query_advice(*column, Rotation(rotation)))
+                    .collect::<Vec<_>>()
+            };
+            let a = limbs(meta, 0, 0);
+            let b = limbs(meta, NUM_LIMBS, 0);
+            let q = limbs(meta, 0, 1);
+            let r = limbs(meta, NUM_LIMBS, 1);
+            let p = to_limbs::<F>(P::MODULUS)
+                .iter()
+                .map(|p| Expression::Constant(*p))
+                .collect::<Vec<_>>();
+
+            let offset = Expression::Constant(F::from_u128(1 << MUL_CARRY_OFFSET_BITS));
+            let carries = self.advices[..NUM_MUL_CARRIES]
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation(2)) - offset.clone())
+                .collect::<Vec<_>>();
+            let two_pow_limb = Expression::Constant(F::from_u128(1 << LIMB_BITS));
+
+            // For each column k of the product,
+            //     column_k + c_{k-1} - 2^LIMB_BITS * c_k = 0,
+            // where c_{-1} = 0 and the final carry is zero.
+            let constraints = (0..(2 * NUM_LIMBS - 1))
+                .map(|k| {
+                    let mut constraint = mul_column(k, &a, &b, &q, &p, &r);
+                    if k > 0 {
+                        constraint = constraint + carries[k - 1].clone();
+                    }
+                    if k < NUM_MUL_CARRIES {
+                        constraint = constraint - two_pow_limb.clone() * carries[k].clone();
+                    }
+                    ("column", constraint)
+                })
+                .collect::<Vec<_>>();
+
+            Constraints::with_selector(q_mul, constraints)
+        });
+    }
+
+    fn create_add_gate(&self, meta: &mut ConstraintSystem<F>) {
+        meta.create_gate("foreign field add", |meta| {
+            let q_add = meta.query_selector(self.q_add);
+            let x = (0..NUM_LIMBS)
+                .map(|i| meta.query_advice(self.advices[i], Rotation::cur()))
+                .collect::<Vec<_>>();
+            let y = (0..NUM_LIMBS)
+                .map(|i| meta.query_advice(self.advices[NUM_LIMBS + i], Rotation::cur()))
+                .collect::<Vec<_>>();
+            let z = (0..NUM_LIMBS)
+                .map(|i| meta.query_advice(self.advices[i], Rotation::next()))
+                .collect::<Vec<_>>();
+            let q = meta.query_advice(self.advices[NUM_LIMBS], Rotation::next());
+            let carries = (0..(NUM_LIMBS - 1))
+                .map(|k| meta.query_advice(self.advices[NUM_LIMBS + 1 + k], Rotation::next()))
+                .collect::<Vec<_>>();
+            let p = to_limbs::<F>(P::MODULUS);
+            let two_pow_limb = Expression::Constant(F::from_u128(1 << LIMB_BITS));
+            let one = Expression::Constant(F::ONE);
+
+            // The limbs of x + y - z - q * p are each less than 2^(LIMB_BITS + 1) in
+            // magnitude, so the carries between them are in {-1, 0, 1}.
+            let q_check = ("q is a bit", q.clone() * (one.clone() - q.clone()));
+            let carry_checks = carries
+                .iter()
+                .map(|c| {
+                    (
+                        "carry is in {-1, 0, 1}",
+                        c.clone() * (c.clone() - one.clone()) * (c.clone() + one.clone()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let limb_checks = (0..NUM_LIMBS)
+                .map(|k| {
+                    let mut constraint = x[k].clone() + y[k].clone()
+                        - z[k].clone()
+                        - q.clone() * Expression::Constant(p[k]);
+                    if k > 0 {
+                        constraint = constraint + carries[k - 1].clone();
+                    }
+                    if k < NUM_LIMBS - 1 {
+                        constraint = constraint - two_pow_limb.clone() * carries[k].clone();
+                    }
+                    ("limb", constraint)
+                })
+                .collect::<Vec<_>>();
+
+            Constraints::with_selector(
+                q_add,
+                std::iter::once(q_check)
+                    .chain(carry_checks)
+                    .chain(limb_checks),
+            )
+        });
+    }
+}
+
+impl<F: PrimeFieldBits, P: ForeignField, Lookup: LookupRangeCheck<F, { sinsemilla::K }>>
+    FieldInstructions<F, P> for ForeignFieldChip<F, P, Lookup>
+{
+    type Element = ForeignFieldElement<F, P>;
+
+    fn witness(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: Value<U256>,
+    ) -> Result<Self::Element, Error> {
+        let value = value.try_map(|value| {
+            if value < P::MODULUS {
+                Ok(value)
+            } else {
+                Err(Error::Witness(format!(
+                    "value {:?} is not less than the modulus",
+                    value
+                )))
+            }
+        })?;
+        let (lo, _) = self.config.advices.split_at(NUM_LIMBS);
+        let limbs = layouter.assign_region(
+            || "witness foreign field element",
+            |mut region| Operand::Witness(value).assign(&mut region, lo, 0),
+        )?;
+        self.canonical(layouter, limbs, value)
+    }
+
+    fn constant(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        value: U256,
+    ) -> Result<Self::Element, Error> {
+        if value >= P::MODULUS {
+            return Err(Error::Witness(format!(
+                "constant {:?} is not less than the modulus",
+                value
+            )));
+        }
+        let (lo, _) = self.config.advices.split_at(NUM_LIMBS);
+        let limbs = layouter.assign_region(
+            || "foreign field constant",
+            |mut region| Operand::Constant(value).assign(&mut region, lo, 0),
+        )?;
+        Ok(ForeignFieldElement {
+            limbs,
+            value: Value::known(value),
+            _marker: PhantomData,
+        })
+    }
+
+    fn constrain_equal(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<(), Error> {
+        // Both elements are canonical, so they are equal if and only if their limbs are.
+        layouter.assign_region(
+            || "foreign field constrain equal",
+            |mut region| {
+                for (a, b) in a.limbs.iter().zip(b.limbs.iter()) {
+                    region.constrain_equal(a.cell(), b.cell())?;
+                }
+                Ok(())
+            },
+        )
+    }
+
+    fn add(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error> {
+        let z = a.value.zip(b.value).map(|(a, b)| add_mod(a, b, P::MODULUS));
+        let (_, _, z_limbs) = self.add_gate(
+            layouter,
+            Operand::Assigned(&a.limbs, a.value),
+            Operand::Assigned(&b.limbs, b.value),
+            Operand::Witness(z),
+            true,
+        )?;
+        self.canonical(layouter, z_limbs, z)
+    }
+
+    fn sub(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error> {
+        // Constrain (a - b) + b = a + q * p.
+        let z = a.value.zip(b.value).map(|(a, b)| sub_mod(a, b, P::MODULUS));
+        let (z_limbs, _, _) = self.add_gate(
+            layouter,
+            Operand::Witness(z),
+            Operand::Assigned(&b.limbs, b.value),
+            Operand::Assigned(&a.limbs, a.value),
+            true,
+        )?;
+        self.canonical(layouter, z_limbs, z)
+    }
+
+    fn neg(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+    ) -> Result<Self::Element, Error> {
+        // Constrain a + (-a) = 0 + q * p.
+        let z = a.value.map(|a| sub_mod(U256::zero(), a, P::MODULUS));
+        let (_, z_limbs, _) = self.add_gate(
+            layouter,
+            Operand::Assigned(&a.limbs, a.value),
+            Operand::Witness(z),
+            Operand::Constant(U256::zero()),
+            true,
+        )?;
+        self.canonical(layouter, z_limbs, z)
+    }
+
+    fn mul(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error> {
+        let r = a.value.zip(b.value).map(|(a, b)| mul_mod(a, b, P::MODULUS));
+        let (_, _, r_limbs) = self.mul_gate(
+            layouter,
+            Operand::Assigned(&a.limbs, a.value),
+            Operand::Assigned(&b.limbs, b.value),
+            Operand::Witness(r),
+        )?;
+        self.canonical(layouter, r_limbs, r)
+    }
+
+    fn inv(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+    ) -> Result<Self::Element, Error> {
+        // Constrain a * a^-1 = q * p + 1.
+        let a_inv = a.value.try_map(inv_non_zero::<P>)?;
+        let (_, a_inv_limbs, _) = self.mul_gate(
+            layouter,
+            Operand::Assigned(&a.limbs, a.value),
+            Operand::Witness(a_inv),
+            Operand::Constant(U256::one()),
+        )?;
+        self.canonical(layouter, a_inv_limbs, a_inv)
+    }
+
+    fn div(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        a: &Self::Element,
+        b: &Self::Element,
+    ) -> Result<Self::Element, Error> {
+        // Witnessing a / b directly would leave it unconstrained when a = b = 0.
+        let b_inv = self.inv(layouter, b)?;
+        self.mul(layouter, a, &b_inv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ForeignFieldChip, ForeignFieldConfig};
+    use crate::{
+        ecdsa::primitives::{Secp256k1Base, Secp256k1Scalar},
+        foreign_field::{add_mod, inv_mod, mul_mod, sub_mod, FieldElement, ForeignField, U256},
+        utilities::lookup_range_check::{LookupRangeCheck, LookupRangeCheckConfig},
+    };
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use pasta_curves::pallas;
+    use rand::{rngs::OsRng, RngCore};
+
+    type BaseChip = ForeignFieldChip<pallas::Base, Secp256k1Base>;
+    type ScalarChip = ForeignFieldChip<pallas::Base, Secp256k1Scalar>;
+    type Base = FieldElement<pallas::Base, Secp256k1Base, BaseChip>;
+    type Scalar = FieldElement<pallas::Base, Secp256k1Scalar, ScalarChip>;
+
+    const P: U256 = Secp256k1Base::MODULUS;
+    const N: U256 = Secp256k1Scalar::MODULUS;
+
+    fn random(modulus: U256) -> U256 {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        U256::from_little_endian(&bytes) % modulus
+    }
+
+    struct MyCircuit {
+        a: Value<U256>,
+        b: Value<U256>,
+        /// Added to the expected product, to check that incorrect results are rejected.
+        product_error: U256,
+    }
+
+    impl Circuit<pallas::Base> for MyCircuit {
+        type Config = (
+            ForeignFieldConfig<pallas::Base, Secp256k1Base>,
+            ForeignFieldConfig<pallas::Base, Secp256k1Scalar>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                a: Value::unknown(),
+                b: Value::unknown(),
+                product_error: self.product_error,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            let advices = [(); 8].map(|_| meta.advice_column());
+            let running_sum = meta.advice_column();
+            let table_idx = meta.lookup_table_column();
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            let range_check = LookupRangeCheckConfig::configure(meta, running_sum, table_idx);
+            (
+                BaseChip::configure(meta, advices, range_check),
+                ScalarChip::configure(meta, advices, range_check),
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            config
+                .0
+                .lookup_config
+                .load_range_check_table(&mut layouter)?;
+            let chip = BaseChip::construct(config.0);
+            let scalar_chip = ScalarChip::construct(config.1);
+
+            // Witnesses `expected` and constrains it to be equal to `actual`.
+            fn check<P: ForeignField>(
+                chip: ForeignFieldChip<pallas::Base, P>,
+                mut layouter: impl Layouter<pallas::Base>,
+                actual: &FieldElement<pallas::Base, P, ForeignFieldChip<pallas::Base, P>>,
+                expected: Value<U256>,
+            ) -> Result<(), Error> {
+                let expected =
+                    FieldElement::new(chip, layouter.namespace(|| "expected"), expected)?;
+                actual.constrain_equal(layouter.namespace(|| "constrain equal"), &expected)
+            }
+
+            let a = Base::new(chip.clone(), layouter.namespace(|| "a"), self.a)?;
+            let b = Base::new(chip.clone(), layouter.namespace(|| "b"), self.b)?;
+            let ab = self.a.zip(self.b);
+
+            let sum = a.add(layouter.namespace(|| "a + b"), &b)?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check a + b"),
+                &sum,
+                ab.map(|(a, b)| add_mod(a, b, P)),
+            )?;
+
+            let difference = a.sub(layouter.namespace(|| "a - b"), &b)?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check a - b"),
+                &difference,
+                ab.map(|(a, b)| sub_mod(a, b, P)),
+            )?;
+
+            let neg_a = a.neg(layouter.namespace(|| "-a"))?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check -a"),
+                &neg_a,
+                self.a.map(|a| sub_mod(U256::zero(), a, P)),
+            )?;
+
+            let product = a.mul(layouter.namespace(|| "a * b"), &b)?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check a * b"),
+                &product,
+                ab.map(|(a, b)| add_mod(mul_mod(a, b, P), self.product_error, P)),
+            )?;
+
+            let b_inv = b.inv(layouter.namespace(|| "b^-1"))?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check b^-1"),
+                &b_inv,
+                self.b.map(|b| inv_mod(b, P)),
+            )?;
+
+            let quotient = a.div(layouter.namespace(|| "a / b"), &b)?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check a / b"),
+                &quotient,
+                ab.map(|(a, b)| mul_mod(a, inv_mod(b, P), P)),
+            )?;
+
+            // Multiply by a constant.
+            let seven = U256::from(7u64);
+            let c = Base::new_from_constant(chip.clone(), layouter.namespace(|| "7"), seven)?;
+            let product = a.mul(layouter.namespace(|| "a * 7"), &c)?;
+            check(
+                chip.clone(),
+                layouter.namespace(|| "check a * 7"),
+                &product,
+                self.a.map(|a| mul_mod(a, seven, P)),
+            )?;
+
+            // Reduce a modulo the scalar field.
+            let a_mod_n = scalar_chip.reduce_from(layouter.namespace(|| "a mod n"), a.inner())?;
+            let a_mod_n = Scalar::from_inner(scalar_chip.clone(), a_mod_n);
+            check(
+                scalar_chip,
+                layouter.namespace(|| "check a mod n"),
+                &a_mod_n,
+                self.a.map(|a| a % N),
+            )
+        }
+    }
+
+    #[test]
+    fn foreign_field_chip() {
+        let one = U256::one();
+        for (a, b) in [
+            (random(P), random(P)),
+            (P - one, P - one),
+            (P - one, one),
+            (U256::zero(), one),
+            (N + U256::from(5u64), random(P)),
+        ] {
+            let circuit = MyCircuit {
+                a: Value::known(a),
+                b: Value::known(b),
+                product_error: U256::zero(),
+            };
+            let prover = MockProver::<pallas::Base>::run(12, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn foreign_field_chip_wrong_product() {
+        let circuit = MyCircuit {
+            a: Value::known(random(P)),
+            b: Value::known(random(P)),
+            product_error: U256::one(),
+        };
+        let prover = MockProver::<pallas::Base>::run(12, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn foreign_field_chip_invalid_inputs() {
+        // Inverting zero is a witness error.
+        let circuit = MyCircuit {
+            a: Value::known(random(P)),
+            b: Value::known(U256::zero()),
+            product_error: U256::zero(),
+        };
+        assert!(matches!(
+            MockProver::<pallas::Base>::run(12, &circuit, vec![]),
+            Err(Error::Witness(message)) if message == "cannot invert zero"
+        ));
+
+        // So is witnessing a non-canonical value.
+        let circuit = MyCircuit {
+            a: Value::known(P),
+            b: Value::known(U256::one()),
+            product_error: U256::zero(),
+        };
+        assert!(matches!(
+            MockProver::<pallas::Base>::run(12, &circuit, vec![]),
+            Err(Error::Witness(message)) if message.ends_with("is not less than the modulus")
+        ));
+    }
+}
@@ -22,6 +22,8 @@
 #![deny(unsafe_code)]
 
 pub mod ecc;
+pub mod ecdsa;
+pub mod foreign_field;
 pub mod poseidon;
 #[cfg(feature = "unstable-sha256-gadget")]
 #[cfg_attr(docsrs, doc(cfg(feature = "unstable-sha256-gadget")))]
@@ -9,11 +9,16 @@ use halo2_proofs::{
     plonk::Error,
 };
 
+pub mod merkle;
 mod pow5;
 pub use pow5::{Pow5Chip, Pow5Config, StateWord};
+mod var_len;
+pub use var_len::{VariableLengthChip, VariableLengthConfig};
 
 pub use ::halo2_poseidon as primitives;
-use primitives::{Absorbing, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State};
+use primitives::{
+    Absorbing, ConstantLength, Domain, Spec, SpongeMode, Squeezing, State, VariableLength,
+};
 
 /// A word from the padded input to a Poseidon sponge.
 #[derive(Clone, Debug)]
@@ -66,6 +71,35 @@ pub trait PoseidonSpongeInstructions<
     fn get_output(state: &State<Self::Word, T>) -> Squeezing<Self::Word, RATE>;
 }
 
+/// The set of circuit instructions required to hash variable-length inputs with [`Hash`].
+pub trait PoseidonPaddingInstructions<F: Field, const RATE: usize>: Chip<F> {
+    /// Pads the first `len` words of `message` with a single `1` followed by zeroes, to
+    /// `message.len() / RATE + 1` blocks of `RATE` words.
+    ///
+    /// `len` is constrained to be at most `message.len()`, and the remaining words of
+    /// `message` are ignored. Returns the padded words and, for each block, a boolean
+    /// flag that is set if the block starts at or before the end of the input, i.e. if
+    /// it is absorbed by the sponge.
+    #[allow(clippy::type_complexity)]
+    fn pad(
+        &self,
+        layouter: impl Layouter<F>,
+        len: &AssignedCell<F, F>,
+        message: &[AssignedCell<F, F>],
+    ) -> Result<(Vec<AssignedCell<F, F>>, Vec<AssignedCell<F, F>>), Error>;
+
+    /// Returns the output corresponding to the last set flag in `absorbed`.
+    ///
+    /// `absorbed` must be the flags returned by [`Self::pad`], of which the first is
+    /// always set.
+    fn select_output(
+        &self,
+        layouter: impl Layouter<F>,
+        absorbed: &[AssignedCell<F, F>],
+        outputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error>;
+}
+
 /// A word over which the Poseidon permutation operates.
 #[derive(Debug)]
 pub struct Word<
@@ -284,3 +318,49 @@ impl<
             .squeeze(layouter.namespace(|| "squeeze"))
     }
 }
+
+impl<
+        F: PrimeField,
+        PoseidonChip: PoseidonSpongeInstructions<F, S, VariableLength<MAX_L>, T, RATE>,
+        S: Spec<F, T, RATE>,
+        const T: usize,
+        const RATE: usize,
+        const MAX_L: usize,
+    > Hash<F, PoseidonChip, S, VariableLength<MAX_L>, T, RATE>
+{
+    /// Hashes the first `len` words of the given input.
+    ///
+    /// The padding is computed in-circuit by `padding_chip`, and every block that could
+    /// be absorbed for an input of up to `MAX_L` words is permuted. The output is then
+    /// taken from the state after the last block that is actually absorbed, so that the
+    /// circuit does not depend on `len`.
+    pub fn hash<PaddingChip: PoseidonPaddingInstructions<F, RATE>>(
+        mut self,
+        mut layouter: impl Layouter<F>,
+        padding_chip: &PaddingChip,
+        len: AssignedCell<F, F>,
+        message: [AssignedCell<F, F>; MAX_L],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (padded, absorbed) = padding_chip.pad(layouter.namespace(|| "pad"), &len, &message)?;
+
+        let mut outputs: Vec<AssignedCell<F, F>> = Vec::with_capacity(absorbed.len());
+        for (i, block) in padded.chunks(RATE).enumerate() {
+            let mut input = Absorbing::<_, RATE>::init_empty();
+            for word in block {
+                input
+                    .absorb(PaddedWord::Message(word.clone()))
+                    .expect("block fits in the rate");
+            }
+
+            let mut output = poseidon_sponge(
+                &self.sponge.chip,
+                layouter.namespace(|| format!("absorb_block_{}", i)),
+                &mut self.sponge.state,
+                Some(&input),
+            )?;
+            outputs.push(output.squeeze().expect("rate is nonzero").into());
+        }
+
+        padding_chip.select_output(layouter.namespace(|| "select output"), &absorbed, &outputs)
+    }
+}
@@ -0,0 +1,532 @@
+//! Chip implementing a Merkle hash using Poseidon as the hash function.
+
+use std::marker::PhantomData;
+
+use group::ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error},
+};
+
+use super::{
+    primitives::{ConstantLength, Spec},
+    Hash, Pow5Chip, Pow5Config,
+};
+use crate::{
+    sinsemilla::merkle::MerkleHashInstructions,
+    utilities::{
+        cond_swap::{CondSwapChip, CondSwapConfig, CondSwapInstructions},
+        UtilitiesInstructions,
+    },
+};
+
+/// Configuration for the [`PoseidonMerkleChip`].
+#[derive(Clone, Debug)]
+pub struct PoseidonMerkleConfig<F: PrimeField, const WIDTH: usize, const RATE: usize> {
+    pow5_config: Pow5Config<F, WIDTH, RATE>,
+    cond_swap_config: CondSwapConfig,
+}
+
+impl<F: PrimeField, const WIDTH: usize, const RATE: usize> PoseidonMerkleConfig<F, WIDTH, RATE> {
+    /// Returns the conditional swap config used in this config.
+    pub fn cond_swap_config(&self) -> &CondSwapConfig {
+        &self.cond_swap_config
+    }
+}
+
+/// Chip implementing [`MerkleHashInstructions`] with the Poseidon hash.
+///
+/// A node with `ARITY` children is computed as `Poseidon(children)`, using the
+/// `ConstantLength<ARITY>` domain of the Poseidon specification `S`. The layer of the
+/// node is not included in the hash input.
+#[derive(Debug)]
+pub struct PoseidonMerkleChip<
+    F: PrimeField,
+    S: Spec<F, WIDTH, RATE>,
+    const WIDTH: usize,
+    const RATE: usize,
+> {
+    config: PoseidonMerkleConfig<F, WIDTH, RATE>,
+    _spec: PhantomData<S>,
+}
+
+// Implemented by hand, as Poseidon specifications are not required to be `Clone`.
+impl<F: PrimeField, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> Clone
+    for PoseidonMerkleChip<F, S, WIDTH, RATE>
+{
+    fn clone(&self) -> Self {
+        Self::construct(self.config.clone())
+    }
+}
+
+impl<F: PrimeField, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize> Chip<F>
+    for PoseidonMerkleChip<F, S, WIDTH, RATE>
+{
+    type Config = PoseidonMerkleConfig<F, WIDTH, RATE>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeField, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    PoseidonMerkleChip<F, S, WIDTH, RATE>
+{
+    /// Configures the [`PoseidonMerkleChip`].
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `advices` will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 5],
+        pow5_config: Pow5Config<F, WIDTH, RATE>,
+    ) -> PoseidonMerkleConfig<F, WIDTH, RATE> {
+        // The outputs of each conditional swap may be copied into a subsequent swap
+        // (for arity greater than 2) or into the hash input.
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+        let cond_swap_config = CondSwapChip::configure(meta, advices);
+
+        PoseidonMerkleConfig {
+            pow5_config,
+            cond_swap_config,
+        }
+    }
+
+    /// Constructs a [`PoseidonMerkleChip`] given a [`PoseidonMerkleConfig`].
+    pub fn construct(config: PoseidonMerkleConfig<F, WIDTH, RATE>) -> Self {
+        PoseidonMerkleChip {
+            config,
+            _spec: PhantomData,
+        }
+    }
+}
+
+impl<
+        F: PrimeField,
+        S: Spec<F, WIDTH, RATE>,
+        const WIDTH: usize,
+        const RATE: usize,
+        const ARITY: usize,
+    > MerkleHashInstructions<F, ARITY> for PoseidonMerkleChip<F, S, WIDTH, RATE>
+{
+    type MerkleDomain = ();
+
+    fn hash_children(
+        &self,
+        mut layouter: impl Layouter<F>,
+        _domain: &(),
+        _l: usize,
+        children: [Self::Var; ARITY],
+    ) -> Result<Self::Var, Error> {
+        let chip = Pow5Chip::construct(self.config.pow5_config.clone());
+        let hasher = Hash::<_, _, S, ConstantLength<ARITY>, WIDTH, RATE>::init(
+            chip,
+            layouter.namespace(|| "init"),
+        )?;
+        hasher.hash(layouter.namespace(|| "hash"), children)
+    }
+}
+
+impl<F: PrimeField, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    UtilitiesInstructions<F> for PoseidonMerkleChip<F, S, WIDTH, RATE>
+{
+    type Var = AssignedCell<F, F>;
+}
+
+impl<F: PrimeField, S: Spec<F, WIDTH, RATE>, const WIDTH: usize, const RATE: usize>
+    CondSwapInstructions<F> for PoseidonMerkleChip<F, S, WIDTH, RATE>
+{
+    #[allow(clippy::type_complexity)]
+    fn swap(
+        &self,
+        layouter: impl Layouter<F>,
+        pair: (Self::Var, Value<F>),
+        swap: Value<bool>,
+    ) -> Result<(Self::Var, Self::Var), Error> {
+        let chip = CondSwapChip::<F>::construct(self.config.cond_swap_config.clone());
+        chip.swap(layouter, pair, swap)
+    }
+
+    fn mux(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        choice: Self::Var,
+        left: Self::Var,
+        right: Self::Var,
+    ) -> Result<Self::Var, Error> {
+        let chip = CondSwapChip::<F>::construct(self.config.cond_swap_config.clone());
+        chip.mux(layouter, choice, left, right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PoseidonMerkleChip, PoseidonMerkleConfig};
+    use crate::{
+        poseidon::{
+            primitives::{self as poseidon, ConstantLength, P128Pow5T3 as OrchardNullifier},
+            Pow5Chip,
+        },
+        sinsemilla::merkle::{
+            depth::{MerkleDepthChip, MerkleDepthConfig},
+            MerkleHashPath, MerkleMultiPath, MerkleMultiProof,
+        },
+        utilities::UtilitiesInstructions,
+    };
+
+    use group::ff::Field;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use rand::{rngs::OsRng, RngCore};
+    use std::convert::TryInto;
+
+    const MERKLE_DEPTH: usize = 6;
+
+    type MyMerkleChip = PoseidonMerkleChip<Fp, OrchardNullifier, 3, 2>;
+
+    type MyConfig = (
+        PoseidonMerkleConfig<Fp, 3, 2>,
+        MerkleDepthConfig,
+        Column<Advice>,
+        Column<Instance>,
+    );
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> MyConfig {
+        let advices = [
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+            meta.advice_column(),
+        ];
+        let rc_a = [
+            meta.fixed_column(),
+            meta.fixed_column(),
+            meta.fixed_column(),
+        ];
+        let rc_b = [
+            meta.fixed_column(),
+            meta.fixed_column(),
+            meta.fixed_column(),
+        ];
+        meta.enable_constant(rc_b[0]);
+
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+
+        let pow5_config = Pow5Chip::configure::<OrchardNullifier>(
+            meta,
+            advices[..3].try_into().unwrap(),
+            advices[3],
+            rc_a,
+            rc_b,
+        );
+        let merkle_config = MyMerkleChip::configure(meta, advices, pow5_config);
+        let depth_config = MerkleDepthChip::configure(meta, advices[..4].try_into().unwrap());
+
+        (merkle_config, depth_config, advices[0], instance)
+    }
+
+    struct MyMerkleCircuit<const ARITY: usize> {
+        leaf: Value<Fp>,
+        leaf_pos: Value<u64>,
+        path: Value<[[Fp; ARITY]; MERKLE_DEPTH]>,
+        // If set, the path is checked at this witnessed depth, and the depth is exposed
+        // as a public input alongside the root.
+        depth: Option<Value<usize>>,
+    }
+
+    impl<const ARITY: usize> Circuit<Fp> for MyMerkleCircuit<ARITY> {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                leaf: Value::unknown(),
+                leaf_pos: Value::unknown(),
+                path: Value::unknown(),
+                depth: self.depth.map(|_| Value::unknown()),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            (merkle_config, depth_config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = MyMerkleChip::construct(merkle_config);
+
+            let leaf = chip.load_private(layouter.namespace(|| "leaf"), advice, self.leaf)?;
+
+            let path = MerkleHashPath::<_, _, MERKLE_DEPTH, ARITY, 1>::construct(
+                [chip.clone()],
+                (),
+                self.leaf_pos,
+                self.path,
+            );
+
+            match self.depth {
+                None => {
+                    let root = path.calculate_root(layouter.namespace(|| "root"), leaf)?;
+                    layouter.constrain_instance(root.cell(), instance, 0)
+                }
+                Some(depth) => {
+                    let depth = chip.load_private(
+                        layouter.namespace(|| "depth"),
+                        advice,
+                        depth.map(|depth| Fp::from(depth as u64)),
+                    )?;
+                    let depth_chip = MerkleDepthChip::construct(depth_config);
+                    let root = path.calculate_root_at_depth(
+                        layouter.namespace(|| "root at depth"),
+                        &depth_chip,
+                        &depth,
+                        leaf,
+                    )?;
+                    layouter.constrain_instance(depth.cell(), instance, 0)?;
+                    layouter.constrain_instance(root.cell(), instance, 1)
+                }
+            }
+        }
+    }
+
+    fn hash<const ARITY: usize>(children: [Fp; ARITY]) -> Fp {
+        poseidon::Hash::<_, OrchardNullifier, ConstantLength<ARITY>, 3, 2>::init().hash(children)
+    }
+
+    /// Computes the root of the subtree of height `depth` containing the leaf.
+    fn root<const ARITY: usize>(
+        leaf: Fp,
+        leaf_pos: u64,
+        path: &[[Fp; ARITY]; MERKLE_DEPTH],
+        depth: usize,
+    ) -> Fp {
+        path.iter()
+            .take(depth)
+            .fold((leaf, leaf_pos), |(node, pos), children| {
+                let mut children = *children;
+                children[(pos % ARITY as u64) as usize] = node;
+                (hash(children), pos / ARITY as u64)
+            })
+            .0
+    }
+
+    fn random_path<const ARITY: usize>() -> (Fp, u64, [[Fp; ARITY]; MERKLE_DEPTH]) {
+        let mut rng = OsRng;
+
+        let leaf = Fp::random(rng);
+        let leaf_pos = rng.next_u64() % (ARITY as u64).pow(MERKLE_DEPTH as u32);
+        let path: Vec<[Fp; ARITY]> = (0..MERKLE_DEPTH)
+            .map(|_| {
+                let children: Vec<_> = (0..ARITY).map(|_| Fp::random(rng)).collect();
+                children.try_into().unwrap()
+            })
+            .collect();
+
+        (leaf, leaf_pos, path.try_into().unwrap())
+    }
+
+    fn test_merkle_path<const ARITY: usize>() {
+        let (leaf, leaf_pos, path) = random_path::<ARITY>();
+
+        let circuit = MyMerkleCircuit::<ARITY> {
+            leaf: Value::known(leaf),
+            leaf_pos: Value::known(leaf_pos),
+            path: Value::known(path),
+            depth: None,
+        };
+        let root = root(leaf, leaf_pos, &path, MERKLE_DEPTH);
+
+        let prover = MockProver::run(11, &circuit, vec![vec![root]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A different position places the leaf elsewhere in the tree.
+        let circuit = MyMerkleCircuit::<ARITY> {
+            leaf_pos: Value::known(leaf_pos ^ 1),
+            ..circuit
+        };
+        let prover = MockProver::run(11, &circuit, vec![vec![root]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn poseidon_merkle_path() {
+        test_merkle_path::<2>();
+    }
+
+    #[test]
+    fn poseidon_merkle_path_arity_4() {
+        test_merkle_path::<4>();
+    }
+
+    #[test]
+    fn poseidon_merkle_path_at_depth() {
+        let (leaf, leaf_pos, path) = random_path::<2>();
+
+        for depth in [0, 1, 4, MERKLE_DEPTH] {
+            let circuit = MyMerkleCircuit::<2> {
+                leaf: Value::known(leaf),
+                leaf_pos: Value::known(leaf_pos),
+                path: Value::known(path),
+                depth: Some(Value::known(depth)),
+            };
+            let root = root(leaf, leaf_pos, &path, depth);
+
+            let prover =
+                MockProver::run(11, &circuit, vec![vec![Fp::from(depth as u64), root]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The root at one depth is not accepted at another.
+            let wrong_depth = Fp::from(((depth + 1) % (MERKLE_DEPTH + 1)) as u64);
+            let prover = MockProver::run(11, &circuit, vec![vec![wrong_depth, root]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // A depth greater than the path length is rejected.
+        let depth = MERKLE_DEPTH + 1;
+        let circuit = MyMerkleCircuit::<2> {
+            leaf: Value::known(leaf),
+            leaf_pos: Value::known(leaf_pos),
+            path: Value::known(path),
+            depth: Some(Value::known(depth)),
+        };
+        let root = root(leaf, leaf_pos, &path, MERKLE_DEPTH);
+        let prover =
+            MockProver::run(11, &circuit, vec![vec![Fp::from(depth as u64), root]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    struct MyMultiProofCircuit<const ARITY: usize> {
+        leaves: Value<Vec<Fp>>,
+        positions: Vec<u64>,
+        siblings: Value<Vec<Fp>>,
+    }
+
+    impl<const ARITY: usize> Circuit<Fp> for MyMultiProofCircuit<ARITY> {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                leaves: Value::unknown(),
+                positions: self.positions.clone(),
+                siblings: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            (merkle_config, _, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = MyMerkleChip::construct(merkle_config);
+
+            let leaves = self
+                .leaves
+                .clone()
+                .transpose_vec(self.positions.len())
+                .into_iter()
+                .enumerate()
+                .map(|(i, leaf)| {
+                    chip.load_private(layouter.namespace(|| format!("leaf {}", i)), advice, leaf)
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+
+            let multi_path = MerkleMultiPath::<_, _, MERKLE_DEPTH, ARITY>::construct(
+                chip,
+                (),
+                self.positions.clone(),
+                self.siblings.clone(),
+                advice,
+            );
+            let root = multi_path.calculate_root(layouter.namespace(|| "root"), &leaves)?;
+
+            layouter.constrain_instance(root.cell(), instance, 0)
+        }
+    }
+
+    fn test_multi_proof<const ARITY: usize>(positions: Vec<u64>) {
+        let rng = OsRng;
+
+        // Build the full tree, from the leaves to the root.
+        let num_leaves = ARITY.pow(MERKLE_DEPTH as u32);
+        let mut tree = vec![(0..num_leaves).map(|_| Fp::random(rng)).collect::<Vec<_>>()];
+        for _ in 0..MERKLE_DEPTH {
+            let layer = tree
+                .last()
+                .unwrap()
+                .chunks(ARITY)
+                .map(|children| hash::<ARITY>(children.try_into().unwrap()))
+                .collect();
+            tree.push(layer);
+        }
+        let root = tree[MERKLE_DEPTH][0];
+
+        let proof = MerkleMultiProof::<_, MERKLE_DEPTH, ARITY>::from_tree(&tree, &positions);
+        let leaves: Vec<_> = positions.iter().map(|pos| tree[0][*pos as usize]).collect();
+
+        // Shared ancestors need fewer siblings than the individual paths.
+        assert!(proof.siblings().len() < positions.len() * (ARITY - 1) * MERKLE_DEPTH);
+        assert_eq!(proof.root(&leaves, |_, children| hash(children)), root);
+
+        let circuit = MyMultiProofCircuit::<ARITY> {
+            leaves: Value::known(leaves.clone()),
+            positions,
+            siblings: Value::known(proof.siblings().to_vec()),
+        };
+        let prover = MockProver::run(12, &circuit, vec![vec![root]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // A proof with the wrong number of siblings is rejected during synthesis.
+        let mut siblings = proof.siblings().to_vec();
+        siblings.pop();
+        let short_circuit = MyMultiProofCircuit::<ARITY> {
+            leaves: Value::known(leaves.clone()),
+            positions: circuit.positions.clone(),
+            siblings: Value::known(siblings),
+        };
+        assert!(matches!(
+            MockProver::run(12, &short_circuit, vec![vec![root]]),
+            Err(Error::Synthesis)
+        ));
+
+        // The leaves must be given in the order of their positions.
+        let circuit = MyMultiProofCircuit::<ARITY> {
+            leaves: Value::known(leaves.into_iter().rev().collect()),
+            ..circuit
+        };
+        let prover = MockProver::run(12, &circuit, vec![vec![root]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn poseidon_merkle_multi_proof() {
+        // Leaves 4 and 5 are siblings, and share ancestors with leaf 1 from layer 2.
+        test_multi_proof::<2>(vec![5, 4, 1, 40]);
+    }
+
+    #[test]
+    fn poseidon_merkle_multi_proof_arity_4() {
+        test_multi_proof::<4>(vec![0, 3, 17, 1000]);
+    }
+}
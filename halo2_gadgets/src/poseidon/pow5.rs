@@ -612,8 +612,10 @@ mod tests {
 
     use super::{PoseidonInstructions, Pow5Chip, Pow5Config, StateWord};
     use crate::poseidon::{
-        primitives::{self as poseidon, ConstantLength, P128Pow5T3 as OrchardNullifier, Spec},
-        Hash,
+        primitives::{
+            self as poseidon, ConstantLength, P128Pow5T3 as OrchardNullifier, Spec, VariableLength,
+        },
+        Hash, VariableLengthChip, VariableLengthConfig,
     };
     use std::convert::TryInto;
     use std::marker::PhantomData;
@@ -894,6 +896,154 @@ mod tests {
         }
     }
 
+    struct MyVariableLengthHashCircuit<
+        S: Spec<Fp, WIDTH, RATE>,
+        const WIDTH: usize,
+        const RATE: usize,
+        const MAX_L: usize,
+    > {
+        message: Value<[Fp; MAX_L]>,
+        len: Value<Fp>,
+        // For the purpose of this test, witness the result.
+        output: Value<Fp>,
+        _spec: PhantomData<S>,
+    }
+
+    impl<S: Spec<Fp, WIDTH, RATE>, const WIDTH: usize, const RATE: usize, const MAX_L: usize>
+        Circuit<Fp> for MyVariableLengthHashCircuit<S, WIDTH, RATE, MAX_L>
+    {
+        type Config = (Pow5Config<Fp, WIDTH, RATE>, VariableLengthConfig);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                message: Value::unknown(),
+                len: Value::unknown(),
+                output: Value::unknown(),
+                _spec: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+            let partial_sbox = meta.advice_column();
+
+            let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+            let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+            meta.enable_constant(rc_b[0]);
+
+            let padding_config =
+                VariableLengthChip::configure(meta, [state[0], state[1], state[2], partial_sbox]);
+
+            let pow5_config = Pow5Chip::configure::<S>(
+                meta,
+                state.try_into().unwrap(),
+                partial_sbox,
+                rc_a.try_into().unwrap(),
+                rc_b.try_into().unwrap(),
+            );
+
+            (pow5_config, padding_config)
+        }
+
+        fn synthesize(
+            &self,
+            (config, padding_config): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = Pow5Chip::construct(config.clone());
+            let padding_chip = VariableLengthChip::construct(padding_config);
+
+            let (len, message) = layouter.assign_region(
+                || "load message",
+                |mut region| {
+                    let len =
+                        region.assign_advice(|| "load len", config.state[0], 0, || self.len)?;
+
+                    let message_word = |i: usize| {
+                        let value = self.message.map(|message_vals| message_vals[i]);
+                        region.assign_advice(
+                            || format!("load message_{}", i),
+                            config.state[i % WIDTH],
+                            1 + i / WIDTH,
+                            || value,
+                        )
+                    };
+
+                    let message: Result<Vec<_>, Error> = (0..MAX_L).map(message_word).collect();
+                    Ok((len, message?.try_into().unwrap()))
+                },
+            )?;
+
+            let hasher = Hash::<_, _, S, VariableLength<MAX_L>, WIDTH, RATE>::init(
+                chip,
+                layouter.namespace(|| "init"),
+            )?;
+            let output = hasher.hash(layouter.namespace(|| "hash"), &padding_chip, len, message)?;
+
+            layouter.assign_region(
+                || "constrain output",
+                |mut region| {
+                    let expected_var = region.assign_advice(
+                        || "load output",
+                        config.state[0],
+                        0,
+                        || self.output,
+                    )?;
+                    region.constrain_equal(output.cell(), expected_var.cell())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn poseidon_hash_variable_length() {
+        let rng = OsRng;
+
+        let message = [(); 5].map(|_| Fp::random(rng));
+
+        for len in 0..=5 {
+            let output = poseidon::Hash::<_, OrchardNullifier, VariableLength<5>, 3, 2>::init()
+                .hash(&message[..len]);
+
+            let k = 9;
+            let circuit = MyVariableLengthHashCircuit::<OrchardNullifier, 3, 2, 5> {
+                message: Value::known(message),
+                len: Value::known(Fp::from(len as u64)),
+                output: Value::known(output),
+                _spec: PhantomData,
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn poseidon_hash_variable_length_invalid() {
+        let rng = OsRng;
+
+        let message = [(); 5].map(|_| Fp::random(rng));
+        let output = poseidon::Hash::<_, OrchardNullifier, VariableLength<5>, 3, 2>::init()
+            .hash(&message[..3]);
+
+        let k = 9;
+        let circuit = |len: u64| MyVariableLengthHashCircuit::<OrchardNullifier, 3, 2, 5> {
+            message: Value::known(message),
+            len: Value::known(Fp::from(len)),
+            output: Value::known(output),
+            _spec: PhantomData,
+        };
+
+        // The output is bound to the witnessed length.
+        let prover = MockProver::run(k, &circuit(4), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // The length is bounded by the maximum length.
+        let prover = MockProver::run(k, &circuit(6), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
     #[cfg(feature = "test-dev-graph")]
     #[test]
     fn print_poseidon_chip() {
@@ -0,0 +1,369 @@
+use std::marker::PhantomData;
+
+use group::ff::PrimeField;
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::PoseidonPaddingInstructions;
+use crate::utilities::{bool_check, ternary};
+
+/// Configuration for a [`VariableLengthChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariableLengthConfig {
+    q_init: Selector,
+    q_pad: Selector,
+    q_tail: Selector,
+    q_first: Selector,
+    q_select: Selector,
+    active: Column<Advice>,
+    count: Column<Advice>,
+    message: Column<Advice>,
+    padded: Column<Advice>,
+}
+
+/// A chip implementing [`PoseidonPaddingInstructions`].
+///
+/// The input is padded in a single region, with one row per padded word. Each word
+/// `m_i` of the message is laid out alongside a boolean flag `a_i`, set for the words
+/// that are part of the input, and the number `c_i` of set flags so far:
+///
+/// | active | count | message | padded | q_init | q_pad | q_tail |
+/// ---------------------------------------------------------------
+/// |   1    |   0   |         |        |   1    |   0   |   0    |
+/// |  a_0   |  c_0  |   m_0   |  p_0   |   0    |   1   |   0    |
+/// |  ...   |  ...  |   ...   |  ...   |   0    |   1   |   0    |
+/// |  a_N   |  c_N  |         |  p_N   |   0    |   1   |   1    |
+///
+/// where `a_i` may only be set if `a_{i-1}` is set, `c_i = c_{i-1} + a_i`, and
+/// `p_i = a_i ? m_i : a_{i-1} - a_i`. The set flags thus form a prefix of length
+/// `c_N = len`, followed by a single padding `1` and zeroes. `q_tail` forces `a_i = 0`
+/// for the rows beyond the message, which bounds `len` by its maximum length.
+///
+/// A block of `RATE` words is absorbed if the flag preceding it is set. The output of
+/// the last absorbed block is then selected in a second region, with one row per
+/// block:
+///
+/// | active | message | padded | q_first | q_select |
+/// --------------------------------------------------
+/// |        |   o_0   |  r_0   |    1    |    0     |
+/// |  b_1   |   o_1   |  r_1   |    0    |    1    |
+/// |  ...   |   ...   |  ...   |    0    |    1    |
+///
+/// where `r_0 = o_0` and `r_j = b_j ? o_j : r_{j-1}`.
+#[derive(Clone, Debug)]
+pub struct VariableLengthChip<F: PrimeField> {
+    config: VariableLengthConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> Chip<F> for VariableLengthChip<F> {
+    type Config = VariableLengthConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeField> VariableLengthChip<F> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// The columns in `advices` are used for the `active`, `count`, `message` and
+    /// `padded` columns respectively.
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `advices` will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 4],
+    ) -> VariableLengthConfig {
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = VariableLengthConfig {
+            q_init: meta.selector(),
+            q_pad: meta.selector(),
+            q_tail: meta.selector(),
+            q_first: meta.selector(),
+            q_select: meta.selector(),
+            active: advices[0],
+            count: advices[1],
+            message: advices[2],
+            padded: advices[3],
+        };
+
+        meta.create_gate("Poseidon padding initialisation", |meta| {
+            let q_init = meta.query_selector(config.q_init);
+
+            let active = meta.query_advice(config.active, Rotation::cur());
+            let count = meta.query_advice(config.count, Rotation::cur());
+
+            Constraints::with_selector(
+                q_init,
+                [
+                    ("active = 1", active - Expression::Constant(F::ONE)),
+                    ("count = 0", count),
+                ],
+            )
+        });
+
+        meta.create_gate("Poseidon padding", |meta| {
+            let q_pad = meta.query_selector(config.q_pad);
+
+            let active_prev = meta.query_advice(config.active, Rotation::prev());
+            let active = meta.query_advice(config.active, Rotation::cur());
+            let count_prev = meta.query_advice(config.count, Rotation::prev());
+            let count = meta.query_advice(config.count, Rotation::cur());
+            let message = meta.query_advice(config.message, Rotation::cur());
+            let padded = meta.query_advice(config.padded, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+
+            Constraints::with_selector(
+                q_pad,
+                [
+                    ("bool_check active", bool_check(active.clone())),
+                    (
+                        "active ⇒ active_prev",
+                        active.clone() * (one - active_prev.clone()),
+                    ),
+                    (
+                        "count = count_prev + active",
+                        count - count_prev - active.clone(),
+                    ),
+                    (
+                        "padded = active ? message : active_prev - active",
+                        padded - ternary(active.clone(), message, active_prev - active.clone()),
+                    ),
+                ],
+            )
+        });
+
+        meta.create_gate("Poseidon padding tail", |meta| {
+            let q_tail = meta.query_selector(config.q_tail);
+            let active = meta.query_advice(config.active, Rotation::cur());
+
+            Constraints::with_selector(q_tail, Some(("active = 0", active)))
+        });
+
+        meta.create_gate("Poseidon output selection initialisation", |meta| {
+            let q_first = meta.query_selector(config.q_first);
+
+            let output = meta.query_advice(config.message, Rotation::cur());
+            let result = meta.query_advice(config.padded, Rotation::cur());
+
+            Constraints::with_selector(q_first, Some(("result = output", result - output)))
+        });
+
+        meta.create_gate("Poseidon output selection", |meta| {
+            let q_select = meta.query_selector(config.q_select);
+
+            let absorbed = meta.query_advice(config.active, Rotation::cur());
+            let output = meta.query_advice(config.message, Rotation::cur());
+            let result_prev = meta.query_advice(config.padded, Rotation::prev());
+            let result = meta.query_advice(config.padded, Rotation::cur());
+
+            Constraints::with_selector(
+                q_select,
+                Some((
+                    "result = absorbed ? output : result_prev",
+                    result - ternary(absorbed, output, result_prev),
+                )),
+            )
+        });
+
+        config
+    }
+
+    /// Constructs a [`VariableLengthChip`] given a [`VariableLengthConfig`].
+    pub fn construct(config: VariableLengthConfig) -> Self {
+        VariableLengthChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: PrimeField, const RATE: usize> PoseidonPaddingInstructions<F, RATE>
+    for VariableLengthChip<F>
+{
+    fn pad(
+        &self,
+        mut layouter: impl Layouter<F>,
+        len: &AssignedCell<F, F>,
+        message: &[AssignedCell<F, F>],
+    ) -> Result<(Vec<AssignedCell<F, F>>, Vec<AssignedCell<F, F>>), Error> {
+        let config = self.config();
+        let num_words = (message.len() / RATE + 1) * RATE;
+
+        // A length beyond the message is witnessed as such, and then fails `q_tail`.
+        let len_val = len.value().map(|len| {
+            (0..=message.len())
+                .find(|i| F::from(*i as u64) == *len)
+                .unwrap_or(num_words)
+        });
+
+        layouter.assign_region(
+            || "pad input",
+            |mut region| {
+                config.q_init.enable(&mut region, 0)?;
+
+                let mut active = vec![region.assign_advice(
+                    || "active_init",
+                    config.active,
+                    0,
+                    || Value::known(F::ONE),
+                )?];
+                let mut count = region.assign_advice(
+                    || "count_init",
+                    config.count,
+                    0,
+                    || Value::known(F::ZERO),
+                )?;
+
+                let mut padded = Vec::with_capacity(num_words);
+                for i in 0..num_words {
+                    let offset = i + 1;
+                    config.q_pad.enable(&mut region, offset)?;
+
+                    let word = match message.get(i) {
+                        Some(word) => word.copy_advice(
+                            || format!("message_{}", i),
+                            &mut region,
+                            config.message,
+                            offset,
+                        )?,
+                        None => {
+                            config.q_tail.enable(&mut region, offset)?;
+                            region.assign_advice(
+                                || format!("message_{}", i),
+                                config.message,
+                                offset,
+                                || Value::known(F::ZERO),
+                            )?
+                        }
+                    };
+
+                    let is_active = len_val.map(|len| i < len);
+                    active.push(region.assign_advice(
+                        || format!("active_{}", i),
+                        config.active,
+                        offset,
+                        || is_active.map(|a| F::from(a as u64)),
+                    )?);
+
+                    count = region.assign_advice(
+                        || format!("count_{}", i),
+                        config.count,
+                        offset,
+                        || len_val.map(|len| F::from(len.min(i + 1) as u64)),
+                    )?;
+
+                    let padded_val = len_val.zip(word.value()).map(|(len, word)| {
+                        if i < len {
+                            *word
+                        } else if i == len {
+                            F::ONE
+                        } else {
+                            F::ZERO
+                        }
+                    });
+                    padded.push(region.assign_advice(
+                        || format!("padded_{}", i),
+                        config.padded,
+                        offset,
+                        || padded_val,
+                    )?);
+                }
+
+                region.constrain_equal(count.cell(), len.cell())?;
+
+                // Block j is absorbed if the word preceding it is active, which is
+                // always the case for the first block.
+                let absorbed = active
+                    .into_iter()
+                    .step_by(RATE)
+                    .take(num_words / RATE)
+                    .collect();
+
+                Ok((padded, absorbed))
+            },
+        )
+    }
+
+    fn select_output(
+        &self,
+        mut layouter: impl Layouter<F>,
+        absorbed: &[AssignedCell<F, F>],
+        outputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(!outputs.is_empty());
+        assert_eq!(absorbed.len(), outputs.len());
+
+        let config = self.config();
+
+        layouter.assign_region(
+            || "select output",
+            |mut region| {
+                config.q_first.enable(&mut region, 0)?;
+
+                let output =
+                    outputs[0].copy_advice(|| "output_0", &mut region, config.message, 0)?;
+                let mut result = region.assign_advice(
+                    || "result_0",
+                    config.padded,
+                    0,
+                    || output.value().copied(),
+                )?;
+
+                for (j, (absorbed, output)) in
+                    absorbed.iter().zip(outputs.iter()).enumerate().skip(1)
+                {
+                    config.q_select.enable(&mut region, j)?;
+
+                    let absorbed = absorbed.copy_advice(
+                        || format!("absorbed_{}", j),
+                        &mut region,
+                        config.active,
+                        j,
+                    )?;
+                    let output = output.copy_advice(
+                        || format!("output_{}", j),
+                        &mut region,
+                        config.message,
+                        j,
+                    )?;
+
+                    let result_val = absorbed
+                        .value()
+                        .zip(output.value())
+                        .zip(result.value())
+                        .map(|((absorbed, output), result)| {
+                            if *absorbed == F::ONE {
+                                *output
+                            } else {
+                                *result
+                            }
+                        });
+                    result = region.assign_advice(
+                        || format!("result_{}", j),
+                        config.padded,
+                        j,
+                        || result_val,
+                    )?;
+                }
+
+                Ok(result)
+            },
+        )
+    }
+}
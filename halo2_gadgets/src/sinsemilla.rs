@@ -530,26 +530,31 @@ pub(crate) mod tests {
     use rand::rngs::OsRng;
 
     use super::{
-        chip::{SinsemillaChip, SinsemillaConfig},
+        chip::{SinsemillaChip, SinsemillaConfig, SinsemillaCurve},
         CommitDomain, CommitDomains, HashDomain, HashDomains, Message, MessagePiece,
+        SinsemillaInstructions,
     };
 
     use crate::{
         ecc::{
             chip::{find_zs_and_us, EccChip, EccConfig, H, NUM_WINDOWS},
-            tests::{FullWidth, TestFixedBases},
-            NonIdentityPoint, ScalarFixed,
+            tests::{FullWidth, TestFixedBases, VestaFixedBases, VestaFullWidth},
+            FixedPoints, NonIdentityPoint, ScalarFixed,
         },
         sinsemilla::primitives::{self as sinsemilla, K},
         test_circuits::test_utils::test_against_stored_circuit,
         utilities::lookup_range_check::{
-            PallasLookupRangeCheck, PallasLookupRangeCheck4_5BConfig, PallasLookupRangeCheckConfig,
+            LookupRangeCheck, LookupRangeCheckConfig, PallasLookupRangeCheck,
+            PallasLookupRangeCheck4_5BConfig, PallasLookupRangeCheckConfig,
         },
     };
 
-    use group::{ff::Field, Curve};
+    use group::{
+        ff::{Field, PrimeField},
+        Curve,
+    };
     use lazy_static::lazy_static;
-    use pasta_curves::pallas;
+    use pasta_curves::{arithmetic::CurveExt, pallas, vesta};
 
     use std::convert::TryInto;
     use std::marker::PhantomData;
@@ -565,6 +570,28 @@ pub(crate) mod tests {
             find_zs_and_us(*R, NUM_WINDOWS).unwrap();
     }
 
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct VestaHashDomain;
+    impl HashDomains<vesta::Affine> for VestaHashDomain {
+        fn Q(&self) -> vesta::Affine {
+            vesta::Point::hash_to_curve(sinsemilla::Q_PERSONALIZATION)(PERSONALIZATION.as_bytes())
+                .to_affine()
+        }
+    }
+
+    // This test does not make use of the CommitDomain.
+    #[derive(Debug, Clone, Eq, PartialEq)]
+    struct VestaCommitDomain;
+    impl CommitDomains<vesta::Affine, VestaFixedBases, VestaHashDomain> for VestaCommitDomain {
+        fn r(&self) -> VestaFullWidth {
+            VestaFullWidth::default()
+        }
+
+        fn hash_domain(&self) -> VestaHashDomain {
+            VestaHashDomain
+        }
+    }
+
     #[derive(Debug, Clone, Eq, PartialEq)]
     pub(crate) struct TestHashDomain;
     impl HashDomains<pallas::Affine> for TestHashDomain {
@@ -928,4 +955,146 @@ pub(crate) mod tests {
             .render(11, &circuit, &root)
             .unwrap();
     }
+
+    /// Hashes a random two-piece message with a `SinsemillaChip` instantiated over the
+    /// curve `C` with `K`-bit words.
+    struct WordSizeCircuit<Hash, Commit, F, C, const K: usize> {
+        domain: Hash,
+        _marker: PhantomData<(Commit, F, C)>,
+    }
+
+    impl<Hash, Commit, F, C, const K: usize> WordSizeCircuit<Hash, Commit, F, C, K> {
+        fn new(domain: Hash) -> Self {
+            Self {
+                domain,
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    impl<Hash, Commit, F, C, const K: usize> Circuit<C::Base> for WordSizeCircuit<Hash, Commit, F, C, K>
+    where
+        Hash: HashDomains<C> + Eq,
+        F: FixedPoints<C>,
+        Commit: CommitDomains<C, F, Hash> + Eq,
+        C: SinsemillaCurve,
+    {
+        type Config =
+            SinsemillaConfig<Hash, Commit, F, LookupRangeCheckConfig<C::NativeField, K>, C, K>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::new(self.domain.clone())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Base>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let running_sum = meta.advice_column();
+            let fixed_y_q = meta.fixed_column();
+
+            // Shared fixed column for loading constants
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+
+            // Fixed columns for the Sinsemilla generator lookup table
+            let table_idx = meta.lookup_table_column();
+            let lookup = (
+                table_idx,
+                meta.lookup_table_column(),
+                meta.lookup_table_column(),
+            );
+
+            let range_check = LookupRangeCheckConfig::configure(meta, running_sum, table_idx);
+
+            SinsemillaChip::configure(
+                meta,
+                advices,
+                advices[2],
+                fixed_y_q,
+                lookup,
+                range_check,
+                false,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Base>,
+        ) -> Result<(), Error> {
+            SinsemillaChip::load(config.clone(), &mut layouter)?;
+            let chip = SinsemillaChip::construct(config);
+
+            // Fill the first piece, and leave a single word for the second piece.
+            let num_words = C::Base::CAPACITY as usize / K + 1;
+            let bitstring: Vec<Value<bool>> = (0..(num_words * K))
+                .map(|_| Value::known(rand::random::<bool>()))
+                .collect();
+            let message: Message<C, _, K, { sinsemilla::C }> =
+                Message::from_bitstring(chip.clone(), layouter.namespace(|| "message"), bitstring)?;
+
+            // In test builds, `hash_to_point` checks its output against a native
+            // computation of the hash.
+            chip.hash_to_point(
+                layouter.namespace(|| "hash"),
+                self.domain.Q(),
+                message.inner,
+            )?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sinsemilla_chip_vesta_8_bit_words() {
+        // The 2^8-row generator table fits in a 2^9-row circuit.
+        let circuit = WordSizeCircuit::<
+            VestaHashDomain,
+            VestaCommitDomain,
+            VestaFixedBases,
+            vesta::Affine,
+            8,
+        >::new(VestaHashDomain);
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn sinsemilla_chip_pallas_12_bit_words() {
+        // Generators beyond the precomputed 2^10-row table are hashed to the curve.
+        let circuit = WordSizeCircuit::<
+            TestHashDomain,
+            TestCommitDomain,
+            TestFixedBases,
+            pallas::Affine,
+            12,
+        >::new(TestHashDomain);
+        let prover = MockProver::run(13, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
+    #[test]
+    fn generators() {
+        fn check<C: SinsemillaCurve, const K: usize>() {
+            let hasher = C::CurveExt::hash_to_curve(sinsemilla::S_PERSONALIZATION);
+            let generators = C::generators::<K>();
+            assert_eq!(generators.len(), 1 << K);
+            for i in [0, 1, (1 << K) - 1] {
+                let expected = hasher(&(i as u32).to_le_bytes()).to_affine();
+                let expected = expected.coordinates().unwrap();
+                assert_eq!(generators[i], (*expected.x(), *expected.y()));
+                assert_eq!(C::sinsemilla_generator(i as u32), generators[i]);
+            }
+        }
+
+        check::<pallas::Affine, 8>();
+        check::<pallas::Affine, 12>();
+        check::<vesta::Affine, 8>();
+    }
 }
@@ -6,13 +6,15 @@ use super::{
 };
 use crate::{
     ecc::{
-        chip::{DoubleAndAdd, NonIdentityEccPoint},
+        chip::{DoubleAndAdd, EccCurve, NonIdentityEccPoint},
         FixedPoints,
     },
-    utilities::lookup_range_check::{PallasLookupRangeCheck, PallasLookupRangeCheckConfig},
+    utilities::lookup_range_check::{LookupRangeCheck, PallasLookupRangeCheckConfig},
 };
-use std::marker::PhantomData;
+use std::{borrow::Cow, marker::PhantomData};
 
+use ff::Field;
+use group::Curve;
 use halo2_proofs::{
     circuit::{AssignedCell, Chip, Layouter, Value},
     plonk::{
@@ -21,25 +23,92 @@ use halo2_proofs::{
     },
     poly::Rotation,
 };
-use pasta_curves::pallas;
+use pasta_curves::{
+    arithmetic::{CurveAffine, CurveExt},
+    pallas, vesta,
+};
 
 pub(crate) mod generator_table;
 use generator_table::GeneratorTableConfig;
 
 mod hash_to_point;
 
+/// A curve over which [`SinsemillaChip`] can be instantiated.
+///
+/// The generator $S[i]$ is $\mathsf{GroupHash}(\texttt{"z.cash:SinsemillaS"}, i)$,
+/// hashing the 32-bit little-endian encoding of $i$ to the curve. It does not depend on
+/// $K$, so the generator table for a given $K$ is a prefix of the table for any larger
+/// $K$.
+pub trait SinsemillaCurve: EccCurve {
+    /// Returns the affine coordinates of the generator $S[i]$.
+    fn sinsemilla_generator(i: u32) -> (Self::Base, Self::Base) {
+        hash_generators::<Self>(i..(i + 1))[0]
+    }
+
+    /// Returns the affine coordinates of the generators $S[0..2^K]$.
+    fn generators<const K: usize>() -> Cow<'static, [(Self::Base, Self::Base)]> {
+        Cow::Owned(hash_generators::<Self>(0..(1 << K)))
+    }
+}
+
+/// Generators with an index below $2^{10}$ are read from the precomputed `SINSEMILLA_S` table.
+impl SinsemillaCurve for pallas::Affine {
+    fn sinsemilla_generator(i: u32) -> (pallas::Base, pallas::Base) {
+        match sinsemilla::SINSEMILLA_S.get(i as usize) {
+            Some(generator) => *generator,
+            None => hash_generators::<Self>(i..(i + 1))[0],
+        }
+    }
+
+    fn generators<const K: usize>() -> Cow<'static, [(pallas::Base, pallas::Base)]> {
+        if K <= sinsemilla::K {
+            Cow::Borrowed(&sinsemilla::SINSEMILLA_S[..(1 << K)])
+        } else {
+            let mut generators = sinsemilla::SINSEMILLA_S.to_vec();
+            generators.extend(hash_generators::<Self>((1 << sinsemilla::K)..(1 << K)));
+            Cow::Owned(generators)
+        }
+    }
+}
+
+impl SinsemillaCurve for vesta::Affine {}
+
+/// Hashes the generators $S[i]$ for each $i$ in `indices` to the curve.
+fn hash_generators<C: CurveAffine>(indices: std::ops::Range<u32>) -> Vec<(C::Base, C::Base)> {
+    let hasher = C::CurveExt::hash_to_curve(sinsemilla::S_PERSONALIZATION);
+    let points: Vec<C::CurveExt> = indices.map(|i| hasher(&i.to_le_bytes())).collect();
+
+    let mut affines = vec![C::identity(); points.len()];
+    C::CurveExt::batch_normalize(&points, &mut affines);
+
+    affines
+        .into_iter()
+        .map(|point| {
+            let coords = point.coordinates().unwrap();
+            (*coords.x(), *coords.y())
+        })
+        .collect()
+}
+
 /// Configuration for the Sinsemilla hash chip
 ///
 /// If `allow_init_from_private_point` is true, the chip can compute a hash from a private point.
 /// However, compared to when `allow_init_from_private_point` is set to false,
 /// computing the hash from a public point will take one additional row.
 #[derive(Eq, PartialEq, Clone, Debug)]
-pub struct SinsemillaConfig<Hash, Commit, F, Lookup = PallasLookupRangeCheckConfig>
-where
-    Hash: HashDomains<pallas::Affine>,
-    F: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, F, Hash>,
-    Lookup: PallasLookupRangeCheck,
+pub struct SinsemillaConfig<
+    Hash,
+    Commit,
+    F,
+    Lookup = PallasLookupRangeCheckConfig,
+    C = pallas::Affine,
+    const K: usize = { sinsemilla::K },
+> where
+    Hash: HashDomains<C>,
+    F: FixedPoints<C>,
+    Commit: CommitDomains<C, F, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
     /// Binary selector used in lookup argument and in the body of the Sinsemilla hash.
     q_sinsemilla1: Selector,
@@ -68,15 +137,16 @@ where
     /// If true, it is possible to compute a hash from a private point.
     allow_init_from_private_point: bool,
 
-    _marker: PhantomData<(Hash, Commit, F)>,
+    _marker: PhantomData<(Hash, Commit, F, C)>,
 }
 
-impl<Hash, Commit, F, Lookup> SinsemillaConfig<Hash, Commit, F, Lookup>
+impl<Hash, Commit, F, Lookup, C, const K: usize> SinsemillaConfig<Hash, Commit, F, Lookup, C, K>
 where
-    Hash: HashDomains<pallas::Affine>,
-    F: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, F, Hash>,
-    Lookup: PallasLookupRangeCheck,
+    Hash: HashDomains<C>,
+    F: FixedPoints<C>,
+    Commit: CommitDomains<C, F, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
     /// Returns an array of all advice columns in this config, in arbitrary order.
     pub(super) fn advices(&self) -> [Column<Advice>; 5] {
@@ -95,35 +165,44 @@ where
     }
 
     /// Derives the expression `q_s3 = (q_s2) * (q_s2 - 1)`.
-    fn q_s3(&self, meta: &mut VirtualCells<pallas::Base>) -> Expression<pallas::Base> {
-        let one = Expression::Constant(pallas::Base::one());
+    fn q_s3(&self, meta: &mut VirtualCells<C::Base>) -> Expression<C::Base> {
+        let one = Expression::Constant(C::Base::ONE);
         let q_s2 = meta.query_fixed(self.q_sinsemilla2);
         q_s2.clone() * (q_s2 - one)
     }
 }
 
-/// A chip that implements 10-bit Sinsemilla using a lookup table and 5 advice columns.
+/// A chip that implements `K`-bit Sinsemilla using a lookup table and 5 advice columns.
 ///
 /// [Chip description](https://zcash.github.io/halo2/design/gadgets/sinsemilla.html#plonk--halo-2-constraints).
 #[derive(Eq, PartialEq, Clone, Debug)]
-pub struct SinsemillaChip<Hash, Commit, Fixed, Lookup = PallasLookupRangeCheckConfig>
-where
-    Hash: HashDomains<pallas::Affine>,
-    Fixed: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, Fixed, Hash>,
-    Lookup: PallasLookupRangeCheck,
+pub struct SinsemillaChip<
+    Hash,
+    Commit,
+    Fixed,
+    Lookup = PallasLookupRangeCheckConfig,
+    C = pallas::Affine,
+    const K: usize = { sinsemilla::K },
+> where
+    Hash: HashDomains<C>,
+    Fixed: FixedPoints<C>,
+    Commit: CommitDomains<C, Fixed, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
-    config: SinsemillaConfig<Hash, Commit, Fixed, Lookup>,
+    config: SinsemillaConfig<Hash, Commit, Fixed, Lookup, C, K>,
 }
 
-impl<Hash, Commit, Fixed, Lookup> Chip<pallas::Base> for SinsemillaChip<Hash, Commit, Fixed, Lookup>
+impl<Hash, Commit, Fixed, Lookup, C, const K: usize> Chip<C::Base>
+    for SinsemillaChip<Hash, Commit, Fixed, Lookup, C, K>
 where
-    Hash: HashDomains<pallas::Affine>,
-    Fixed: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, Fixed, Hash>,
-    Lookup: PallasLookupRangeCheck,
+    Hash: HashDomains<C>,
+    Fixed: FixedPoints<C>,
+    Commit: CommitDomains<C, Fixed, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
-    type Config = SinsemillaConfig<Hash, Commit, Fixed, Lookup>;
+    type Config = SinsemillaConfig<Hash, Commit, Fixed, Lookup, C, K>;
     type Loaded = ();
 
     fn config(&self) -> &Self::Config {
@@ -135,25 +214,31 @@ where
     }
 }
 
-impl<Hash, Commit, F, Lookup> SinsemillaChip<Hash, Commit, F, Lookup>
+impl<Hash, Commit, F, Lookup, C, const K: usize> SinsemillaChip<Hash, Commit, F, Lookup, C, K>
 where
-    Hash: HashDomains<pallas::Affine>,
-    F: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, F, Hash>,
-    Lookup: PallasLookupRangeCheck,
+    Hash: HashDomains<C>,
+    F: FixedPoints<C>,
+    Commit: CommitDomains<C, F, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
     /// Reconstructs this chip from the given config.
-    pub fn construct(config: <Self as Chip<pallas::Base>>::Config) -> Self {
+    pub fn construct(config: <Self as Chip<C::Base>>::Config) -> Self {
         Self { config }
     }
 
     /// Loads the lookup table required by this chip into the circuit.
+    ///
+    /// The generator table has $2^K$ rows (plus any rows added by `Lookup`), which must
+    /// fit in the usable rows of the circuit.
     pub fn load(
-        config: SinsemillaConfig<Hash, Commit, F, Lookup>,
-        layouter: &mut impl Layouter<pallas::Base>,
-    ) -> Result<<Self as Chip<pallas::Base>>::Loaded, Error> {
+        config: SinsemillaConfig<Hash, Commit, F, Lookup, C, K>,
+        layouter: &mut impl Layouter<C::Base>,
+    ) -> Result<<Self as Chip<C::Base>>::Loaded, Error> {
         // Load the lookup table.
-        config.generator_table.load(config.lookup_config, layouter)
+        config
+            .generator_table
+            .load::<C, K>(config.lookup_config, layouter)
     }
 
     /// Creates the Sinsemilla chip
@@ -168,20 +253,20 @@ where
     #[allow(clippy::too_many_arguments)]
     #[allow(non_snake_case)]
     pub fn configure(
-        meta: &mut ConstraintSystem<pallas::Base>,
+        meta: &mut ConstraintSystem<C::Base>,
         advices: [Column<Advice>; 5],
         witness_pieces: Column<Advice>,
         fixed_y_q: Column<Fixed>,
         lookup: (TableColumn, TableColumn, TableColumn),
         range_check: Lookup,
         allow_init_from_private_point: bool,
-    ) -> <Self as Chip<pallas::Base>>::Config {
+    ) -> <Self as Chip<C::Base>>::Config {
         // Enable equality on all advice columns
         for advice in advices.iter() {
             meta.enable_equality(*advice);
         }
 
-        let config = SinsemillaConfig::<Hash, Commit, F, Lookup> {
+        let config = SinsemillaConfig::<Hash, Commit, F, Lookup, C, K> {
             q_sinsemilla1: meta.complex_selector(),
             q_sinsemilla2: meta.fixed_column(),
             q_sinsemilla4: meta.selector(),
@@ -207,18 +292,16 @@ where
         // Set up lookup argument
         GeneratorTableConfig::configure(meta, config.clone());
 
-        let two = pallas::Base::from(2);
+        let two = C::Base::from(2);
 
         // Closures for expressions that are derived multiple times
         // x_r = lambda_1^2 - x_a - x_p
-        let x_r = |meta: &mut VirtualCells<pallas::Base>, rotation| {
-            config.double_and_add.x_r(meta, rotation)
-        };
+        let x_r =
+            |meta: &mut VirtualCells<C::Base>, rotation| config.double_and_add.x_r(meta, rotation);
 
         // Y_A = (lambda_1 + lambda_2) * (x_a - x_r)
-        let Y_A = |meta: &mut VirtualCells<pallas::Base>, rotation| {
-            config.double_and_add.Y_A(meta, rotation)
-        };
+        let Y_A =
+            |meta: &mut VirtualCells<C::Base>, rotation| config.double_and_add.Y_A(meta, rotation);
 
         // Check that the initial x_A, x_P, lambda_1, lambda_2 are consistent with y_Q.
         // https://p.z.cash/halo2-0.1:sinsemilla-constraints?partial
@@ -267,7 +350,7 @@ where
             //    - rhs = (2 * Y_A_cur + (2 - q_s3) * Y_A_next + 2 * q_s3 * y_a_final)
             let y_check = {
                 // lhs = 4 * lambda_2_cur * (x_a_cur - x_a_next)
-                let lhs = lambda_2_cur * pallas::Base::from(4) * (x_a_cur - x_a_next);
+                let lhs = lambda_2_cur * C::Base::from(4) * (x_a_cur - x_a_next);
 
                 // rhs = 2 * Y_A_cur + (2 - q_s3) * Y_A_next + 2 * q_s3 * y_a_final
                 let rhs = {
@@ -289,24 +372,24 @@ where
 }
 
 // Implement `SinsemillaInstructions` for `SinsemillaChip`
-impl<Hash, Commit, F, Lookup>
-    SinsemillaInstructions<pallas::Affine, { sinsemilla::K }, { sinsemilla::C }>
-    for SinsemillaChip<Hash, Commit, F, Lookup>
+impl<Hash, Commit, F, Lookup, C, const K: usize> SinsemillaInstructions<C, K, { sinsemilla::C }>
+    for SinsemillaChip<Hash, Commit, F, Lookup, C, K>
 where
-    Hash: HashDomains<pallas::Affine>,
-    F: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, F, Hash>,
-    Lookup: PallasLookupRangeCheck,
+    Hash: HashDomains<C>,
+    F: FixedPoints<C>,
+    Commit: CommitDomains<C, F, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
-    type CellValue = AssignedCell<pallas::Base, pallas::Base>;
+    type CellValue = AssignedCell<C::Base, C::Base>;
 
-    type Message = Message<pallas::Base, { sinsemilla::K }, { sinsemilla::C }>;
-    type MessagePiece = MessagePiece<pallas::Base, { sinsemilla::K }>;
+    type Message = Message<C::Base, K, { sinsemilla::C }>;
+    type MessagePiece = MessagePiece<C::Base, K>;
 
     type RunningSum = Vec<Self::CellValue>;
 
-    type X = AssignedCell<pallas::Base, pallas::Base>;
-    type NonIdentityPoint = NonIdentityEccPoint;
+    type X = AssignedCell<C::Base, C::Base>;
+    type NonIdentityPoint = NonIdentityEccPoint<C>;
     type FixedPoints = F;
 
     type HashDomains = Hash;
@@ -314,8 +397,8 @@ where
 
     fn witness_message_piece(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        field_elem: Value<pallas::Base>,
+        mut layouter: impl Layouter<C::Base>,
+        field_elem: Value<C::Base>,
         num_words: usize,
     ) -> Result<Self::MessagePiece, Error> {
         let config = self.config().clone();
@@ -338,8 +421,8 @@ where
     #[allow(clippy::type_complexity)]
     fn hash_to_point(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
-        Q: pallas::Affine,
+        mut layouter: impl Layouter<C::Base>,
+        Q: C,
         message: Self::Message,
     ) -> Result<(Self::NonIdentityPoint, Vec<Self::RunningSum>), Error> {
         layouter.assign_region(
@@ -352,7 +435,7 @@ where
     #[allow(clippy::type_complexity)]
     fn hash_to_point_with_private_init(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
+        mut layouter: impl Layouter<C::Base>,
         Q: &Self::NonIdentityPoint,
         message: Self::Message,
     ) -> Result<(Self::NonIdentityPoint, Vec<Self::RunningSum>), Error> {
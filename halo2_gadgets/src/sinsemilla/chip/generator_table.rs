@@ -1,17 +1,12 @@
-use ff::PrimeFieldBits;
-use group::ff::PrimeField;
+use group::ff::{Field, PrimeField};
 use halo2_proofs::{
     circuit::Layouter,
     plonk::{ConstraintSystem, Error, Expression, TableColumn},
     poly::Rotation,
 };
 
-use super::{CommitDomains, FixedPoints, HashDomains};
-use crate::{
-    sinsemilla::primitives::{self as sinsemilla, SINSEMILLA_S},
-    utilities::lookup_range_check::{LookupRangeCheck, PallasLookupRangeCheck},
-};
-use pasta_curves::pallas;
+use super::{CommitDomains, FixedPoints, HashDomains, SinsemillaCurve};
+use crate::utilities::lookup_range_check::LookupRangeCheck;
 
 /// Table containing independent generators S[0..2^k]
 #[derive(Eq, PartialEq, Copy, Clone, Debug)]
@@ -27,14 +22,15 @@ impl GeneratorTableConfig {
     /// Even though the lookup table can be used in other parts of the circuit,
     /// this specific configuration sets up Sinsemilla-specific constraints
     /// controlled by `q_sinsemilla`, and would likely not apply to other chips.
-    pub fn configure<Hash, Commit, F, Lookup>(
-        meta: &mut ConstraintSystem<pallas::Base>,
-        config: super::SinsemillaConfig<Hash, Commit, F, Lookup>,
+    pub fn configure<Hash, Commit, F, Lookup, C, const K: usize>(
+        meta: &mut ConstraintSystem<C::Base>,
+        config: super::SinsemillaConfig<Hash, Commit, F, Lookup, C, K>,
     ) where
-        Hash: HashDomains<pallas::Affine>,
-        F: FixedPoints<pallas::Affine>,
-        Commit: CommitDomains<pallas::Affine, F, Hash>,
-        Lookup: PallasLookupRangeCheck,
+        Hash: HashDomains<C>,
+        F: FixedPoints<C>,
+        Commit: CommitDomains<C, F, Hash>,
+        Lookup: LookupRangeCheck<C::NativeField, K>,
+        C: SinsemillaCurve,
     {
         let (table_idx, table_x, table_y) = (
             config.generator_table.table_idx,
@@ -55,7 +51,7 @@ impl GeneratorTableConfig {
             let word = {
                 let z_cur = meta.query_advice(config.bits, Rotation::cur());
                 let z_next = meta.query_advice(config.bits, Rotation::next());
-                z_cur - (q_run * z_next * pallas::Base::from(1 << sinsemilla::K))
+                z_cur - (q_run * z_next * C::Base::from(1 << K))
             };
 
             let x_p = meta.query_advice(config.double_and_add.x_p, Rotation::cur());
@@ -66,13 +62,13 @@ impl GeneratorTableConfig {
                 let x_a = meta.query_advice(config.double_and_add.x_a, Rotation::cur());
                 let Y_A = config.double_and_add.Y_A(meta, Rotation::cur());
 
-                (Y_A * pallas::Base::TWO_INV) - (lambda1 * (x_a - x_p.clone()))
+                (Y_A * C::Base::TWO_INV) - (lambda1 * (x_a - x_p.clone()))
             };
 
             // Lookup expressions default to the first entry when `q_s1`
             // is not enabled.
-            let (init_x, init_y) = SINSEMILLA_S[0];
-            let not_q_s1 = Expression::Constant(pallas::Base::one()) - q_s1.clone();
+            let (init_x, init_y) = C::sinsemilla_generator(0);
+            let not_q_s1 = Expression::Constant(C::Base::ONE) - q_s1.clone();
 
             let m = q_s1.clone() * word; // The first table index is 0.
             let x_p = q_s1.clone() * x_p + not_q_s1.clone() * init_x;
@@ -83,14 +79,11 @@ impl GeneratorTableConfig {
     }
 
     /// Load the generator table into the circuit.
-    pub fn load<F, const K: usize>(
+    pub fn load<C: SinsemillaCurve, const K: usize>(
         &self,
-        lookup_config: impl LookupRangeCheck<F, K>,
-        layouter: &mut impl Layouter<pallas::Base>,
-    ) -> Result<(), Error>
-    where
-        F: PrimeFieldBits,
-    {
-        lookup_config.load(self, layouter)
+        lookup_config: impl LookupRangeCheck<C::NativeField, K>,
+        layouter: &mut impl Layouter<C::Base>,
+    ) -> Result<(), Error> {
+        lookup_config.load(self, &C::generators::<K>(), layouter)
     }
 }
@@ -1,9 +1,9 @@
 use super::super::{CommitDomains, HashDomains, SinsemillaInstructions};
-use super::{NonIdentityEccPoint, SinsemillaChip};
+use super::{NonIdentityEccPoint, SinsemillaChip, SinsemillaCurve};
 use crate::{
     ecc::FixedPoints,
-    sinsemilla::primitives::{self as sinsemilla, lebs2ip_k, INV_TWO_POW_K, SINSEMILLA_S},
-    utilities::lookup_range_check::PallasLookupRangeCheck,
+    sinsemilla::primitives as sinsemilla,
+    utilities::{lebs2ip, lookup_range_check::LookupRangeCheck},
 };
 
 use ff::Field;
@@ -12,41 +12,38 @@ use halo2_proofs::{
     plonk::{Assigned, Error},
 };
 
-use group::ff::{PrimeField, PrimeFieldBits};
-use pasta_curves::{arithmetic::CurveAffine, pallas};
+use group::ff::PrimeFieldBits;
 
 use std::ops::Deref;
 
 /// `EccPointQ` can hold either a public or a private ECC Point
 #[cfg(test)]
-enum EccPointQ {
-    PublicPoint(pallas::Affine),
-    PrivatePoint(NonIdentityEccPoint),
+enum EccPointQ<C: SinsemillaCurve> {
+    PublicPoint(C),
+    PrivatePoint(NonIdentityEccPoint<C>),
 }
 
-impl<Hash, Commit, Fixed, Lookup> SinsemillaChip<Hash, Commit, Fixed, Lookup>
+impl<Hash, Commit, Fixed, Lookup, C, const K: usize>
+    SinsemillaChip<Hash, Commit, Fixed, Lookup, C, K>
 where
-    Hash: HashDomains<pallas::Affine>,
-    Fixed: FixedPoints<pallas::Affine>,
-    Commit: CommitDomains<pallas::Affine, Fixed, Hash>,
-    Lookup: PallasLookupRangeCheck,
+    Hash: HashDomains<C>,
+    Fixed: FixedPoints<C>,
+    Commit: CommitDomains<C, Fixed, Hash>,
+    Lookup: LookupRangeCheck<C::NativeField, K>,
+    C: SinsemillaCurve,
 {
     /// [Specification](https://p.z.cash/halo2-0.1:sinsemilla-constraints?partial).
     #[allow(non_snake_case)]
     #[allow(clippy::type_complexity)]
     pub(super) fn hash_message(
         &self,
-        region: &mut Region<'_, pallas::Base>,
-        Q: pallas::Affine,
-        message: &<Self as SinsemillaInstructions<
-            pallas::Affine,
-            { sinsemilla::K },
-            { sinsemilla::C },
-        >>::Message,
+        region: &mut Region<'_, C::Base>,
+        Q: C,
+        message: &<Self as SinsemillaInstructions<C, K, { sinsemilla::C }>>::Message,
     ) -> Result<
         (
-            NonIdentityEccPoint,
-            Vec<Vec<AssignedCell<pallas::Base, pallas::Base>>>,
+            NonIdentityEccPoint<C>,
+            Vec<Vec<AssignedCell<C::Base, C::Base>>>,
         ),
         Error,
     > {
@@ -71,17 +68,13 @@ where
     #[allow(clippy::type_complexity)]
     pub(super) fn hash_message_with_private_init(
         &self,
-        region: &mut Region<'_, pallas::Base>,
-        Q: &NonIdentityEccPoint,
-        message: &<Self as SinsemillaInstructions<
-            pallas::Affine,
-            { sinsemilla::K },
-            { sinsemilla::C },
-        >>::Message,
+        region: &mut Region<'_, C::Base>,
+        Q: &NonIdentityEccPoint<C>,
+        message: &<Self as SinsemillaInstructions<C, K, { sinsemilla::C }>>::Message,
     ) -> Result<
         (
-            NonIdentityEccPoint,
-            Vec<Vec<AssignedCell<pallas::Base, pallas::Base>>>,
+            NonIdentityEccPoint<C>,
+            Vec<Vec<AssignedCell<C::Base, C::Base>>>,
         ),
         Error,
     > {
@@ -121,9 +114,9 @@ where
     /// |   1    | x_Q |     |         1     |
     fn public_q_initialization(
         &self,
-        region: &mut Region<'_, pallas::Base>,
-        Q: pallas::Affine,
-    ) -> Result<(usize, X<pallas::Base>, Y<pallas::Base>), Error> {
+        region: &mut Region<'_, C::Base>,
+        Q: C,
+    ) -> Result<InitialQ<C::Base>, Error> {
         let config = self.config().clone();
         let mut offset = 0;
 
@@ -133,10 +126,10 @@ where
 
         // Constrain the initial x_a, lambda_1, lambda_2, x_p using the q_sinsemilla4
         // selector.
-        let y_a: Y<pallas::Base> = if config.allow_init_from_private_point {
+        let y_a: Y<C::Base> = if config.allow_init_from_private_point {
             // Enable `q_sinsemilla4` on the second row.
             config.q_sinsemilla4.enable(region, offset + 1)?;
-            let y_a: AssignedCell<Assigned<pallas::Base>, pallas::Base> = region
+            let y_a: AssignedCell<Assigned<C::Base>, C::Base> = region
                 .assign_advice_from_constant(
                     || "variable y_q",
                     config.double_and_add.x_p,
@@ -159,7 +152,7 @@ where
         };
 
         // Constrain the initial x_q to equal the x-coordinate of the domain's `Q`.
-        let x_a: X<pallas::Base> = {
+        let x_a: X<C::Base> = {
             let x_a = region.assign_advice_from_constant(
                 || "variable x_q",
                 config.double_and_add.x_a,
@@ -182,9 +175,9 @@ where
     /// |   1    | x_Q |     |         1     |
     fn private_q_initialization(
         &self,
-        region: &mut Region<'_, pallas::Base>,
-        Q: &NonIdentityEccPoint,
-    ) -> Result<(usize, X<pallas::Base>, Y<pallas::Base>), Error> {
+        region: &mut Region<'_, C::Base>,
+        Q: &NonIdentityEccPoint<C>,
+    ) -> Result<InitialQ<C::Base>, Error> {
         let config = self.config().clone();
 
         if !config.allow_init_from_private_point {
@@ -193,18 +186,18 @@ where
 
         // Assign `x_Q` and `y_Q` in the region and constrain the initial x_a, lambda_1, lambda_2,
         // x_p, y_Q using the q_sinsemilla4 selector.
-        let y_a: Y<pallas::Base> = {
+        let y_a: Y<C::Base> = {
             // Enable `q_sinsemilla4` on the second row.
             config.q_sinsemilla4.enable(region, 1)?;
-            let q_y: AssignedCell<Assigned<pallas::Base>, pallas::Base> = Q.y().into();
-            let y_a: AssignedCell<Assigned<pallas::Base>, pallas::Base> =
+            let q_y: AssignedCell<Assigned<C::Base>, C::Base> = Q.y().into();
+            let y_a: AssignedCell<Assigned<C::Base>, C::Base> =
                 q_y.copy_advice(|| "fixed y_q", region, config.double_and_add.x_p, 0)?;
 
             y_a.value_field().into()
         };
 
-        let x_a: X<pallas::Base> = {
-            let q_x: AssignedCell<Assigned<pallas::Base>, pallas::Base> = Q.x().into();
+        let x_a: X<C::Base> = {
+            let q_x: AssignedCell<Assigned<C::Base>, C::Base> = Q.x().into();
             let x_a = q_x.copy_advice(|| "fixed x_q", region, config.double_and_add.x_a, 1)?;
 
             x_a.into()
@@ -217,26 +210,22 @@ where
     /// Hash `message` from the initial point `Q`.
     fn hash_all_pieces(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         mut offset: usize,
-        message: &<Self as SinsemillaInstructions<
-            pallas::Affine,
-            { sinsemilla::K },
-            { sinsemilla::C },
-        >>::Message,
-        mut x_a: X<pallas::Base>,
-        mut y_a: Y<pallas::Base>,
+        message: &<Self as SinsemillaInstructions<C, K, { sinsemilla::C }>>::Message,
+        mut x_a: X<C::Base>,
+        mut y_a: Y<C::Base>,
     ) -> Result<
         (
-            X<pallas::Base>,
-            AssignedCell<Assigned<pallas::Base>, pallas::Base>,
-            Vec<Vec<AssignedCell<pallas::Base, pallas::Base>>>,
+            X<C::Base>,
+            AssignedCell<Assigned<C::Base>, C::Base>,
+            Vec<Vec<AssignedCell<C::Base, C::Base>>>,
         ),
         Error,
     > {
         let config = self.config().clone();
 
-        let mut zs_sum: Vec<Vec<AssignedCell<pallas::Base, pallas::Base>>> = Vec::new();
+        let mut zs_sum: Vec<Vec<AssignedCell<C::Base, C::Base>>> = Vec::new();
 
         // Hash each piece in the message.
         for (idx, piece) in message.iter().enumerate() {
@@ -269,13 +258,13 @@ where
                     || "dummy lambda2",
                     config.double_and_add.lambda_2,
                     offset,
-                    || Value::known(pallas::Base::zero()),
+                    || Value::known(C::Base::ZERO),
                 )?;
                 region.assign_advice(
                     || "dummy x_p",
                     config.double_and_add.x_p,
                     offset,
-                    || Value::known(pallas::Base::zero()),
+                    || Value::known(C::Base::ZERO),
                 )?;
             }
 
@@ -294,24 +283,13 @@ where
     /// already assigned within this region at the correct offset.
     fn hash_piece(
         &self,
-        region: &mut Region<'_, pallas::Base>,
+        region: &mut Region<'_, C::Base>,
         offset: usize,
-        piece: &<Self as SinsemillaInstructions<
-            pallas::Affine,
-            { sinsemilla::K },
-            { sinsemilla::C },
-        >>::MessagePiece,
-        mut x_a: X<pallas::Base>,
-        mut y_a: Y<pallas::Base>,
+        piece: &<Self as SinsemillaInstructions<C, K, { sinsemilla::C }>>::MessagePiece,
+        mut x_a: X<C::Base>,
+        mut y_a: Y<C::Base>,
         final_piece: bool,
-    ) -> Result<
-        (
-            X<pallas::Base>,
-            Y<pallas::Base>,
-            Vec<AssignedCell<pallas::Base, pallas::Base>>,
-        ),
-        Error,
-    > {
+    ) -> Result<(X<C::Base>, Y<C::Base>, Vec<AssignedCell<C::Base, C::Base>>), Error> {
         let config = self.config().clone();
 
         // Selector assignments
@@ -327,7 +305,7 @@ where
                     || "q_s2 = 1",
                     config.q_sinsemilla2,
                     offset + row,
-                    || Value::known(pallas::Base::one()),
+                    || Value::known(C::Base::ONE),
                 )?;
             }
 
@@ -345,9 +323,9 @@ where
                 offset + piece.num_words() - 1,
                 || {
                     Value::known(if final_piece {
-                        pallas::Base::from(2)
+                        C::Base::from(2)
                     } else {
-                        pallas::Base::zero()
+                        C::Base::ZERO
                     })
                 },
             )?;
@@ -358,23 +336,22 @@ where
             value
                 .to_le_bits()
                 .into_iter()
-                .take(sinsemilla::K * piece.num_words())
+                .take(K * piece.num_words())
                 .collect()
         });
 
         let words: Value<Vec<u32>> = bitstring.map(|bitstring| {
             bitstring
-                .chunks_exact(sinsemilla::K)
-                .map(|chunk| chunk.try_into().expect("correct length"))
-                .map(lebs2ip_k)
+                .chunks_exact(K)
+                .map(|chunk| lebs2ip::<K>(chunk.try_into().expect("correct length")) as u32)
                 .collect()
         });
 
         // Get (x_p, y_p) for each word.
-        let generators: Value<Vec<(pallas::Base, pallas::Base)>> = words.clone().map(|words| {
+        let generators: Value<Vec<(C::Base, C::Base)>> = words.clone().map(|words| {
             words
                 .iter()
-                .map(|word| SINSEMILLA_S[*word as usize])
+                .map(|word| C::sinsemilla_generator(*word))
                 .collect()
         });
 
@@ -402,11 +379,11 @@ where
             // We end up with z_n = 0. (z_n is not directly encoded as a cell value;
             // it is implicitly taken as 0 by adjusting the definition of m_{i+1}.)
             let mut z = piece.field_elem();
-            let inv_2_k = Value::known(pallas::Base::from_repr(INV_TWO_POW_K).unwrap());
+            let inv_2_k = Value::known(C::Base::from(1 << K).invert().unwrap());
 
             // We do not assign the final z_n as it is constrained to be zero.
             for (idx, word) in words[0..(words.len() - 1)].iter().enumerate() {
-                let word = word.map(|word| pallas::Base::from(word as u64));
+                let word = word.map(|word| C::Base::from(word as u64));
                 // z_{i + 1} = (z_i - m_{i + 1}) / 2^K
                 z = (z - word) * inv_2_k;
                 let cell = region.assign_advice(
@@ -453,8 +430,7 @@ where
 
             // Compute and assign `lambda_2`
             let lambda_2 = {
-                let lambda_2 =
-                    y_a.0 * pallas::Base::from(2) * (x_a.value() - x_r).invert() - lambda_1;
+                let lambda_2 = y_a.0 * C::Base::from(2) * (x_a.value() - x_r).invert() - lambda_1;
 
                 region.assign_advice(
                     || "lambda_2",
@@ -467,7 +443,7 @@ where
             };
 
             // Compute and assign `x_a` for the next row.
-            let x_a_new: X<pallas::Base> = {
+            let x_a_new: X<C::Base> = {
                 let x_a_new = lambda_2.square() - x_a.value() - x_r;
 
                 let x_a_cell = region.assign_advice(
@@ -481,8 +457,7 @@ where
             };
 
             // Compute y_a for the next row.
-            let y_a_new: Y<pallas::Base> =
-                (lambda_2 * (x_a.value() - x_a_new.value()) - y_a.0).into();
+            let y_a_new: Y<C::Base> = (lambda_2 * (x_a.value() - x_a_new.value()) - y_a.0).into();
 
             // Update the mutable `x_a`, `y_a` variables.
             x_a = x_a_new;
@@ -496,20 +471,16 @@ where
     #[allow(non_snake_case)]
     fn check_hash_result(
         &self,
-        Q: EccPointQ,
-        message: &<Self as SinsemillaInstructions<
-            pallas::Affine,
-            { sinsemilla::K },
-            { sinsemilla::C },
-        >>::Message,
-        x_a: &X<pallas::Base>,
-        y_a: &AssignedCell<Assigned<pallas::Base>, pallas::Base>,
+        Q: EccPointQ<C>,
+        message: &<Self as SinsemillaInstructions<C, K, { sinsemilla::C }>>::Message,
+        x_a: &X<C::Base>,
+        y_a: &AssignedCell<Assigned<C::Base>, C::Base>,
     ) {
         // Check equivalence to result from primitives::sinsemilla::hash_to_point
         {
-            use crate::sinsemilla::primitives::{K, S_PERSONALIZATION};
+            use crate::sinsemilla::primitives::S_PERSONALIZATION;
 
-            use group::{prime::PrimeCurveAffine, Curve};
+            use group::Curve;
             use pasta_curves::arithmetic::CurveExt;
 
             let field_elems: Value<Vec<_>> = message
@@ -534,11 +505,10 @@ where
                         })
                         .collect();
 
-                    let hasher_S = pallas::Point::hash_to_curve(S_PERSONALIZATION);
+                    let hasher_S = C::CurveExt::hash_to_curve(S_PERSONALIZATION);
                     let S = |chunk: &[bool]| {
-                        hasher_S(
-                            &lebs2ip_k(chunk.try_into().expect("correct length")).to_le_bytes(),
-                        )
+                        let word = lebs2ip::<K>(chunk.try_into().expect("correct length")) as u32;
+                        hasher_S(&word.to_le_bytes())
                     };
 
                     // We can use complete addition here because it differs from
@@ -546,14 +516,17 @@ where
                     let expected_point = bitstring
                         .chunks(K)
                         .fold(value_Q.to_curve(), |acc, chunk| (acc + S(chunk)) + acc);
-                    let actual_point =
-                        pallas::Affine::from_xy(x_a.evaluate(), y_a.evaluate()).unwrap();
+                    let actual_point = C::from_xy(x_a.evaluate(), y_a.evaluate()).unwrap();
                     expected_point.to_affine() == actual_point
                 });
         }
     }
 }
 
+/// The offset of the first hash row, and the initial x- and y-coordinates of the
+/// accumulator.
+type InitialQ<F> = (usize, X<F>, Y<F>);
+
 /// The x-coordinate of the accumulator in a Sinsemilla hash instance.
 struct X<F: Field>(AssignedCell<Assigned<F>, F>);
 
@@ -1,16 +1,23 @@
 //! Gadgets for implementing a Merkle tree with Sinsemilla.
+//!
+//! The [`MerkleHashPath`] gadget generalises [`MerklePath`] to any hash implementing
+//! [`MerkleHashInstructions`], trees of any arity, and paths of witnessed depth.
 
+use group::ff::Field;
 use halo2_proofs::{
     circuit::{Chip, Layouter, Value},
-    plonk::Error,
+    plonk::{Advice, Column, Error},
 };
 use pasta_curves::arithmetic::CurveAffine;
+use std::{convert::TryInto, fmt};
 
 use super::{HashDomains, SinsemillaInstructions};
 
 use crate::utilities::{cond_swap::CondSwapInstructions, i2lebsp, UtilitiesInstructions};
 
 pub mod chip;
+pub mod depth;
+pub mod sparse;
 
 /// SWU hash-to-curve personalization for the Merkle CRH generator
 pub const MERKLE_CRH_PERSONALIZATION: &str = "z.cash:Orchard-MerkleCRH";
@@ -43,6 +50,41 @@ pub trait MerkleInstructions<
     ) -> Result<Self::Var, Error>;
 }
 
+/// Instructions for the hash function used to compute the nodes of a Merkle tree in
+/// which every internal node has `ARITY` children.
+pub trait MerkleHashInstructions<F: Field, const ARITY: usize>:
+    CondSwapInstructions<F> + UtilitiesInstructions<F> + Chip<F>
+{
+    /// Runtime domain separation for the hash, such as a Sinsemilla hash domain.
+    /// Hashes without runtime domain separation use `()`.
+    type MerkleDomain: Clone + fmt::Debug;
+
+    /// Computes a node at layer `l` from its `ARITY` children, ordered by position.
+    /// The hashes that are applied to leaves are at layer `l = 0`.
+    fn hash_children(
+        &self,
+        layouter: impl Layouter<F>,
+        domain: &Self::MerkleDomain,
+        l: usize,
+        children: [Self::Var; ARITY],
+    ) -> Result<Self::Var, Error>;
+}
+
+/// Instructions to select the root of a Merkle path with a witnessed depth.
+pub trait MerkleDepthInstructions<F: Field>: UtilitiesInstructions<F> + Chip<F> {
+    /// Returns `nodes[depth]`.
+    ///
+    /// `nodes` are the nodes on a Merkle path, ordered from the leaf (`nodes[0]`)
+    /// towards the root. The circuit is unsatisfiable unless `depth` is less than
+    /// `nodes.len()`.
+    fn select_root(
+        &self,
+        layouter: impl Layouter<F>,
+        depth: &Self::Var,
+        nodes: &[Self::Var],
+    ) -> Result<Self::Var, Error>;
+}
+
 /// Gadget representing a Merkle path that proves a leaf exists in a Merkle tree at a
 /// specific position.
 #[derive(Clone, Debug)]
@@ -170,12 +212,416 @@ where
     }
 }
 
+/// Gadget representing a Merkle path in a tree of arity `ARITY`, that proves a leaf
+/// exists in the tree at a specific position.
+///
+/// Unlike [`MerklePath`], this gadget is generic over the hash function used to compute
+/// the nodes of the tree, and supports paths whose depth is witnessed in the circuit
+/// (see [`MerkleHashPath::calculate_root_at_depth`]).
+#[derive(Clone, Debug)]
+pub struct MerkleHashPath<
+    F: Field,
+    MerkleChip,
+    const PATH_LENGTH: usize,
+    const ARITY: usize,
+    const PAR: usize,
+> where
+    MerkleChip: MerkleHashInstructions<F, ARITY> + Clone,
+{
+    chips: [MerkleChip; PAR],
+    domain: MerkleChip::MerkleDomain,
+    leaf_pos: Value<u64>,
+    // The Merkle path is ordered from leaves to root. Each entry holds the children of
+    // the parent of the path node at that layer; the child at the position of the path
+    // node itself is ignored, and replaced by the node computed in the circuit.
+    path: Value<[[F; ARITY]; PATH_LENGTH]>,
+}
+
+impl<F: Field, MerkleChip, const PATH_LENGTH: usize, const ARITY: usize, const PAR: usize>
+    MerkleHashPath<F, MerkleChip, PATH_LENGTH, ARITY, PAR>
+where
+    MerkleChip: MerkleHashInstructions<F, ARITY> + Clone,
+{
+    /// Constructs a [`MerkleHashPath`].
+    ///
+    /// `leaf_pos` is the position of the leaf, whose base-`ARITY` digits (least
+    /// significant first) give the position of the path node among its siblings at each
+    /// layer.
+    ///
+    /// As for [`MerklePath`], the path hashing is distributed across each `MerkleChip` in
+    /// `chips`, such that each chip processes `ceil(PATH_LENGTH / PAR)` layers.
+    pub fn construct(
+        chips: [MerkleChip; PAR],
+        domain: MerkleChip::MerkleDomain,
+        leaf_pos: Value<u64>,
+        path: Value<[[F; ARITY]; PATH_LENGTH]>,
+    ) -> Self {
+        assert_ne!(PAR, 0);
+        assert!(ARITY >= 2);
+        Self {
+            chips,
+            domain,
+            leaf_pos,
+            path,
+        }
+    }
+
+    /// Calculates the root of the tree containing the given leaf at this Merkle path.
+    pub fn calculate_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaf: MerkleChip::Var,
+    ) -> Result<MerkleChip::Var, Error> {
+        let mut nodes = self.calculate_nodes(&mut layouter, leaf)?;
+        Ok(nodes.pop().expect("path contains the leaf"))
+    }
+
+    /// Calculates the root of a tree of the given `depth` containing the given leaf at
+    /// this Merkle path.
+    ///
+    /// Only the first `depth` layers of the path are significant; the remaining children
+    /// and position digits may take arbitrary values. `depth` is an assigned cell, so
+    /// that the caller decides how it is constrained (for example, against a public
+    /// input); the circuit is unsatisfiable unless it is at most `PATH_LENGTH`.
+    pub fn calculate_root_at_depth<DepthChip>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        depth_chip: &DepthChip,
+        depth: &MerkleChip::Var,
+        leaf: MerkleChip::Var,
+    ) -> Result<MerkleChip::Var, Error>
+    where
+        DepthChip: MerkleDepthInstructions<F, Var = MerkleChip::Var>,
+    {
+        let nodes = self.calculate_nodes(&mut layouter, leaf)?;
+        depth_chip.select_root(layouter.namespace(|| "select root"), depth, &nodes)
+    }
+
+    /// Returns the nodes on this Merkle path, from the leaf to the root.
+    fn calculate_nodes(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        leaf: MerkleChip::Var,
+    ) -> Result<Vec<MerkleChip::Var>, Error> {
+        // Each chip processes `ceil(PATH_LENGTH / PAR)` layers.
+        let layers_per_chip = (PATH_LENGTH + PAR - 1) / PAR;
+
+        // Assign each layer to a chip.
+        let chips = (0..PATH_LENGTH).map(|i| self.chips[i / layers_per_chip].clone());
+
+        let path = self.path.transpose_array();
+
+        // Get position as PATH_LENGTH base-ARITY digits (least significant first).
+        let pos: [Value<usize>; PATH_LENGTH] = {
+            let pos: Value<[usize; PATH_LENGTH]> = self.leaf_pos.map(|mut pos| {
+                let digits: Vec<_> = (0..PATH_LENGTH)
+                    .map(|_| {
+                        let digit = pos % ARITY as u64;
+                        pos /= ARITY as u64;
+                        digit as usize
+                    })
+                    .collect();
+                digits.try_into().unwrap()
+            });
+            pos.transpose_array()
+        };
+
+        let mut nodes = Vec::with_capacity(PATH_LENGTH + 1);
+        nodes.push(leaf);
+        for (l, ((children, pos), chip)) in path.iter().zip(pos.iter()).zip(chips).enumerate() {
+            // Place the node among its `ARITY - 1` siblings with a chain of conditional
+            // swaps. The node is carried along while `j < pos`, and lands in position
+            // `pos`; each subsequent swap then carries the previous sibling along by one
+            // position. For `ARITY = 2` this is the single swap used by `MerklePath`.
+            let mut carry = nodes.last().expect("nodes is non-empty").clone();
+            let mut inputs = Vec::with_capacity(ARITY);
+            for j in 0..(ARITY - 1) {
+                let sibling = children.zip(*pos).map(|(children, pos)| {
+                    if j < pos {
+                        children[j]
+                    } else {
+                        children[j + 1]
+                    }
+                });
+                let (child, next) = chip.swap(
+                    layouter.namespace(|| format!("node position {}", j)),
+                    (carry, sibling),
+                    pos.map(|pos| j < pos),
+                )?;
+                inputs.push(child);
+                carry = next;
+            }
+            inputs.push(carry);
+
+            let node = chip.hash_children(
+                layouter.namespace(|| format!("hash layer {}", l)),
+                &self.domain,
+                l,
+                inputs.try_into().unwrap(),
+            )?;
+            nodes.push(node);
+        }
+
+        Ok(nodes)
+    }
+}
+
+/// The shape of a Merkle multi-proof, which is determined by the positions of the
+/// leaves being proven.
+#[derive(Clone, Debug)]
+struct MultiProofShape<const ARITY: usize> {
+    // The indices of the leaves, ordered by position.
+    leaf_order: Vec<usize>,
+    // For each layer from the leaves to the root, the position of each distinct parent
+    // together with the source of each of its children: either the index of a node in
+    // the previous layer (ordered by position), or `None` for the next proof sibling.
+    layers: Vec<Vec<(u64, [Option<usize>; ARITY])>>,
+}
+
+impl<const ARITY: usize> MultiProofShape<ARITY> {
+    fn new(positions: &[u64], path_length: usize) -> Self {
+        assert!(ARITY >= 2);
+        assert!(!positions.is_empty());
+        if let Some(num_leaves) = (ARITY as u64).checked_pow(path_length as u32) {
+            assert!(positions.iter().all(|pos| *pos < num_leaves));
+        }
+
+        let mut leaf_order: Vec<usize> = (0..positions.len()).collect();
+        leaf_order.sort_by_key(|i| positions[*i]);
+
+        let mut known: Vec<u64> = leaf_order.iter().map(|i| positions[*i]).collect();
+        assert!(
+            known.windows(2).all(|pair| pair[0] < pair[1]),
+            "leaf positions must be distinct"
+        );
+
+        let layers = (0..path_length)
+            .map(|_| {
+                let mut parents: Vec<u64> = known.iter().map(|pos| pos / ARITY as u64).collect();
+                parents.dedup();
+
+                let layer = parents
+                    .iter()
+                    .map(|parent| {
+                        let mut children = [None; ARITY];
+                        for (c, child) in children.iter_mut().enumerate() {
+                            *child = known
+                                .binary_search(&(parent * ARITY as u64 + c as u64))
+                                .ok();
+                        }
+                        (*parent, children)
+                    })
+                    .collect();
+
+                known = parents;
+                layer
+            })
+            .collect();
+
+        MultiProofShape { leaf_order, layers }
+    }
+
+    fn num_siblings(&self) -> usize {
+        self.layers
+            .iter()
+            .flatten()
+            .flat_map(|(_, children)| children.iter())
+            .filter(|child| child.is_none())
+            .count()
+    }
+}
+
+/// A compact proof that several leaves exist in a Merkle tree of arity `ARITY` and depth
+/// `PATH_LENGTH`.
+///
+/// The proof contains each node needed to recompute the root that is not itself an
+/// ancestor of one of the leaves, exactly once. Siblings are ordered by layer from the
+/// leaves to the root, and by position within each layer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleMultiProof<F: Field, const PATH_LENGTH: usize, const ARITY: usize> {
+    positions: Vec<u64>,
+    siblings: Vec<F>,
+}
+
+impl<F: Field, const PATH_LENGTH: usize, const ARITY: usize>
+    MerkleMultiProof<F, PATH_LENGTH, ARITY>
+{
+    /// Builds the proof for the leaves at the given distinct `positions` of `tree`.
+    ///
+    /// `tree[l]` contains the nodes at layer `l` ordered by position, from the leaves
+    /// (`tree[0]`) to the root (`tree[PATH_LENGTH]`).
+    pub fn from_tree(tree: &[Vec<F>], positions: &[u64]) -> Self {
+        assert_eq!(tree.len(), PATH_LENGTH + 1);
+        let shape = MultiProofShape::<ARITY>::new(positions, PATH_LENGTH);
+
+        let siblings = shape
+            .layers
+            .iter()
+            .zip(tree)
+            .flat_map(|(layer, nodes)| {
+                layer.iter().flat_map(move |(parent, children)| {
+                    children
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, child)| child.is_none())
+                        .map(move |(c, _)| nodes[(parent * ARITY as u64) as usize + c])
+                })
+            })
+            .collect();
+
+        MerkleMultiProof {
+            positions: positions.to_vec(),
+            siblings,
+        }
+    }
+
+    /// Returns the positions of the leaves proven by this proof.
+    pub fn positions(&self) -> &[u64] {
+        &self.positions
+    }
+
+    /// Returns the siblings in this proof.
+    pub fn siblings(&self) -> &[F] {
+        &self.siblings
+    }
+
+    /// Computes the root of the tree from the `leaves` at [`Self::positions`], where
+    /// `hash(l, children)` computes a node at layer `l` from its children.
+    pub fn root(&self, leaves: &[F], mut hash: impl FnMut(usize, [F; ARITY]) -> F) -> F {
+        assert_eq!(leaves.len(), self.positions.len());
+        let shape = MultiProofShape::<ARITY>::new(&self.positions, PATH_LENGTH);
+        assert_eq!(self.siblings.len(), shape.num_siblings());
+
+        let mut siblings = self.siblings.iter();
+        let mut known: Vec<F> = shape.leaf_order.iter().map(|i| leaves[*i]).collect();
+        for (l, layer) in shape.layers.iter().enumerate() {
+            known = layer
+                .iter()
+                .map(|(_, children)| {
+                    let children = children.map(|child| match child {
+                        Some(i) => known[i],
+                        None => *siblings.next().expect("checked number of siblings"),
+                    });
+                    hash(l, children)
+                })
+                .collect();
+        }
+
+        known[0]
+    }
+}
+
+/// Gadget representing a Merkle multi-proof, that proves several leaves exist in the
+/// same Merkle tree of arity `ARITY`.
+///
+/// Each internal node that is an ancestor of one or more of the leaves is computed once,
+/// from the previously computed nodes and the siblings of a [`MerkleMultiProof`].
+///
+/// The positions of the leaves determine which nodes are computed, and are therefore
+/// fixed by the circuit rather than witnessed. Circuits proving leaves at different
+/// positions need a separate verifying key for each set of positions, and the
+/// positions are not hidden: anyone holding the verifying key learns them. To keep the
+/// positions private, prove each leaf with a [`MerkleHashPath`] instead.
+#[derive(Clone, Debug)]
+pub struct MerkleMultiPath<F: Field, MerkleChip, const PATH_LENGTH: usize, const ARITY: usize>
+where
+    MerkleChip: MerkleHashInstructions<F, ARITY> + Clone,
+{
+    chip: MerkleChip,
+    domain: MerkleChip::MerkleDomain,
+    positions: Vec<u64>,
+    siblings: Value<Vec<F>>,
+    advice: Column<Advice>,
+}
+
+impl<F: Field, MerkleChip, const PATH_LENGTH: usize, const ARITY: usize>
+    MerkleMultiPath<F, MerkleChip, PATH_LENGTH, ARITY>
+where
+    MerkleChip: MerkleHashInstructions<F, ARITY> + Clone,
+{
+    /// Constructs a [`MerkleMultiPath`] for the leaves at the given distinct `positions`,
+    /// with the siblings of a [`MerkleMultiProof`].
+    ///
+    /// The siblings are witnessed in the equality-enabled column `advice`.
+    pub fn construct(
+        chip: MerkleChip,
+        domain: MerkleChip::MerkleDomain,
+        positions: Vec<u64>,
+        siblings: Value<Vec<F>>,
+        advice: Column<Advice>,
+    ) -> Self {
+        MerkleMultiPath {
+            chip,
+            domain,
+            positions,
+            siblings,
+            advice,
+        }
+    }
+
+    /// Calculates the root of the tree containing the given leaves, which are ordered
+    /// as the positions given to [`MerkleMultiPath::construct`].
+    ///
+    /// Returns [`Error::Synthesis`] if the number of witnessed siblings does not match
+    /// the positions.
+    pub fn calculate_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        leaves: &[MerkleChip::Var],
+    ) -> Result<MerkleChip::Var, Error> {
+        assert_eq!(leaves.len(), self.positions.len());
+        let shape = MultiProofShape::<ARITY>::new(&self.positions, PATH_LENGTH);
+
+        let num_siblings = shape.num_siblings();
+        self.siblings
+            .as_ref()
+            .error_if_known_and(|siblings| siblings.len() != num_siblings)?;
+        let mut siblings = self
+            .siblings
+            .clone()
+            .transpose_vec(num_siblings)
+            .into_iter();
+        let mut known: Vec<MerkleChip::Var> = shape
+            .leaf_order
+            .iter()
+            .map(|i| leaves[*i].clone())
+            .collect();
+
+        for (l, layer) in shape.layers.iter().enumerate() {
+            let mut parents = Vec::with_capacity(layer.len());
+            for (parent, children) in layer.iter() {
+                let mut inputs = Vec::with_capacity(ARITY);
+                for (c, child) in children.iter().enumerate() {
+                    inputs.push(match child {
+                        Some(i) => known[*i].clone(),
+                        None => self.chip.load_private(
+                            layouter.namespace(|| format!("sibling {} of {}", c, parent)),
+                            self.advice,
+                            siblings.next().expect("checked number of siblings"),
+                        )?,
+                    });
+                }
+
+                parents.push(self.chip.hash_children(
+                    layouter.namespace(|| format!("hash layer {}, node {}", l, parent)),
+                    &self.domain,
+                    l,
+                    inputs.try_into().unwrap(),
+                )?);
+            }
+            known = parents;
+        }
+
+        Ok(known.pop().expect("a tree has one root"))
+    }
+}
+
 #[cfg(test)]
 /// Sinsemilla Merkle tree tests.
 pub mod tests {
     use super::{
         chip::{MerkleChip, MerkleConfig},
-        MerklePath,
+        MerkleHashPath, MerklePath,
     };
 
     use crate::{
@@ -233,7 +679,7 @@ pub mod tests {
     }
 
     #[allow(clippy::type_complexity)]
-    fn configure<Lookup: PallasLookupRangeCheck>(
+    pub(super) fn configure<Lookup: PallasLookupRangeCheck>(
         meta: &mut ConstraintSystem<pallas::Base>,
         allow_init_from_private_point: bool,
     ) -> (
@@ -426,6 +872,80 @@ pub mod tests {
         test_against_stored_circuit(circuit, "merkle_chip", 4160);
     }
 
+    /// Computes the root of the same path with both [`MerklePath`] and
+    /// [`MerkleHashPath`], and constrains them to be equal.
+    struct MyMerkleHashPathCircuit(MyMerkleCircuit<PallasLookupRangeCheckConfig>);
+
+    impl Circuit<pallas::Base> for MyMerkleHashPathCircuit {
+        type Config =
+            <MyMerkleCircuit<PallasLookupRangeCheckConfig> as Circuit<pallas::Base>>::Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyMerkleHashPathCircuit(self.0.without_witnesses())
+        }
+
+        fn configure(meta: &mut ConstraintSystem<pallas::Base>) -> Self::Config {
+            configure::<PallasLookupRangeCheckConfig>(meta, false)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<pallas::Base>,
+        ) -> Result<(), Error> {
+            SinsemillaChip::<
+                TestHashDomain,
+                TestCommitDomain,
+                TestFixedBases,
+                PallasLookupRangeCheckConfig,
+            >::load(config.0.sinsemilla_config.clone(), &mut layouter)?;
+
+            let chip_1 = MerkleChip::construct(config.0.clone());
+            let chip_2 = MerkleChip::construct(config.1.clone());
+
+            let leaf = chip_1.load_private(
+                layouter.namespace(|| ""),
+                config.0.cond_swap_config.a(),
+                self.0.leaf,
+            )?;
+
+            let path = MerklePath {
+                chips: [chip_1.clone(), chip_2.clone()],
+                domain: TestHashDomain,
+                leaf_pos: self.0.leaf_pos,
+                path: self.0.merkle_path,
+            };
+            let root = path.calculate_root(layouter.namespace(|| "root"), leaf.clone())?;
+
+            // The child at the position of the path node is ignored, so we can fill it
+            // with the sibling.
+            let hash_path = MerkleHashPath::<_, _, MERKLE_DEPTH, 2, 2>::construct(
+                [chip_1, chip_2],
+                TestHashDomain,
+                self.0.leaf_pos.map(u64::from),
+                self.0
+                    .merkle_path
+                    .map(|path| path.map(|sibling| [sibling, sibling])),
+            );
+            let hash_root =
+                hash_path.calculate_root(layouter.namespace(|| "hash path root"), leaf)?;
+
+            layouter.assign_region(
+                || "constrain roots",
+                |mut region| region.constrain_equal(root.cell(), hash_root.cell()),
+            )
+        }
+    }
+
+    #[test]
+    fn merkle_hash_path_matches_merkle_path() {
+        let circuit = MyMerkleHashPathCircuit(generate_circuit());
+
+        let prover = MockProver::run(12, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()))
+    }
+
     #[cfg(feature = "test-dev-graph")]
     #[test]
     fn print_merkle_chip() {
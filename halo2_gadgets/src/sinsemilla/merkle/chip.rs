@@ -7,7 +7,7 @@ use halo2_proofs::{
 };
 use pasta_curves::pallas;
 
-use super::MerkleInstructions;
+use super::{MerkleHashInstructions, MerkleInstructions};
 
 use crate::{
     sinsemilla::{primitives as sinsemilla, MessagePiece},
@@ -229,13 +229,55 @@ where
     #[allow(non_snake_case)]
     fn hash_layer(
         &self,
-        mut layouter: impl Layouter<pallas::Base>,
+        layouter: impl Layouter<pallas::Base>,
         Q: pallas::Affine,
         // l = MERKLE_DEPTH - layer - 1
         l: usize,
         left: Self::Var,
         right: Self::Var,
     ) -> Result<Self::Var, Error> {
+        self.merkle_crh(layouter, Q, l, left, right)
+    }
+}
+
+impl<Hash, Commit, Fixed, Lookup> MerkleHashInstructions<pallas::Base, 2>
+    for MerkleChip<Hash, Commit, Fixed, Lookup>
+where
+    Hash: HashDomains<pallas::Affine> + Eq,
+    Fixed: FixedPoints<pallas::Affine>,
+    Commit: CommitDomains<pallas::Affine, Fixed, Hash> + Eq,
+    Lookup: PallasLookupRangeCheck,
+{
+    type MerkleDomain = Hash;
+
+    fn hash_children(
+        &self,
+        layouter: impl Layouter<pallas::Base>,
+        domain: &Hash,
+        l: usize,
+        [left, right]: [Self::Var; 2],
+    ) -> Result<Self::Var, Error> {
+        self.merkle_crh(layouter, domain.Q(), l, left, right)
+    }
+}
+
+impl<Hash, Commit, Fixed, Lookup> MerkleChip<Hash, Commit, Fixed, Lookup>
+where
+    Hash: HashDomains<pallas::Affine> + Eq,
+    Fixed: FixedPoints<pallas::Affine>,
+    Commit: CommitDomains<pallas::Affine, Fixed, Hash> + Eq,
+    Lookup: PallasLookupRangeCheck,
+{
+    /// Computes `MerkleCRH(l, left, right)` with the Sinsemilla hash domain `Q`.
+    #[allow(non_snake_case)]
+    fn merkle_crh(
+        &self,
+        mut layouter: impl Layouter<pallas::Base>,
+        Q: pallas::Affine,
+        l: usize,
+        left: AssignedCell<pallas::Base, pallas::Base>,
+        right: AssignedCell<pallas::Base, pallas::Base>,
+    ) -> Result<AssignedCell<pallas::Base, pallas::Base>, Error> {
         let config = self.config().clone();
 
         // We need to hash `l || left || right`, where `l` is a 10-bit value.
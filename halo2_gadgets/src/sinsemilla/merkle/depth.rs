@@ -0,0 +1,223 @@
+//! Chip selecting the root of a Merkle path with a witnessed depth.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+use super::MerkleDepthInstructions;
+
+use crate::utilities::{bool_check, ternary, UtilitiesInstructions};
+use group::ff::PrimeField;
+
+/// Configuration for the [`MerkleDepthChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleDepthConfig {
+    q_init: Selector,
+    q_step: Selector,
+    active: Column<Advice>,
+    depth: Column<Advice>,
+    node: Column<Advice>,
+    root: Column<Advice>,
+}
+
+/// Chip implementing [`MerkleDepthInstructions`].
+///
+/// The nodes on the path are laid out one per row, alongside a boolean flag `active`
+/// that is set for each layer below the witnessed depth:
+///
+/// | active | depth | node | root | q_init | q_step |
+/// ---------------------------------------------------
+/// |   1    |   0   | n_0  | n_0  |   1    |   0    |
+/// |  a_1   |  d_1  | n_1  | r_1  |   0    |   1    |
+/// |  ...   |  ...  | ...  | ...  |   0    |   1    |
+/// |  a_L   |  d_L  | n_L  | r_L  |   0    |   1    |
+///
+/// where `a_i` may only be set if `a_{i-1}` is set, `d_i = d_{i-1} + a_i`, and
+/// `r_i = a_i ? n_i : r_{i-1}`. The set flags thus form a prefix, `d_L` counts them,
+/// and `r_L = n_{d_L}`. `d_L` is constrained to equal the given depth.
+#[derive(Clone, Debug)]
+pub struct MerkleDepthChip<F> {
+    config: MerkleDepthConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeField> Chip<F> for MerkleDepthChip<F> {
+    type Config = MerkleDepthConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeField> UtilitiesInstructions<F> for MerkleDepthChip<F> {
+    type Var = AssignedCell<F, F>;
+}
+
+impl<F: PrimeField> MerkleDepthInstructions<F> for MerkleDepthChip<F> {
+    fn select_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        depth: &Self::Var,
+        nodes: &[Self::Var],
+    ) -> Result<Self::Var, Error> {
+        assert!(!nodes.is_empty());
+
+        let config = self.config();
+
+        // An out-of-range depth is witnessed as the last layer, and then fails the copy
+        // constraint on `d_L`.
+        let depth_value = depth.value().map(|depth| {
+            (0..nodes.len())
+                .find(|i| F::from(*i as u64) == *depth)
+                .unwrap_or(nodes.len() - 1)
+        });
+
+        layouter.assign_region(
+            || "select Merkle root",
+            |mut region| {
+                config.q_init.enable(&mut region, 0)?;
+
+                region.assign_advice(|| "active_0", config.active, 0, || Value::known(F::ONE))?;
+                let mut depth_cell = region.assign_advice(
+                    || "depth_0",
+                    config.depth,
+                    0,
+                    || Value::known(F::ZERO),
+                )?;
+                nodes[0].copy_advice(|| "node_0", &mut region, config.node, 0)?;
+                let mut root = nodes[0].copy_advice(|| "root_0", &mut region, config.root, 0)?;
+
+                for (i, node) in nodes.iter().enumerate().skip(1) {
+                    config.q_step.enable(&mut region, i)?;
+
+                    let active = depth_value.map(|depth| i <= depth);
+                    region.assign_advice(
+                        || format!("active_{}", i),
+                        config.active,
+                        i,
+                        || active.map(|active| F::from(active as u64)),
+                    )?;
+
+                    depth_cell = region.assign_advice(
+                        || format!("depth_{}", i),
+                        config.depth,
+                        i,
+                        || depth_value.map(|depth| F::from(i.min(depth) as u64)),
+                    )?;
+
+                    let node =
+                        node.copy_advice(|| format!("node_{}", i), &mut region, config.node, i)?;
+
+                    let root_val = active
+                        .zip(node.value())
+                        .zip(root.value())
+                        .map(|((active, node), root)| if active { *node } else { *root });
+                    root = region.assign_advice(
+                        || format!("root_{}", i),
+                        config.root,
+                        i,
+                        || root_val,
+                    )?;
+                }
+
+                region.constrain_equal(depth_cell.cell(), depth.cell())?;
+
+                Ok(root)
+            },
+        )
+    }
+}
+
+impl<F: PrimeField> MerkleDepthChip<F> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// The columns in `advices` are used for the `active`, `depth`, `node` and `root`
+    /// columns respectively.
+    ///
+    /// # Side-effects
+    ///
+    /// `advices[1]`, `advices[2]` and `advices[3]` will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 4],
+    ) -> MerkleDepthConfig {
+        for advice in advices.iter().skip(1) {
+            meta.enable_equality(*advice);
+        }
+
+        let config = MerkleDepthConfig {
+            q_init: meta.selector(),
+            q_step: meta.selector(),
+            active: advices[0],
+            depth: advices[1],
+            node: advices[2],
+            root: advices[3],
+        };
+
+        meta.create_gate("Merkle depth initialisation", |meta| {
+            let q_init = meta.query_selector(config.q_init);
+
+            let active = meta.query_advice(config.active, Rotation::cur());
+            let depth = meta.query_advice(config.depth, Rotation::cur());
+            let node = meta.query_advice(config.node, Rotation::cur());
+            let root = meta.query_advice(config.root, Rotation::cur());
+
+            Constraints::with_selector(
+                q_init,
+                [
+                    ("active = 1", active - Expression::Constant(F::ONE)),
+                    ("depth = 0", depth),
+                    ("root = node", root - node),
+                ],
+            )
+        });
+
+        meta.create_gate("Merkle depth step", |meta| {
+            let q_step = meta.query_selector(config.q_step);
+
+            let active_prev = meta.query_advice(config.active, Rotation::prev());
+            let active = meta.query_advice(config.active, Rotation::cur());
+            let depth_prev = meta.query_advice(config.depth, Rotation::prev());
+            let depth = meta.query_advice(config.depth, Rotation::cur());
+            let node = meta.query_advice(config.node, Rotation::cur());
+            let root_prev = meta.query_advice(config.root, Rotation::prev());
+            let root = meta.query_advice(config.root, Rotation::cur());
+
+            let one = Expression::Constant(F::ONE);
+
+            Constraints::with_selector(
+                q_step,
+                [
+                    ("bool_check active", bool_check(active.clone())),
+                    ("active ⇒ active_prev", active.clone() * (one - active_prev)),
+                    (
+                        "depth = depth_prev + active",
+                        depth - depth_prev - active.clone(),
+                    ),
+                    (
+                        "root = active ? node : root_prev",
+                        root - ternary(active, node, root_prev),
+                    ),
+                ],
+            )
+        });
+
+        config
+    }
+
+    /// Constructs a [`MerkleDepthChip`] given a [`MerkleDepthConfig`].
+    pub fn construct(config: MerkleDepthConfig) -> Self {
+        MerkleDepthChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,728 @@
+//! Gadgets for a sparse Merkle tree, in which the leaf for each key is at the position
+//! given by the key.
+//!
+//! Empty leaves have the value zero, so that the non-membership of a key is proven by
+//! showing that its leaf is empty.
+//!
+//! Keys are `PATH_LENGTH`-bit integers, and `PATH_LENGTH` must be less than the number
+//! of bits of the field. A full field element, such as a 255-bit nullifier, therefore
+//! cannot be used as a key directly: the circuit is unsatisfiable for keys that do not
+//! fit in `PATH_LENGTH` bits. Callers that derive keys by truncating or hashing such
+//! values down to `PATH_LENGTH` bits must account for collisions, since two values with
+//! the same key share a leaf, and the presence of one then refutes the non-membership
+//! of the other. For example, the full value can be stored as the leaf, and checked
+//! against when a key is found.
+
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+use std::fmt;
+
+use group::ff::{Field, PrimeFieldBits};
+use halo2_proofs::{
+    circuit::{Chip, Layouter, Value},
+    plonk::{Advice, Column, Error},
+};
+
+use super::MerkleHashInstructions;
+use crate::utilities::UtilitiesInstructions;
+
+pub mod chip;
+
+/// Instructions to decompose the key of a sparse Merkle tree into the bits that give
+/// its position in the tree.
+pub trait MerkleKeyInstructions<F: Field>: UtilitiesInstructions<F> + Chip<F> {
+    /// Constrains `key` to be a `num_bits`-bit integer, and returns its bits (least
+    /// significant first).
+    fn decompose_key(
+        &self,
+        layouter: impl Layouter<F>,
+        key: Self::Var,
+        num_bits: usize,
+    ) -> Result<Vec<Self::Var>, Error>;
+
+    /// Constrains `value` to be non-zero.
+    fn constrain_non_zero(&self, layouter: impl Layouter<F>, value: Self::Var)
+        -> Result<(), Error>;
+}
+
+/// Gadget representing the path to the leaf for a key in a binary sparse Merkle tree of
+/// depth `PATH_LENGTH`.
+///
+/// Keys are `PATH_LENGTH`-bit integers, and the leaf for each key is at the position
+/// given by the key. Unlike [`MerkleHashPath`], the position of the leaf is therefore
+/// constrained in the circuit.
+///
+/// [`MerkleHashPath`]: super::MerkleHashPath
+#[derive(Clone, Debug)]
+pub struct SparseMerklePath<F: Field, MerkleChip, KeyChip, const PATH_LENGTH: usize>
+where
+    MerkleChip: MerkleHashInstructions<F, 2> + Clone,
+    KeyChip: MerkleKeyInstructions<F, Var = MerkleChip::Var> + Clone,
+{
+    merkle_chip: MerkleChip,
+    key_chip: KeyChip,
+    domain: MerkleChip::MerkleDomain,
+    // The siblings of the leaf, ordered from the leaf to the root.
+    path: Value<[F; PATH_LENGTH]>,
+    advice: Column<Advice>,
+}
+
+impl<F: Field, MerkleChip, KeyChip, const PATH_LENGTH: usize>
+    SparseMerklePath<F, MerkleChip, KeyChip, PATH_LENGTH>
+where
+    MerkleChip: MerkleHashInstructions<F, 2> + Clone,
+    KeyChip: MerkleKeyInstructions<F, Var = MerkleChip::Var> + Clone,
+{
+    /// Constructs a [`SparseMerklePath`] from the siblings of a leaf, which may be
+    /// obtained from [`SparseMerkleTree::path`].
+    ///
+    /// The siblings are witnessed in the equality-enabled column `advice`. The
+    /// [`CondSwapInstructions::mux`] of `merkle_chip` is used to order each node and its
+    /// sibling, and so all of its columns must be equality-enabled.
+    ///
+    /// [`CondSwapInstructions::mux`]: crate::utilities::cond_swap::CondSwapInstructions::mux
+    pub fn construct(
+        merkle_chip: MerkleChip,
+        key_chip: KeyChip,
+        domain: MerkleChip::MerkleDomain,
+        path: Value<[F; PATH_LENGTH]>,
+        advice: Column<Advice>,
+    ) -> Self {
+        SparseMerklePath {
+            merkle_chip,
+            key_chip,
+            domain,
+            path,
+            advice,
+        }
+    }
+
+    /// Calculates the root of the tree in which the leaf for `key` is `leaf`.
+    pub fn calculate_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        key: MerkleChip::Var,
+        leaf: MerkleChip::Var,
+    ) -> Result<MerkleChip::Var, Error> {
+        let mut roots = self.calculate_roots(&mut layouter, key, &[leaf])?;
+        Ok(roots.pop().expect("one root per leaf"))
+    }
+
+    /// Calculates the root of a tree in which `key` is absent, i.e. in which the leaf
+    /// for `key` is empty.
+    pub fn calculate_non_membership_root(
+        &self,
+        mut layouter: impl Layouter<F>,
+        key: MerkleChip::Var,
+    ) -> Result<MerkleChip::Var, Error> {
+        let empty = self.empty_leaf(&mut layouter)?;
+        self.calculate_root(layouter, key, empty)
+    }
+
+    /// Calculates the roots `(old_root, new_root)` of the trees before and after the
+    /// leaf for `key` is changed from `old_leaf` to `new_leaf`.
+    ///
+    /// Both roots are computed from the same siblings, which constrains the two trees
+    /// to differ only in the leaf for `key`.
+    pub fn calculate_update(
+        &self,
+        mut layouter: impl Layouter<F>,
+        key: MerkleChip::Var,
+        old_leaf: MerkleChip::Var,
+        new_leaf: MerkleChip::Var,
+    ) -> Result<(MerkleChip::Var, MerkleChip::Var), Error> {
+        let roots = self.calculate_roots(&mut layouter, key, &[old_leaf, new_leaf])?;
+        let [old_root, new_root]: [MerkleChip::Var; 2] = roots.try_into().unwrap();
+        Ok((old_root, new_root))
+    }
+
+    /// Calculates the roots `(old_root, new_root)` of the trees before and after `key`
+    /// is inserted with the given `leaf`. `key` is absent from the old tree.
+    ///
+    /// `leaf` is constrained to be non-zero: a zero leaf is indistinguishable from an
+    /// empty one, so inserting it would leave `key` absent from the new tree.
+    pub fn calculate_insertion(
+        &self,
+        mut layouter: impl Layouter<F>,
+        key: MerkleChip::Var,
+        leaf: MerkleChip::Var,
+    ) -> Result<(MerkleChip::Var, MerkleChip::Var), Error> {
+        self.key_chip
+            .constrain_non_zero(layouter.namespace(|| "non-zero leaf"), leaf.clone())?;
+        let empty = self.empty_leaf(&mut layouter)?;
+        self.calculate_update(layouter, key, empty, leaf)
+    }
+
+    /// Assigns a leaf constrained to be empty.
+    fn empty_leaf(&self, layouter: &mut impl Layouter<F>) -> Result<MerkleChip::Var, Error> {
+        layouter
+            .assign_region(
+                || "empty leaf",
+                |mut region| {
+                    region.assign_advice_from_constant(|| "empty leaf", self.advice, 0, F::ZERO)
+                },
+            )
+            .map(MerkleChip::Var::from)
+    }
+
+    /// Calculates the root of the tree for each of `leaves` at the position of `key`,
+    /// sharing the decomposition of `key` and the siblings between them.
+    fn calculate_roots(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        key: MerkleChip::Var,
+        leaves: &[MerkleChip::Var],
+    ) -> Result<Vec<MerkleChip::Var>, Error> {
+        let bits = self.key_chip.decompose_key(
+            layouter.namespace(|| "decompose key"),
+            key,
+            PATH_LENGTH,
+        )?;
+
+        let siblings = self
+            .path
+            .transpose_array()
+            .iter()
+            .enumerate()
+            .map(|(l, sibling)| {
+                self.merkle_chip.load_private(
+                    layouter.namespace(|| format!("sibling {}", l)),
+                    self.advice,
+                    *sibling,
+                )
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        leaves
+            .iter()
+            .map(|leaf| {
+                let mut node = leaf.clone();
+                for (l, (bit, sibling)) in bits.iter().zip(siblings.iter()).enumerate() {
+                    // The node is the right child if the key bit at this layer is set.
+                    let left = self.merkle_chip.mux(
+                        &mut layouter.namespace(|| format!("left child {}", l)),
+                        bit.clone(),
+                        node.clone(),
+                        sibling.clone(),
+                    )?;
+                    let right = self.merkle_chip.mux(
+                        &mut layouter.namespace(|| format!("right child {}", l)),
+                        bit.clone(),
+                        sibling.clone(),
+                        node,
+                    )?;
+
+                    node = self.merkle_chip.hash_children(
+                        layouter.namespace(|| format!("hash layer {}", l)),
+                        &self.domain,
+                        l,
+                        [left, right],
+                    )?;
+                }
+                Ok(node)
+            })
+            .collect()
+    }
+}
+
+/// An out-of-circuit binary sparse Merkle tree of depth `PATH_LENGTH`, for generating
+/// the witnesses of a [`SparseMerklePath`].
+///
+/// Only the nodes that differ from those of the empty tree are stored.
+pub struct SparseMerkleTree<F: PrimeFieldBits, H, const PATH_LENGTH: usize>
+where
+    H: Fn(usize, [F; 2]) -> F,
+{
+    hash: H,
+    // empty[l] is a node at layer l of the empty tree.
+    empty: Vec<F>,
+    // The non-empty nodes, keyed by their layer and the bits of their position.
+    nodes: BTreeMap<(usize, Vec<bool>), F>,
+}
+
+impl<F: PrimeFieldBits, H, const PATH_LENGTH: usize> fmt::Debug
+    for SparseMerkleTree<F, H, PATH_LENGTH>
+where
+    H: Fn(usize, [F; 2]) -> F,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SparseMerkleTree")
+            .field("root", &self.root())
+            .field("nodes", &self.nodes.len())
+            .finish()
+    }
+}
+
+impl<F: PrimeFieldBits, H, const PATH_LENGTH: usize> SparseMerkleTree<F, H, PATH_LENGTH>
+where
+    H: Fn(usize, [F; 2]) -> F,
+{
+    /// Constructs an empty tree, in which `hash(l, [left, right])` computes a node at
+    /// layer `l` from its children.
+    pub fn new(hash: H) -> Self {
+        assert!(PATH_LENGTH < F::NUM_BITS as usize);
+
+        let mut empty = Vec::with_capacity(PATH_LENGTH + 1);
+        empty.push(F::ZERO);
+        for l in 0..PATH_LENGTH {
+            empty.push(hash(l, [empty[l], empty[l]]));
+        }
+
+        SparseMerkleTree {
+            hash,
+            empty,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the root of the tree.
+    pub fn root(&self) -> F {
+        self.node(PATH_LENGTH, vec![])
+    }
+
+    /// Returns the leaf for `key`, which is zero if `key` is absent.
+    pub fn get(&self, key: &F) -> F {
+        self.node(0, Self::position(key))
+    }
+
+    /// Returns the siblings of the leaf for `key`, ordered from the leaf to the root.
+    pub fn path(&self, key: &F) -> [F; PATH_LENGTH] {
+        let position = Self::position(key);
+        let path: Vec<_> = (0..PATH_LENGTH)
+            .map(|l| {
+                let mut sibling = position[l..].to_vec();
+                sibling[0] = !sibling[0];
+                self.node(l, sibling)
+            })
+            .collect();
+        path.try_into().unwrap()
+    }
+
+    /// Sets the leaf for `key`, and returns the previous leaf. Setting a leaf to zero
+    /// removes `key` from the tree.
+    pub fn set(&mut self, key: &F, leaf: F) -> F {
+        let position = Self::position(key);
+        let old_leaf = self.get(key);
+
+        let mut node = leaf;
+        self.set_node(0, position.clone(), node);
+        for l in 0..PATH_LENGTH {
+            let mut sibling = position[l..].to_vec();
+            sibling[0] = !sibling[0];
+            let sibling = self.node(l, sibling);
+
+            node = if position[l] {
+                (self.hash)(l, [sibling, node])
+            } else {
+                (self.hash)(l, [node, sibling])
+            };
+            self.set_node(l + 1, position[(l + 1)..].to_vec(), node);
+        }
+
+        old_leaf
+    }
+
+    /// Returns the bits of the position of the leaf for `key` (least significant first).
+    fn position(key: &F) -> Vec<bool> {
+        let bits: Vec<bool> = key.to_le_bits().iter().by_vals().collect();
+        assert!(
+            bits[PATH_LENGTH..].iter().all(|bit| !bit),
+            "key must be a PATH_LENGTH-bit integer"
+        );
+        bits[..PATH_LENGTH].to_vec()
+    }
+
+    /// Returns the node at layer `l` whose position has the given bits.
+    fn node(&self, l: usize, position: Vec<bool>) -> F {
+        self.nodes
+            .get(&(l, position))
+            .copied()
+            .unwrap_or(self.empty[l])
+    }
+
+    fn set_node(&mut self, l: usize, position: Vec<bool>, node: F) {
+        if node == self.empty[l] {
+            self.nodes.remove(&(l, position));
+        } else {
+            self.nodes.insert((l, position), node);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        chip::{MerkleKeyChip, MerkleKeyConfig},
+        SparseMerklePath, SparseMerkleTree,
+    };
+    use crate::{
+        ecc::tests::TestFixedBases,
+        poseidon::{
+            merkle::{PoseidonMerkleChip, PoseidonMerkleConfig},
+            primitives::{self as poseidon, ConstantLength, P128Pow5T3 as OrchardNullifier},
+            Pow5Chip,
+        },
+        sinsemilla::{
+            chip::SinsemillaChip,
+            merkle::{
+                chip::{MerkleChip, MerkleConfig},
+                tests::configure as configure_sinsemilla_merkle,
+            },
+            primitives as sinsemilla,
+            tests::{TestCommitDomain, TestHashDomain},
+            HashDomains,
+        },
+        utilities::{
+            i2lebsp, lookup_range_check::PallasLookupRangeCheckConfig, UtilitiesInstructions,
+        },
+    };
+
+    use group::ff::{Field, PrimeField, PrimeFieldBits};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use rand::rngs::OsRng;
+    use std::{convert::TryInto, iter};
+
+    const TREE_DEPTH: usize = 16;
+
+    type MyMerkleChip = PoseidonMerkleChip<Fp, OrchardNullifier, 3, 2>;
+
+    /// The out-of-circuit hash computing a node at layer `l` from its children.
+    type NodeHash = fn(usize, [Fp; 2]) -> Fp;
+
+    type Tree = SparseMerkleTree<Fp, NodeHash, TREE_DEPTH>;
+
+    fn poseidon_hash(_: usize, children: [Fp; 2]) -> Fp {
+        poseidon::Hash::<_, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(children)
+    }
+
+    /// MerkleCRH, as implemented by [`MerkleChip`] with the domain [`TestHashDomain`].
+    fn sinsemilla_hash(l: usize, [left, right]: [Fp; 2]) -> Fp {
+        sinsemilla::HashDomain::from_Q(TestHashDomain.Q().into())
+            .hash(
+                iter::empty()
+                    .chain(i2lebsp::<10>(l as u64))
+                    .chain(
+                        left.to_le_bits()
+                            .iter()
+                            .by_vals()
+                            .take(Fp::NUM_BITS as usize),
+                    )
+                    .chain(
+                        right
+                            .to_le_bits()
+                            .iter()
+                            .by_vals()
+                            .take(Fp::NUM_BITS as usize),
+                    ),
+            )
+            .unwrap()
+    }
+
+    fn tree_with_keys(hash: NodeHash, keys: &[u64]) -> Tree {
+        let mut tree = SparseMerkleTree::new(hash);
+        for key in keys {
+            tree.set(&Fp::from(*key), Fp::random(OsRng));
+        }
+        tree
+    }
+
+    /// Checks a transition of the leaf for `key` from `old_leaf` to `new_leaf`, or the
+    /// non-membership of `key` if `new_leaf` is `None`. If `insert` is set, the
+    /// transition is checked as an insertion, ignoring `old_leaf`.
+    struct MyCircuit {
+        key: Value<Fp>,
+        old_leaf: Value<Fp>,
+        new_leaf: Option<Value<Fp>>,
+        insert: bool,
+        path: Value<[Fp; TREE_DEPTH]>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (
+            PoseidonMerkleConfig<Fp, 3, 2>,
+            MerkleKeyConfig,
+            Column<Advice>,
+            Column<Instance>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                key: Value::unknown(),
+                old_leaf: Value::unknown(),
+                new_leaf: self.new_leaf.map(|_| Value::unknown()),
+                insert: self.insert,
+                path: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advices = [
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+                meta.advice_column(),
+            ];
+            let rc_a = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            let rc_b = [
+                meta.fixed_column(),
+                meta.fixed_column(),
+                meta.fixed_column(),
+            ];
+            meta.enable_constant(rc_b[0]);
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let pow5_config = Pow5Chip::configure::<OrchardNullifier>(
+                meta,
+                advices[..3].try_into().unwrap(),
+                advices[3],
+                rc_a,
+                rc_b,
+            );
+            let merkle_config = MyMerkleChip::configure(meta, advices, pow5_config);
+            let key_config = MerkleKeyChip::configure(meta, [advices[0], advices[1]]);
+
+            (merkle_config, key_config, advices[0], instance)
+        }
+
+        fn synthesize(
+            &self,
+            (merkle_config, key_config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let merkle_chip = MyMerkleChip::construct(merkle_config);
+            let key_chip = MerkleKeyChip::construct(key_config);
+
+            let key = merkle_chip.load_private(layouter.namespace(|| "key"), advice, self.key)?;
+
+            let path =
+                SparseMerklePath::construct(merkle_chip.clone(), key_chip, (), self.path, advice);
+
+            match self.new_leaf {
+                None => {
+                    let root =
+                        path.calculate_non_membership_root(layouter.namespace(|| "root"), key)?;
+                    layouter.constrain_instance(root.cell(), instance, 0)
+                }
+                Some(new_leaf) => {
+                    let new_leaf = merkle_chip.load_private(
+                        layouter.namespace(|| "new leaf"),
+                        advice,
+                        new_leaf,
+                    )?;
+                    let (old_root, new_root) = if self.insert {
+                        path.calculate_insertion(layouter.namespace(|| "insert"), key, new_leaf)?
+                    } else {
+                        let old_leaf = merkle_chip.load_private(
+                            layouter.namespace(|| "old leaf"),
+                            advice,
+                            self.old_leaf,
+                        )?;
+                        path.calculate_update(
+                            layouter.namespace(|| "update"),
+                            key,
+                            old_leaf,
+                            new_leaf,
+                        )?
+                    };
+                    layouter.constrain_instance(old_root.cell(), instance, 0)?;
+                    layouter.constrain_instance(new_root.cell(), instance, 1)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sparse_merkle_tree() {
+        let mut tree = tree_with_keys(poseidon_hash, &[]);
+        let empty_root = tree.root();
+
+        let key = Fp::from(12345);
+        let leaf = Fp::random(OsRng);
+        assert_eq!(tree.set(&key, leaf), Fp::ZERO);
+        assert_eq!(tree.get(&key), leaf);
+        assert_ne!(tree.root(), empty_root);
+
+        // Removing the only key restores the empty tree.
+        assert_eq!(tree.set(&key, Fp::ZERO), leaf);
+        assert_eq!(tree.root(), empty_root);
+    }
+
+    #[test]
+    fn sparse_merkle_non_membership() {
+        let tree = tree_with_keys(poseidon_hash, &[1, 7, 1000, 65535]);
+
+        for (key, expected) in [(1000, false), (999, true), (0, true)] {
+            let key = Fp::from(key);
+            let circuit = MyCircuit {
+                key: Value::known(key),
+                old_leaf: Value::unknown(),
+                new_leaf: None,
+                insert: false,
+                path: Value::known(tree.path(&key)),
+            };
+            let prover = MockProver::run(11, &circuit, vec![vec![tree.root()]]).unwrap();
+            assert_eq!(prover.verify().is_ok(), expected);
+        }
+
+        // Keys outside the tree are rejected.
+        let circuit = MyCircuit {
+            key: Value::known(Fp::from(1 << TREE_DEPTH)),
+            old_leaf: Value::unknown(),
+            new_leaf: None,
+            insert: false,
+            path: Value::known(tree.path(&Fp::from(0))),
+        };
+        let prover = MockProver::run(11, &circuit, vec![vec![tree.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn sparse_merkle_update() {
+        let mut tree = tree_with_keys(poseidon_hash, &[1, 7, 1000, 65535]);
+
+        // Insert a new key, then update an existing one.
+        for (key, insert) in [(42, true), (7, false)] {
+            let key = Fp::from(key);
+            let path = tree.path(&key);
+            let old_root = tree.root();
+            let new_leaf = Fp::random(OsRng);
+            let old_leaf = tree.set(&key, new_leaf);
+            let new_root = tree.root();
+
+            let circuit = MyCircuit {
+                key: Value::known(key),
+                old_leaf: Value::known(old_leaf),
+                new_leaf: Some(Value::known(new_leaf)),
+                insert,
+                path: Value::known(path),
+            };
+            let prover = MockProver::run(12, &circuit, vec![vec![old_root, new_root]]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // The transition must end at the new root.
+            let prover = MockProver::run(12, &circuit, vec![vec![old_root, old_root]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn sparse_merkle_insert_zero_leaf() {
+        let tree = tree_with_keys(poseidon_hash, &[1, 7, 1000, 65535]);
+
+        // Inserting a zero leaf would leave the tree unchanged, and is rejected.
+        let key = Fp::from(42);
+        let circuit = MyCircuit {
+            key: Value::known(key),
+            old_leaf: Value::unknown(),
+            new_leaf: Some(Value::known(Fp::ZERO)),
+            insert: true,
+            path: Value::known(tree.path(&key)),
+        };
+        let prover = MockProver::run(12, &circuit, vec![vec![tree.root(), tree.root()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Checks the insertion of `key` with `leaf` in a tree hashed with the Sinsemilla
+    /// [`MerkleChip`].
+    struct SinsemillaCircuit {
+        key: Value<Fp>,
+        leaf: Value<Fp>,
+        path: Value<[Fp; TREE_DEPTH]>,
+    }
+
+    impl Circuit<Fp> for SinsemillaCircuit {
+        type Config = (
+            MerkleConfig<TestHashDomain, TestCommitDomain, TestFixedBases>,
+            MerkleKeyConfig,
+            Column<Instance>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            SinsemillaCircuit {
+                key: Value::unknown(),
+                leaf: Value::unknown(),
+                path: Value::unknown(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let (merkle_config, _) =
+                configure_sinsemilla_merkle::<PallasLookupRangeCheckConfig>(meta, false);
+            let key_advices = [meta.advice_column(), meta.advice_column()];
+            let key_config = MerkleKeyChip::configure(meta, key_advices);
+
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            (merkle_config, key_config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (merkle_config, key_config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            SinsemillaChip::<
+                TestHashDomain,
+                TestCommitDomain,
+                TestFixedBases,
+                PallasLookupRangeCheckConfig,
+            >::load(merkle_config.sinsemilla_config.clone(), &mut layouter)?;
+
+            let advice = merkle_config.cond_swap_config.a();
+            let merkle_chip = MerkleChip::construct(merkle_config);
+            let key_chip = MerkleKeyChip::construct(key_config);
+
+            let key = merkle_chip.load_private(layouter.namespace(|| "key"), advice, self.key)?;
+            let leaf =
+                merkle_chip.load_private(layouter.namespace(|| "leaf"), advice, self.leaf)?;
+
+            let path = SparseMerklePath::construct(
+                merkle_chip,
+                key_chip,
+                TestHashDomain,
+                self.path,
+                advice,
+            );
+            let (old_root, new_root) =
+                path.calculate_insertion(layouter.namespace(|| "insert"), key, leaf)?;
+            layouter.constrain_instance(old_root.cell(), instance, 0)?;
+            layouter.constrain_instance(new_root.cell(), instance, 1)
+        }
+    }
+
+    #[test]
+    fn sparse_merkle_insert_sinsemilla() {
+        // The empty subtrees are hashed from all-zero leaves, with the layer as a prefix.
+        let mut tree = tree_with_keys(sinsemilla_hash, &[1, 1000]);
+
+        let key = Fp::from(42);
+        let path = tree.path(&key);
+        let old_root = tree.root();
+        let leaf = Fp::random(OsRng);
+        assert_eq!(tree.set(&key, leaf), Fp::ZERO);
+        let new_root = tree.root();
+
+        let circuit = SinsemillaCircuit {
+            key: Value::known(key),
+            leaf: Value::known(leaf),
+            path: Value::known(path),
+        };
+        let prover = MockProver::run(12, &circuit, vec![vec![old_root, new_root]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+}
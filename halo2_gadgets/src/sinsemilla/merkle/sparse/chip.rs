@@ -0,0 +1,211 @@
+//! Chip decomposing the key of a sparse Merkle tree into bits.
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+use std::marker::PhantomData;
+
+use super::MerkleKeyInstructions;
+
+use crate::utilities::{bool_check, UtilitiesInstructions};
+use group::ff::PrimeFieldBits;
+
+/// Configuration for the [`MerkleKeyChip`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleKeyConfig {
+    q_bit: Selector,
+    q_final: Selector,
+    q_non_zero: Selector,
+    z: Column<Advice>,
+    bit: Column<Advice>,
+}
+
+/// Chip implementing [`MerkleKeyInstructions`].
+///
+/// The key is decomposed with a running sum `z_0 = key`, `z_{i+1} = (z_i - b_i) / 2`,
+/// where each bit `b_i` is witnessed in its own cell:
+///
+/// |   z     |   bit   | q_bit | q_final |
+/// ---------------------------------------
+/// |  z_0    |  b_0    |   1   |    0    |
+/// |  ...    |  ...    |   1   |    0    |
+/// | z_{n-1} | b_{n-1} |   1   |    0    |
+/// |  z_n    |         |   0   |    1    |
+///
+/// `z_n` is constrained to be zero, and so the key is an `n`-bit integer.
+///
+/// A value is constrained to be non-zero by witnessing its inverse in the `bit` column
+/// of the same row, with the selector `q_non_zero`.
+#[derive(Clone, Debug)]
+pub struct MerkleKeyChip<F> {
+    config: MerkleKeyConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: PrimeFieldBits> Chip<F> for MerkleKeyChip<F> {
+    type Config = MerkleKeyConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<F: PrimeFieldBits> UtilitiesInstructions<F> for MerkleKeyChip<F> {
+    type Var = AssignedCell<F, F>;
+}
+
+impl<F: PrimeFieldBits> MerkleKeyInstructions<F> for MerkleKeyChip<F> {
+    fn decompose_key(
+        &self,
+        mut layouter: impl Layouter<F>,
+        key: Self::Var,
+        num_bits: usize,
+    ) -> Result<Vec<Self::Var>, Error> {
+        // A key of fewer bits than the field has a unique decomposition.
+        assert!(num_bits < F::NUM_BITS as usize);
+
+        let config = self.config();
+
+        layouter.assign_region(
+            || "decompose key",
+            |mut region| {
+                let mut z = key.copy_advice(|| "z_0", &mut region, config.z, 0)?;
+
+                let bits: Value<Vec<bool>> = key
+                    .value()
+                    .map(|key| key.to_le_bits().iter().by_vals().take(num_bits).collect());
+                let bits = bits.transpose_vec(num_bits);
+
+                let mut cells = Vec::with_capacity(num_bits);
+                for (i, bit) in bits.into_iter().enumerate() {
+                    config.q_bit.enable(&mut region, i)?;
+
+                    let bit = bit.map(|bit| F::from(bit as u64));
+                    cells.push(region.assign_advice(
+                        || format!("b_{}", i),
+                        config.bit,
+                        i,
+                        || bit,
+                    )?);
+
+                    let z_next = z.value().zip(bit).map(|(z, bit)| (*z - bit) * F::TWO_INV);
+                    z = region.assign_advice(
+                        || format!("z_{}", i + 1),
+                        config.z,
+                        i + 1,
+                        || z_next,
+                    )?;
+                }
+
+                config.q_final.enable(&mut region, num_bits)?;
+
+                Ok(cells)
+            },
+        )
+    }
+
+    fn constrain_non_zero(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Self::Var,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        layouter.assign_region(
+            || "non-zero",
+            |mut region| {
+                config.q_non_zero.enable(&mut region, 0)?;
+
+                value.copy_advice(|| "value", &mut region, config.z, 0)?;
+                // A zero value has no inverse, and leaves the constraint unsatisfied.
+                let inv = value.value().map(|value| value.invert().unwrap_or(F::ZERO));
+                region.assign_advice(|| "inverse", config.bit, 0, || inv)?;
+
+                Ok(())
+            },
+        )
+    }
+}
+
+impl<F: PrimeFieldBits> MerkleKeyChip<F> {
+    /// Configures this chip for use in a circuit.
+    ///
+    /// The columns in `advices` are used for the running sum and the bits respectively.
+    ///
+    /// # Side-effects
+    ///
+    /// All columns in `advices` will be equality-enabled.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advices: [Column<Advice>; 2],
+    ) -> MerkleKeyConfig {
+        for advice in advices.iter() {
+            meta.enable_equality(*advice);
+        }
+
+        let config = MerkleKeyConfig {
+            q_bit: meta.selector(),
+            q_final: meta.selector(),
+            q_non_zero: meta.selector(),
+            z: advices[0],
+            bit: advices[1],
+        };
+
+        meta.create_gate("Merkle key bit", |meta| {
+            let q_bit = meta.query_selector(config.q_bit);
+
+            let z_cur = meta.query_advice(config.z, Rotation::cur());
+            let z_next = meta.query_advice(config.z, Rotation::next());
+            let bit = meta.query_advice(config.bit, Rotation::cur());
+
+            Constraints::with_selector(
+                q_bit,
+                [
+                    ("bool_check bit", bool_check(bit.clone())),
+                    (
+                        "z_cur = 2 * z_next + bit",
+                        z_cur - z_next * F::from(2) - bit,
+                    ),
+                ],
+            )
+        });
+
+        meta.create_gate("Merkle key final", |meta| {
+            let q_final = meta.query_selector(config.q_final);
+            let z = meta.query_advice(config.z, Rotation::cur());
+
+            Constraints::with_selector(q_final, Some(("z_n = 0", z)))
+        });
+
+        meta.create_gate("Merkle non-zero", |meta| {
+            let q_non_zero = meta.query_selector(config.q_non_zero);
+            let value = meta.query_advice(config.z, Rotation::cur());
+            let inv = meta.query_advice(config.bit, Rotation::cur());
+
+            Constraints::with_selector(
+                q_non_zero,
+                Some((
+                    "value * inv = 1",
+                    value * inv - Expression::Constant(F::ONE),
+                )),
+            )
+        });
+
+        config
+    }
+
+    /// Constructs a [`MerkleKeyChip`] given a [`MerkleKeyConfig`].
+    pub fn construct(config: MerkleKeyConfig) -> Self {
+        MerkleKeyChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -12,7 +12,6 @@ use ff::PrimeFieldBits;
 
 use crate::sinsemilla::{chip::generator_table::GeneratorTableConfig, primitives as sinsemilla};
 use pasta_curves::pallas;
-use sinsemilla::SINSEMILLA_S;
 
 use super::*;
 
@@ -94,10 +93,14 @@ pub trait LookupRangeCheck<F: PrimeFieldBits, const K: usize>: Eq + Copy + Debug
         Self: Sized;
 
     /// Load the generator table into the circuit.
+    ///
+    /// `generators` holds the affine coordinates of the $2^K$ Sinsemilla generators
+    /// $S[0..2^K]$.
     fn load(
         &self,
         generator_table_config: &GeneratorTableConfig,
-        layouter: &mut impl Layouter<pallas::Base>,
+        generators: &[(F, F)],
+        layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error>;
 
     #[cfg(test)]
@@ -393,21 +396,22 @@ impl<F: PrimeFieldBits, const K: usize> LookupRangeCheck<F, K> for LookupRangeCh
     /// |     0     |    X(S\[0\])   |    Y(S\[0\])   |
     /// |     1     |    X(S\[1\])   |    Y(S\[1\])   |
     /// |    ...    |      ...       |       ...      |
-    /// |   2^10-1  | X(S\[2^10-1\]) | Y(S\[2^10-1\]) |
+    /// |   2^K-1   | X(S\[2^K-1\])  | Y(S\[2^K-1\])  |
     fn load(
         &self,
         generator_table_config: &GeneratorTableConfig,
-        layouter: &mut impl Layouter<pallas::Base>,
+        generators: &[(F, F)],
+        layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         layouter.assign_table(
             || "generator_table",
             |mut table| {
-                for (index, (x, y)) in SINSEMILLA_S.iter().enumerate() {
+                for (index, (x, y)) in generators.iter().enumerate() {
                     table.assign_cell(
                         || "table_idx",
                         generator_table_config.table_idx,
                         index,
-                        || Value::known(pallas::Base::from(index as u64)),
+                        || Value::known(F::from(index as u64)),
                     )?;
                     table.assign_cell(
                         || "table_x",
@@ -656,7 +660,7 @@ impl<F: PrimeFieldBits, const K: usize> LookupRangeCheck<F, K>
     /// |     0     |    X(S\[0\])   |    Y(S\[0\])   |           0           |
     /// |     1     |    X(S\[1\])   |    Y(S\[1\])   |           0           |
     /// |    ...    |      ...       |       ...      |           0           |
-    /// |   2^10-1  | X(S\[2^10-1\]) | Y(S\[2^10-1\]) |           0           |
+    /// |   2^K-1   | X(S\[2^K-1\])  | Y(S\[2^K-1\])  |           0           |
     /// |     0     |    X(S\[0\])   |    Y(S\[0\])   |           4           |
     /// |     1     |    X(S\[1\])   |    Y(S\[1\])   |           4           |
     /// |    ...    |       ...      |       ...      |           4           |
@@ -687,17 +691,18 @@ impl<F: PrimeFieldBits, const K: usize> LookupRangeCheck<F, K>
     fn load(
         &self,
         generator_table_config: &GeneratorTableConfig,
-        layouter: &mut impl Layouter<pallas::Base>,
+        generators: &[(F, F)],
+        layouter: &mut impl Layouter<F>,
     ) -> Result<(), Error> {
         layouter.assign_table(
             || "generator_table",
             |mut table| {
-                for (index, (x, y)) in SINSEMILLA_S.iter().enumerate() {
+                for (index, (x, y)) in generators.iter().enumerate() {
                     table.assign_cell(
                         || "table_idx",
                         generator_table_config.table_idx,
                         index,
-                        || Value::known(pallas::Base::from(index as u64)),
+                        || Value::known(F::from(index as u64)),
                     )?;
                     table.assign_cell(
                         || "table_x",
@@ -716,15 +721,15 @@ impl<F: PrimeFieldBits, const K: usize> LookupRangeCheck<F, K>
                         || "table_range_check_tag",
                         self.table_range_check_tag,
                         index,
-                        || Value::known(pallas::Base::zero()),
+                        || Value::known(F::ZERO),
                     )?;
                     if index < (1 << 4) {
-                        let new_index = index + (1 << sinsemilla::K);
+                        let new_index = index + (1 << K);
                         table.assign_cell(
                             || "table_idx",
                             generator_table_config.table_idx,
                             new_index,
-                            || Value::known(pallas::Base::from(index as u64)),
+                            || Value::known(F::from(index as u64)),
                         )?;
                         table.assign_cell(
                             || "table_x",
@@ -742,16 +747,16 @@ impl<F: PrimeFieldBits, const K: usize> LookupRangeCheck<F, K>
                             || "table_range_check_tag",
                             self.table_range_check_tag,
                             new_index,
-                            || Value::known(pallas::Base::from(4_u64)),
+                            || Value::known(F::from(4_u64)),
                         )?;
                     }
                     if index < (1 << 5) {
-                        let new_index = index + (1 << 10) + (1 << 4);
+                        let new_index = index + (1 << K) + (1 << 4);
                         table.assign_cell(
                             || "table_idx",
                             generator_table_config.table_idx,
                             new_index,
-                            || Value::known(pallas::Base::from(index as u64)),
+                            || Value::known(F::from(index as u64)),
                         )?;
                         table.assign_cell(
                             || "table_x",
@@ -769,7 +774,7 @@ impl<F: PrimeFieldBits, const K: usize> LookupRangeCheck<F, K>
                             || "table_range_check_tag",
                             self.table_range_check_tag,
                             new_index,
-                            || Value::known(pallas::Base::from(5_u64)),
+                            || Value::known(F::from(5_u64)),
                         )?;
                     }
                 }
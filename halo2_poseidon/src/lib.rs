@@ -412,6 +412,38 @@ impl<F: PrimeField, const RATE: usize, const L: usize> Domain<F, RATE> for Const
     }
 }
 
+/// A Poseidon hash function used with variable input length, of at most `MAX_L`.
+///
+/// The input is padded with a single `1` followed by zeroes to a multiple of RATE, as
+/// specified in [ePrint 2019/458 section 4.2](https://eprint.iacr.org/2019/458.pdf).
+#[derive(Clone, Copy, Debug)]
+pub struct VariableLength<const MAX_L: usize>;
+
+impl<F: PrimeField, const RATE: usize, const MAX_L: usize> Domain<F, RATE>
+    for VariableLength<MAX_L>
+{
+    type Padding = iter::Chain<iter::Once<F>, iter::Take<iter::Repeat<F>>>;
+
+    fn name() -> String {
+        format!("VariableLength<{}>", MAX_L)
+    }
+
+    fn initial_capacity_element() -> F {
+        // Capacity value is $2^128 + (o-1)$ where o is the output length. We hard-code
+        // an output length of 1. [`ConstantLength`] encodes lengths below $2^64$ in
+        // bits 64 to 127, so the two domains never share an initial state.
+        F::from_u128(1 << 64).square()
+    }
+
+    fn padding(input_len: usize) -> Self::Padding {
+        assert!(input_len <= MAX_L);
+        // The padding is sponge-compliant: the final `1` marks the end of the input, so
+        // inputs of different lengths are padded to different sequences.
+        let k = input_len / RATE + 1;
+        iter::once(F::ONE).chain(iter::repeat(F::ZERO).take(k * RATE - input_len - 1))
+    }
+}
+
 /// A Poseidon hash function, built around a sponge.
 pub struct Hash<
     F: Field,
@@ -465,12 +497,31 @@ impl<F: PrimeField, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, cons
     }
 }
 
+impl<F: PrimeField, S: Spec<F, T, RATE>, const T: usize, const RATE: usize, const MAX_L: usize>
+    Hash<F, S, VariableLength<MAX_L>, T, RATE>
+{
+    /// Hashes the given input.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input is longer than `MAX_L`.
+    pub fn hash(mut self, message: &[F]) -> F {
+        let padding = <VariableLength<MAX_L> as Domain<F, RATE>>::padding(message.len());
+        for value in message.iter().copied().chain(padding) {
+            self.sponge.absorb(value);
+        }
+        self.sponge.finish_absorbing().squeeze()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use group::ff::PrimeField;
+    use group::ff::{Field, PrimeField};
     use pasta_curves::pallas;
 
-    use super::{permute, ConstantLength, Hash, P128Pow5T3 as OrchardNullifier, Spec};
+    use super::{
+        permute, ConstantLength, Hash, P128Pow5T3 as OrchardNullifier, Spec, VariableLength,
+    };
 
     #[test]
     fn orchard_spec_equivalence() {
@@ -487,4 +538,42 @@ mod tests {
         permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
         assert_eq!(state[0], result);
     }
+
+    #[test]
+    fn variable_length_padding() {
+        let message = [pallas::Base::from(6), pallas::Base::from(42)];
+
+        let (round_constants, mds, _) = OrchardNullifier::constants();
+
+        let hasher = Hash::<_, OrchardNullifier, VariableLength<4>, 3, 2>::init();
+        let result = hasher.hash(&message);
+
+        // The message fills the rate, so the padding occupies a second block.
+        let mut state = [
+            message[0],
+            message[1],
+            pallas::Base::from_u128(1 << 64).square(),
+        ];
+        permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
+        state[0] += pallas::Base::ONE;
+        permute::<_, OrchardNullifier, 3, 2>(&mut state, &mds, &round_constants);
+        assert_eq!(state[0], result);
+    }
+
+    #[test]
+    fn variable_length_domain_separation() {
+        let message = [pallas::Base::from(6), pallas::Base::from(42)];
+
+        // Trailing zeroes are part of the message.
+        assert_ne!(
+            Hash::<_, OrchardNullifier, VariableLength<4>, 3, 2>::init().hash(&message[..1]),
+            Hash::<_, OrchardNullifier, VariableLength<4>, 3, 2>::init()
+                .hash(&[message[0], pallas::Base::ZERO]),
+        );
+        // The domain is distinct from that of constant-length hashing.
+        assert_ne!(
+            Hash::<_, OrchardNullifier, VariableLength<4>, 3, 2>::init().hash(&message),
+            Hash::<_, OrchardNullifier, ConstantLength<2>, 3, 2>::init().hash(message),
+        );
+    }
 }
@@ -2,7 +2,7 @@
 
 use std::{fmt, marker::PhantomData};
 
-use ff::Field;
+use ff::{Field, PrimeField};
 
 use crate::plonk::{Advice, Any, Assigned, Column, Error, Fixed, Instance, Selector, TableColumn};
 
@@ -17,6 +17,9 @@ pub mod layouter;
 mod table_layouter;
 pub use table_layouter::TableLayouter;
 
+mod column_group;
+pub use column_group::{ColumnGroup, GroupCell, GroupRow};
+
 /// A chip implements a set of instructions that can be used by gadgets.
 ///
 /// The chip stores state that is required at circuit synthesis time in
@@ -441,6 +444,92 @@ pub trait Layouter<F: Field> {
         N: Fn() -> NR,
         NR: Into<String>;
 
+    /// Assign several independent regions of gates, returning the result of each
+    /// assignment in order.
+    ///
+    /// This behaves like calling [`Layouter::assign_region`] for each assignment in
+    /// turn, except that the layouter may run the assignments concurrently once the
+    /// positions of their regions are known, as [`floor_planner::V1`] does when the
+    /// `multicore` feature flag is enabled. The regions must therefore not depend on
+    /// each other: cells assigned in one region may only be constrained to cells of
+    /// another region via the returned results.
+    ///
+    /// Regions assigned concurrently cannot read instance values, via either
+    /// [`Region::assign_advice_from_instance`] or [`Region::instance_value`]; doing
+    /// so returns [`Error::Synthesis`].
+    ///
+    /// ```ignore
+    /// let rows = layouter.assign_regions(
+    ///     || "row",
+    ///     values.iter().map(|value| move |mut region: Region<'_, F>| {
+    ///         region.assign_advice(|| "value", config.a, 0, || *value)
+    ///     }).collect(),
+    /// )?;
+    /// ```
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        assignments
+            .into_iter()
+            .map(|assignment| self.assign_region(&name, assignment))
+            .collect()
+    }
+
+    /// Assign `count` instances of a region template, returning the result of each
+    /// assignment in order.
+    ///
+    /// `assignment` is called with the index of each instance. Every instance must fit
+    /// in the shape (the columns and rows it uses, and the selectors it enables) of the
+    /// first instance, and may differ from it only in the values it assigns. This lets
+    /// the layouter position all of the instances at once: [`floor_planner::V1`]
+    /// measures only the first instance, uses clones of its result in place of the
+    /// results of the others, and lays the instances out one after another as a single
+    /// block. It returns [`Error::Synthesis`] if an instance does not fit.
+    ///
+    /// ```ignore
+    /// let rounds = layouter.assign_region_instances(|| "round", 64, |round, mut region| {
+    ///     config.q_round.enable(&mut region, 0)?;
+    ///     region.assign_advice(|| "state", config.state, 0, || state[round])
+    /// })?;
+    /// ```
+    fn assign_region_instances<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        count: usize,
+        mut assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(usize, Region<'_, F>) -> Result<AR, Error>,
+        AR: Clone,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        (0..count)
+            .map(|instance| self.assign_region(&name, |region| assignment(instance, region)))
+            .collect()
+    }
+
+    /// Assigns each distinct constant to a single fixed cell, to which all of its uses
+    /// (via [`Region::assign_advice_from_constant`] and [`Region::constrain_constant`])
+    /// are copy-constrained, instead of assigning a fixed cell for every use.
+    ///
+    /// This changes the fixed columns of the circuit, and therefore its verifying key,
+    /// so circuits must opt in to it by calling this before assigning any regions.
+    /// Layouters that do not support deduplication ignore it.
+    fn deduplicate_constants(&mut self)
+    where
+        F: PrimeField,
+    {
+    }
+
     /// Assign a table region to an absolute row number.
     ///
     /// ```ignore
@@ -511,6 +600,42 @@ impl<'a, F: Field, L: Layouter<F> + 'a> Layouter<F> for NamespacedLayouter<'a, F
         self.0.assign_region(name, assignment)
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_regions(name, assignments)
+    }
+
+    fn assign_region_instances<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        count: usize,
+        assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(usize, Region<'_, F>) -> Result<AR, Error>,
+        AR: Clone,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        self.0.assign_region_instances(name, count, assignment)
+    }
+
+    fn deduplicate_constants(&mut self)
+    where
+        F: PrimeField,
+    {
+        self.0.deduplicate_constants()
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
@@ -0,0 +1,348 @@
+//! Named groups of the cells that a gate queries.
+
+use ff::Field;
+
+use super::{AssignedCell, Region, Value};
+use crate::{
+    plonk::{
+        Advice, Any, Assigned, Column, ConstraintSystem, Error, Expression, Selector, VirtualCells,
+    },
+    poly::Rotation,
+};
+
+/// The cells that a gate queries, named once in `configure` relative to the row on which
+/// the gate's selector is enabled.
+///
+/// Gates query the cells of a group with [`GroupCell::query`], and regions assign them
+/// through a [`GroupRow`], which places each cell at the rotation at which it is
+/// queried. This replaces raw column indices and offsets in chip code:
+///
+/// ```ignore
+/// // In `configure`:
+/// let mut add = ColumnGroup::new("add", meta.selector());
+/// let a = add.advice("a", advices[0], Rotation::cur());
+/// let b = add.advice("b", advices[1], Rotation::cur());
+/// let c = add.advice("c", advices[0], Rotation::next());
+/// meta.create_gate("add", |meta| {
+///     let s = meta.query_selector(add.selector());
+///     vec![s * (a.query(meta) + b.query(meta) - c.query(meta))]
+/// });
+/// add.check_queries(meta);
+///
+/// // In `synthesize`:
+/// let mut row = config.add.enable(&mut region, offset)?;
+/// row.assign_advice(|| "a", &config.a, || a)?;
+/// row.assign_advice(|| "b", &config.b, || b)?;
+/// row.assign_advice(|| "c", &config.c, || a + b)?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct ColumnGroup {
+    name: &'static str,
+    selector: Selector,
+    cells: Vec<GroupCell>,
+}
+
+/// A named cell of a [`ColumnGroup`]: an advice column, at a rotation relative to the
+/// row on which the group is enabled.
+#[derive(Clone, Copy, Debug)]
+pub struct GroupCell {
+    name: &'static str,
+    // The selector of the group this cell belongs to.
+    selector: Selector,
+    column: Column<Advice>,
+    rotation: Rotation,
+}
+
+impl ColumnGroup {
+    /// Creates an empty group of cells, whose gates are enabled by `selector`.
+    pub fn new(name: &'static str, selector: Selector) -> Self {
+        ColumnGroup {
+            name,
+            selector,
+            cells: vec![],
+        }
+    }
+
+    /// Adds a cell to this group, in the given column at the given rotation.
+    pub fn advice(
+        &mut self,
+        name: &'static str,
+        column: Column<Advice>,
+        rotation: Rotation,
+    ) -> GroupCell {
+        let cell = GroupCell {
+            name,
+            selector: self.selector,
+            column,
+            rotation,
+        };
+        self.cells.push(cell);
+        cell
+    }
+
+    /// Returns the name of this group.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the selector that enables the gates of this group.
+    pub fn selector(&self) -> Selector {
+        self.selector
+    }
+
+    /// Returns the cells of this group.
+    pub fn cells(&self) -> &[GroupCell] {
+        &self.cells
+    }
+
+    /// Checks that the gates enabled by this group's selector query every cell of this
+    /// group at its rotation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a cell is not queried by any of the group's gates, which means its
+    /// rotation does not match the rotations that the gates actually query.
+    pub fn check_queries<F: Field>(&self, meta: &ConstraintSystem<F>) {
+        let gates: Vec<_> = meta
+            .gates
+            .iter()
+            .filter(|gate| gate.queried_selectors().contains(&self.selector))
+            .collect();
+        for cell in &self.cells {
+            let column = Column::<Any>::from(cell.column);
+            if !gates.iter().any(|gate| {
+                gate.queried_cells()
+                    .iter()
+                    .any(|queried| queried.column == column && queried.rotation == cell.rotation)
+            }) {
+                panic!(
+                    "Cell '{}' of column group '{}' is not queried by any of its gates at rotation {}",
+                    cell.name, self.name, cell.rotation.0
+                );
+            }
+        }
+    }
+
+    /// Enables this group's selector at `offset` within `region`, returning the row
+    /// through which its cells are assigned.
+    pub fn enable<'g, 'r, 'a, F: Field>(
+        &'g self,
+        region: &'r mut Region<'a, F>,
+        offset: usize,
+    ) -> Result<GroupRow<'g, 'r, 'a, F>, Error> {
+        region.enable_selector(|| self.name, &self.selector, offset)?;
+        Ok(GroupRow {
+            group: self,
+            region,
+            offset,
+        })
+    }
+}
+
+impl GroupCell {
+    /// Returns the name of this cell.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the column of this cell.
+    pub fn column(&self) -> Column<Advice> {
+        self.column
+    }
+
+    /// Returns the rotation of this cell, relative to the row on which its group is
+    /// enabled.
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Queries this cell while creating a gate.
+    pub fn query<F: Field>(&self, meta: &mut VirtualCells<'_, F>) -> Expression<F> {
+        meta.query_advice(self.column, self.rotation)
+    }
+}
+
+/// A row of a region on which a [`ColumnGroup`] is enabled.
+#[derive(Debug)]
+pub struct GroupRow<'g, 'r, 'a, F: Field> {
+    group: &'g ColumnGroup,
+    region: &'r mut Region<'a, F>,
+    offset: usize,
+}
+
+impl<'g, 'r, 'a, F: Field> GroupRow<'g, 'r, 'a, F> {
+    /// Returns the offset within the region at which the group is enabled.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Assigns the value of a cell of the group, at the rotation at which its gates
+    /// query it.
+    ///
+    /// Returns [`Error::GroupCellOutsideRegion`] if the cell would be before the start
+    /// of the region, or at an offset too large to address.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell` belongs to a different group.
+    pub fn assign_advice<'v, V, VR, A, AR>(
+        &'v mut self,
+        annotation: A,
+        cell: &GroupCell,
+        to: V,
+    ) -> Result<AssignedCell<VR, F>, Error>
+    where
+        V: FnMut() -> Value<VR> + 'v,
+        for<'vr> Assigned<F>: From<&'vr VR>,
+        A: Fn() -> AR,
+        AR: Into<String>,
+    {
+        assert!(
+            cell.selector == self.group.selector,
+            "Cell '{}' assigned through column group '{}', which it does not belong to",
+            cell.name,
+            self.group.name,
+        );
+
+        let offset = i64::try_from(self.offset)
+            .ok()
+            .and_then(|offset| offset.checked_add(cell.rotation.0.into()))
+            .and_then(|offset| usize::try_from(offset).ok())
+            .ok_or_else(|| Error::GroupCellOutsideRegion {
+                group: self.group.name.to_string(),
+                cell: cell.name.to_string(),
+                offset: self.offset,
+                rotation: cell.rotation.0,
+            })?;
+
+        self.region
+            .assign_advice(annotation, cell.column, offset, to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::{ColumnGroup, GroupCell};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct AddConfig {
+        add: ColumnGroup,
+        a: GroupCell,
+        b: GroupCell,
+        c: GroupCell,
+    }
+
+    fn configure_add(meta: &mut ConstraintSystem<Fp>, advices: [Column<Advice>; 2]) -> AddConfig {
+        let mut add = ColumnGroup::new("add", meta.selector());
+        let a = add.advice("a", advices[0], Rotation::prev());
+        let b = add.advice("b", advices[1], Rotation::cur());
+        let c = add.advice("c", advices[0], Rotation::cur());
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(add.selector());
+            vec![s * (a.query(meta) + b.query(meta) - c.query(meta))]
+        });
+        add.check_queries(meta);
+
+        AddConfig { add, a, b, c }
+    }
+
+    #[derive(Default)]
+    struct MyCircuit {
+        offset: usize,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = AddConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                offset: self.offset,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> AddConfig {
+            let advices = [meta.advice_column(), meta.advice_column()];
+            configure_add(meta, advices)
+        }
+
+        fn synthesize(
+            &self,
+            config: AddConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "add",
+                |mut region| {
+                    let mut row = config.add.enable(&mut region, self.offset)?;
+                    row.assign_advice(|| "a", &config.a, || Value::known(Fp::from(2)))?;
+                    row.assign_advice(|| "b", &config.b, || Value::known(Fp::from(3)))?;
+                    row.assign_advice(|| "c", &config.c, || Value::known(Fp::from(5)))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn column_group() {
+        // The cells are assigned at the rotations the gate queries.
+        let prover = MockProver::run(4, &MyCircuit { offset: 1 }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // Enabling the group on the first row of the region would assign `a` before it.
+        let err = MockProver::run(4, &MyCircuit { offset: 0 }, vec![]).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::GroupCellOutsideRegion { ref cell, offset: 0, rotation: -1, .. } if cell == "a"
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cell 'c' of column group 'mul' is not queried by any of its gates")]
+    fn column_group_unqueried_cell() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let advice = meta.advice_column();
+
+        let mut mul = ColumnGroup::new("mul", meta.selector());
+        let a = mul.advice("a", advice, Rotation::cur());
+        mul.advice("c", advice, Rotation::next());
+        meta.create_gate("mul", |meta| {
+            let s = meta.query_selector(mul.selector());
+            // Queries the output at the wrong rotation.
+            let c = meta.query_advice(advice, Rotation(2));
+            vec![s * (a.query(meta) * a.query(meta) - c)]
+        });
+        mul.check_queries(&meta);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cell 'b' of column group 'add' is not queried by any of its gates")]
+    fn column_group_cell_queried_by_other_gate() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let advices = [meta.advice_column(), meta.advice_column()];
+
+        let mut add = ColumnGroup::new("add", meta.selector());
+        let a = add.advice("a", advices[0], Rotation::cur());
+        add.advice("b", advices[1], Rotation::cur());
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(add.selector());
+            vec![s * a.query(meta)]
+        });
+        // Another gate queries `b` at the same rotation.
+        let q_other = meta.selector();
+        meta.create_gate("other", |meta| {
+            let s = meta.query_selector(q_other);
+            vec![s * meta.query_advice(advices[1], Rotation::cur())]
+        });
+        add.check_queries(&meta);
+    }
+}
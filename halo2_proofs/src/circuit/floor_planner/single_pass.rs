@@ -3,11 +3,11 @@ use std::collections::HashMap;
 use std::fmt;
 use std::marker::PhantomData;
 
-use ff::Field;
+use ff::{Field, PrimeField};
 
 use crate::{
     circuit::{
-        layouter::{RegionColumn, RegionLayouter, RegionShape},
+        layouter::{ConstantPool, RegionColumn, RegionLayouter, RegionShape},
         table_layouter::{compute_table_lengths, SimpleTableLayouter},
         Cell, Layouter, Region, RegionIndex, RegionStart, Table, TableLayouter, Value,
     },
@@ -47,6 +47,10 @@ pub struct SingleChipLayouter<'a, F: Field, CS: Assignment<F> + 'a> {
     columns: HashMap<RegionColumn, usize>,
     /// Stores the table fixed columns.
     table_columns: Vec<TableColumn>,
+    /// Stores the fixed cells to which constants have been assigned.
+    constant_pool: ConstantPool<F>,
+    /// Stores the names of the namespaces that have been entered.
+    namespace: Vec<String>,
     _marker: PhantomData<F>,
 }
 
@@ -68,6 +72,8 @@ impl<'a, F: Field, CS: Assignment<F>> SingleChipLayouter<'a, F, CS> {
             regions: vec![],
             columns: HashMap::default(),
             table_columns: vec![],
+            constant_pool: ConstantPool::new(),
+            namespace: vec![],
             _marker: PhantomData,
         };
         Ok(ret)
@@ -89,7 +95,8 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
         let mut shape = RegionShape::new(region_index.into());
         {
             let region: &mut dyn RegionLayouter<F> = &mut shape;
-            assignment(region.into())?;
+            assignment(region.into())
+                .map_err(|e| e.in_region(|| name().into(), &self.namespace))?;
         }
 
         // Lay out this region. We implement the simplest approach here: position the
@@ -106,12 +113,13 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
         }
 
         // Assign region cells.
-        self.cs.enter_region(name);
+        self.cs.enter_region(&name);
         let mut region = SingleChipLayouterRegion::new(self, region_index.into());
         let result = {
             let region: &mut dyn RegionLayouter<F> = &mut region;
             assignment(region.into())
-        }?;
+        }
+        .map_err(|e| e.in_region(|| name().into(), &region.layouter.namespace))?;
         let constants_to_assign = region.constants;
         self.cs.exit_region();
 
@@ -128,25 +136,31 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
                 .entry(Column::<Any>::from(constants_column).into())
                 .or_default();
             for (constant, advice) in constants_to_assign {
-                self.cs.assign_fixed(
-                    || format!("Constant({:?})", constant.evaluate()),
-                    constants_column,
-                    *next_constant_row,
-                    || Value::known(constant),
-                )?;
+                let (fixed_column, fixed_row) =
+                    self.constant_pool.assign(self.cs, constant, || {
+                        let row = *next_constant_row;
+                        *next_constant_row += 1;
+                        Ok((constants_column, row))
+                    })?;
                 self.cs.copy(
-                    constants_column.into(),
-                    *next_constant_row,
+                    fixed_column.into(),
+                    fixed_row,
                     advice.column,
                     *self.regions[*advice.region_index] + advice.row_offset,
                 )?;
-                *next_constant_row += 1;
             }
         }
 
         Ok(result)
     }
 
+    fn deduplicate_constants(&mut self)
+    where
+        F: PrimeField,
+    {
+        self.constant_pool.deduplicate();
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, mut assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
@@ -207,10 +221,13 @@ impl<'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for SingleChipLayouter<'a
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        self.cs.push_namespace(name_fn)
+        let name = name_fn().into();
+        self.namespace.push(name.clone());
+        self.cs.push_namespace(|| name)
     }
 
     fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.namespace.pop();
         self.cs.pop_namespace(gadget_name)
     }
 }
@@ -368,6 +385,7 @@ mod tests {
 
     use super::SimpleFloorPlanner;
     use crate::{
+        circuit::{Layouter, Value},
         dev::MockProver,
         plonk::{Advice, Circuit, Column, Error},
     };
@@ -415,4 +433,123 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn synthesis_error_in_region() {
+        struct MyCircuit {
+            fail: Value<bool>,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    fail: Value::unknown(),
+                }
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                let mut layouter = layouter.namespace(|| "gadget");
+                layouter.assign_region(
+                    || "failing region",
+                    |mut region| {
+                        region.assign_advice(
+                            || "zero",
+                            config,
+                            0,
+                            || Value::known(vesta::Scalar::zero()),
+                        )?;
+                        self.fail.error_if_known_and(|fail| *fail)
+                    },
+                )
+            }
+        }
+
+        let circuit = MyCircuit {
+            fail: Value::known(true),
+        };
+        match MockProver::run(3, &circuit, vec![]).unwrap_err() {
+            Error::InRegion {
+                region,
+                namespace,
+                cause,
+            } => {
+                assert_eq!(region, "failing region");
+                assert_eq!(namespace, vec!["gadget".to_string()]);
+                assert!(matches!(*cause, Error::Synthesis));
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn witness_error_in_region() {
+        struct MyCircuit {
+            input: Value<u64>,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    input: Value::unknown(),
+                }
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "byte",
+                    |mut region| {
+                        let byte = self.input.try_map(|input| {
+                            u8::try_from(input)
+                                .map_err(|_| Error::Witness(format!("{} is not a byte", input)))
+                        })?;
+                        region.assign_advice(
+                            || "byte",
+                            config,
+                            0,
+                            || byte.map(|b| vesta::Scalar::from(u64::from(b))),
+                        )?;
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let circuit = MyCircuit {
+            input: Value::known(7),
+        };
+        assert!(MockProver::run(3, &circuit, vec![]).is_ok());
+
+        let circuit = MyCircuit {
+            input: Value::known(300),
+        };
+        match MockProver::run(3, &circuit, vec![]).unwrap_err() {
+            Error::InRegion { region, cause, .. } => {
+                assert_eq!(region, "byte");
+                assert!(matches!(*cause, Error::Witness(ref m) if m == "300 is not a byte"));
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
 }
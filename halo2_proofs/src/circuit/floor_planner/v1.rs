@@ -1,19 +1,23 @@
 use std::fmt;
 
-use ff::Field;
+use ff::{Field, PrimeField};
 
 use crate::{
     circuit::{
-        layouter::{RegionColumn, RegionLayouter, RegionShape},
+        layouter::{ConstantPool, RegionColumn, RegionLayouter, RegionShape},
         table_layouter::{compute_table_lengths, SimpleTableLayouter},
         Cell, Layouter, Region, RegionIndex, RegionStart, Table, TableLayouter, Value,
     },
+    multicore::IntoParallelIterator,
     plonk::{
         Advice, Any, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner, Instance,
         Selector, TableColumn,
     },
 };
 
+#[cfg(feature = "multicore")]
+use crate::multicore::ParallelIterator;
+
 mod strategy;
 
 /// The version 1 [`FloorPlanner`] provided by `halo2`.
@@ -24,6 +28,12 @@ mod strategy;
 /// - Regions are measured as rectangles, bounded on the cells they assign.
 /// - Regions are laid out using a greedy first-fit strategy, after sorting regions by
 ///   their "advice area" (number of advice columns * rows).
+/// - The instances of a region template (assigned via
+///   [`Layouter::assign_region_instances`]) are measured and positioned as a single
+///   region, in which the instances are laid out one after another.
+/// - Regions assigned together via [`Layouter::assign_regions`] are witnessed
+///   concurrently (when the `multicore` feature flag is enabled), and then assigned in
+///   order, so the resulting layout does not depend on scheduling.
 #[derive(Debug)]
 pub struct V1;
 
@@ -33,8 +43,12 @@ struct V1Plan<'a, F: Field, CS: Assignment<F> + 'a> {
     regions: Vec<RegionStart>,
     /// Stores the constants to be assigned, and the cells to which they are copied.
     constants: Vec<(Assigned<F>, Cell)>,
+    /// Stores the fixed cells to which constants have been assigned.
+    constant_pool: ConstantPool<F>,
     /// Stores the table fixed columns.
     table_columns: Vec<TableColumn>,
+    /// Stores the names of the namespaces that have been entered.
+    namespace: Vec<String>,
 }
 
 impl<'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for V1Plan<'a, F, CS> {
@@ -50,7 +64,9 @@ impl<'a, F: Field, CS: Assignment<F>> V1Plan<'a, F, CS> {
             cs,
             regions: vec![],
             constants: vec![],
+            constant_pool: ConstantPool::new(),
             table_columns: vec![],
+            namespace: vec![],
         };
         Ok(ret)
     }
@@ -63,81 +79,129 @@ impl FloorPlanner for V1 {
         config: C::Config,
         constants: Vec<Column<Fixed>>,
     ) -> Result<(), Error> {
-        let mut plan = V1Plan::new(cs)?;
-
-        // First pass: measure the regions within the circuit.
-        let mut measure = MeasurementPass::new();
-        {
-            let pass = &mut measure;
-            circuit
-                .without_witnesses()
-                .synthesize(config.clone(), V1Pass::<_, CS>::measure(pass))?;
-        }
-
-        // Planning:
-        // - Position the regions.
-        let (regions, column_allocations) = strategy::slot_in_biggest_advice_first(measure.regions);
-        plan.regions = regions;
-
-        // - Determine how many rows our planned circuit will require.
-        let first_unassigned_row = column_allocations
-            .values()
-            .map(|a| a.unbounded_interval_start())
-            .max()
-            .unwrap_or(0);
-
-        // - Position the constants within those rows.
-        let fixed_allocations: Vec<_> = constants
-            .into_iter()
-            .map(|c| {
-                (
-                    c,
-                    column_allocations
-                        .get(&Column::<Any>::from(c).into())
-                        .cloned()
-                        .unwrap_or_default(),
-                )
-            })
-            .collect();
-        let constant_positions = || {
-            fixed_allocations.iter().flat_map(|(c, a)| {
-                let c = *c;
-                a.free_intervals(0, Some(first_unassigned_row))
-                    .flat_map(move |e| e.range().unwrap().map(move |i| (c, i)))
-            })
-        };
+        synthesize(
+            cs,
+            circuit,
+            config,
+            constants,
+            strategy::slot_in_biggest_advice_first,
+        )
+    }
+}
 
-        // Second pass:
-        // - Assign the regions.
-        let mut assign = AssignmentPass::new(&mut plan);
-        {
-            let pass = &mut assign;
-            circuit.synthesize(config, V1Pass::assign(pass))?;
-        }
-
-        // - Assign the constants.
-        if constant_positions().count() < plan.constants.len() {
-            return Err(Error::NotEnoughColumnsForConstants);
-        }
-        for ((fixed_column, fixed_row), (value, advice)) in
-            constant_positions().zip(plan.constants.into_iter())
-        {
-            plan.cs.assign_fixed(
-                || format!("Constant({:?})", value.evaluate()),
-                fixed_column,
-                fixed_row,
-                || Value::known(value),
-            )?;
-            plan.cs.copy(
-                fixed_column.into(),
-                fixed_row,
-                advice.column,
-                *plan.regions[*advice.region_index] + advice.row_offset,
-            )?;
-        }
+/// A [`FloorPlanner`] that packs regions into as few rows as it can.
+///
+/// This is the same as [`V1`], except that regions are positioned using whichever of
+/// several orderings leaves the fewest rows in use:
+/// - Regions are laid out using a greedy first-fit strategy, after sorting them by
+///   "advice area" (as [`V1`] does), by total area, by number of rows, by number of
+///   columns, or not at all.
+/// - If `SEARCH_BUDGET` is non-zero, the best of these orderings is then improved by
+///   swapping pairs of regions, trying at most `SEARCH_BUDGET` further layouts. Each
+///   layout tried takes time proportional to the number of regions.
+///
+/// Regions are measured without witnesses, and ties between layouts are broken
+/// deterministically, so keygen and proving always produce the same layout. Use
+/// [`CircuitCost`] to see how densely the regions are packed.
+///
+/// [`CircuitCost`]: crate::dev::CircuitCost
+#[derive(Debug)]
+pub struct ColumnPacking<const SEARCH_BUDGET: usize = 0>;
 
-        Ok(())
+impl<const SEARCH_BUDGET: usize> FloorPlanner for ColumnPacking<SEARCH_BUDGET> {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        synthesize(cs, circuit, config, constants, |region_shapes| {
+            strategy::pack_fewest_rows(region_shapes, SEARCH_BUDGET)
+        })
+    }
+}
+
+/// Synthesizes the circuit in two passes, positioning the measured regions with the
+/// given layout strategy.
+fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+    cs: &mut CS,
+    circuit: &C,
+    config: C::Config,
+    constants: Vec<Column<Fixed>>,
+    layout: impl FnOnce(Vec<RegionShape>) -> (Vec<RegionStart>, strategy::CircuitAllocations),
+) -> Result<(), Error> {
+    let mut plan = V1Plan::new(cs)?;
+
+    // First pass: measure the regions within the circuit.
+    let mut measure = MeasurementPass::new();
+    {
+        let pass = &mut measure;
+        circuit
+            .without_witnesses()
+            .synthesize(config.clone(), V1Pass::<_, CS>::measure(pass))?;
+    }
+
+    // Planning:
+    // - Position the regions.
+    let (regions, column_allocations) = layout(measure.regions);
+    // - Position the instances of each region template one after another.
+    plan.regions = regions
+        .into_iter()
+        .zip(measure.instances)
+        .flat_map(|(start, (count, rows))| {
+            (0..count).map(move |instance| (*start + instance * rows).into())
+        })
+        .collect();
+
+    // - Determine how many rows our planned circuit will require.
+    let first_unassigned_row = strategy::rows_used(&column_allocations);
+
+    // - Position the constants within those rows.
+    let fixed_allocations: Vec<_> = constants
+        .into_iter()
+        .map(|c| {
+            (
+                c,
+                column_allocations
+                    .get(&Column::<Any>::from(c).into())
+                    .cloned()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect();
+    let constant_positions = || {
+        fixed_allocations.iter().flat_map(|(c, a)| {
+            let c = *c;
+            a.free_intervals(0, Some(first_unassigned_row))
+                .flat_map(move |e| e.range().unwrap().map(move |i| (c, i)))
+        })
+    };
+
+    // Second pass:
+    // - Assign the regions.
+    let mut assign = AssignmentPass::new(&mut plan, measure.templates);
+    {
+        let pass = &mut assign;
+        circuit.synthesize(config, V1Pass::assign(pass))?;
+    }
+
+    // - Assign the constants.
+    let mut constant_positions = constant_positions();
+    for (value, advice) in plan.constants {
+        let (fixed_column, fixed_row) = plan.constant_pool.assign(plan.cs, value, || {
+            constant_positions
+                .next()
+                .ok_or(Error::NotEnoughColumnsForConstants)
+        })?;
+        plan.cs.copy(
+            fixed_column.into(),
+            fixed_row,
+            advice.column,
+            *plan.regions[*advice.region_index] + advice.row_offset,
+        )?;
     }
+
+    Ok(())
 }
 
 #[derive(Debug)]
@@ -175,6 +239,54 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for V1Pass<'p, 'a, F,
         }
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        match &mut self.0 {
+            Pass::Measurement(pass) => assignments
+                .into_iter()
+                .map(|assignment| pass.assign_region(assignment))
+                .collect(),
+            Pass::Assignment(pass) => pass.assign_regions(name, assignments),
+        }
+    }
+
+    fn assign_region_instances<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        count: usize,
+        assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(usize, Region<'_, F>) -> Result<AR, Error>,
+        AR: Clone,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        match &mut self.0 {
+            Pass::Measurement(pass) => pass.assign_region_instances(count, assignment),
+            Pass::Assignment(pass) => pass.assign_region_instances(name, count, assignment),
+        }
+    }
+
+    fn deduplicate_constants(&mut self)
+    where
+        F: PrimeField,
+    {
+        // Constants are only assigned after the assignment pass.
+        if let Pass::Assignment(pass) = &mut self.0 {
+            pass.plan.constant_pool.deduplicate();
+        }
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
@@ -209,12 +321,15 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for V1Pass<'p, 'a, F,
         N: FnOnce() -> NR,
     {
         if let Pass::Assignment(pass) = &mut self.0 {
-            pass.plan.cs.push_namespace(name_fn);
+            let name = name_fn().into();
+            pass.plan.namespace.push(name.clone());
+            pass.plan.cs.push_namespace(|| name);
         }
     }
 
     fn pop_namespace(&mut self, gadget_name: Option<String>) {
         if let Pass::Assignment(pass) = &mut self.0 {
+            pass.plan.namespace.pop();
             pass.plan.cs.pop_namespace(gadget_name);
         }
     }
@@ -224,18 +339,31 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> Layouter<F> for V1Pass<'p, 'a, F,
 #[derive(Debug)]
 pub struct MeasurementPass {
     regions: Vec<RegionShape>,
+    /// The number of instances in each of `regions`, and the number of rows taken up
+    /// by each instance. Regions that are not region templates have one instance.
+    instances: Vec<(usize, usize)>,
+    /// The shape of a single instance of each region template, in order.
+    templates: Vec<RegionShape>,
+    /// The index of the next region to be measured.
+    region_index: usize,
 }
 
 impl MeasurementPass {
     fn new() -> Self {
-        MeasurementPass { regions: vec![] }
+        MeasurementPass {
+            regions: vec![],
+            instances: vec![],
+            templates: vec![],
+            region_index: 0,
+        }
     }
 
     fn assign_region<F: Field, A, AR>(&mut self, mut assignment: A) -> Result<AR, Error>
     where
         A: FnMut(Region<'_, F>) -> Result<AR, Error>,
     {
-        let region_index = self.regions.len();
+        let region_index = self.region_index;
+        self.region_index += 1;
 
         // Get shape of the region.
         let mut shape = RegionShape::new(region_index.into());
@@ -243,10 +371,45 @@ impl MeasurementPass {
             let region: &mut dyn RegionLayouter<F> = &mut shape;
             assignment(region.into())
         }?;
+        self.instances.push((1, shape.row_count()));
         self.regions.push(shape);
 
         Ok(result)
     }
+
+    fn assign_region_instances<F: Field, A, AR>(
+        &mut self,
+        count: usize,
+        mut assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(usize, Region<'_, F>) -> Result<AR, Error>,
+        AR: Clone,
+    {
+        if count == 0 {
+            return Ok(vec![]);
+        }
+
+        // Every instance has the shape of the first, so only the first is measured. The
+        // assignment pass checks that the other instances fit in this shape.
+        let region_index = self.region_index;
+        self.region_index += count;
+
+        let mut shape = RegionShape::new(region_index.into());
+        let result = {
+            let region: &mut dyn RegionLayouter<F> = &mut shape;
+            assignment(0, region.into())
+        }?;
+
+        // Measure the instances as a single region, in which they are stacked.
+        let rows = shape.row_count();
+        self.templates.push(shape.clone());
+        shape.row_count = rows * count;
+        self.instances.push((count, rows));
+        self.regions.push(shape);
+
+        Ok(vec![result; count])
+    }
 }
 
 /// Assigns the circuit.
@@ -255,13 +418,19 @@ pub struct AssignmentPass<'p, 'a, F: Field, CS: Assignment<F> + 'a> {
     plan: &'p mut V1Plan<'a, F, CS>,
     /// Counter tracking which region we need to assign next.
     region_index: usize,
+    /// The measured shape of each region template.
+    templates: Vec<RegionShape>,
+    /// Counter tracking which region template we need to assign next.
+    template_index: usize,
 }
 
 impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
-    fn new(plan: &'p mut V1Plan<'a, F, CS>) -> Self {
+    fn new(plan: &'p mut V1Plan<'a, F, CS>, templates: Vec<RegionShape>) -> Self {
         AssignmentPass {
             plan,
             region_index: 0,
+            templates,
+            template_index: 0,
         }
     }
 
@@ -275,17 +444,108 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
         let region_index = self.region_index;
         self.region_index += 1;
 
-        self.plan.cs.enter_region(name);
+        self.plan.cs.enter_region(&name);
         let mut region = V1Region::new(self.plan, region_index.into());
         let result = {
             let region: &mut dyn RegionLayouter<F> = &mut region;
             assignment(region.into())
-        }?;
+        }
+        .map_err(|e| e.in_region(|| name().into(), &self.plan.namespace))?;
         self.plan.cs.exit_region();
 
         Ok(result)
     }
 
+    fn assign_region_instances<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        count: usize,
+        mut assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(usize, Region<'_, F>) -> Result<AR, Error>,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        if count == 0 {
+            return Ok(vec![]);
+        }
+
+        let template = &self.templates[self.template_index];
+        self.template_index += 1;
+
+        // The instances have been positioned, so are assigned like any other region,
+        // except that they may not use any cells outside the measured template.
+        (0..count)
+            .map(|instance| {
+                let region_index = self.region_index;
+                self.region_index += 1;
+
+                self.plan.cs.enter_region(&name);
+                let mut region =
+                    V1Region::new(self.plan, region_index.into()).with_template(template);
+                let result = {
+                    let region: &mut dyn RegionLayouter<F> = &mut region;
+                    assignment(instance, region.into())
+                }
+                .map_err(|e| e.in_region(|| name().into(), &self.plan.namespace))?;
+                self.plan.cs.exit_region();
+
+                Ok(result)
+            })
+            .collect()
+    }
+
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        // The measurement pass has already positioned these regions, so we can witness
+        // them independently of `self.plan.cs`, and then assign them in order.
+        let first_region = self.region_index;
+        self.region_index += assignments.len();
+
+        let recorded: Vec<_> = assignments
+            .into_iter()
+            .enumerate()
+            .map(|(i, assignment)| (RegionIndex::from(first_region + i), assignment))
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(region_index, mut assignment)| {
+                let mut region = RecordedRegion::new(region_index);
+                let result = {
+                    let region: &mut dyn RegionLayouter<F> = &mut region;
+                    assignment(region.into())
+                }?;
+                Ok((region, result))
+            })
+            .collect();
+
+        recorded
+            .into_iter()
+            .map(|recorded| {
+                let (recorded, result) = recorded
+                    .map_err(|e: Error| e.in_region(|| name().into(), &self.plan.namespace))?;
+
+                self.plan.cs.enter_region(&name);
+                let mut region = V1Region::new(self.plan, recorded.region_index);
+                recorded
+                    .replay(&mut region)
+                    .map_err(|e| e.in_region(|| name().into(), &self.plan.namespace))?;
+                self.plan.cs.exit_region();
+
+                Ok(result)
+            })
+            .collect()
+    }
+
     fn assign_table<A, AR, N, NR>(&mut self, name: N, mut assignment: A) -> Result<AR, Error>
     where
         A: FnMut(Table<'_, F>) -> Result<AR, Error>,
@@ -343,6 +603,8 @@ impl<'p, 'a, F: Field, CS: Assignment<F> + 'a> AssignmentPass<'p, 'a, F, CS> {
 struct V1Region<'r, 'a, F: Field, CS: Assignment<F> + 'a> {
     plan: &'r mut V1Plan<'a, F, CS>,
     region_index: RegionIndex,
+    /// The shape that this region must fit in, if it is an instance of a region template.
+    template: Option<&'r RegionShape>,
 }
 
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for V1Region<'r, 'a, F, CS> {
@@ -350,13 +612,37 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> fmt::Debug for V1Region<'r, 'a, F
         f.debug_struct("V1Region")
             .field("plan", &self.plan)
             .field("region_index", &self.region_index)
+            .field("template", &self.template)
             .finish()
     }
 }
 
 impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> V1Region<'r, 'a, F, CS> {
     fn new(plan: &'r mut V1Plan<'a, F, CS>, region_index: RegionIndex) -> Self {
-        V1Region { plan, region_index }
+        V1Region {
+            plan,
+            region_index,
+            template: None,
+        }
+    }
+
+    fn with_template(self, template: &'r RegionShape) -> Self {
+        V1Region {
+            template: Some(template),
+            ..self
+        }
+    }
+
+    /// Checks that a cell of this region lies within its template, if it has one.
+    fn check_template(&self, column: RegionColumn, offset: usize) -> Result<(), Error> {
+        match self.template {
+            Some(template)
+                if offset >= template.row_count() || !template.columns().contains(&column) =>
+            {
+                Err(Error::Synthesis)
+            }
+            _ => Ok(()),
+        }
     }
 }
 
@@ -367,6 +653,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         selector: &Selector,
         offset: usize,
     ) -> Result<(), Error> {
+        self.check_template((*selector).into(), offset)?;
         self.plan.cs.enable_selector(
             annotation,
             selector,
@@ -381,6 +668,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         offset: usize,
         to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
     ) -> Result<Cell, Error> {
+        self.check_template(Column::<Any>::from(column).into(), offset)?;
         self.plan.cs.assign_advice(
             annotation,
             column,
@@ -446,6 +734,7 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
         offset: usize,
         to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
     ) -> Result<Cell, Error> {
+        self.check_template(Column::<Any>::from(column).into(), offset)?;
         self.plan.cs.assign_fixed(
             annotation,
             column,
@@ -477,11 +766,195 @@ impl<'r, 'a, F: Field, CS: Assignment<F> + 'a> RegionLayouter<F> for V1Region<'r
     }
 }
 
+/// A region whose assignments are recorded, so that it can be witnessed without access
+/// to the [`Assignment`], and then replayed into a [`V1Region`].
+#[derive(Debug)]
+struct RecordedRegion<F: Field> {
+    region_index: RegionIndex,
+    assignments: Vec<RecordedAssignment<F>>,
+}
+
+#[derive(Debug)]
+enum RecordedAssignment<F: Field> {
+    Selector {
+        annotation: String,
+        selector: Selector,
+        offset: usize,
+    },
+    Advice {
+        annotation: String,
+        column: Column<Advice>,
+        offset: usize,
+        value: Value<Assigned<F>>,
+    },
+    Fixed {
+        annotation: String,
+        column: Column<Fixed>,
+        offset: usize,
+        value: Value<Assigned<F>>,
+    },
+    Constant {
+        cell: Cell,
+        constant: Assigned<F>,
+    },
+    Equality {
+        left: Cell,
+        right: Cell,
+    },
+}
+
+impl<F: Field> RecordedRegion<F> {
+    fn new(region_index: RegionIndex) -> Self {
+        RecordedRegion {
+            region_index,
+            assignments: vec![],
+        }
+    }
+
+    /// Replays the recorded assignments, in the order they were made.
+    fn replay(self, region: &mut dyn RegionLayouter<F>) -> Result<(), Error> {
+        for assignment in self.assignments {
+            match assignment {
+                RecordedAssignment::Selector {
+                    annotation,
+                    selector,
+                    offset,
+                } => region.enable_selector(&|| annotation.clone(), &selector, offset)?,
+                RecordedAssignment::Advice {
+                    annotation,
+                    column,
+                    offset,
+                    value,
+                } => {
+                    region.assign_advice(&|| annotation.clone(), column, offset, &mut || value)?;
+                }
+                RecordedAssignment::Fixed {
+                    annotation,
+                    column,
+                    offset,
+                    value,
+                } => {
+                    region.assign_fixed(&|| annotation.clone(), column, offset, &mut || value)?;
+                }
+                RecordedAssignment::Constant { cell, constant } => {
+                    region.constrain_constant(cell, constant)?
+                }
+                RecordedAssignment::Equality { left, right } => {
+                    region.constrain_equal(left, right)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<F: Field> RegionLayouter<F> for RecordedRegion<F> {
+    fn enable_selector<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        selector: &Selector,
+        offset: usize,
+    ) -> Result<(), Error> {
+        self.assignments.push(RecordedAssignment::Selector {
+            annotation: annotation(),
+            selector: *selector,
+            offset,
+        });
+        Ok(())
+    }
+
+    fn assign_advice<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.assignments.push(RecordedAssignment::Advice {
+            annotation: annotation(),
+            column,
+            offset,
+            value: to(),
+        });
+
+        Ok(Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column: column.into(),
+        })
+    }
+
+    fn assign_advice_from_constant<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Advice>,
+        offset: usize,
+        constant: Assigned<F>,
+    ) -> Result<Cell, Error> {
+        let advice =
+            self.assign_advice(annotation, column, offset, &mut || Value::known(constant))?;
+        self.constrain_constant(advice, constant)?;
+
+        Ok(advice)
+    }
+
+    fn assign_advice_from_instance<'v>(
+        &mut self,
+        _: &'v (dyn Fn() -> String + 'v),
+        _: Column<Instance>,
+        _: usize,
+        _: Column<Advice>,
+        _: usize,
+    ) -> Result<(Cell, Value<F>), Error> {
+        // Instance values can only be read from the `Assignment`.
+        Err(Error::Synthesis)
+    }
+
+    fn instance_value(&mut self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        // Instance values can only be read from the `Assignment`.
+        Err(Error::Synthesis)
+    }
+
+    fn assign_fixed<'v>(
+        &'v mut self,
+        annotation: &'v (dyn Fn() -> String + 'v),
+        column: Column<Fixed>,
+        offset: usize,
+        to: &'v mut (dyn FnMut() -> Value<Assigned<F>> + 'v),
+    ) -> Result<Cell, Error> {
+        self.assignments.push(RecordedAssignment::Fixed {
+            annotation: annotation(),
+            column,
+            offset,
+            value: to(),
+        });
+
+        Ok(Cell {
+            region_index: self.region_index,
+            row_offset: offset,
+            column: column.into(),
+        })
+    }
+
+    fn constrain_constant(&mut self, cell: Cell, constant: Assigned<F>) -> Result<(), Error> {
+        self.assignments
+            .push(RecordedAssignment::Constant { cell, constant });
+        Ok(())
+    }
+
+    fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error> {
+        self.assignments
+            .push(RecordedAssignment::Equality { left, right });
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pasta_curves::vesta;
 
     use crate::{
+        circuit::{Layouter, Value},
         dev::MockProver,
         plonk::{Advice, Circuit, Column, Error},
     };
@@ -529,4 +1002,376 @@ mod tests {
             Error::NotEnoughColumnsForConstants,
         ));
     }
+
+    #[test]
+    fn assign_regions_matches_assign_region() {
+        use crate::{
+            circuit::Region,
+            plonk::{keygen_vk, Selector},
+            poly::{commitment::Params, Rotation},
+        };
+
+        #[derive(Clone)]
+        struct MyConfig {
+            a: Column<Advice>,
+            b: Column<Advice>,
+            q: Selector,
+        }
+
+        struct MyCircuit {
+            parallel: bool,
+            values: Vec<Value<vesta::Scalar>>,
+        }
+
+        impl Circuit<vesta::Scalar> for MyCircuit {
+            type Config = MyConfig;
+            type FloorPlanner = super::V1;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    parallel: self.parallel,
+                    values: vec![Value::unknown(); self.values.len()],
+                }
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let a = meta.advice_column();
+                let b = meta.advice_column();
+                let q = meta.selector();
+                meta.enable_equality(a);
+                meta.enable_equality(b);
+                meta.create_gate("square", |meta| {
+                    let q = meta.query_selector(q);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    let b = meta.query_advice(b, Rotation::cur());
+                    vec![q * (a.clone() * a - b)]
+                });
+                MyConfig { a, b, q }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                let square = |value: Value<vesta::Scalar>| {
+                    let config = config.clone();
+                    move |mut region: Region<'_, vesta::Scalar>| {
+                        config.q.enable(&mut region, 0)?;
+                        region.assign_advice(|| "a", config.a, 0, || value)?;
+                        region.assign_advice(|| "b", config.b, 0, || value * value)
+                    }
+                };
+
+                let squares = if self.parallel {
+                    layouter.assign_regions(
+                        || "square",
+                        self.values.iter().map(|value| square(*value)).collect(),
+                    )?
+                } else {
+                    self.values
+                        .iter()
+                        .map(|value| layouter.assign_region(|| "square", square(*value)))
+                        .collect::<Result<Vec<_>, _>>()?
+                };
+
+                // The results of each region can be used in later regions.
+                layouter.assign_region(
+                    || "copy squares",
+                    |mut region| {
+                        for (offset, square) in squares.iter().enumerate() {
+                            square.copy_advice(|| "square", &mut region, config.a, offset)?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        const K: u32 = 5;
+        let values: Vec<_> = (1..=8)
+            .map(|i| Value::known(vesta::Scalar::from(i)))
+            .collect();
+        let params = Params::<pasta_curves::EqAffine>::new(K);
+
+        let pinned: Vec<_> = [false, true]
+            .into_iter()
+            .map(|parallel| {
+                let circuit = MyCircuit {
+                    parallel,
+                    values: values.clone(),
+                };
+                let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+                assert_eq!(prover.verify(), Ok(()));
+
+                let vk = keygen_vk(&params, &circuit).unwrap();
+                format!("{:?}", vk.pinned())
+            })
+            .collect();
+        assert_eq!(pinned[0], pinned[1]);
+    }
+
+    struct TemplateCircuit {
+        values: Vec<Value<vesta::Scalar>>,
+        // The instance that (incorrectly) uses an extra row.
+        misshapen: Option<usize>,
+    }
+
+    impl Circuit<vesta::Scalar> for TemplateCircuit {
+        type Config = (Column<Advice>, crate::plonk::Selector);
+        type FloorPlanner = super::V1;
+
+        fn without_witnesses(&self) -> Self {
+            TemplateCircuit {
+                values: vec![Value::unknown(); self.values.len()],
+                misshapen: self.misshapen,
+            }
+        }
+
+        fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+            let a = meta.advice_column();
+            let q = meta.selector();
+            meta.enable_equality(a);
+            meta.create_gate("double", |meta| {
+                let q = meta.query_selector(q);
+                let cur = meta.query_advice(a, crate::poly::Rotation::cur());
+                let next = meta.query_advice(a, crate::poly::Rotation::next());
+                vec![q * (next - cur.clone() - cur)]
+            });
+            (a, q)
+        }
+
+        fn synthesize(
+            &self,
+            (a, q): Self::Config,
+            mut layouter: impl Layouter<vesta::Scalar>,
+        ) -> Result<(), Error> {
+            let doubled = layouter.assign_region_instances(
+                || "double",
+                self.values.len(),
+                |instance, mut region| {
+                    let value = self.values[instance];
+                    q.enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", a, 0, || value)?;
+                    if self.misshapen == Some(instance) {
+                        region.assign_advice(|| "extra", a, 2, || value)?;
+                    }
+                    region.assign_advice(|| "doubled", a, 1, || value + value)
+                },
+            )?;
+
+            layouter.assign_region(
+                || "sum",
+                |mut region| {
+                    for (offset, doubled) in doubled.iter().enumerate() {
+                        doubled.copy_advice(|| "doubled", &mut region, a, offset)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn region_instances() {
+        let circuit = TemplateCircuit {
+            values: (0..20)
+                .map(|i| Value::known(vesta::Scalar::from(i)))
+                .collect(),
+            misshapen: None,
+        };
+        let prover = MockProver::run(7, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // The instances are laid out one after another.
+        let rows = crate::dev::CircuitRows::measure(&circuit).unwrap();
+        let offsets: Vec<_> = rows
+            .regions()
+            .iter()
+            .filter(|region| region.name == "double")
+            .map(|region| region.offset.unwrap())
+            .collect();
+        assert_eq!(offsets, (0..20).map(|i| 2 * i).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn region_instances_must_match_template() {
+        let circuit = TemplateCircuit {
+            values: vec![Value::known(vesta::Scalar::one()); 5],
+            misshapen: Some(3),
+        };
+        assert!(matches!(
+            MockProver::run(7, &circuit, vec![]).unwrap_err(),
+            Error::InRegion { cause, .. } if matches!(*cause, Error::Synthesis),
+        ));
+    }
+
+    #[test]
+    fn column_packing_uses_fewer_rows() {
+        use std::marker::PhantomData;
+
+        use crate::{circuit::SimpleFloorPlanner, dev::CircuitCost, plonk::FloorPlanner};
+
+        struct MyCircuit<P: FloorPlanner>(PhantomData<P>);
+
+        impl<P: FloorPlanner> Circuit<vesta::Scalar> for MyCircuit<P> {
+            type Config = [Column<Advice>; 3];
+            type FloorPlanner = P;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit(PhantomData)
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                [
+                    meta.advice_column(),
+                    meta.advice_column(),
+                    meta.advice_column(),
+                ]
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                // Regions that the `V1` ordering packs poorly.
+                for (columns, rows) in [
+                    (&[2][..], 8),
+                    (&[1, 2][..], 5),
+                    (&[0][..], 6),
+                    (&[0, 1][..], 6),
+                ] {
+                    layouter.assign_region(
+                        || "region",
+                        |mut region| {
+                            for &column in columns {
+                                for offset in 0..rows {
+                                    region.assign_advice(
+                                        || "zero",
+                                        config[column],
+                                        offset,
+                                        || Value::known(vesta::Scalar::zero()),
+                                    )?;
+                                }
+                            }
+                            Ok(())
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        fn utilisation<P: FloorPlanner>() -> f64 {
+            let circuit = MyCircuit::<P>(PhantomData);
+            let prover = MockProver::run(6, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            CircuitCost::<pasta_curves::vesta::Point, _>::measure(6, &circuit).utilisation()
+        }
+
+        // The regions cover 36 cells of the 3 columns, so the utilisation reflects the
+        // number of rows used.
+        assert_eq!(utilisation::<SimpleFloorPlanner>(), 36.0 / (3.0 * 19.0));
+        assert_eq!(utilisation::<super::V1>(), 36.0 / (3.0 * 19.0));
+        assert_eq!(utilisation::<super::ColumnPacking>(), 36.0 / (3.0 * 17.0));
+        assert_eq!(
+            utilisation::<super::ColumnPacking<100>>(),
+            36.0 / (3.0 * 13.0)
+        );
+    }
+
+    #[test]
+    fn constants_are_deduplicated() {
+        use std::marker::PhantomData;
+
+        use crate::{circuit::SimpleFloorPlanner, plonk::FloorPlanner};
+
+        struct MyCircuit<P: FloorPlanner> {
+            deduplicate: bool,
+            _marker: PhantomData<P>,
+        }
+
+        impl<P: FloorPlanner> Circuit<vesta::Scalar> for MyCircuit<P> {
+            type Config = [Column<Advice>; 2];
+            type FloorPlanner = P;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    deduplicate: self.deduplicate,
+                    _marker: PhantomData,
+                }
+            }
+
+            fn configure(meta: &mut crate::plonk::ConstraintSystem<vesta::Scalar>) -> Self::Config {
+                let constants = meta.fixed_column();
+                meta.enable_constant(constants);
+
+                let advice = [meta.advice_column(), meta.advice_column()];
+                for column in advice {
+                    meta.enable_equality(column);
+                }
+                advice
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<vesta::Scalar>,
+            ) -> Result<(), Error> {
+                if self.deduplicate {
+                    layouter.deduplicate_constants();
+                }
+
+                // Each region uses the same constant many times, which would not fit in
+                // the constants column if every use were assigned its own fixed cell.
+                for (column, other) in [(0, 1), (1, 2)] {
+                    layouter.assign_region(
+                        || "constants",
+                        |mut region| {
+                            for offset in 0..8 {
+                                region.assign_advice_from_constant(
+                                    || "one",
+                                    config[column],
+                                    offset,
+                                    vesta::Scalar::one(),
+                                )?;
+                            }
+                            region.assign_advice_from_constant(
+                                || "other",
+                                config[column],
+                                8,
+                                vesta::Scalar::from(other),
+                            )?;
+                            Ok(())
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+        }
+
+        fn check<P: FloorPlanner>() {
+            let circuit = MyCircuit::<P> {
+                deduplicate: true,
+                _marker: PhantomData,
+            };
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+
+            // Deduplication is opt-in.
+            let circuit = MyCircuit::<P> {
+                deduplicate: false,
+                _marker: PhantomData,
+            };
+            assert!(matches!(
+                MockProver::run(4, &circuit, vec![]).unwrap_err(),
+                Error::NotEnoughRowsAvailable { .. } | Error::NotEnoughColumnsForConstants,
+            ));
+        }
+
+        check::<SimpleFloorPlanner>();
+        check::<super::V1>();
+        check::<super::ColumnPacking>();
+    }
 }
@@ -241,6 +241,138 @@ pub fn slot_in_biggest_advice_first(
     (regions, column_allocations)
 }
 
+/// Returns the number of rows used by the given column allocations.
+pub(crate) fn rows_used(column_allocations: &CircuitAllocations) -> usize {
+    column_allocations
+        .values()
+        .map(|a| a.unbounded_interval_start())
+        .max()
+        .unwrap_or(0)
+}
+
+/// A layout of the regions, as produced by [`slot_in`].
+struct Packing {
+    regions: Vec<RegionStart>,
+    column_allocations: CircuitAllocations,
+    rows: usize,
+}
+
+impl Packing {
+    /// Lays out the regions in the given order with the [`slot_in`] strategy.
+    fn new(region_shapes: &[RegionShape], order: &[usize]) -> Self {
+        let (mut regions, column_allocations) =
+            slot_in(order.iter().map(|&i| region_shapes[i].clone()).collect());
+
+        // Un-sort the regions so they match the original indexing.
+        regions.sort_unstable_by_key(|(_, region)| region.region_index().0);
+        let regions = regions.into_iter().map(|(start, _)| start).collect();
+
+        let rows = rows_used(&column_allocations);
+        Packing {
+            regions,
+            column_allocations,
+            rows,
+        }
+    }
+}
+
+/// Lays out the regions with the [`slot_in`] strategy, using whichever of several
+/// orderings of the regions uses the fewest rows.
+///
+/// The orderings tried are:
+/// - the ordering used by [`slot_in_biggest_advice_first`];
+/// - by decreasing area (number of columns * rows);
+/// - by decreasing number of rows;
+/// - by decreasing number of columns;
+/// - by region index.
+///
+/// If `search_budget` is non-zero, the best of these orderings is then improved by
+/// swapping pairs of regions, keeping any swap that reduces the number of rows used,
+/// until no swap helps or `search_budget` layouts have been tried.
+///
+/// Ties are always broken in favour of the earliest ordering tried, so the layout is
+/// deterministic.
+pub fn pack_fewest_rows(
+    region_shapes: Vec<RegionShape>,
+    search_budget: usize,
+) -> (Vec<RegionStart>, CircuitAllocations) {
+    // `region_shapes` is sorted by region index, which we rely on to index the orderings.
+    let num_regions = region_shapes.len();
+    let advice_cols = |shape: &RegionShape| {
+        shape
+            .columns()
+            .iter()
+            .filter(|c| match c {
+                RegionColumn::Column(c) => matches!(c.column_type(), Any::Advice),
+                _ => false,
+            })
+            .count()
+    };
+    // Sorts by decreasing key; the sort is stable, so ties are ordered by region index.
+    let ordered_by = |key: &dyn Fn(&RegionShape) -> usize| {
+        let mut order: Vec<_> = (0..num_regions).collect();
+        order.sort_by_key(|&i| cmp::Reverse(key(&region_shapes[i])));
+        order
+    };
+
+    let mut orderings = vec![];
+    // `slot_in_biggest_advice_first` reverses an ascending sort, so ties are ordered
+    // by decreasing region index.
+    orderings.push({
+        let mut order: Vec<_> = (0..num_regions).collect();
+        order.sort_by_key(|&i| advice_cols(&region_shapes[i]) * region_shapes[i].row_count());
+        order.reverse();
+        order
+    });
+    orderings.push(ordered_by(&|shape| {
+        shape.columns().len() * shape.row_count()
+    }));
+    orderings.push(ordered_by(&|shape| shape.row_count()));
+    orderings.push(ordered_by(&|shape| shape.columns().len()));
+    orderings.push((0..num_regions).collect());
+
+    let (mut order, mut best) = orderings
+        .into_iter()
+        .map(|order| {
+            let packing = Packing::new(&region_shapes, &order);
+            (order, packing)
+        })
+        .reduce(|best, candidate| {
+            if candidate.1.rows < best.1.rows {
+                candidate
+            } else {
+                best
+            }
+        })
+        .expect("at least one ordering is tried");
+
+    // Hill-climb from the best ordering by swapping pairs of regions.
+    let mut layouts_tried = 0;
+    let mut improved = true;
+    'search: while improved {
+        improved = false;
+        for i in 0..num_regions {
+            for j in (i + 1)..num_regions {
+                if layouts_tried >= search_budget {
+                    break 'search;
+                }
+                layouts_tried += 1;
+
+                order.swap(i, j);
+                let candidate = Packing::new(&region_shapes, &order);
+                if candidate.rows < best.rows {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    order.swap(i, j);
+                }
+            }
+        }
+    }
+
+    (best.regions, best.column_allocations)
+}
+
 #[test]
 fn test_slot_in() {
     use crate::plonk::Column;
@@ -280,3 +412,36 @@ fn test_slot_in() {
         vec![0.into(), 0.into(), 15.into()]
     );
 }
+
+#[test]
+fn test_pack_fewest_rows() {
+    use crate::plonk::Column;
+
+    let shape = |region_index: usize, columns: &[usize], row_count| RegionShape {
+        region_index: region_index.into(),
+        columns: columns
+            .iter()
+            .map(|&c| Column::new(c, Any::Advice).into())
+            .collect(),
+        row_count,
+    };
+    let regions = vec![
+        shape(0, &[2], 8),
+        shape(1, &[1, 2], 5),
+        shape(2, &[0], 6),
+        shape(3, &[0, 1], 6),
+    ];
+
+    let (_, v1) = slot_in_biggest_advice_first(regions.clone());
+    assert_eq!(rows_used(&v1), 19);
+
+    // Trying other orderings finds a better layout.
+    let (starts, packed) = pack_fewest_rows(regions.clone(), 0);
+    assert_eq!(rows_used(&packed), 17);
+    assert_eq!(starts, vec![0.into(), 12.into(), 0.into(), 6.into()]);
+
+    // Searching finds an optimal layout.
+    let (starts, packed) = pack_fewest_rows(regions, 100);
+    assert_eq!(rows_used(&packed), 13);
+    assert_eq!(starts, vec![0.into(), 8.into(), 6.into(), 0.into()]);
+}
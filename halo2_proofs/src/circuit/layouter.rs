@@ -1,16 +1,16 @@
 //! Implementations of common circuit layouters.
 
 use std::cmp;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 
-use ff::Field;
+use ff::{Field, PrimeField};
 
 #[deprecated(note = "use halo2_proofs::circuit::TableLayouter instead")]
 pub use super::table_layouter::TableLayouter;
 
 use super::{Cell, RegionIndex, Value};
-use crate::plonk::{Advice, Any, Assigned, Column, Error, Fixed, Instance, Selector};
+use crate::plonk::{Advice, Any, Assigned, Assignment, Column, Error, Fixed, Instance, Selector};
 
 /// Helper trait for implementing a custom [`Layouter`].
 ///
@@ -112,6 +112,65 @@ pub trait RegionLayouter<F: Field>: fmt::Debug {
     fn constrain_equal(&mut self, left: Cell, right: Cell) -> Result<(), Error>;
 }
 
+/// The fixed cells to which a layouter has assigned constants.
+///
+/// By default every use of a constant is assigned its own fixed cell. Once
+/// [`ConstantPool::deduplicate`] has been called, each distinct constant is instead
+/// assigned to a single fixed cell, to which all of its uses are copy-constrained.
+#[derive(Debug)]
+pub(crate) struct ConstantPool<F: Field> {
+    /// Computes the key identifying a constant, if deduplication is enabled.
+    key: Option<fn(&F) -> Vec<u8>>,
+    cells: HashMap<Vec<u8>, (Column<Fixed>, usize)>,
+}
+
+impl<F: Field> ConstantPool<F> {
+    pub(crate) fn new() -> Self {
+        ConstantPool {
+            key: None,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Reuses the fixed cells of constants that are assigned from now on.
+    pub(crate) fn deduplicate(&mut self)
+    where
+        F: PrimeField,
+    {
+        self.key = Some(|value| value.to_repr().as_ref().to_vec());
+    }
+
+    /// Returns the fixed cell containing `constant`. If the constant has not been
+    /// assigned yet (or deduplication is disabled), it is assigned to the cell returned
+    /// by `next_cell`.
+    pub(crate) fn assign<CS: Assignment<F>>(
+        &mut self,
+        cs: &mut CS,
+        constant: Assigned<F>,
+        next_cell: impl FnOnce() -> Result<(Column<Fixed>, usize), Error>,
+    ) -> Result<(Column<Fixed>, usize), Error> {
+        let value = constant.evaluate();
+        let key = self.key.map(|key| key(&value));
+
+        if let Some(&(column, row)) = key.as_ref().and_then(|key| self.cells.get(key)) {
+            return Ok((column, row));
+        }
+
+        let (column, row) = next_cell()?;
+        cs.assign_fixed(
+            || format!("Constant({:?})", value),
+            column,
+            row,
+            || Value::known(constant),
+        )?;
+        if let Some(key) = key {
+            self.cells.insert(key, (column, row));
+        }
+
+        Ok((column, row))
+    }
+}
+
 /// The shape of a region. For a region at a certain index, we track
 /// the set of columns it uses as well as the number of rows it uses.
 #[derive(Clone, Debug)]
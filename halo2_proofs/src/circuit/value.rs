@@ -100,6 +100,25 @@ impl<V> Value<V> {
         }
     }
 
+    /// Maps a `Value<V>` to `Value<W>` by applying a fallible function to the contained
+    /// value, returning the error if the function fails.
+    ///
+    /// If `self` is [`Value::unknown()`], `f` is not called and
+    /// `Ok(Value::unknown())` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use halo2_proofs::{circuit::Value, plonk::Error};
+    ///
+    /// let byte = Value::known(300u32)
+    ///     .try_map(|v| u8::try_from(v).map_err(|_| Error::Witness("not a byte".into())));
+    /// assert!(matches!(byte, Err(Error::Witness(_))));
+    /// ```
+    pub fn try_map<W, E, F: FnOnce(V) -> Result<W, E>>(self, f: F) -> Result<Value<W>, E> {
+        self.map(f).transpose_result()
+    }
+
     /// Returns [`Value::unknown()`] if the value is [`Value::unknown()`], otherwise calls
     /// `f` with the wrapped value and returns the result.
     pub fn and_then<W, F: FnOnce(V) -> Value<W>>(self, f: F) -> Value<W> {
@@ -134,6 +153,20 @@ impl<V, W> Value<(V, W)> {
     }
 }
 
+impl<V, E> Value<Result<V, E>> {
+    /// Transposes a `Value` of a [`Result`] into a [`Result`] of a `Value`.
+    ///
+    /// `Value::known(Ok(v))` is mapped to `Ok(Value::known(v))`, `Value::known(Err(e))`
+    /// to `Err(e)`, and [`Value::unknown()`] to `Ok(Value::unknown())`.
+    pub fn transpose_result(self) -> Result<Value<V>, E> {
+        match self.inner {
+            Some(Ok(v)) => Ok(Value::known(v)),
+            Some(Err(e)) => Err(e),
+            None => Ok(Value::unknown()),
+        }
+    }
+}
+
 impl<V> Value<&V> {
     /// Maps a `Value<&V>` to a `Value<V>` by copying the contents of the value.
     #[must_use = "`self` will be dropped if the result is not used"]
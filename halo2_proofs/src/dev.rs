@@ -1,5 +1,6 @@
 //! Tools for developing circuits.
 
+use std::cmp;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::iter;
@@ -12,7 +13,7 @@ use crate::{
     circuit,
     plonk::{
         permutation, Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Error, Expression,
-        Fixed, FloorPlanner, Instance, Selector,
+        Fixed, FloorPlanner, GateRotations, Instance, Selector,
     },
 };
 
@@ -23,7 +24,7 @@ mod failure;
 pub use failure::{FailureLocation, VerifyFailure};
 
 pub mod cost;
-pub use cost::CircuitCost;
+pub use cost::{CircuitCost, CircuitRows};
 
 mod gates;
 pub use gates::CircuitGates;
@@ -31,6 +32,12 @@ pub use gates::CircuitGates;
 mod tfp;
 pub use tfp::TracingFloorPlanner;
 
+mod profile;
+pub use profile::{ProfilingFloorPlanner, StackStats, WitnessProfile};
+
+pub mod test_vectors;
+pub use test_vectors::{TestRng, TestVector};
+
 #[cfg(feature = "dev-graph")]
 mod graph;
 
@@ -265,6 +272,7 @@ impl<F: Field> Mul<F> for Value<F> {
 ///     MockProver::<Fp>::run(2, &circuit, vec![]).unwrap_err(),
 ///     Error::NotEnoughRowsAvailable {
 ///         current_k,
+///         ..
 ///     } if current_k == 2,
 /// ));
 /// ```
@@ -288,6 +296,7 @@ pub struct MockProver<F: Field> {
     instance: Vec<Vec<InstanceValue<F>>>,
 
     selectors: Vec<Vec<bool>>,
+    gate_rotations: GateRotations,
 
     permutation: permutation::keygen::Assembly,
 
@@ -336,7 +345,7 @@ impl<F: Field> Assignment<F> for MockProver<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
 
         // Track that this selector was enabled. We require that all selectors are enabled
@@ -360,7 +369,7 @@ impl<F: Field> Assignment<F> for MockProver<F> {
         row: usize,
     ) -> Result<circuit::Value<F>, Error> {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
 
         self.instance
@@ -384,7 +393,7 @@ impl<F: Field> Assignment<F> for MockProver<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
 
         if let Some(region) = self.current_region.as_mut() {
@@ -416,7 +425,7 @@ impl<F: Field> Assignment<F> for MockProver<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
 
         if let Some(region) = self.current_region.as_mut() {
@@ -442,7 +451,11 @@ impl<F: Field> Assignment<F> for MockProver<F> {
         right_row: usize,
     ) -> Result<(), crate::plonk::Error> {
         if !self.usable_rows.contains(&left_row) || !self.usable_rows.contains(&right_row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(
+                self.k,
+                self.usable_rows.end,
+                cmp::max(left_row, right_row),
+            ));
         }
 
         self.permutation
@@ -456,7 +469,11 @@ impl<F: Field> Assignment<F> for MockProver<F> {
         to: circuit::Value<Assigned<F>>,
     ) -> Result<(), Error> {
         if !self.usable_rows.contains(&from_row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(
+                self.k,
+                self.usable_rows.end,
+                from_row,
+            ));
         }
 
         for row in self.usable_rows.clone().skip(from_row) {
@@ -494,7 +511,7 @@ impl<F: Field + Ord> MockProver<F> {
         let cs = cs;
 
         if n < cs.minimum_rows() {
-            return Err(Error::not_enough_rows_available(k));
+            return Err(Error::not_enough_rows_available(k, cs.minimum_rows()));
         }
 
         if instance.len() != cs.num_instance_columns {
@@ -536,6 +553,7 @@ impl<F: Field + Ord> MockProver<F> {
         ];
         let permutation = permutation::keygen::Assembly::new(n, &cs.permutation);
         let constants = cs.constants.clone();
+        let gate_rotations = GateRotations::new(&cs);
 
         let mut prover = MockProver {
             k,
@@ -547,6 +565,7 @@ impl<F: Field + Ord> MockProver<F> {
             advice,
             instance,
             selectors,
+            gate_rotations,
             permutation,
             usable_rows: 0..usable_rows,
         };
@@ -903,6 +922,41 @@ impl<F: Field + Ord> MockProver<F> {
         }
     }
 
+    /// Checks that no selector is enabled on a row where one of its gates queries a row
+    /// before the start of the circuit, or one of the rows reserved for blinding factors.
+    ///
+    /// Such queries wrap around the domain or read blinding factors. Keygen rejects
+    /// circuits that make them, but `MockProver::run` does not, so this reports them
+    /// without generating keys. It returns the first offending gate as an
+    /// [`Error::QueryOutsideUsableRows`], as [`keygen_vk`] does.
+    ///
+    /// [`keygen_vk`]: crate::plonk::keygen_vk
+    ///
+    /// Only gates that query a simple selector are checked. Gates without a selector
+    /// apply on every row, and are skipped.
+    pub fn check_gate_rotations(&self) -> Result<(), Error> {
+        for region in &self.regions {
+            let mut selectors: Vec<_> = region.enabled_selectors.iter().collect();
+            selectors.sort_by_key(|(selector, _)| selector.0);
+            for (selector, rows) in selectors {
+                for &row in rows {
+                    self.gate_rotations
+                        .check(selector, row, &self.usable_rows)
+                        .map_err(|(gate, rotation)| {
+                            Error::query_outside_usable_rows(
+                                gate,
+                                Some(&region.name),
+                                row,
+                                rotation,
+                                self.k,
+                            )
+                        })?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Panics if the circuit being checked by this `MockProver` is not satisfied.
     ///
     /// Any verification failures will be pretty-printed to stderr before the function
@@ -1011,6 +1065,180 @@ mod tests {
         );
     }
 
+    #[test]
+    fn not_enough_rows_available() {
+        struct MyCircuit {
+            rows: usize,
+        }
+
+        impl Circuit<Fp> for MyCircuit {
+            type Config = Column<Advice>;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                meta.advice_column()
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self { rows: self.rows }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "rows",
+                    |mut region| {
+                        for offset in 0..self.rows {
+                            region.assign_advice(
+                                || "a",
+                                config,
+                                offset,
+                                || Value::known(Fp::ZERO),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let circuit = MyCircuit { rows: 20 };
+        let required_rows = match MockProver::run(4, &circuit, vec![]).unwrap_err() {
+            Error::NotEnoughRowsAvailable {
+                current_k: 4,
+                required_rows: Some(required_rows),
+            } => required_rows,
+            e => panic!("unexpected error: {:?}", e),
+        };
+        // Synthesis stops at the first row that is not usable, so this is a lower bound.
+        assert!(required_rows > 1 << 4);
+
+        // Here, the circuit fits in the number of rows that were found to be needed.
+        let k = required_rows.next_power_of_two().trailing_zeros();
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn check_gate_rotations() {
+        const K: u32 = 4;
+
+        #[derive(Clone)]
+        struct FaultyCircuitConfig {
+            a: Column<Advice>,
+            q: Selector,
+        }
+
+        struct FaultyCircuit {
+            row: usize,
+            last_row: usize,
+        }
+
+        impl Circuit<Fp> for FaultyCircuit {
+            type Config = FaultyCircuitConfig;
+            type FloorPlanner = SimpleFloorPlanner;
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.selector();
+
+                meta.create_gate("Running sum", |cells| {
+                    let prev = cells.query_advice(a, Rotation::prev());
+                    let cur = cells.query_advice(a, Rotation::cur());
+                    let next = cells.query_advice(a, Rotation::next());
+                    let q = cells.query_selector(q);
+
+                    vec![q * (prev + cur - next)]
+                });
+
+                FaultyCircuitConfig { a, q }
+            }
+
+            fn without_witnesses(&self) -> Self {
+                Self {
+                    row: self.row,
+                    last_row: self.last_row,
+                }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_region(
+                    || "Faulty synthesis",
+                    |mut region| {
+                        config.q.enable(&mut region, self.row)?;
+                        for row in 0..=std::cmp::min(self.row + 1, self.last_row) {
+                            region.assign_advice(
+                                || "a",
+                                config.a,
+                                row,
+                                || Value::known(Fp::ZERO),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            }
+        }
+
+        let cs = {
+            let mut cs = ConstraintSystem::<Fp>::default();
+            FaultyCircuit::configure(&mut cs);
+            cs
+        };
+        assert_eq!(cs.rotation_padding(), (1, 1));
+        let usable_rows = (1 << K) - (cs.blinding_factors() + 1);
+
+        // Rows that leave room for the gate's rotations are fine.
+        let circuit = FaultyCircuit {
+            row: 1,
+            last_row: usable_rows - 1,
+        };
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        assert!(prover.check_gate_rotations().is_ok());
+
+        let params = crate::poly::commitment::Params::<pasta_curves::EqAffine>::new(K);
+        assert!(crate::plonk::keygen_vk(&params, &circuit).is_ok());
+
+        // Rows where the gate would query before the first row, or into the rows
+        // reserved for blinding factors, are rejected by keygen and reported by the lint.
+        for (row, expected_rotation) in [(0, -1), (usable_rows - 1, 1)] {
+            let circuit = FaultyCircuit {
+                row,
+                last_row: usable_rows - 1,
+            };
+            let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+            for error in [
+                prover.check_gate_rotations().unwrap_err(),
+                crate::plonk::keygen_vk(&params, &circuit).unwrap_err(),
+            ] {
+                match error {
+                    Error::QueryOutsideUsableRows {
+                        gate,
+                        region,
+                        row: err_row,
+                        rotation,
+                        ..
+                    } => {
+                        assert_eq!(gate, "Running sum");
+                        assert_eq!(region.as_deref(), Some("Faulty synthesis"));
+                        assert_eq!(err_row, row);
+                        assert_eq!(rotation, expected_rotation);
+                    }
+                    e => panic!("unexpected error: {:?}", e),
+                }
+            }
+        }
+    }
+
     #[test]
     fn bad_lookup() {
         const K: u32 = 4;
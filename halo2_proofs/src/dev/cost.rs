@@ -15,7 +15,7 @@ use crate::{
     circuit::{layouter::RegionColumn, Value},
     plonk::{
         Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
-        FloorPlanner, Instance, Selector,
+        FloorPlanner, GateRotations, Instance, Selector,
     },
     poly::Rotation,
 };
@@ -50,6 +50,8 @@ pub struct CircuitCost<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> {
     num_advice_columns: usize,
     num_instance_columns: usize,
     num_total_columns: usize,
+    /// Fraction of the area spanned by the regions that is covered by them.
+    utilisation: f64,
 
     _marker: PhantomData<(G, ConcreteCircuit)>,
 }
@@ -90,6 +92,12 @@ pub(crate) struct Layout {
     pub(crate) equality: Vec<(Column<Any>, usize, Column<Any>, usize)>,
     /// Selector assignments used for optimization pass
     pub(crate) selectors: Vec<Vec<bool>>,
+    /// The rows that must be usable for the columns filled from a row onwards, which
+    /// includes the row the filling starts from.
+    pub(crate) fill_rows: usize,
+    /// Whether the selector columns grow to fit the rows they are enabled on, rather than
+    /// being bounded by `k`.
+    grow_selectors: bool,
 }
 
 impl Layout {
@@ -108,6 +116,16 @@ impl Layout {
             equality: vec![],
             /// Selector assignments used for optimization pass
             selectors: vec![vec![false; n]; num_selectors],
+            fill_rows: 0,
+            grow_selectors: false,
+        }
+    }
+
+    /// Creates an empty layout that is not bounded by any `k`.
+    pub(crate) fn unbounded(num_selectors: usize) -> Self {
+        Layout {
+            grow_selectors: true,
+            ..Layout::new(0, 0, num_selectors)
         }
     }
 
@@ -173,10 +191,13 @@ impl<F: Field> Assignment<F> for Layout {
         A: FnOnce() -> AR,
         AR: Into<String>,
     {
+        if self.grow_selectors && row >= self.selectors[selector.0].len() {
+            self.selectors[selector.0].resize(row + 1, false);
+        }
         if let Some(cell) = self.selectors[selector.0].get_mut(row) {
             *cell = true;
         } else {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::not_enough_rows_available(self.k, row + 1));
         }
 
         self.update((*selector).into(), row);
@@ -235,9 +256,10 @@ impl<F: Field> Assignment<F> for Layout {
     fn fill_from_row(
         &mut self,
         _: Column<Fixed>,
-        _: usize,
+        from_row: usize,
         _: Value<Assigned<F>>,
     ) -> Result<(), Error> {
+        self.fill_rows = cmp::max(self.fill_rows, from_row + 1);
         Ok(())
     }
 
@@ -274,6 +296,37 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
 
         assert!((1 << k) >= cs.minimum_rows());
 
+        // Measure how densely the floor planner packed the regions: the area covered by
+        // regions, relative to the rows spanned by them in the columns that they use.
+        let region_area: usize = layout
+            .regions
+            .iter()
+            .map(|region| region.columns.len() * region.rows)
+            .sum();
+        let region_columns: HashSet<_> = layout
+            .regions
+            .iter()
+            .flat_map(|region| region.columns.iter())
+            .collect();
+        let region_rows = layout
+            .regions
+            .iter()
+            .filter_map(|region| region.offset.map(|offset| offset + region.rows))
+            .max()
+            .unwrap_or(0);
+        let utilisation = if region_area == 0 {
+            1.0
+        } else {
+            region_area as f64 / (region_columns.len() * region_rows) as f64
+        };
+
+        // Hashed instance columns are evaluated by the verifier, not opened.
+        let instance_queries: Vec<_> = cs
+            .instance_queries
+            .iter()
+            .filter(|(c, _)| !cs.is_hashed_instance_column(*c))
+            .collect();
+
         // Figure out how many point sets we have due to queried cells.
         let mut column_queries: HashMap<Column<Any>, HashSet<i32>> = HashMap::new();
         for (c, r) in iter::empty()
@@ -282,7 +335,7 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
                     .iter()
                     .map(|(c, r)| (Column::<Any>::from(*c), *r)),
             )
-            .chain(cs.instance_queries.iter().map(|(c, r)| ((*c).into(), *r)))
+            .chain(instance_queries.iter().map(|(c, r)| ((*c).into(), *r)))
             .chain(cs.fixed_queries.iter().map(|(c, r)| ((*c).into(), *r)))
             .chain(
                 cs.permutation
@@ -319,7 +372,7 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             k,
             max_deg,
             advice_columns: cs.num_advice_columns,
-            instance_queries: cs.instance_queries.len(),
+            instance_queries: instance_queries.len(),
             advice_queries: cs.advice_queries.len(),
             fixed_queries: cs.fixed_queries.len(),
             lookups: cs.lookups.len(),
@@ -335,9 +388,19 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
             num_total_columns: cs.num_instance_columns
                 + cs.num_advice_columns
                 + cs.num_fixed_columns,
+            utilisation,
         }
     }
 
+    /// Returns the fraction of the area spanned by the circuit's regions that is covered
+    /// by them, where the area spanned is the number of columns used by any region times
+    /// the number of rows up to the end of the last region.
+    ///
+    /// A utilisation of 1 means the floor planner left no gaps between regions.
+    pub fn utilisation(&self) -> f64 {
+        self.utilisation
+    }
+
     fn permutation_chunks(&self) -> usize {
         let chunk_size = self.max_deg - 2;
         (self.permutation_cols + chunk_size - 1) / chunk_size
@@ -416,6 +479,123 @@ impl<G: PrimeGroup, ConcreteCircuit: Circuit<G::Scalar>> CircuitCost<G, Concrete
     }
 }
 
+/// The rows used by a region of a circuit, as measured by [`CircuitRows`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionRows {
+    /// The name of the region. Not required to be unique.
+    pub name: String,
+    /// The row that this region starts on, or `None` if the region assigns no cells.
+    pub offset: Option<usize>,
+    /// The number of rows that this region takes up.
+    pub rows: usize,
+}
+
+/// Measures the number of rows that a circuit needs, to find the smallest `k` for which
+/// it can be synthesized.
+///
+/// ```ignore
+/// let rows = CircuitRows::measure(&circuit)?;
+/// let prover = MockProver::run(rows.k(), &circuit, vec![public_inputs])?;
+/// ```
+#[derive(Debug)]
+pub struct CircuitRows {
+    k: u32,
+    usable_rows: usize,
+    required_rows: usize,
+    regions: Vec<RegionRows>,
+}
+
+impl CircuitRows {
+    /// Measures a circuit, without its witnesses.
+    ///
+    /// The circuit is synthesized with its floor planner, recording every row that is
+    /// assigned to (including by lookup tables and constants), the row from which each
+    /// lookup table column is padded, every row of an instance column that is
+    /// constrained to a cell, and the rows queried by the gates of every enabled
+    /// selector. These rows must all be usable, so the circuit needs enough
+    /// further rows for its blinding factors, and at least
+    /// [`ConstraintSystem::minimum_rows`].
+    ///
+    /// Instance values that are not constrained by the circuit also take up usable rows;
+    /// callers passing such values to the prover must account for them.
+    pub fn measure<F: Field, ConcreteCircuit: Circuit<F>>(
+        circuit: &ConcreteCircuit,
+    ) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = ConcreteCircuit::configure(&mut cs);
+        let mut layout = Layout::unbounded(cs.num_selectors);
+        ConcreteCircuit::FloorPlanner::synthesize(
+            &mut layout,
+            &circuit.without_witnesses(),
+            config,
+            cs.constants.clone(),
+        )?;
+
+        let gate_rotations = GateRotations::new(&cs);
+        let selector_rows = layout
+            .selectors
+            .iter()
+            .enumerate()
+            .filter_map(|(selector, rows)| {
+                rows.iter()
+                    .rposition(|enabled| *enabled)
+                    .map(|row| row + 1 + gate_rotations.rows_after(selector))
+            });
+        let instance_rows = layout
+            .equality
+            .iter()
+            .flat_map(|(l_col, l_row, r_col, r_row)| [(l_col, l_row), (r_col, r_row)])
+            .filter(|(column, _)| *column.column_type() == Any::Instance)
+            .map(|(_, row)| row + 1);
+        let usable_rows = iter::once(layout.total_rows)
+            .chain(iter::once(layout.fill_rows))
+            .chain(selector_rows)
+            .chain(instance_rows)
+            .max()
+            .unwrap();
+
+        let required_rows = cmp::max(cs.minimum_rows(), usable_rows + cs.blinding_factors() + 1);
+
+        Ok(CircuitRows {
+            k: required_rows.next_power_of_two().trailing_zeros(),
+            usable_rows,
+            required_rows,
+            regions: layout
+                .regions
+                .into_iter()
+                .map(|region| RegionRows {
+                    name: region.name,
+                    offset: region.offset,
+                    rows: region.rows,
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the smallest `k` for which the circuit can be synthesized.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    /// Returns the number of rows that the circuit assigns or queries, all of which
+    /// must be usable.
+    pub fn usable_rows(&self) -> usize {
+        self.usable_rows
+    }
+
+    /// Returns the number of rows that the circuit needs, including those reserved for
+    /// blinding factors.
+    pub fn required_rows(&self) -> usize {
+        self.required_rows
+    }
+
+    /// Returns the rows used by each region of the circuit, in the order they were
+    /// assigned.
+    pub fn regions(&self) -> &[RegionRows] {
+        &self.regions
+    }
+}
+
 /// (commitments, evaluations)
 #[derive(Debug)]
 struct ProofContribution {
@@ -543,4 +723,135 @@ mod tests {
         }
         CircuitCost::<Eq, MyCircuit>::measure(K, &MyCircuit).proof_size(1);
     }
+
+    #[test]
+    fn circuit_rows() {
+        use crate::{
+            circuit::{floor_planner::V1, Value},
+            dev::MockProver,
+            plonk::TableColumn,
+            poly::Rotation,
+        };
+
+        #[derive(Clone)]
+        struct MyConfig {
+            a: Column<Advice>,
+            q: Selector,
+            table: TableColumn,
+        }
+
+        struct MyCircuit {
+            rows: usize,
+            table_rows: usize,
+        }
+
+        impl Circuit<Fp> for MyCircuit {
+            type Config = MyConfig;
+            type FloorPlanner = V1;
+
+            fn without_witnesses(&self) -> Self {
+                MyCircuit {
+                    rows: self.rows,
+                    table_rows: self.table_rows,
+                }
+            }
+
+            fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+                let a = meta.advice_column();
+                let q = meta.complex_selector();
+                let table = meta.lookup_table_column();
+
+                meta.create_gate("constant", |meta| {
+                    let q = meta.query_selector(q);
+                    let cur = meta.query_advice(a, Rotation::cur());
+                    let next = meta.query_advice(a, Rotation::next());
+                    vec![q * (next - cur)]
+                });
+                meta.lookup(|meta| {
+                    let q = meta.query_selector(q);
+                    let a = meta.query_advice(a, Rotation::cur());
+                    vec![(q * a, table)]
+                });
+
+                MyConfig { a, q, table }
+            }
+
+            fn synthesize(
+                &self,
+                config: Self::Config,
+                mut layouter: impl crate::circuit::Layouter<Fp>,
+            ) -> Result<(), Error> {
+                layouter.assign_table(
+                    || "table",
+                    |mut table| {
+                        for row in 0..self.table_rows {
+                            table.assign_cell(
+                                || "value",
+                                config.table,
+                                row,
+                                || Value::known(Fp::from(row as u64)),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+                layouter.assign_region(
+                    || "main",
+                    |mut region| {
+                        for row in 0..self.rows {
+                            config.q.enable(&mut region, row)?;
+                            region.assign_advice(
+                                || "a",
+                                config.a,
+                                row,
+                                || Value::known(Fp::one()),
+                            )?;
+                        }
+                        // The gate on the last row queries the next row.
+                        region.assign_advice(
+                            || "a",
+                            config.a,
+                            self.rows,
+                            || Value::known(Fp::one()),
+                        )
+                    },
+                )?;
+                Ok(())
+            }
+        }
+
+        for (rows, table_rows) in [(1, 2), (10, 2), (20, 5), (2, 40), (57, 2), (58, 2)] {
+            let circuit = MyCircuit { rows, table_rows };
+            let measured = CircuitRows::measure(&circuit).unwrap();
+            assert_eq!(
+                measured.usable_rows(),
+                // The table columns are padded from the row after the table.
+                cmp::max(rows + 1, table_rows + 1),
+                "rows = {}, table_rows = {}",
+                rows,
+                table_rows,
+            );
+            assert_eq!(
+                measured.regions(),
+                &[
+                    RegionRows {
+                        name: "table".into(),
+                        offset: Some(0),
+                        rows: table_rows,
+                    },
+                    RegionRows {
+                        name: "main".into(),
+                        offset: Some(0),
+                        rows: rows + 1,
+                    },
+                ]
+            );
+
+            // The circuit fits in the measured `k`, but not in any smaller `k`.
+            let k = measured.k();
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert_eq!(prover.verify(), Ok(()));
+            assert!(MockProver::run(k - 1, &circuit, vec![]).is_err());
+        }
+    }
 }
@@ -0,0 +1,428 @@
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fmt::Write,
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use ff::Field;
+
+use crate::{
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, Error, Fixed, FloorPlanner, Instance,
+        Selector,
+    },
+};
+
+thread_local! {
+    static PROFILE: RefCell<WitnessProfile> = RefCell::new(WitnessProfile::default());
+}
+
+/// A helper type that augments a [`FloorPlanner`] with a profile of the wall-clock time
+/// spent and the number of cells assigned within each region and namespace of a circuit.
+///
+/// Each time the circuit is synthesized (for example by [`MockProver::run`], keygen or
+/// proving), the profile is added to a [`WitnessProfile`] for the current thread, which
+/// can be retrieved with [`WitnessProfile::take`]. The time within a region is the time
+/// that the floor planner spends assigning it, which includes the time spent generating
+/// its witnesses.
+///
+/// [`MockProver::run`]: crate::dev::MockProver::run
+///
+/// # No stability guarantees
+///
+/// The profile is intended for use during circuit development. It should not be
+/// considered production-stable, and the precise format or data exposed may change at any
+/// time.
+///
+/// # Examples
+///
+/// ```ignore
+/// impl<F: Field> Circuit<F> for MyCircuit<F> {
+///     // Wrap `ProfilingFloorPlanner` around your existing floor planner of choice.
+///     //type FloorPlanner = floor_planner::V1;
+///     type FloorPlanner = ProfilingFloorPlanner<floor_planner::V1>;
+///
+///     // The rest of your `Circuit` implementation is unchanged.
+/// }
+///
+/// let prover = MockProver::run(k, &circuit, instances).unwrap();
+///
+/// // Write the time spent in each namespace and region, in a format that can be
+/// // rendered by flame graph tools such as `inferno-flamegraph`.
+/// std::fs::write("witness.folded", WitnessProfile::take().folded_time()).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ProfilingFloorPlanner<P: FloorPlanner> {
+    _phantom: PhantomData<P>,
+}
+
+impl<P: FloorPlanner> FloorPlanner for ProfilingFloorPlanner<P> {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        let mut cs = ProfilingAssignment::new(cs, std::any::type_name::<C>());
+        let result = P::synthesize(&mut cs, circuit, config, constants);
+
+        let profile = cs.finish();
+        PROFILE.with(|p| p.borrow_mut().merge(profile));
+
+        result
+    }
+}
+
+/// The wall-clock time spent and the number of cells assigned within a stack of
+/// namespaces and regions, excluding those within nested namespaces and regions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StackStats {
+    /// The wall-clock time spent.
+    pub time: Duration,
+    /// The number of advice and fixed cells assigned.
+    pub cells: usize,
+}
+
+/// A profile of circuit synthesis, recorded by [`ProfilingFloorPlanner`].
+///
+/// The profile is keyed by stacks of frames. The outermost frame of every stack is the
+/// name of the circuit type, which is followed by the namespaces and region that the
+/// time was spent or cells were assigned in.
+#[derive(Clone, Debug, Default)]
+pub struct WitnessProfile {
+    stacks: BTreeMap<Vec<String>, StackStats>,
+}
+
+impl WitnessProfile {
+    /// Takes the profile recorded by [`ProfilingFloorPlanner`] on the current thread,
+    /// leaving an empty profile in its place.
+    pub fn take() -> Self {
+        PROFILE.with(|p| std::mem::take(&mut *p.borrow_mut()))
+    }
+
+    /// Returns the stacks of this profile in lexicographic order, along with the time
+    /// spent and cells assigned directly within each of them.
+    pub fn stacks(&self) -> impl Iterator<Item = (&[String], StackStats)> {
+        self.stacks
+            .iter()
+            .map(|(stack, stats)| (&stack[..], *stats))
+    }
+
+    /// Returns the time spent within each stack, in nanoseconds, in the folded-stack
+    /// format used by flame graph tools.
+    pub fn folded_time(&self) -> String {
+        self.folded(|stats| stats.time.as_nanos())
+    }
+
+    /// Returns the number of cells assigned within each stack, in the folded-stack format
+    /// used by flame graph tools.
+    pub fn folded_cells(&self) -> String {
+        self.folded(|stats| stats.cells as u128)
+    }
+
+    fn folded(&self, value: impl Fn(&StackStats) -> u128) -> String {
+        let mut folded = String::new();
+        for (stack, stats) in &self.stacks {
+            let value = value(stats);
+            // Flame graph tools ignore empty frames.
+            if value == 0 {
+                continue;
+            }
+            let frames: Vec<_> = stack.iter().map(|frame| frame.replace(';', ":")).collect();
+            writeln!(folded, "{} {}", frames.join(";"), value).unwrap();
+        }
+        folded
+    }
+
+    fn merge(&mut self, other: Self) {
+        for (stack, stats) in other.stacks {
+            let entry = self.stacks.entry(stack).or_default();
+            entry.time += stats.time;
+            entry.cells += stats.cells;
+        }
+    }
+}
+
+/// A namespace or region that is being profiled.
+struct Frame {
+    name: String,
+    start: Instant,
+    // The time spent within nested frames.
+    nested: Duration,
+    cells: usize,
+}
+
+/// A helper type that augments an [`Assignment`] with a [`WitnessProfile`].
+struct ProfilingAssignment<'cs, F: Field, CS: Assignment<F>> {
+    cs: &'cs mut CS,
+    frames: Vec<Frame>,
+    profile: WitnessProfile,
+    _phantom: PhantomData<F>,
+}
+
+impl<'cs, F: Field, CS: Assignment<F>> ProfilingAssignment<'cs, F, CS> {
+    fn new(cs: &'cs mut CS, circuit: &str) -> Self {
+        let mut ret = Self {
+            cs,
+            frames: vec![],
+            profile: WitnessProfile::default(),
+            _phantom: PhantomData,
+        };
+        ret.push_frame(circuit.to_string());
+        ret
+    }
+
+    fn push_frame(&mut self, name: String) {
+        self.frames.push(Frame {
+            name,
+            start: Instant::now(),
+            nested: Duration::ZERO,
+            cells: 0,
+        });
+    }
+
+    fn pop_frame(&mut self) {
+        if let Some(frame) = self.frames.pop() {
+            let elapsed = frame.start.elapsed();
+            let stack = self
+                .frames
+                .iter()
+                .map(|frame| frame.name.clone())
+                .chain(Some(frame.name))
+                .collect();
+
+            let stats = self.profile.stacks.entry(stack).or_default();
+            stats.time += elapsed.saturating_sub(frame.nested);
+            stats.cells += frame.cells;
+
+            if let Some(parent) = self.frames.last_mut() {
+                parent.nested += elapsed;
+            }
+        }
+    }
+
+    /// Pops the innermost namespace or region, leaving the frame of the circuit itself
+    /// to be popped by `finish`.
+    fn pop_nested_frame(&mut self) {
+        if self.frames.len() > 1 {
+            self.pop_frame();
+        }
+    }
+
+    fn count_cell(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.cells += 1;
+        }
+    }
+
+    fn finish(mut self) -> WitnessProfile {
+        while !self.frames.is_empty() {
+            self.pop_frame();
+        }
+        self.profile
+    }
+}
+
+impl<'cs, F: Field, CS: Assignment<F>> Assignment<F> for ProfilingAssignment<'cs, F, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.push_frame(name.clone());
+        self.cs.enter_region(|| name);
+    }
+
+    fn exit_region(&mut self) {
+        self.cs.exit_region();
+        self.pop_nested_frame();
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.count_cell();
+        self.cs.assign_advice(annotation, column, row, to)
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.count_cell();
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.cs.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.cs.fill_from_row(column, row, to)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = name_fn().into();
+        self.push_frame(name.clone());
+        self.cs.push_namespace(|| name)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name);
+        self.pop_nested_frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use pasta_curves::Fp;
+
+    use super::{ProfilingFloorPlanner, WitnessProfile};
+    use crate::{
+        circuit::{floor_planner::V1, Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, FloorPlanner},
+    };
+
+    struct MyCircuit<P: FloorPlanner>(PhantomData<P>);
+
+    impl<P: FloorPlanner> Circuit<Fp> for MyCircuit<P> {
+        type Config = Column<Advice>;
+        type FloorPlanner = ProfilingFloorPlanner<P>;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit(PhantomData)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            meta.advice_column()
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            for (namespace, cells) in [("outer; gadget", 3), ("other", 2)] {
+                let mut layouter = layouter.namespace(|| namespace);
+                layouter.assign_region(
+                    || "region",
+                    |mut region| {
+                        for offset in 0..cells {
+                            region.assign_advice(
+                                || "zero",
+                                config,
+                                offset,
+                                || Value::known(Fp::zero()),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )?;
+            }
+            Ok(())
+        }
+    }
+
+    fn profile<P: FloorPlanner>() {
+        // Discard any profile left by an earlier test on this thread.
+        WitnessProfile::take();
+
+        let circuit = MyCircuit::<P>(PhantomData);
+        let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let profile = WitnessProfile::take();
+        let root = std::any::type_name::<MyCircuit<P>>();
+        let cells: Vec<_> = profile
+            .stacks()
+            .map(|(stack, stats)| (stack.to_vec(), stats.cells))
+            .collect();
+        assert_eq!(
+            cells,
+            vec![
+                (vec![root.to_string()], 0),
+                (vec![root.to_string(), "other".into()], 0),
+                (vec![root.to_string(), "other".into(), "region".into()], 2),
+                (vec![root.to_string(), "outer; gadget".into()], 0),
+                (
+                    vec![root.to_string(), "outer; gadget".into(), "region".into()],
+                    3
+                ),
+            ]
+        );
+
+        let root = root.replace(';', ":");
+        assert_eq!(
+            profile.folded_cells(),
+            format!("{root};other;region 2\n{root};outer: gadget;region 3\n"),
+        );
+        for line in profile.folded_time().lines() {
+            let (_, nanos) = line.rsplit_once(' ').unwrap();
+            assert!(nanos.parse::<u128>().unwrap() > 0);
+        }
+    }
+
+    #[test]
+    fn profiling_floor_planner() {
+        profile::<SimpleFloorPlanner>();
+        profile::<V1>();
+    }
+}
@@ -0,0 +1,660 @@
+//! Developer tools for pinning a circuit's verifying key and proofs across versions.
+//!
+//! A [`TestVector`] records everything needed to detect an unintended change to a
+//! circuit: the pinned representation of its verifying key, the instance values it was
+//! proven with, and a proof created with a [`TestRng`] seeded from a fixed seed. Because
+//! proof creation is deterministic given its RNG, regenerating a test vector from the same
+//! circuit, `k`, instances and seed yields byte-identical output.
+//!
+//! # Format
+//!
+//! Test vectors are stored as UTF-8 text, so that changes show up legibly in a diff:
+//!
+//! ```text
+//! k = 4
+//! seed = 0000000000000000000000000000000000000000000000000000000000000000
+//! instance = 0100000000000000000000000000000000000000000000000000000000000000,...
+//! proof = 8ac3...
+//! vk =
+//! PinnedVerificationKey {
+//!     ...
+//! }
+//! ```
+//!
+//! - `k` is the circuit size parameter in decimal.
+//! - `seed` is the 32-byte seed of the [`TestRng`] used for proving, hex-encoded.
+//! - There is one `instance` line per instance column, in column order. Each line holds
+//!   the comma-separated values of that column, each encoded as the hex of the field
+//!   element's canonical byte representation.
+//! - `proof` is the hex-encoded proof, as written by [`Blake2bWrite`].
+//! - Everything after the `vk =` line is the `{:#?}` formatting of
+//!   [`VerifyingKey::pinned`].
+
+use std::{
+    fmt,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Read, Write},
+    path::Path,
+};
+
+use blake2b_simd::Params as Blake2bParams;
+use ff::{FromUniformBytes, PrimeField};
+use rand_core::{impls, RngCore, SeedableRng};
+
+use crate::{
+    arithmetic::CurveAffine,
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, SingleVerifier,
+        VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+
+/// The environment variable that causes [`check_test_vector`] to regenerate the stored
+/// test vector instead of checking it.
+pub const GENERATE_ENV_VAR: &str = "HALO2_TEST_VECTORS_GENERATE";
+
+/// A deterministic RNG, for creating reproducible proofs.
+///
+/// The output stream is the concatenation of `BLAKE2b-512(seed || counter)` for
+/// successive 64-bit little-endian counters, using the personalization
+/// `Halo2-TestVecRng`. It is fully determined by its seed, and stable across versions of
+/// this crate.
+///
+/// # Security
+///
+/// Proofs created with a `TestRng` with a known seed are **not zero-knowledge**. It
+/// must only be used for generating test vectors.
+#[derive(Clone, Debug)]
+pub struct TestRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 64],
+    position: usize,
+}
+
+impl TestRng {
+    fn refill(&mut self) {
+        let hash = Blake2bParams::new()
+            .hash_length(64)
+            .personal(b"Halo2-TestVecRng")
+            .to_state()
+            .update(&self.seed)
+            .update(&self.counter.to_le_bytes())
+            .finalize();
+        self.block.copy_from_slice(hash.as_bytes());
+        self.counter += 1;
+        self.position = 0;
+    }
+}
+
+impl SeedableRng for TestRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        TestRng {
+            seed,
+            counter: 0,
+            block: [0; 64],
+            position: 64,
+        }
+    }
+}
+
+impl RngCore for TestRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, mut dest: &mut [u8]) {
+        while !dest.is_empty() {
+            if self.position == self.block.len() {
+                self.refill();
+            }
+            let n = std::cmp::min(dest.len(), self.block.len() - self.position);
+            dest[..n].copy_from_slice(&self.block[self.position..self.position + n]);
+            self.position += n;
+            dest = &mut dest[n..];
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// A reproducible record of a circuit's verifying key and a proof for it.
+///
+/// See the [module documentation](self) for the serialized format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestVector<C: CurveAffine> {
+    k: u32,
+    seed: [u8; 32],
+    instances: Vec<Vec<C::Scalar>>,
+    proof: Vec<u8>,
+    vk: String,
+}
+
+/// The ways in which a [`TestVector`] can fail to match a circuit.
+#[derive(Debug)]
+pub enum TestVectorError {
+    /// The test vector could not be read or written.
+    Io(io::Error),
+    /// The stored test vector is not in the expected format.
+    Malformed {
+        /// The 1-based line on which the problem was found.
+        line: usize,
+        /// A description of the problem.
+        reason: String,
+    },
+    /// Key generation or proving failed for the circuit.
+    Synthesis(Error),
+    /// The stored and regenerated test vectors use different `k` or seeds.
+    ParametersMismatch,
+    /// The stored and regenerated test vectors use different instance values.
+    InstancesMismatch,
+    /// The circuit's verifying key has changed.
+    VerifyingKeyMismatch {
+        /// The first line at which the pinned verifying keys differ (1-based).
+        line: usize,
+        /// The stored line, or `None` if the stored key is shorter.
+        expected: Option<String>,
+        /// The regenerated line, or `None` if the regenerated key is shorter.
+        actual: Option<String>,
+    },
+    /// The stored proof does not verify against the regenerated verifying key.
+    StoredProofInvalid(Error),
+    /// The regenerated proof differs from the stored proof, even though the stored proof
+    /// still verifies.
+    ProofMismatch {
+        /// The first byte offset at which the proofs differ.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for TestVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TestVectorError::Io(e) => write!(f, "I/O error: {}", e),
+            TestVectorError::Malformed { line, reason } => {
+                write!(f, "Malformed test vector on line {}: {}", line, reason)
+            }
+            TestVectorError::Synthesis(e) => write!(f, "Failed to regenerate test vector: {}", e),
+            TestVectorError::ParametersMismatch => {
+                write!(f, "Test vectors were generated with different k or seeds")
+            }
+            TestVectorError::InstancesMismatch => {
+                write!(f, "Test vectors were generated with different instances")
+            }
+            TestVectorError::VerifyingKeyMismatch {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Verifying key changed at line {}: expected {:?}, got {:?}",
+                line, expected, actual,
+            ),
+            TestVectorError::StoredProofInvalid(e) => {
+                write!(f, "Stored proof no longer verifies: {}", e)
+            }
+            TestVectorError::ProofMismatch { offset } => {
+                write!(f, "Regenerated proof differs at byte offset {}", offset)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TestVectorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TestVectorError::Io(e) => Some(e),
+            TestVectorError::Synthesis(e) | TestVectorError::StoredProofInvalid(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TestVectorError {
+    fn from(error: io::Error) -> Self {
+        TestVectorError::Io(error)
+    }
+}
+
+impl<C: CurveAffine> TestVector<C>
+where
+    C::Scalar: FromUniformBytes<64>,
+{
+    /// Generates a test vector for `circuit` with parameter `k`, proving it with the
+    /// given instance columns and a [`TestRng`] seeded with `seed`.
+    pub fn generate<ConcreteCircuit: Circuit<C::Scalar>>(
+        k: u32,
+        circuit: &ConcreteCircuit,
+        instances: Vec<Vec<C::Scalar>>,
+        seed: [u8; 32],
+    ) -> Result<Self, Error> {
+        let params = Params::new(k);
+        let vk = keygen_vk(&params, circuit)?;
+        let vk_text = format!("{:#?}", vk.pinned());
+        let pk = keygen_pk(&params, vk, circuit)?;
+
+        let instance_slices: Vec<_> = instances.iter().map(|column| &column[..]).collect();
+        let mut transcript = Blake2bWrite::<_, C, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            std::slice::from_ref(circuit),
+            &[&instance_slices],
+            TestRng::from_seed(seed),
+            &mut transcript,
+        )?;
+
+        Ok(TestVector {
+            k,
+            seed,
+            instances,
+            proof: transcript.finalize(),
+            vk: vk_text,
+        })
+    }
+
+    /// Verifies the proof in this test vector against `vk`.
+    pub fn verify(&self, params: &Params<C>, vk: &VerifyingKey<C>) -> Result<(), Error> {
+        let instance_slices: Vec<_> = self.instances.iter().map(|column| &column[..]).collect();
+        let strategy = SingleVerifier::new(params);
+        let mut transcript = Blake2bRead::<_, C, Challenge255<_>>::init(&self.proof[..]);
+        verify_proof(params, vk, strategy, &[&instance_slices], &mut transcript)
+    }
+
+    /// Regenerates this test vector from `circuit`, and checks that the result matches.
+    ///
+    /// The checks are performed in order of decreasing severity: a change to the
+    /// verifying key is reported before any difference in the proof. A stored proof that
+    /// verifies against the regenerated verifying key but differs from the regenerated
+    /// proof indicates a change in the prover that is not a compatibility break.
+    pub fn check<ConcreteCircuit: Circuit<C::Scalar>>(
+        &self,
+        circuit: &ConcreteCircuit,
+    ) -> Result<(), TestVectorError> {
+        let params = Params::new(self.k);
+        let vk = keygen_vk(&params, circuit).map_err(TestVectorError::Synthesis)?;
+
+        let vk_text = format!("{:#?}", vk.pinned());
+        if vk_text != self.vk {
+            let mut expected = self.vk.lines();
+            let mut actual = vk_text.lines();
+            let mut line = 1;
+            loop {
+                match (expected.next(), actual.next()) {
+                    (Some(e), Some(a)) if e == a => line += 1,
+                    (expected, actual) => {
+                        return Err(TestVectorError::VerifyingKeyMismatch {
+                            line,
+                            expected: expected.map(String::from),
+                            actual: actual.map(String::from),
+                        })
+                    }
+                }
+            }
+        }
+
+        self.verify(&params, &vk)
+            .map_err(TestVectorError::StoredProofInvalid)?;
+
+        let regenerated = Self::generate(self.k, circuit, self.instances.clone(), self.seed)
+            .map_err(TestVectorError::Synthesis)?;
+        self.diff_proof(&regenerated)
+    }
+
+    /// Compares the proof in this test vector with the one in `other`, which must have
+    /// been generated with the same parameters and instances.
+    fn diff_proof(&self, other: &Self) -> Result<(), TestVectorError> {
+        if self.proof != other.proof {
+            let offset = self
+                .proof
+                .iter()
+                .zip(other.proof.iter())
+                .position(|(a, b)| a != b)
+                .unwrap_or_else(|| std::cmp::min(self.proof.len(), other.proof.len()));
+            return Err(TestVectorError::ProofMismatch { offset });
+        }
+        Ok(())
+    }
+
+    /// Returns the pinned representation of the verifying key in this test vector.
+    pub fn pinned_vk(&self) -> &str {
+        &self.vk
+    }
+
+    /// Returns the proof in this test vector.
+    pub fn proof(&self) -> &[u8] {
+        &self.proof
+    }
+
+    /// Returns the instance columns that the proof in this test vector was created for.
+    pub fn instances(&self) -> &[Vec<C::Scalar>] {
+        &self.instances
+    }
+
+    /// Writes this test vector in the format described in the
+    /// [module documentation](self).
+    pub fn write<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "k = {}", self.k)?;
+        writeln!(writer, "seed = {}", encode_hex(&self.seed))?;
+        for column in &self.instances {
+            let values: Vec<_> = column
+                .iter()
+                .map(|value| encode_hex(value.to_repr().as_ref()))
+                .collect();
+            writeln!(writer, "instance = {}", values.join(","))?;
+        }
+        writeln!(writer, "proof = {}", encode_hex(&self.proof))?;
+        writeln!(writer, "vk =")?;
+        writeln!(writer, "{}", self.vk)
+    }
+
+    /// Reads a test vector in the format described in the [module documentation](self).
+    pub fn read<R: Read>(reader: R) -> Result<Self, TestVectorError> {
+        let mut lines = BufReader::new(reader).lines().enumerate();
+        fn next_field(
+            lines: &mut impl Iterator<Item = (usize, io::Result<String>)>,
+            name: &str,
+        ) -> Result<(usize, String), TestVectorError> {
+            let (index, line) = lines.next().ok_or_else(|| TestVectorError::Malformed {
+                line: 0,
+                reason: format!("missing field `{}`", name),
+            })?;
+            Ok((index + 1, line?))
+        }
+        let field = |line: usize, text: &str, name: &str| -> Result<String, TestVectorError> {
+            text.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(" ="))
+                .map(|value| value.trim_start().to_owned())
+                .ok_or_else(|| TestVectorError::Malformed {
+                    line,
+                    reason: format!("expected field `{}`", name),
+                })
+        };
+        let malformed = |line: usize, reason: &str| TestVectorError::Malformed {
+            line,
+            reason: reason.to_owned(),
+        };
+
+        let (line, text) = next_field(&mut lines, "k")?;
+        let k = field(line, &text, "k")?
+            .parse()
+            .map_err(|_| malformed(line, "invalid k"))?;
+
+        let (line, text) = next_field(&mut lines, "seed")?;
+        let seed = decode_hex(&field(line, &text, "seed")?)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| malformed(line, "invalid seed"))?;
+
+        let mut instances = vec![];
+        let (line, text) = loop {
+            let (line, text) = next_field(&mut lines, "proof")?;
+            if !text.starts_with("instance") {
+                break (line, text);
+            }
+            let values = field(line, &text, "instance")?;
+            let column = values
+                .split(',')
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    let bytes = decode_hex(value)?;
+                    let mut repr = <C::Scalar as PrimeField>::Repr::default();
+                    if bytes.len() != repr.as_ref().len() {
+                        return None;
+                    }
+                    repr.as_mut().copy_from_slice(&bytes);
+                    Option::from(C::Scalar::from_repr(repr))
+                })
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| malformed(line, "invalid instance value"))?;
+            instances.push(column);
+        };
+
+        let proof = decode_hex(&field(line, &text, "proof")?)
+            .ok_or_else(|| malformed(line, "invalid proof encoding"))?;
+
+        let (line, text) = next_field(&mut lines, "vk")?;
+        if !field(line, &text, "vk")?.is_empty() {
+            return Err(malformed(
+                line,
+                "expected the pinned verifying key on the next line",
+            ));
+        }
+        let vk = lines
+            .map(|(_, line)| line)
+            .collect::<Result<Vec<_>, _>>()?
+            .join("\n");
+
+        Ok(TestVector {
+            k,
+            seed,
+            instances,
+            proof,
+            vk,
+        })
+    }
+}
+
+/// Checks `circuit` against the test vector stored at `path`.
+///
+/// If the [`GENERATE_ENV_VAR`] environment variable is set, the test vector is instead
+/// (re)generated from `circuit`, `k`, `instances` and `seed`, and written to `path`.
+///
+/// This is intended to be called from a crate's tests, to detect changes to a circuit
+/// that would break compatibility with previously-created proofs or verifying keys.
+pub fn check_test_vector<C, ConcreteCircuit>(
+    path: impl AsRef<Path>,
+    k: u32,
+    circuit: &ConcreteCircuit,
+    instances: Vec<Vec<C::Scalar>>,
+    seed: [u8; 32],
+) -> Result<(), TestVectorError>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let path = path.as_ref();
+    if std::env::var_os(GENERATE_ENV_VAR).is_some() {
+        let test_vector = TestVector::<C>::generate(k, circuit, instances, seed)
+            .map_err(TestVectorError::Synthesis)?;
+        let mut buf = vec![];
+        test_vector.write(&mut buf)?;
+        fs::write(path, buf)?;
+        Ok(())
+    } else {
+        let stored = TestVector::<C>::read(File::open(path)?)?;
+        if stored.k != k || stored.seed != seed {
+            return Err(TestVectorError::ParametersMismatch);
+        }
+        if stored.instances != instances {
+            return Err(TestVectorError::InstancesMismatch);
+        }
+        stored.check(circuit)
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim();
+    if s.len() % 2 != 0 {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use ff::Field;
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::*;
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Advice, Column, ConstraintSystem, Fixed, Instance},
+        poly::Rotation,
+    };
+
+    #[derive(Clone)]
+    struct MyConfig {
+        a: Column<Advice>,
+        instance: Column<Instance>,
+        fixed: Column<Fixed>,
+    }
+
+    #[derive(Clone, Default)]
+    struct MyCircuit {
+        a: Value<Fp>,
+        // Assigns an otherwise unused fixed cell, which changes the verifying key
+        // without changing the witness.
+        unused_fixed_cell: bool,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit {
+                a: Value::unknown(),
+                unused_fixed_cell: self.unused_fixed_cell,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> MyConfig {
+            let a = meta.advice_column();
+            let instance = meta.instance_column();
+            let fixed = meta.fixed_column();
+            meta.enable_equality(a);
+            meta.enable_equality(instance);
+            let s = meta.selector();
+            meta.create_gate("a * a == a", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                vec![s * (a.clone() * a.clone() - a)]
+            });
+            MyConfig { a, instance, fixed }
+        }
+
+        fn synthesize(
+            &self,
+            config: MyConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let cell = layouter.assign_region(
+                || "a",
+                |mut region| {
+                    if self.unused_fixed_cell {
+                        region.assign_fixed(
+                            || "unused",
+                            config.fixed,
+                            0,
+                            || Value::known(Fp::ONE),
+                        )?;
+                    }
+                    region.assign_advice(|| "a", config.a, 0, || self.a)
+                },
+            )?;
+            layouter.constrain_instance(cell.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let mut a = TestRng::from_seed([7; 32]);
+        let mut b = TestRng::from_seed([7; 32]);
+        let mut c = TestRng::from_seed([8; 32]);
+
+        let mut buf_a = [0; 100];
+        let mut buf_b = [0; 100];
+        a.fill_bytes(&mut buf_a[..3]);
+        a.fill_bytes(&mut buf_a[3..]);
+        b.fill_bytes(&mut buf_b);
+        assert_eq!(buf_a, buf_b);
+        assert_ne!(a.next_u64(), c.next_u64());
+    }
+
+    #[test]
+    fn test_vector_round_trip() {
+        let circuit = MyCircuit {
+            a: Value::known(Fp::ONE),
+            unused_fixed_cell: false,
+        };
+        let instances = vec![vec![Fp::ONE]];
+
+        let tv = TestVector::<EqAffine>::generate(4, &circuit, instances.clone(), [0; 32]).unwrap();
+        let tv2 = TestVector::<EqAffine>::generate(4, &circuit, instances, [0; 32]).unwrap();
+        assert_eq!(tv, tv2);
+
+        let mut buf = vec![];
+        tv.write(&mut buf).unwrap();
+        let read = TestVector::<EqAffine>::read(&buf[..]).unwrap();
+        assert_eq!(tv, read);
+
+        read.check(&circuit).unwrap();
+
+        // A different seed produces a different, but still valid, proof.
+        let mut reseeded = read.clone();
+        reseeded.seed = [1; 32];
+        assert_matches!(
+            reseeded.check(&circuit),
+            Err(TestVectorError::ProofMismatch { .. })
+        );
+
+        // Truncating the proof invalidates it.
+        let mut truncated = read;
+        truncated.proof.pop();
+        assert_matches!(
+            truncated.check(&circuit),
+            Err(TestVectorError::StoredProofInvalid(_))
+        );
+    }
+
+    #[test]
+    fn malformed_test_vector() {
+        assert_matches!(
+            TestVector::<EqAffine>::read(&b"k = 4\nseed = 00\n"[..]),
+            Err(TestVectorError::Malformed { line: 2, .. })
+        );
+        assert_matches!(
+            TestVector::<EqAffine>::read(&b"k = four\n"[..]),
+            Err(TestVectorError::Malformed { line: 1, .. })
+        );
+    }
+
+    #[test]
+    fn changed_verifying_key() {
+        let circuit = MyCircuit {
+            a: Value::known(Fp::ONE),
+            unused_fixed_cell: false,
+        };
+        let tv =
+            TestVector::<EqAffine>::generate(4, &circuit, vec![vec![Fp::ONE]], [0; 32]).unwrap();
+
+        let changed = MyCircuit {
+            a: Value::known(Fp::ONE),
+            unused_fixed_cell: true,
+        };
+        assert_matches!(
+            tv.check(&changed),
+            Err(TestVectorError::VerifyingKeyMismatch { .. })
+        );
+    }
+}
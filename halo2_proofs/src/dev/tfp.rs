@@ -1,6 +1,6 @@
 use std::{fmt, marker::PhantomData};
 
-use ff::Field;
+use ff::{Field, PrimeField};
 use tracing::{debug, debug_span, span::EnteredSpan};
 
 use crate::{
@@ -172,6 +172,62 @@ impl<F: Field, L: Layouter<F>> Layouter<F> for TracingLayouter<F, L> {
         })
     }
 
+    fn assign_regions<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        assignments: Vec<A>,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(Region<'_, F>) -> Result<AR, Error> + Send,
+        AR: Send,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let _span = debug_span!("regions", name = name().into()).entered();
+        self.layouter.assign_regions(
+            name,
+            assignments
+                .into_iter()
+                .map(|mut assignment| {
+                    move |region: Region<'_, F>| {
+                        let mut region = TracingRegion(region);
+                        let region: &mut dyn RegionLayouter<F> = &mut region;
+                        assignment(region.into())
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    fn assign_region_instances<A, AR, N, NR>(
+        &mut self,
+        name: N,
+        count: usize,
+        mut assignment: A,
+    ) -> Result<Vec<AR>, Error>
+    where
+        A: FnMut(usize, Region<'_, F>) -> Result<AR, Error>,
+        AR: Clone,
+        N: Fn() -> NR,
+        NR: Into<String>,
+    {
+        let _span = debug_span!("region instances", name = name().into(), count).entered();
+        self.layouter
+            .assign_region_instances(name, count, |instance, region| {
+                let _span = debug_span!("instance", instance).entered();
+                let mut region = TracingRegion(region);
+                let region: &mut dyn RegionLayouter<F> = &mut region;
+                assignment(instance, region.into())
+            })
+    }
+
+    fn deduplicate_constants(&mut self)
+    where
+        F: PrimeField,
+    {
+        self.layouter.deduplicate_constants()
+    }
+
     fn assign_table<A, N, NR>(&mut self, name: N, assignment: A) -> Result<(), Error>
     where
         A: FnMut(Table<'_, F>) -> Result<(), Error>,
@@ -476,3 +532,104 @@ impl<'cs, F: Field, CS: Assignment<F>> Assignment<F> for TracingAssignment<'cs,
         // We exit namespace spans in TracingLayouter.
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use pasta_curves::Fp;
+
+    use super::TracingFloorPlanner;
+    use crate::{
+        circuit::{floor_planner::V1, Layouter, Region, Value},
+        dev::MockProver,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+
+    #[derive(Clone, Copy)]
+    enum Assignment {
+        // Region instances, where the instance at the given index uses an extra row.
+        MisshapenInstance(usize),
+        // Concurrently assigned regions, which read from the instance column.
+        RegionsReadingInstance,
+    }
+
+    struct MyCircuit(Assignment);
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (Column<Advice>, Column<Instance>);
+        type FloorPlanner = TracingFloorPlanner<V1>;
+
+        fn without_witnesses(&self) -> Self {
+            MyCircuit(self.0)
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            (meta.advice_column(), meta.instance_column())
+        }
+
+        fn synthesize(
+            &self,
+            (advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            match self.0 {
+                Assignment::MisshapenInstance(misshapen) => layouter
+                    .assign_region_instances(
+                        || "instance",
+                        4,
+                        |index, mut region| {
+                            let rows = if index == misshapen { 2 } else { 1 };
+                            for offset in 0..rows {
+                                region.assign_advice(
+                                    || "zero",
+                                    advice,
+                                    offset,
+                                    || Value::known(Fp::zero()),
+                                )?;
+                            }
+                            Ok(())
+                        },
+                    )
+                    .map(|_| ()),
+                Assignment::RegionsReadingInstance => layouter
+                    .assign_regions(
+                        || "region",
+                        (0..4)
+                            .map(|row| {
+                                move |mut region: Region<'_, Fp>| {
+                                    region.assign_advice_from_instance(
+                                        || "instance",
+                                        instance,
+                                        row,
+                                        advice,
+                                        0,
+                                    )
+                                }
+                            })
+                            .collect(),
+                    )
+                    .map(|_| ()),
+            }
+        }
+    }
+
+    #[test]
+    fn forwards_region_instances() {
+        // V1 checks every instance against the shape of the first, which it only does
+        // when the instances are assigned together.
+        let circuit = MyCircuit(Assignment::MisshapenInstance(2));
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![vec![]]).unwrap_err(),
+            Error::InRegion { cause, .. } if matches!(*cause, Error::Synthesis),
+        ));
+    }
+
+    #[test]
+    fn forwards_assign_regions() {
+        // V1 rejects instance reads from the regions it assigns concurrently.
+        let circuit = MyCircuit(Assignment::RegionsReadingInstance);
+        assert!(matches!(
+            MockProver::run(4, &circuit, vec![vec![Fp::zero(); 4]]).unwrap_err(),
+            Error::InRegion { cause, .. } if matches!(*cause, Error::Synthesis),
+        ));
+    }
+}
@@ -6,10 +6,14 @@
 //! [plonk]: https://eprint.iacr.org/2019/953
 
 use blake2b_simd::Params as Blake2bParams;
-use group::ff::{Field, FromUniformBytes, PrimeField};
+use group::{
+    ff::{Field, FromUniformBytes, PrimeField},
+    Curve,
+};
 
 use crate::arithmetic::CurveAffine;
 use crate::poly::{
+    commitment::{Blind, Params},
     Coeff, EvaluationDomain, ExtendedLagrangeCoeff, LagrangeCoeff, PinnedEvaluationDomain,
     Polynomial,
 };
@@ -21,6 +25,7 @@ mod error;
 mod keygen;
 mod lookup;
 pub(crate) mod permutation;
+pub mod proof;
 mod vanishing;
 
 mod prover;
@@ -100,6 +105,21 @@ impl<C: CurveAffine> VerifyingKey<C> {
         Ok(())
     }
 
+    /// Commits to the values of an instance column.
+    ///
+    /// The verifier can be given this commitment in place of the column's values; see
+    /// [`InstanceInput::Commitment`].
+    pub fn commit_instance(&self, params: &Params<C>, values: &[C::Scalar]) -> Result<C, Error> {
+        if values.len() > params.n as usize - (self.cs.blinding_factors() + 1) {
+            return Err(Error::InstanceTooLarge);
+        }
+        let mut poly = values.to_vec();
+        poly.resize(params.n as usize, C::Scalar::ZERO);
+        let poly = self.domain.lagrange_from_vec(poly);
+
+        Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
+    }
+
     /// Obtains a pinned representation of this verification key that contains
     /// the minimal information necessary to reconstruct the verification key.
     pub fn pinned(&self) -> PinnedVerificationKey<'_, C> {
@@ -114,6 +134,22 @@ impl<C: CurveAffine> VerifyingKey<C> {
     }
 }
 
+/// Hashes the values of a hashed instance column (see
+/// [`ConstraintSystem::hash_instance_column`]) into a transcript.
+fn hash_instance_values<C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>>(
+    transcript: &mut T,
+    values: &[C::Scalar],
+) -> io::Result<()> {
+    // Hash the number of values first, so that the boundaries between columns are
+    // unambiguous.
+    transcript.common_scalar(C::Scalar::from(values.len() as u64))?;
+    for value in values {
+        transcript.common_scalar(*value)?;
+    }
+
+    Ok(())
+}
+
 /// Minimal representation of a verification key that can be used to identify
 /// its active contents.
 #[allow(dead_code)]
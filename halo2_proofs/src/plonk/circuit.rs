@@ -3,7 +3,7 @@ use core::ops::{Add, Mul};
 use ff::Field;
 use std::{
     convert::TryFrom,
-    ops::{Neg, Sub},
+    ops::{Neg, Range, Sub},
 };
 
 use super::{lookup, permutation, Assigned, Error};
@@ -926,6 +926,64 @@ impl<F: Field> Gate<F> {
     pub(crate) fn queried_cells(&self) -> &[VirtualCell] {
         &self.queried_cells
     }
+
+    /// Returns the most negative and most positive rotations at which this gate queries
+    /// a cell. The range always includes the current row.
+    pub(crate) fn rotation_bounds(&self) -> (i32, i32) {
+        self.queried_cells.iter().fold((0, 0), |(min, max), cell| {
+            (min.min(cell.rotation.0), max.max(cell.rotation.0))
+        })
+    }
+}
+
+/// The rotation bounds of the gates enabled by each selector, used to check that every
+/// row on which a selector is enabled only queries usable rows.
+///
+/// Gates that do not query a selector are not recorded.
+#[derive(Clone, Debug)]
+pub(crate) struct GateRotations(Vec<Vec<(&'static str, i32, i32)>>);
+
+impl GateRotations {
+    pub(crate) fn new<F: Field>(cs: &ConstraintSystem<F>) -> Self {
+        let mut bounds = vec![vec![]; cs.num_selectors];
+        for gate in &cs.gates {
+            let (min, max) = gate.rotation_bounds();
+            for selector in gate.queried_selectors() {
+                bounds[selector.0].push((gate.name(), min, max));
+            }
+        }
+        GateRotations(bounds)
+    }
+
+    /// Returns the number of rows after a row on which the selector with the given index
+    /// is enabled that its gates query.
+    pub(crate) fn rows_after(&self, selector_index: usize) -> usize {
+        self.0[selector_index]
+            .iter()
+            .map(|&(_, _, max)| max as usize)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Checks that the gates enabled by `selector` on `row` only query cells within
+    /// `usable_rows`, returning the name of the first offending gate and the rotation
+    /// at which it queries outside them.
+    pub(crate) fn check(
+        &self,
+        selector: &Selector,
+        row: usize,
+        usable_rows: &Range<usize>,
+    ) -> Result<(), (&'static str, i32)> {
+        for &(gate, min, max) in &self.0[selector.0] {
+            if (row as i64) + (min as i64) < usable_rows.start as i64 {
+                return Err((gate, min));
+            }
+            if (row as i64) + (max as i64) >= usable_rows.end as i64 {
+                return Err((gate, max));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// This is a description of the circuit environment, such as the gate, column and
@@ -951,6 +1009,10 @@ pub struct ConstraintSystem<F: Field> {
     pub(crate) instance_queries: Vec<(Column<Instance>, Rotation)>,
     pub(crate) fixed_queries: Vec<(Column<Fixed>, Rotation)>,
 
+    // Instance columns whose values are hashed into the transcript, instead of being
+    // committed to.
+    pub(crate) hashed_instance_columns: Vec<Column<Instance>>,
+
     // Permutation argument for performing equality constraints
     pub(crate) permutation: permutation::Argument,
 
@@ -966,8 +1028,6 @@ pub struct ConstraintSystem<F: Field> {
 }
 
 /// Represents the minimal parameters that determine a `ConstraintSystem`.
-#[allow(dead_code)]
-#[derive(Debug)]
 pub struct PinnedConstraintSystem<'a, F: Field> {
     num_fixed_columns: &'a usize,
     num_advice_columns: &'a usize,
@@ -981,6 +1041,32 @@ pub struct PinnedConstraintSystem<'a, F: Field> {
     lookups: &'a Vec<lookup::Argument<F>>,
     constants: &'a Vec<Column<Fixed>>,
     minimum_degree: &'a Option<usize>,
+    hashed_instance_columns: &'a Vec<Column<Instance>>,
+}
+
+impl<'a, F: Field> std::fmt::Debug for PinnedConstraintSystem<'a, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let mut debug_struct = f.debug_struct("PinnedConstraintSystem");
+        debug_struct
+            .field("num_fixed_columns", self.num_fixed_columns)
+            .field("num_advice_columns", self.num_advice_columns)
+            .field("num_instance_columns", self.num_instance_columns)
+            .field("num_selectors", self.num_selectors)
+            .field("gates", &self.gates)
+            .field("advice_queries", self.advice_queries)
+            .field("instance_queries", self.instance_queries)
+            .field("fixed_queries", self.fixed_queries)
+            .field("permutation", self.permutation)
+            .field("lookups", self.lookups)
+            .field("constants", self.constants)
+            .field("minimum_degree", self.minimum_degree);
+        // Only include the hashed instance columns if there are any, so that the pinned
+        // representation of circuits without them is unchanged.
+        if !self.hashed_instance_columns.is_empty() {
+            debug_struct.field("hashed_instance_columns", self.hashed_instance_columns);
+        }
+        debug_struct.finish()
+    }
 }
 
 struct PinnedGates<'a, F: Field>(&'a Vec<Gate<F>>);
@@ -1006,6 +1092,7 @@ impl<F: Field> Default for ConstraintSystem<F> {
             advice_queries: Vec::new(),
             num_advice_queries: Vec::new(),
             instance_queries: Vec::new(),
+            hashed_instance_columns: Vec::new(),
             permutation: permutation::Argument::new(),
             lookups: Vec::new(),
             constants: vec![],
@@ -1032,6 +1119,7 @@ impl<F: Field> ConstraintSystem<F> {
             lookups: &self.lookups,
             constants: &self.constants,
             minimum_degree: &self.minimum_degree,
+            hashed_instance_columns: &self.hashed_instance_columns,
         }
     }
 
@@ -1047,6 +1135,35 @@ impl<F: Field> ConstraintSystem<F> {
         }
     }
 
+    /// Hashes the values of this instance column into the transcript, instead of
+    /// committing to them.
+    ///
+    /// Wherever a hashed instance column is queried, the verifier evaluates it from
+    /// the column's values instead of checking an opening of its commitment. This
+    /// replaces the verifier's multiscalar multiplication for the column with a number
+    /// of field operations linear in the number of values, and removes the column's
+    /// evaluations from the proof. The verifier must be given the values of a hashed
+    /// instance column; it cannot be given a commitment.
+    pub fn hash_instance_column(&mut self, column: Column<Instance>) {
+        if !self.hashed_instance_columns.contains(&column) {
+            self.hashed_instance_columns.push(column);
+        }
+    }
+
+    /// Returns whether the values of this instance column are hashed into the
+    /// transcript, instead of being committed to.
+    pub(crate) fn is_hashed_instance_column(&self, column: Column<Instance>) -> bool {
+        self.hashed_instance_columns.contains(&column)
+    }
+
+    /// Returns whether the instance column with the given index is hashed, for callers
+    /// that enumerate the instance columns by index.
+    pub(crate) fn is_hashed_instance_index(&self, index: usize) -> bool {
+        self.hashed_instance_columns
+            .iter()
+            .any(|column| column.index() == index)
+    }
+
     /// Enable the ability to enforce equality over cells in this column
     pub fn enable_equality<C: Into<Column<Any>>>(&mut self, column: C) {
         let column = column.into();
@@ -1459,6 +1576,28 @@ impl<F: Field> ConstraintSystem<F> {
         factors + 1
     }
 
+    /// Returns the number of rows before and after a row on which a gate is enabled
+    /// that the gate may query, as determined by the most negative and most positive
+    /// rotations used across all gates.
+    ///
+    /// A gate enabled on a row with fewer usable rows than this before or after it may
+    /// query one of the rows reserved for blinding factors, or wrap around the start of
+    /// the domain. Keygen rejects circuits where this happens, and
+    /// [`MockProver::check_gate_rotations`] reports where it does.
+    ///
+    /// [`MockProver::check_gate_rotations`]: crate::dev::MockProver::check_gate_rotations
+    pub fn rotation_padding(&self) -> (usize, usize) {
+        self.gates.iter().map(|gate| gate.rotation_bounds()).fold(
+            (0, 0),
+            |(before, after), (min, max)| {
+                (
+                    std::cmp::max(before, min.unsigned_abs() as usize),
+                    std::cmp::max(after, max as usize),
+                )
+            },
+        )
+    }
+
     /// Returns the minimum necessary rows that need to exist in order to
     /// account for e.g. blinding factors.
     pub fn minimum_rows(&self) -> usize {
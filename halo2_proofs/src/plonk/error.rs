@@ -1,3 +1,4 @@
+use std::cmp;
 use std::error;
 use std::fmt;
 use std::io;
@@ -16,6 +17,13 @@ pub enum Error {
     /// The provided instances do not match the circuit parameters.
     InvalidInstances,
     /// The constraint system is not satisfied.
+    ///
+    /// The custom gates, the permutation argument and the lookup arguments are checked
+    /// jointly through the vanishing argument, so the verifier cannot tell which of
+    /// them failed. Use [`MockProver`] to find the constraints that a witness does not
+    /// satisfy.
+    ///
+    /// [`MockProver`]: crate::dev::MockProver
     ConstraintSystemFailure,
     /// Out of bounds index passed to a backend
     BoundsFailure,
@@ -23,11 +31,60 @@ pub enum Error {
     Opening,
     /// Transcript error
     Transcript(io::Error),
+    /// The proof was rejected by the verifier.
+    Verifier(VerifierError),
     /// `k` is too small for the given circuit.
+    #[non_exhaustive]
     NotEnoughRowsAvailable {
         /// The current value of `k` being used.
         current_k: u32,
+        /// The number of rows (including those reserved for blinding factors) that the
+        /// circuit was found to need at the point of failure, if known. This is a lower
+        /// bound: the circuit may need more rows than this.
+        required_rows: Option<usize>,
+    },
+    /// A gate is enabled on a row where it queries a cell outside the usable rows of
+    /// the circuit: either one of the rows reserved for blinding factors, or a row
+    /// before the start of the circuit. Returned by keygen, and by
+    /// [`MockProver::check_gate_rotations`].
+    ///
+    /// [`MockProver::check_gate_rotations`]: crate::dev::MockProver::check_gate_rotations
+    #[non_exhaustive]
+    QueryOutsideUsableRows {
+        /// The name of the gate.
+        gate: String,
+        /// The name of the region in which the gate was enabled, if any.
+        region: Option<String>,
+        /// The row on which the gate was enabled.
+        row: usize,
+        /// The rotation at which the gate queries outside the usable rows.
+        rotation: i32,
+        /// The current value of `k` being used.
+        current_k: u32,
+    },
+    /// A cell of a [`ColumnGroup`] was assigned through a row of the group at which its
+    /// rotation would place it before the start of the region, or beyond the largest
+    /// offset that can be addressed.
+    ///
+    /// [`ColumnGroup`]: crate::circuit::ColumnGroup
+    #[non_exhaustive]
+    GroupCellOutsideRegion {
+        /// The name of the column group.
+        group: String,
+        /// The name of the cell.
+        cell: String,
+        /// The offset within the region at which the group was enabled.
+        offset: usize,
+        /// The rotation of the cell relative to the row on which the group is enabled.
+        rotation: i32,
     },
+    /// A witness value could not be computed, for example because an input to the
+    /// circuit is invalid. The message describes the failure.
+    ///
+    /// See [`Value::try_map`] for computing witness values that can fail.
+    ///
+    /// [`Value::try_map`]: crate::circuit::Value::try_map
+    Witness(String),
     /// Instance provided exceeds number of available rows
     InstanceTooLarge,
     /// Circuit synthesis requires global constants, but circuit configuration did not
@@ -42,6 +99,23 @@ pub enum Error {
     TableError(TableError),
     /// Try to hash from a private point when allow_init_from_private_point is not set.
     IllegalHashFromPrivatePoint,
+    /// An error occurred while assigning a region of the circuit.
+    ///
+    /// The floor planners attach this context to [`Error::Synthesis`] and
+    /// [`Error::Witness`] errors returned from within [`Layouter::assign_region`].
+    ///
+    /// [`Layouter::assign_region`]: crate::circuit::Layouter::assign_region
+    InRegion {
+        /// The name of the region in which the error occurred.
+        region: String,
+        /// The namespaces (from [`Layouter::namespace`]) that the region was assigned
+        /// within, outermost first.
+        ///
+        /// [`Layouter::namespace`]: crate::circuit::Layouter::namespace
+        namespace: Vec<String>,
+        /// The error that occurred.
+        cause: Box<Error>,
+    },
 }
 
 impl From<io::Error> for Error {
@@ -53,8 +127,51 @@ impl From<io::Error> for Error {
 
 impl Error {
     /// Constructs an `Error::NotEnoughRowsAvailable`.
-    pub(crate) fn not_enough_rows_available(current_k: u32) -> Self {
-        Error::NotEnoughRowsAvailable { current_k }
+    pub(crate) fn not_enough_rows_available(current_k: u32, required_rows: usize) -> Self {
+        Error::NotEnoughRowsAvailable {
+            current_k,
+            required_rows: Some(required_rows),
+        }
+    }
+
+    /// Constructs an `Error::NotEnoughRowsAvailable` for an attempt to use `row`, when
+    /// only the first `usable_rows` rows can be used.
+    pub(crate) fn row_not_usable(current_k: u32, usable_rows: usize, row: usize) -> Self {
+        // The rows after the usable rows are reserved for blinding factors.
+        let reserved_rows = (1usize << current_k) - usable_rows;
+        Self::not_enough_rows_available(current_k, row + 1 + reserved_rows)
+    }
+
+    /// Constructs an `Error::QueryOutsideUsableRows`.
+    pub(crate) fn query_outside_usable_rows(
+        gate: &str,
+        region: Option<&str>,
+        row: usize,
+        rotation: i32,
+        current_k: u32,
+    ) -> Self {
+        Error::QueryOutsideUsableRows {
+            gate: gate.to_string(),
+            region: region.map(|region| region.to_string()),
+            row,
+            rotation,
+            current_k,
+        }
+    }
+
+    /// Attaches the context of the region in which this error occurred.
+    ///
+    /// Only [`Error::Synthesis`] and [`Error::Witness`] errors are given context; other
+    /// errors are returned unchanged so that callers can continue to match on them.
+    pub(crate) fn in_region(self, region: impl FnOnce() -> String, namespace: &[String]) -> Self {
+        match self {
+            Error::Synthesis | Error::Witness(_) => Error::InRegion {
+                region: region(),
+                namespace: namespace.to_vec(),
+                cause: Box::new(self),
+            },
+            _ => self,
+        }
     }
 }
 
@@ -67,11 +184,57 @@ impl fmt::Display for Error {
             Error::BoundsFailure => write!(f, "An out-of-bounds index was passed to the backend"),
             Error::Opening => write!(f, "Multi-opening proof was invalid"),
             Error::Transcript(e) => write!(f, "Transcript error: {}", e),
-            Error::NotEnoughRowsAvailable { current_k } => write!(
+            Error::Verifier(e) => write!(f, "Proof verification failed: {}", e),
+            Error::NotEnoughRowsAvailable {
+                current_k,
+                required_rows: Some(required_rows),
+            } => write!(
+                f,
+                "k = {} is too small for the given circuit, which needs at least {} rows. Try using k >= {}",
+                current_k,
+                required_rows,
+                cmp::max(*current_k + 1, required_rows.next_power_of_two().trailing_zeros()),
+            ),
+            Error::NotEnoughRowsAvailable {
+                current_k,
+                required_rows: None,
+            } => write!(
                 f,
                 "k = {} is too small for the given circuit. Try using a larger value of k",
                 current_k,
             ),
+            Error::QueryOutsideUsableRows {
+                gate,
+                region,
+                row,
+                rotation,
+                current_k,
+            } => {
+                write!(f, "Gate '{}' enabled on row {}", gate, row)?;
+                if let Some(region) = region {
+                    write!(f, " in region '{}'", region)?;
+                }
+                write!(
+                    f,
+                    " queries rotation {}, which is outside the usable rows for k = {}",
+                    rotation, current_k
+                )?;
+                if *rotation > 0 {
+                    write!(f, ". Try using a larger k")?;
+                }
+                Ok(())
+            }
+            Error::GroupCellOutsideRegion {
+                group,
+                cell,
+                offset,
+                rotation,
+            } => write!(
+                f,
+                "Cell '{}' of column group '{}' is at rotation {}, which is outside the region when the group is enabled at offset {}",
+                cell, group, rotation, offset
+            ),
+            Error::Witness(message) => write!(f, "Witness error: {}", message),
             Error::InstanceTooLarge => write!(f, "Instance vectors are larger than the circuit"),
             Error::NotEnoughColumnsForConstants => {
                 write!(
@@ -88,7 +251,18 @@ impl fmt::Display for Error {
             Error::IllegalHashFromPrivatePoint =>  write!(
                 f,
                 "Hashing from private point is disabled"
-            )
+            ),
+            Error::InRegion {
+                region,
+                namespace,
+                cause,
+            } => {
+                write!(f, "In region '{}'", region)?;
+                if !namespace.is_empty() {
+                    write!(f, " (in {})", namespace.join(" / "))?;
+                }
+                write!(f, ": {}", cause)
+            }
         }
     }
 }
@@ -97,6 +271,65 @@ impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
             Error::Transcript(e) => Some(e),
+            Error::Verifier(e) => Some(e),
+            Error::InRegion { cause, .. } => Some(cause.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// The reason a proof was rejected by [`verify_proof`].
+///
+/// Byte offsets are relative to the start of the proof, as read from the transcript by
+/// [`verify_proof`].
+///
+/// [`verify_proof`]: crate::plonk::verify_proof
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum VerifierError {
+    /// The proof could not be read from the transcript, for example because it was
+    /// truncated.
+    MalformedTranscript {
+        /// The offset at which the failing read started.
+        offset: usize,
+        /// The error returned by the transcript.
+        error: io::Error,
+    },
+    /// The proof contains an invalid encoding of a curve point, or the point at
+    /// infinity.
+    InvalidPointEncoding {
+        /// The offset of the encoded point.
+        offset: usize,
+    },
+    /// The proof contains a non-canonical encoding of a scalar.
+    InvalidScalarEncoding {
+        /// The offset of the encoded scalar.
+        offset: usize,
+    },
+}
+
+impl fmt::Display for VerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifierError::MalformedTranscript { offset, error } => write!(
+                f,
+                "Failed to read proof at offset {:#06x}: {}",
+                offset, error
+            ),
+            VerifierError::InvalidPointEncoding { offset } => {
+                write!(f, "Invalid point encoding at offset {:#06x}", offset)
+            }
+            VerifierError::InvalidScalarEncoding { offset } => {
+                write!(f, "Invalid scalar encoding at offset {:#06x}", offset)
+            }
+        }
+    }
+}
+
+impl error::Error for VerifierError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            VerifierError::MalformedTranscript { error, .. } => Some(error),
             _ => None,
         }
     }
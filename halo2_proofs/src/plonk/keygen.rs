@@ -1,14 +1,18 @@
 #![allow(clippy::int_plus_one)]
 
+use std::cmp;
+use std::collections::HashMap;
+use std::io;
 use std::ops::Range;
 
-use ff::{Field, FromUniformBytes};
+use blake2b_simd::Params as Blake2bParams;
+use ff::{Field, FromUniformBytes, PrimeField, WithSmallOrderMulGroup};
 use group::Curve;
 
 use super::{
     circuit::{
-        Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
-        Selector,
+        Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner,
+        GateRotations, Instance, Selector,
     },
     permutation, Assigned, Error, LagrangeCoeff, Polynomial, ProvingKey, VerifyingKey,
 };
@@ -18,7 +22,7 @@ use crate::{
     poly::{
         batch_invert_assigned,
         commitment::{Blind, Params},
-        EvaluationDomain,
+        Coeff, EvaluationDomain, ExtendedLagrangeCoeff,
     },
 };
 
@@ -50,22 +54,26 @@ struct Assembly<F: Field> {
     fixed: Vec<Polynomial<Assigned<F>, LagrangeCoeff>>,
     permutation: permutation::keygen::Assembly,
     selectors: Vec<Vec<bool>>,
+    gate_rotations: GateRotations,
+    // The name of the region being assigned, for error reporting.
+    current_region: Option<String>,
     // A range of available rows for assignment and copies.
     usable_rows: Range<usize>,
     _marker: std::marker::PhantomData<F>,
 }
 
 impl<F: Field> Assignment<F> for Assembly<F> {
-    fn enter_region<NR, N>(&mut self, _: N)
+    fn enter_region<NR, N>(&mut self, name: N)
     where
         NR: Into<String>,
         N: FnOnce() -> NR,
     {
-        // Do nothing; we don't care about regions in this context.
+        // We only track the region name for error reporting.
+        self.current_region = Some(name().into());
     }
 
     fn exit_region(&mut self) {
-        // Do nothing; we don't care about regions in this context.
+        self.current_region = None;
     }
 
     fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
@@ -74,8 +82,19 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
+        self.gate_rotations
+            .check(selector, row, &self.usable_rows)
+            .map_err(|(gate, rotation)| {
+                Error::query_outside_usable_rows(
+                    gate,
+                    self.current_region.as_deref(),
+                    row,
+                    rotation,
+                    self.k,
+                )
+            })?;
 
         self.selectors[selector.0][row] = true;
 
@@ -84,7 +103,7 @@ impl<F: Field> Assignment<F> for Assembly<F> {
 
     fn query_instance(&self, _: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
 
         // There is no instance in this context.
@@ -122,7 +141,7 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         AR: Into<String>,
     {
         if !self.usable_rows.contains(&row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
         }
 
         *self
@@ -142,7 +161,11 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         right_row: usize,
     ) -> Result<(), Error> {
         if !self.usable_rows.contains(&left_row) || !self.usable_rows.contains(&right_row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(
+                self.k,
+                self.usable_rows.end,
+                cmp::max(left_row, right_row),
+            ));
         }
 
         self.permutation
@@ -156,7 +179,11 @@ impl<F: Field> Assignment<F> for Assembly<F> {
         to: Value<Assigned<F>>,
     ) -> Result<(), Error> {
         if !self.usable_rows.contains(&from_row) {
-            return Err(Error::not_enough_rows_available(self.k));
+            return Err(Error::row_not_usable(
+                self.k,
+                self.usable_rows.end,
+                from_row,
+            ));
         }
 
         let col = self
@@ -185,20 +212,221 @@ impl<F: Field> Assignment<F> for Assembly<F> {
     }
 }
 
-/// Generate a `VerifyingKey` from an instance of `Circuit`.
-pub fn keygen_vk<C, ConcreteCircuit>(
+/// A cache of the commitments to fixed columns, and of their polynomials in coefficient
+/// and extended Lagrange form, keyed by the contents of the columns.
+///
+/// Many circuits contain identical fixed columns, such as lookup tables. Passing the
+/// same cache to [`keygen_vk_with_cache`] and [`keygen_pk_with_cache`] when generating
+/// the keys of several circuits computes these for each distinct column only once.
+///
+/// Commitments are keyed by the [`Params`] they were computed with, and polynomials by
+/// the size of the extended domain, so a cache may be shared between circuits with
+/// different parameters.
+#[derive(Debug)]
+pub struct FixedColumnCache<C: CurveAffine> {
+    commitments: HashMap<([u8; 64], [u8; 64]), C>,
+    #[allow(clippy::type_complexity)]
+    polys: HashMap<
+        ([u8; 64], usize),
+        (
+            Polynomial<C::Scalar, Coeff>,
+            Polynomial<C::Scalar, ExtendedLagrangeCoeff>,
+        ),
+    >,
+}
+
+impl<C: CurveAffine> Default for FixedColumnCache<C> {
+    fn default() -> Self {
+        FixedColumnCache {
+            commitments: HashMap::new(),
+            polys: HashMap::new(),
+        }
+    }
+}
+
+impl<C: CurveAffine> FixedColumnCache<C> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the key identifying the contents of a fixed column.
+    fn column_key(column: &Polynomial<C::Scalar, LagrangeCoeff>) -> [u8; 64] {
+        let mut hasher = Blake2bParams::new()
+            .hash_length(64)
+            .personal(b"Halo2-Fixed-Cols")
+            .to_state();
+        hasher.update(&(column.len() as u64).to_le_bytes());
+        for value in column.iter() {
+            hasher.update(value.to_repr().as_ref());
+        }
+        *hasher.finalize().as_array()
+    }
+
+    /// Returns the commitment to each of the given fixed columns.
+    fn commit(
+        &mut self,
+        params: &Params<C>,
+        columns: &[Polynomial<C::Scalar, LagrangeCoeff>],
+    ) -> Vec<C> {
+        let params_key = params.key();
+        columns
+            .iter()
+            .map(|column| {
+                *self
+                    .commitments
+                    .entry((params_key, Self::column_key(column)))
+                    .or_insert_with(|| params.commit_lagrange(column, Blind::default()).to_affine())
+            })
+            .collect()
+    }
+
+    /// Returns each of the given fixed columns in coefficient and extended Lagrange form.
+    #[allow(clippy::type_complexity)]
+    fn polys(
+        &mut self,
+        domain: &EvaluationDomain<C::Scalar>,
+        columns: &[Polynomial<C::Scalar, LagrangeCoeff>],
+    ) -> (
+        Vec<Polynomial<C::Scalar, Coeff>>,
+        Vec<Polynomial<C::Scalar, ExtendedLagrangeCoeff>>,
+    ) {
+        columns
+            .iter()
+            .map(|column| {
+                self.polys
+                    .entry((Self::column_key(column), domain.extended_len()))
+                    .or_insert_with(|| {
+                        let poly = domain.lagrange_to_coeff(column.clone());
+                        let coset = domain.coeff_to_extended(poly.clone());
+                        (poly, coset)
+                    })
+                    .clone()
+            })
+            .unzip()
+    }
+}
+
+/// The fixed columns and permutation mapping of a circuit, as assigned when it is
+/// synthesized during key generation.
+///
+/// Along with the circuit's [`VerifyingKey`], a snapshot is all that
+/// [`keygen_pk_from_snapshot`] needs to build the circuit's [`ProvingKey`], without
+/// synthesizing the circuit again. Snapshots can be saved with
+/// [`AssemblySnapshot::write`] and loaded with [`AssemblySnapshot::read`], for example to
+/// rebuild proving keys on other machines.
+#[derive(Clone, Debug)]
+pub struct AssemblySnapshot<F: Field> {
+    fixed: Vec<Polynomial<F, LagrangeCoeff>>,
+    mapping: Vec<Vec<(usize, usize)>>,
+}
+
+impl<F: WithSmallOrderMulGroup<3>> AssemblySnapshot<F> {
+    /// Writes this snapshot to a buffer.
+    pub fn write<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.fixed.len() as u32).to_le_bytes())?;
+        for column in &self.fixed {
+            for value in column.iter() {
+                writer.write_all(value.to_repr().as_ref())?;
+            }
+        }
+        writer.write_all(&(self.mapping.len() as u32).to_le_bytes())?;
+        for column in &self.mapping {
+            for &(permuted_column, permuted_row) in column {
+                writer.write_all(&(permuted_column as u32).to_le_bytes())?;
+                writer.write_all(&(permuted_row as u32).to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads a snapshot of the circuit with the verifying key `vk` from a buffer.
+    ///
+    /// Returns an error if the number of fixed or permuted columns in the snapshot does
+    /// not match the constraint system of `vk`.
+    pub fn read<C, R>(reader: &mut R, vk: &VerifyingKey<C>) -> io::Result<Self>
+    where
+        C: CurveAffine<ScalarExt = F>,
+        R: io::Read,
+    {
+        fn read_u32<R: io::Read>(reader: &mut R) -> io::Result<usize> {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes[..])?;
+            Ok(u32::from_le_bytes(bytes) as usize)
+        }
+
+        let domain = &vk.domain;
+        let n = 1usize << domain.k();
+
+        // Check the column counts before allocating anything for them.
+        let check_count = |count: usize, expected: usize| {
+            if count == expected {
+                Ok(count)
+            } else {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "snapshot does not match the verifying key",
+                ))
+            }
+        };
+
+        let num_fixed = check_count(read_u32(reader)?, vk.cs.num_fixed_columns)?;
+        let mut fixed = Vec::with_capacity(num_fixed);
+        for _ in 0..num_fixed {
+            let mut values = Vec::with_capacity(n);
+            for _ in 0..n {
+                let mut repr = F::Repr::default();
+                reader.read_exact(repr.as_mut())?;
+                let value = Option::from(F::from_repr(repr)).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid fixed value")
+                })?;
+                values.push(value);
+            }
+            fixed.push(domain.lagrange_from_vec(values));
+        }
+
+        let num_columns = check_count(read_u32(reader)?, vk.cs.permutation.get_columns().len())?;
+        let mut mapping = Vec::with_capacity(num_columns);
+        for _ in 0..num_columns {
+            let mut column = Vec::with_capacity(n);
+            for _ in 0..n {
+                let permuted_column = read_u32(reader)?;
+                let permuted_row = read_u32(reader)?;
+                if permuted_column >= num_columns || permuted_row >= n {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "permutation mapping out of range",
+                    ));
+                }
+                column.push((permuted_column, permuted_row));
+            }
+            mapping.push(column);
+        }
+
+        Ok(AssemblySnapshot { fixed, mapping })
+    }
+}
+
+/// Synthesizes `circuit` over the given domain, returning a snapshot of its assignment
+/// along with its constraint system (with its selectors compressed into fixed columns).
+#[allow(clippy::type_complexity)]
+fn synthesize_snapshot<C, ConcreteCircuit>(
     params: &Params<C>,
+    domain: &EvaluationDomain<C::Scalar>,
+    cs: ConstraintSystem<C::Scalar>,
+    config: ConcreteCircuit::Config,
     circuit: &ConcreteCircuit,
-) -> Result<VerifyingKey<C>, Error>
+) -> Result<(ConstraintSystem<C::Scalar>, AssemblySnapshot<C::Scalar>), Error>
 where
     C: CurveAffine,
-    C::Scalar: FromUniformBytes<64>,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params);
-
     if (params.n as usize) < cs.minimum_rows() {
-        return Err(Error::not_enough_rows_available(params.k));
+        return Err(Error::not_enough_rows_available(
+            params.k,
+            cs.minimum_rows(),
+        ));
     }
 
     let mut assembly: Assembly<C::Scalar> = Assembly {
@@ -206,6 +434,8 @@ where
         fixed: vec![domain.empty_lagrange_assigned(); cs.num_fixed_columns],
         permutation: permutation::keygen::Assembly::new(params.n as usize, &cs.permutation),
         selectors: vec![vec![false; params.n as usize]; cs.num_selectors],
+        gate_rotations: GateRotations::new(&cs),
+        current_region: None,
         usable_rows: 0..params.n as usize - (cs.blinding_factors() + 1),
         _marker: std::marker::PhantomData,
     };
@@ -226,20 +456,104 @@ where
             .map(|poly| domain.lagrange_from_vec(poly)),
     );
 
-    let permutation_vk = assembly
-        .permutation
-        .build_vk(params, &domain, &cs.permutation);
+    Ok((
+        cs,
+        AssemblySnapshot {
+            fixed,
+            mapping: assembly.permutation.mapping,
+        },
+    ))
+}
+
+/// Generate a `VerifyingKey` from an instance of `Circuit`.
+pub fn keygen_vk<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    keygen_vk_inner(params, circuit, None).map(|(vk, _)| vk)
+}
 
-    let fixed_commitments = fixed
-        .iter()
-        .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
-        .collect();
+/// Generate a `VerifyingKey` from an instance of `Circuit`, reusing the commitments to
+/// any fixed columns that are in `cache`, and adding the others to it.
+pub fn keygen_vk_with_cache<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+    cache: &mut FixedColumnCache<C>,
+) -> Result<VerifyingKey<C>, Error>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    keygen_vk_inner(params, circuit, Some(cache)).map(|(vk, _)| vk)
+}
 
-    Ok(VerifyingKey::from_parts(
-        domain,
-        fixed_commitments,
-        permutation_vk,
-        cs,
+/// Generate a `VerifyingKey` from an instance of `Circuit`, along with the
+/// [`AssemblySnapshot`] from which [`keygen_pk_from_snapshot`] can later build its
+/// `ProvingKey`.
+pub fn keygen_vk_with_snapshot<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<(VerifyingKey<C>, AssemblySnapshot<C::Scalar>), Error>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    keygen_vk_inner(params, circuit, None)
+}
+
+/// Generate a `VerifyingKey` and a `ProvingKey` from an instance of `Circuit`.
+///
+/// This synthesizes the circuit once, whereas calling [`keygen_vk`] and then
+/// [`keygen_pk`] synthesizes it twice.
+pub fn keygen<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+) -> Result<(VerifyingKey<C>, ProvingKey<C>), Error>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let (vk, snapshot) = keygen_vk_inner(params, circuit, None)?;
+    let pk = build_pk(params, vk.clone(), snapshot, None);
+    Ok((vk, pk))
+}
+
+fn keygen_vk_inner<C, ConcreteCircuit>(
+    params: &Params<C>,
+    circuit: &ConcreteCircuit,
+    cache: Option<&mut FixedColumnCache<C>>,
+) -> Result<(VerifyingKey<C>, AssemblySnapshot<C::Scalar>), Error>
+where
+    C: CurveAffine,
+    C::Scalar: FromUniformBytes<64>,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let (domain, cs, config) = create_domain::<C, ConcreteCircuit>(params);
+    let (cs, snapshot) = synthesize_snapshot(params, &domain, cs, config, circuit)?;
+
+    let permutation_vk =
+        permutation::keygen::build_vk(params, &domain, &cs.permutation, &snapshot.mapping);
+
+    let fixed_commitments = match cache {
+        Some(cache) => cache.commit(params, &snapshot.fixed),
+        None => snapshot
+            .fixed
+            .iter()
+            .map(|poly| params.commit_lagrange(poly, Blind::default()).to_affine())
+            .collect(),
+    };
+
+    Ok((
+        VerifyingKey::from_parts(domain, fixed_commitments, permutation_vk, cs),
+        snapshot,
     ))
 }
 
@@ -253,53 +567,113 @@ where
     C: CurveAffine,
     ConcreteCircuit: Circuit<C::Scalar>,
 {
-    let mut cs = ConstraintSystem::default();
-    let config = ConcreteCircuit::configure(&mut cs);
+    keygen_pk_inner(params, vk, circuit, None)
+}
 
-    let cs = cs;
+/// Generate a `ProvingKey` from a `VerifyingKey` and an instance of `Circuit`, reusing
+/// the polynomials of any fixed columns that are in `cache`, and adding the others to it.
+pub fn keygen_pk_with_cache<C, ConcreteCircuit>(
+    params: &Params<C>,
+    vk: VerifyingKey<C>,
+    circuit: &ConcreteCircuit,
+    cache: &mut FixedColumnCache<C>,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    keygen_pk_inner(params, vk, circuit, Some(cache))
+}
 
-    if (params.n as usize) < cs.minimum_rows() {
-        return Err(Error::not_enough_rows_available(params.k));
+/// Generate a `ProvingKey` from a `VerifyingKey` and the [`AssemblySnapshot`] of the
+/// same circuit, without synthesizing the circuit.
+///
+/// Returns [`Error::ConstraintSystemFailure`] if the shape of the snapshot does not
+/// match the constraint system of `vk`, or if its fixed columns do not match the fixed
+/// commitments of `vk`.
+pub fn keygen_pk_from_snapshot<C>(
+    params: &Params<C>,
+    vk: VerifyingKey<C>,
+    snapshot: AssemblySnapshot<C::Scalar>,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+{
+    let n = params.n as usize;
+    if vk.domain.k() != params.k
+        || snapshot.fixed.len() != vk.cs.num_fixed_columns
+        || snapshot.fixed.iter().any(|column| column.len() != n)
+        || snapshot.mapping.len() != vk.cs.permutation.get_columns().len()
+        || snapshot.mapping.iter().any(|column| column.len() != n)
+    {
+        return Err(Error::ConstraintSystemFailure);
     }
 
-    let mut assembly: Assembly<C::Scalar> = Assembly {
-        k: params.k,
-        fixed: vec![vk.domain.empty_lagrange_assigned(); cs.num_fixed_columns],
-        permutation: permutation::keygen::Assembly::new(params.n as usize, &cs.permutation),
-        selectors: vec![vec![false; params.n as usize]; cs.num_selectors],
-        usable_rows: 0..params.n as usize - (cs.blinding_factors() + 1),
-        _marker: std::marker::PhantomData,
-    };
+    // The snapshot is not derived from `vk`, so check that it is of the same circuit.
+    let fixed_commitments_match =
+        snapshot
+            .fixed
+            .iter()
+            .zip(vk.fixed_commitments.iter())
+            .all(|(column, commitment)| {
+                params.commit_lagrange(column, Blind::default()).to_affine() == *commitment
+            });
+    if !fixed_commitments_match {
+        return Err(Error::ConstraintSystemFailure);
+    }
 
-    // Synthesize the circuit to obtain URS
-    ConcreteCircuit::FloorPlanner::synthesize(
-        &mut assembly,
-        circuit,
-        config,
-        cs.constants.clone(),
-    )?;
+    Ok(build_pk(params, vk, snapshot, None))
+}
 
-    let mut fixed = batch_invert_assigned(assembly.fixed);
-    let (cs, selector_polys) = cs.compress_selectors(assembly.selectors);
-    fixed.extend(
-        selector_polys
-            .into_iter()
-            .map(|poly| vk.domain.lagrange_from_vec(poly)),
-    );
+fn keygen_pk_inner<C, ConcreteCircuit>(
+    params: &Params<C>,
+    vk: VerifyingKey<C>,
+    circuit: &ConcreteCircuit,
+    cache: Option<&mut FixedColumnCache<C>>,
+) -> Result<ProvingKey<C>, Error>
+where
+    C: CurveAffine,
+    ConcreteCircuit: Circuit<C::Scalar>,
+{
+    let mut cs = ConstraintSystem::default();
+    let config = ConcreteCircuit::configure(&mut cs);
 
-    let fixed_polys: Vec<_> = fixed
-        .iter()
-        .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
-        .collect();
+    let (_, snapshot) = synthesize_snapshot(params, &vk.domain, cs, config, circuit)?;
 
-    let fixed_cosets = fixed_polys
-        .iter()
-        .map(|poly| vk.domain.coeff_to_extended(poly.clone()))
-        .collect();
+    Ok(build_pk(params, vk, snapshot, cache))
+}
+
+fn build_pk<C>(
+    params: &Params<C>,
+    vk: VerifyingKey<C>,
+    snapshot: AssemblySnapshot<C::Scalar>,
+    cache: Option<&mut FixedColumnCache<C>>,
+) -> ProvingKey<C>
+where
+    C: CurveAffine,
+{
+    let AssemblySnapshot { fixed, mapping } = snapshot;
+    let cs = &vk.cs;
+
+    let (fixed_polys, fixed_cosets) = match cache {
+        Some(cache) => cache.polys(&vk.domain, &fixed),
+        None => {
+            let fixed_polys: Vec<_> = fixed
+                .iter()
+                .map(|poly| vk.domain.lagrange_to_coeff(poly.clone()))
+                .collect();
+
+            let fixed_cosets = fixed_polys
+                .iter()
+                .map(|poly| vk.domain.coeff_to_extended(poly.clone()))
+                .collect();
+
+            (fixed_polys, fixed_cosets)
+        }
+    };
 
-    let permutation_pk = assembly
-        .permutation
-        .build_pk(params, &vk.domain, &cs.permutation);
+    let permutation_pk =
+        permutation::keygen::build_pk(params, &vk.domain, &cs.permutation, &mapping);
 
     // Compute l_0(X)
     // TODO: this can be done more efficiently
@@ -324,7 +698,7 @@ where
     let l_last = vk.domain.lagrange_to_coeff(l_last);
     let l_last = vk.domain.coeff_to_extended(l_last);
 
-    Ok(ProvingKey {
+    ProvingKey {
         vk,
         l0,
         l_blind,
@@ -333,5 +707,184 @@ where
         fixed_polys,
         fixed_cosets,
         permutation: permutation_pk,
-    })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use ff::Field;
+    use pasta_curves::{EqAffine, Fp};
+
+    use super::{
+        keygen, keygen_pk, keygen_pk_from_snapshot, keygen_pk_with_cache, keygen_vk,
+        keygen_vk_with_cache, keygen_vk_with_snapshot, AssemblySnapshot, FixedColumnCache,
+    };
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{Circuit, Column, ConstraintSystem, Error, Fixed, ProvingKey},
+        poly::commitment::Params,
+    };
+
+    #[derive(Clone)]
+    struct TableCircuit {
+        table: Vec<u64>,
+        other: Vec<u64>,
+    }
+
+    impl Circuit<Fp> for TableCircuit {
+        type Config = [Column<Fixed>; 2];
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let columns = [meta.fixed_column(), meta.fixed_column()];
+            for column in columns {
+                meta.enable_equality(column);
+            }
+            columns
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "fixed",
+                |mut region| {
+                    let mut cells = vec![];
+                    for (column, values) in config.iter().zip([&self.table, &self.other]) {
+                        for (offset, value) in values.iter().enumerate() {
+                            cells.push(region.assign_fixed(
+                                || "value",
+                                *column,
+                                offset,
+                                || Value::known(Fp::from(*value)),
+                            )?);
+                        }
+                    }
+
+                    // Copy the first table entry after the other values.
+                    let copy = region.assign_fixed(
+                        || "copy",
+                        config[1],
+                        self.other.len(),
+                        || Value::known(Fp::from(self.table[0])),
+                    )?;
+                    region.constrain_equal(cells[0].cell(), copy.cell())
+                },
+            )
+        }
+    }
+
+    fn assert_same_pk(a: &ProvingKey<EqAffine>, b: &ProvingKey<EqAffine>) {
+        assert_eq!(
+            format!("{:?}", a.vk.pinned()),
+            format!("{:?}", b.vk.pinned())
+        );
+        for (a, b) in a.fixed_cosets.iter().zip(b.fixed_cosets.iter()) {
+            assert_eq!(a[..], b[..]);
+        }
+        for (a, b) in a.permutation.cosets.iter().zip(b.permutation.cosets.iter()) {
+            assert_eq!(a[..], b[..]);
+        }
+    }
+
+    #[test]
+    fn keygen_from_snapshot() {
+        let params: Params<EqAffine> = Params::new(4);
+        let circuit = TableCircuit {
+            table: vec![1, 2, 3, 4],
+            other: vec![5, 6],
+        };
+
+        let vk = keygen_vk(&params, &circuit).unwrap();
+        let expected = keygen_pk(&params, vk, &circuit).unwrap();
+
+        let (vk, pk) = keygen(&params, &circuit).unwrap();
+        assert_eq!(
+            format!("{:?}", vk.pinned()),
+            format!("{:?}", expected.vk.pinned())
+        );
+        assert_same_pk(&pk, &expected);
+
+        // Build the proving key from a saved snapshot, without the circuit.
+        let (vk, snapshot) = keygen_vk_with_snapshot(&params, &circuit).unwrap();
+        let mut bytes = vec![];
+        snapshot.write(&mut bytes).unwrap();
+        let snapshot = AssemblySnapshot::read(&mut &bytes[..], &vk).unwrap();
+        let pk = keygen_pk_from_snapshot(&params, vk, snapshot).unwrap();
+        assert_same_pk(&pk, &expected);
+
+        // A saved snapshot must have the column counts of the verifying key.
+        let (vk, _) = keygen_vk_with_snapshot(&params, &circuit).unwrap();
+        let mut misshapen = bytes.clone();
+        misshapen[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            AssemblySnapshot::read(&mut &misshapen[..], &vk)
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        // A snapshot must match the shape of the verifying key.
+        let (vk, mut snapshot) = keygen_vk_with_snapshot(&params, &circuit).unwrap();
+        snapshot.fixed.pop();
+        assert!(matches!(
+            keygen_pk_from_snapshot(&params, vk, snapshot),
+            Err(Error::ConstraintSystemFailure)
+        ));
+
+        // A snapshot must match the fixed commitments of the verifying key.
+        let (vk, mut snapshot) = keygen_vk_with_snapshot(&params, &circuit).unwrap();
+        snapshot.fixed[0][0] += Fp::ONE;
+        assert!(matches!(
+            keygen_pk_from_snapshot(&params, vk, snapshot),
+            Err(Error::ConstraintSystemFailure)
+        ));
+    }
+
+    #[test]
+    fn fixed_column_cache() {
+        let params: Params<EqAffine> = Params::new(4);
+        let circuits = [
+            TableCircuit {
+                table: vec![1, 2, 3, 4],
+                other: vec![5, 6],
+            },
+            TableCircuit {
+                table: vec![1, 2, 3, 4],
+                other: vec![7, 8],
+            },
+        ];
+
+        let mut cache = FixedColumnCache::new();
+        for (i, circuit) in circuits.iter().enumerate() {
+            let vk = keygen_vk(&params, circuit).unwrap();
+            let cached_vk = keygen_vk_with_cache(&params, circuit, &mut cache).unwrap();
+            assert_eq!(
+                format!("{:?}", vk.pinned()),
+                format!("{:?}", cached_vk.pinned())
+            );
+            assert_eq!(vk.fixed_commitments, cached_vk.fixed_commitments);
+
+            let pk = keygen_pk(&params, vk, circuit).unwrap();
+            let cached_pk = keygen_pk_with_cache(&params, cached_vk, circuit, &mut cache).unwrap();
+            for (a, b) in pk.fixed_polys.iter().zip(cached_pk.fixed_polys.iter()) {
+                assert_eq!(a[..], b[..]);
+            }
+            for (a, b) in pk.fixed_cosets.iter().zip(cached_pk.fixed_cosets.iter()) {
+                assert_eq!(a[..], b[..]);
+            }
+
+            // The second circuit only adds its `other` column to the cache.
+            assert_eq!(cache.commitments.len(), 2 + i);
+            assert_eq!(cache.polys.len(), 2 + i);
+        }
+    }
 }
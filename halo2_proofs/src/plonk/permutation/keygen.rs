@@ -98,115 +98,117 @@ impl Assembly {
 
         Ok(())
     }
+}
 
-    pub(crate) fn build_vk<C: CurveAffine>(
-        self,
-        params: &Params<C>,
-        domain: &EvaluationDomain<C::Scalar>,
-        p: &Argument,
-    ) -> VerifyingKey<C> {
-        // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
-        let mut omega_powers = Vec::with_capacity(params.n as usize);
-        {
-            let mut cur = C::Scalar::ONE;
-            for _ in 0..params.n {
-                omega_powers.push(cur);
-                cur *= &domain.get_omega();
-            }
+/// Builds the permutation verifying key from the mapping of an [`Assembly`].
+pub(crate) fn build_vk<C: CurveAffine>(
+    params: &Params<C>,
+    domain: &EvaluationDomain<C::Scalar>,
+    p: &Argument,
+    mapping: &[Vec<(usize, usize)>],
+) -> VerifyingKey<C> {
+    // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
+    let mut omega_powers = Vec::with_capacity(params.n as usize);
+    {
+        let mut cur = C::Scalar::ONE;
+        for _ in 0..params.n {
+            omega_powers.push(cur);
+            cur *= &domain.get_omega();
         }
+    }
 
-        // Compute [omega_powers * \delta^0, omega_powers * \delta^1, ..., omega_powers * \delta^m]
-        let mut deltaomega = Vec::with_capacity(p.columns.len());
-        {
-            let mut cur = C::Scalar::ONE;
-            for _ in 0..p.columns.len() {
-                let mut omega_powers = omega_powers.clone();
-                for o in &mut omega_powers {
-                    *o *= &cur;
-                }
-
-                deltaomega.push(omega_powers);
-
-                cur *= &C::Scalar::DELTA;
+    // Compute [omega_powers * \delta^0, omega_powers * \delta^1, ..., omega_powers * \delta^m]
+    let mut deltaomega = Vec::with_capacity(p.columns.len());
+    {
+        let mut cur = C::Scalar::ONE;
+        for _ in 0..p.columns.len() {
+            let mut omega_powers = omega_powers.clone();
+            for o in &mut omega_powers {
+                *o *= &cur;
             }
-        }
 
-        // Pre-compute commitments for the URS.
-        let mut commitments = Vec::with_capacity(p.columns.len());
-        for i in 0..p.columns.len() {
-            // Computes the permutation polynomial based on the permutation
-            // description in the assembly.
-            let mut permutation_poly = domain.empty_lagrange();
-            for (j, p) in permutation_poly.iter_mut().enumerate() {
-                let (permuted_i, permuted_j) = self.mapping[i][j];
-                *p = deltaomega[permuted_i][permuted_j];
-            }
+            deltaomega.push(omega_powers);
 
-            // Compute commitment to permutation polynomial
-            commitments.push(
-                params
-                    .commit_lagrange(&permutation_poly, Blind::default())
-                    .to_affine(),
-            );
+            cur *= &C::Scalar::DELTA;
         }
-        VerifyingKey { commitments }
     }
 
-    pub(crate) fn build_pk<C: CurveAffine>(
-        self,
-        params: &Params<C>,
-        domain: &EvaluationDomain<C::Scalar>,
-        p: &Argument,
-    ) -> ProvingKey<C> {
-        // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
-        let mut omega_powers = Vec::with_capacity(params.n as usize);
-        {
-            let mut cur = C::Scalar::ONE;
-            for _ in 0..params.n {
-                omega_powers.push(cur);
-                cur *= &domain.get_omega();
-            }
+    // Pre-compute commitments for the URS.
+    let mut commitments = Vec::with_capacity(p.columns.len());
+    for column in mapping.iter().take(p.columns.len()) {
+        // Computes the permutation polynomial based on the permutation
+        // description in the assembly.
+        let mut permutation_poly = domain.empty_lagrange();
+        for (j, p) in permutation_poly.iter_mut().enumerate() {
+            let (permuted_i, permuted_j) = column[j];
+            *p = deltaomega[permuted_i][permuted_j];
         }
 
-        // Compute [omega_powers * \delta^0, omega_powers * \delta^1, ..., omega_powers * \delta^m]
-        let mut deltaomega = Vec::with_capacity(p.columns.len());
-        {
-            let mut cur = C::Scalar::ONE;
-            for _ in 0..p.columns.len() {
-                let mut omega_powers = omega_powers.clone();
-                for o in &mut omega_powers {
-                    *o *= &cur;
-                }
-
-                deltaomega.push(omega_powers);
+        // Compute commitment to permutation polynomial
+        commitments.push(
+            params
+                .commit_lagrange(&permutation_poly, Blind::default())
+                .to_affine(),
+        );
+    }
+    VerifyingKey { commitments }
+}
 
-                cur *= &C::Scalar::DELTA;
-            }
+/// Builds the permutation proving key from the mapping of an [`Assembly`].
+pub(crate) fn build_pk<C: CurveAffine>(
+    params: &Params<C>,
+    domain: &EvaluationDomain<C::Scalar>,
+    p: &Argument,
+    mapping: &[Vec<(usize, usize)>],
+) -> ProvingKey<C> {
+    // Compute [omega^0, omega^1, ..., omega^{params.n - 1}]
+    let mut omega_powers = Vec::with_capacity(params.n as usize);
+    {
+        let mut cur = C::Scalar::ONE;
+        for _ in 0..params.n {
+            omega_powers.push(cur);
+            cur *= &domain.get_omega();
         }
+    }
 
-        // Compute permutation polynomials, convert to coset form.
-        let mut permutations = vec![];
-        let mut polys = vec![];
-        let mut cosets = vec![];
-        for i in 0..p.columns.len() {
-            // Computes the permutation polynomial based on the permutation
-            // description in the assembly.
-            let mut permutation_poly = domain.empty_lagrange();
-            for (j, p) in permutation_poly.iter_mut().enumerate() {
-                let (permuted_i, permuted_j) = self.mapping[i][j];
-                *p = deltaomega[permuted_i][permuted_j];
+    // Compute [omega_powers * \delta^0, omega_powers * \delta^1, ..., omega_powers * \delta^m]
+    let mut deltaomega = Vec::with_capacity(p.columns.len());
+    {
+        let mut cur = C::Scalar::ONE;
+        for _ in 0..p.columns.len() {
+            let mut omega_powers = omega_powers.clone();
+            for o in &mut omega_powers {
+                *o *= &cur;
             }
 
-            // Store permutation polynomial and precompute its coset evaluation
-            permutations.push(permutation_poly.clone());
-            let poly = domain.lagrange_to_coeff(permutation_poly);
-            polys.push(poly.clone());
-            cosets.push(domain.coeff_to_extended(poly));
+            deltaomega.push(omega_powers);
+
+            cur *= &C::Scalar::DELTA;
         }
-        ProvingKey {
-            permutations,
-            polys,
-            cosets,
+    }
+
+    // Compute permutation polynomials, convert to coset form.
+    let mut permutations = vec![];
+    let mut polys = vec![];
+    let mut cosets = vec![];
+    for column in mapping.iter().take(p.columns.len()) {
+        // Computes the permutation polynomial based on the permutation
+        // description in the assembly.
+        let mut permutation_poly = domain.empty_lagrange();
+        for (j, p) in permutation_poly.iter_mut().enumerate() {
+            let (permuted_i, permuted_j) = column[j];
+            *p = deltaomega[permuted_i][permuted_j];
         }
+
+        // Store permutation polynomial and precompute its coset evaluation
+        permutations.push(permutation_poly.clone());
+        let poly = domain.lagrange_to_coeff(permutation_poly);
+        polys.push(poly.clone());
+        cosets.push(domain.coeff_to_extended(poly));
+    }
+    ProvingKey {
+        permutations,
+        polys,
+        cosets,
     }
 }
@@ -0,0 +1,939 @@
+//! Parsing and printing of Halo 2 proofs.
+//!
+//! A proof is the sequence of curve points and scalars that [`create_proof`] writes to a
+//! [`TranscriptWrite`]. Its shape is entirely determined by the [`VerifyingKey`] of the
+//! circuit and the number of circuit instances that were proven together, so given those
+//! a proof can be parsed into a [`Proof`] without performing any verification. This is
+//! useful when debugging interoperability with other verifiers, or when inspecting why
+//! a proof fails to verify.
+//!
+//! [`create_proof`]: crate::plonk::create_proof
+//! [`TranscriptWrite`]: crate::transcript::TranscriptWrite
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::io;
+
+use group::ff::PrimeField;
+
+use super::{Any, Column, VerifyingKey};
+use crate::{arithmetic::CurveAffine, poly::Rotation};
+
+/// A Halo 2 proof, parsed into its component commitments and evaluations.
+///
+/// The fields are listed in roughly the order that they appear in the proof, but note
+/// that the parts of the proof specific to each circuit instance are interleaved: for
+/// example, the advice commitments of every circuit instance come before the lookup
+/// commitments of any circuit instance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proof<C: CurveAffine> {
+    /// The parts of the proof specific to each circuit instance, in order.
+    pub circuits: Vec<CircuitProof<C>>,
+    /// The commitment to the vanishing argument's random polynomial.
+    pub vanishing_random_poly_commitment: C,
+    /// The commitments to the pieces of the quotient polynomial `h(X)`.
+    pub vanishing_h_commitments: Vec<C>,
+    /// The evaluations of the fixed column queries, shared between circuit instances.
+    pub fixed_evals: Vec<C::Scalar>,
+    /// The evaluation of the vanishing argument's random polynomial at `x`.
+    pub vanishing_random_eval: C::Scalar,
+    /// The evaluations of the permutation argument's fixed polynomials at `x`.
+    pub permutation_common_evals: Vec<C::Scalar>,
+    /// The multi-point opening argument.
+    pub multiopen: MultiopenProof<C>,
+}
+
+/// The parts of a [`Proof`] that are specific to a single circuit instance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitProof<C: CurveAffine> {
+    /// The commitments to each advice column.
+    pub advice_commitments: Vec<C>,
+    /// The commitments and evaluations for each lookup argument.
+    pub lookups: Vec<LookupProof<C>>,
+    /// The commitments to the permutation argument's product polynomials, one per
+    /// chunk of columns.
+    pub permutation_product_commitments: Vec<C>,
+    /// The evaluations of the instance column queries, other than those of hashed
+    /// instance columns.
+    pub instance_evals: Vec<C::Scalar>,
+    /// The evaluations of the advice column queries.
+    pub advice_evals: Vec<C::Scalar>,
+    /// The evaluations of the permutation argument's product polynomials, one per chunk
+    /// of columns.
+    pub permutation_product_evals: Vec<PermutationProductEvals<C>>,
+}
+
+/// The commitments and evaluations of a single lookup argument.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupProof<C: CurveAffine> {
+    /// The commitment to the permuted input expression.
+    pub permuted_input_commitment: C,
+    /// The commitment to the permuted table expression.
+    pub permuted_table_commitment: C,
+    /// The commitment to the product polynomial.
+    pub product_commitment: C,
+    /// The evaluation of the product polynomial at `x`.
+    pub product_eval: C::Scalar,
+    /// The evaluation of the product polynomial at `\omega x`.
+    pub product_next_eval: C::Scalar,
+    /// The evaluation of the permuted input expression at `x`.
+    pub permuted_input_eval: C::Scalar,
+    /// The evaluation of the permuted input expression at `\omega^{-1} x`.
+    pub permuted_input_inv_eval: C::Scalar,
+    /// The evaluation of the permuted table expression at `x`.
+    pub permuted_table_eval: C::Scalar,
+}
+
+/// The evaluations of one of the permutation argument's product polynomials.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermutationProductEvals<C: CurveAffine> {
+    /// The evaluation at `x`.
+    pub eval: C::Scalar,
+    /// The evaluation at `\omega x`.
+    pub next_eval: C::Scalar,
+    /// The evaluation at `\omega^{last} x`. This is present for every product
+    /// polynomial except the last.
+    pub last_eval: Option<C::Scalar>,
+}
+
+/// The multi-point opening argument of a [`Proof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiopenProof<C: CurveAffine> {
+    /// The commitment to the multi-point quotient polynomial `f(X)`.
+    pub f_commitment: C,
+    /// The evaluations of each point set's compressed polynomial `q(X)` at `x_3`.
+    pub q_evals: Vec<C::Scalar>,
+    /// The inner product argument opening the final commitment at `x_3`.
+    pub opening: OpeningProof<C>,
+}
+
+/// The inner product argument of a [`Proof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OpeningProof<C: CurveAffine> {
+    /// The commitment to the random polynomial `s(X)`.
+    pub s_poly_commitment: C,
+    /// The `(L_j, R_j)` commitments for each of the `k` rounds.
+    pub rounds: Vec<(C, C)>,
+    /// The final value `c` of the folded polynomial.
+    pub c: C::Scalar,
+    /// The final synthetic blinding factor `f`.
+    pub f: C::Scalar,
+}
+
+/// An error encountered while parsing a [`Proof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The proof ended before an expected element.
+    Truncated {
+        /// The byte offset at which the missing element should have started.
+        offset: usize,
+        /// The element that was expected.
+        expected: String,
+    },
+    /// An element of the proof that should be a curve point was not a valid encoding.
+    InvalidPoint {
+        /// The byte offset of the invalid encoding.
+        offset: usize,
+        /// The element that was being parsed.
+        element: String,
+    },
+    /// An element of the proof that should be a scalar was not a valid encoding.
+    InvalidScalar {
+        /// The byte offset of the invalid encoding.
+        offset: usize,
+        /// The element that was being parsed.
+        element: String,
+    },
+    /// The proof continued after its last expected element.
+    TrailingData {
+        /// The byte offset at which the proof should have ended.
+        offset: usize,
+        /// The number of unexpected bytes.
+        len: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Truncated { offset, expected } => write!(
+                f,
+                "Proof is truncated: expected {} at byte offset {}",
+                expected, offset
+            ),
+            ParseError::InvalidPoint { offset, element } => write!(
+                f,
+                "Invalid point encoding for {} at byte offset {}",
+                element, offset
+            ),
+            ParseError::InvalidScalar { offset, element } => write!(
+                f,
+                "Invalid scalar encoding for {} at byte offset {}",
+                element, offset
+            ),
+            ParseError::TrailingData { offset, len } => write!(
+                f,
+                "Proof has {} bytes of trailing data after byte offset {}",
+                len, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A cursor over the bytes of a proof.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize, element: &dyn Fn() -> String) -> Result<&'a [u8], ParseError> {
+        if self.bytes.len() - self.offset < len {
+            return Err(ParseError::Truncated {
+                offset: self.offset,
+                expected: element(),
+            });
+        }
+        let ret = &self.bytes[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(ret)
+    }
+
+    fn point<C: CurveAffine>(&mut self, element: &dyn Fn() -> String) -> Result<C, ParseError> {
+        let offset = self.offset;
+        let mut repr = C::Repr::default();
+        let len = repr.as_ref().len();
+        repr.as_mut().copy_from_slice(self.take(len, element)?);
+        Option::from(C::from_bytes(&repr)).ok_or_else(|| ParseError::InvalidPoint {
+            offset,
+            element: element(),
+        })
+    }
+
+    fn points<C: CurveAffine>(
+        &mut self,
+        n: usize,
+        element: &dyn Fn(usize) -> String,
+    ) -> Result<Vec<C>, ParseError> {
+        (0..n).map(|i| self.point(&|| element(i))).collect()
+    }
+
+    fn scalar<F: PrimeField>(&mut self, element: &dyn Fn() -> String) -> Result<F, ParseError> {
+        let offset = self.offset;
+        let mut repr = F::Repr::default();
+        let len = repr.as_ref().len();
+        repr.as_mut().copy_from_slice(self.take(len, element)?);
+        Option::from(F::from_repr(repr)).ok_or_else(|| ParseError::InvalidScalar {
+            offset,
+            element: element(),
+        })
+    }
+
+    fn scalars<F: PrimeField>(
+        &mut self,
+        n: usize,
+        element: &dyn Fn(usize) -> String,
+    ) -> Result<Vec<F>, ParseError> {
+        (0..n).map(|i| self.scalar(&|| element(i))).collect()
+    }
+}
+
+/// A single element of a proof, as visited by [`Proof::visit`].
+enum Element<'a, C: CurveAffine> {
+    Point(&'a C),
+    Scalar(&'a C::Scalar),
+}
+
+impl<C: CurveAffine> Proof<C> {
+    /// Parses `proof` as a proof for `num_circuits` instances of the circuit with the
+    /// given verifying key.
+    ///
+    /// This checks that every element of the proof is canonically encoded, and that the
+    /// proof has exactly the expected length. It does not verify the proof.
+    pub fn read(
+        vk: &VerifyingKey<C>,
+        num_circuits: usize,
+        proof: &[u8],
+    ) -> Result<Self, ParseError> {
+        let cs = &vk.cs;
+        let mut reader = Reader {
+            bytes: proof,
+            offset: 0,
+        };
+        let r = &mut reader;
+
+        let permutation_chunks = {
+            let chunk_len = vk.cs_degree - 2;
+            (cs.permutation.get_columns().len() + chunk_len - 1) / chunk_len
+        };
+
+        let advice_commitments = (0..num_circuits)
+            .map(|i| {
+                r.points(cs.num_advice_columns, &|j| {
+                    format!("circuits[{}].advice_commitments[{}]", i, j)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lookups_permuted = (0..num_circuits)
+            .map(|i| {
+                (0..cs.lookups.len())
+                    .map(|j| {
+                        let input = r.point(&|| {
+                            format!("circuits[{}].lookups[{}].permuted_input_commitment", i, j)
+                        })?;
+                        let table = r.point(&|| {
+                            format!("circuits[{}].lookups[{}].permuted_table_commitment", i, j)
+                        })?;
+                        Ok((input, table))
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let permutation_product_commitments = (0..num_circuits)
+            .map(|i| {
+                r.points(permutation_chunks, &|j| {
+                    format!("circuits[{}].permutation_product_commitments[{}]", i, j)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lookup_product_commitments = (0..num_circuits)
+            .map(|i| {
+                r.points(cs.lookups.len(), &|j| {
+                    format!("circuits[{}].lookups[{}].product_commitment", i, j)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let vanishing_random_poly_commitment =
+            r.point(&|| "vanishing_random_poly_commitment".into())?;
+        let vanishing_h_commitments = r.points(vk.domain.get_quotient_poly_degree(), &|j| {
+            format!("vanishing_h_commitments[{}]", j)
+        })?;
+
+        // Hashed instance columns are evaluated by the verifier.
+        let num_instance_evals = cs
+            .instance_queries
+            .iter()
+            .filter(|(column, _)| !cs.is_hashed_instance_column(*column))
+            .count();
+        let instance_evals = (0..num_circuits)
+            .map(|i| {
+                r.scalars(num_instance_evals, &|j| {
+                    format!("circuits[{}].instance_evals[{}]", i, j)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let advice_evals = (0..num_circuits)
+            .map(|i| {
+                r.scalars(cs.advice_queries.len(), &|j| {
+                    format!("circuits[{}].advice_evals[{}]", i, j)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let fixed_evals = r.scalars(cs.fixed_queries.len(), &|j| format!("fixed_evals[{}]", j))?;
+        let vanishing_random_eval = r.scalar(&|| "vanishing_random_eval".into())?;
+        let permutation_common_evals = r.scalars(cs.permutation.get_columns().len(), &|j| {
+            format!("permutation_common_evals[{}]", j)
+        })?;
+
+        let permutation_product_evals = (0..num_circuits)
+            .map(|i| {
+                (0..permutation_chunks)
+                    .map(|j| {
+                        let element = |name: &'static str| {
+                            move || {
+                                format!("circuits[{}].permutation_product_evals[{}].{}", i, j, name)
+                            }
+                        };
+                        let eval = r.scalar(&element("eval"))?;
+                        let next_eval = r.scalar(&element("next_eval"))?;
+                        let last_eval = if j + 1 < permutation_chunks {
+                            Some(r.scalar(&element("last_eval"))?)
+                        } else {
+                            None
+                        };
+                        Ok(PermutationProductEvals {
+                            eval,
+                            next_eval,
+                            last_eval,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let lookup_evals = (0..num_circuits)
+            .map(|i| {
+                (0..cs.lookups.len())
+                    .map(|j| {
+                        r.scalars(5, &|k| {
+                            let name = [
+                                "product_eval",
+                                "product_next_eval",
+                                "permuted_input_eval",
+                                "permuted_input_inv_eval",
+                                "permuted_table_eval",
+                            ][k];
+                            format!("circuits[{}].lookups[{}].{}", i, j, name)
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let f_commitment = r.point(&|| "multiopen.f_commitment".into())?;
+        let q_evals = r.scalars(num_point_sets(vk, num_circuits), &|j| {
+            format!("multiopen.q_evals[{}]", j)
+        })?;
+        let s_poly_commitment = r.point(&|| "multiopen.opening.s_poly_commitment".into())?;
+        let rounds = (0..vk.domain.k() as usize)
+            .map(|j| {
+                let left = r.point(&|| format!("multiopen.opening.rounds[{}].L", j))?;
+                let right = r.point(&|| format!("multiopen.opening.rounds[{}].R", j))?;
+                Ok((left, right))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let c = r.scalar(&|| "multiopen.opening.c".into())?;
+        let f = r.scalar(&|| "multiopen.opening.f".into())?;
+
+        if reader.offset != proof.len() {
+            return Err(ParseError::TrailingData {
+                offset: reader.offset,
+                len: proof.len() - reader.offset,
+            });
+        }
+
+        let circuits = advice_commitments
+            .into_iter()
+            .zip(lookups_permuted)
+            .zip(permutation_product_commitments)
+            .zip(lookup_product_commitments)
+            .zip(instance_evals)
+            .zip(advice_evals)
+            .zip(permutation_product_evals)
+            .zip(lookup_evals)
+            .map(
+                |(
+                    (
+                        (
+                            (
+                                (
+                                    (
+                                        (advice_commitments, lookups_permuted),
+                                        permutation_commitments,
+                                    ),
+                                    lookup_products,
+                                ),
+                                instance_evals,
+                            ),
+                            advice_evals,
+                        ),
+                        permutation_product_evals,
+                    ),
+                    lookup_evals,
+                )| CircuitProof {
+                    advice_commitments,
+                    lookups: lookups_permuted
+                        .into_iter()
+                        .zip(lookup_products)
+                        .zip(lookup_evals)
+                        .map(|(((input, table), product), evals)| LookupProof {
+                            permuted_input_commitment: input,
+                            permuted_table_commitment: table,
+                            product_commitment: product,
+                            product_eval: evals[0],
+                            product_next_eval: evals[1],
+                            permuted_input_eval: evals[2],
+                            permuted_input_inv_eval: evals[3],
+                            permuted_table_eval: evals[4],
+                        })
+                        .collect(),
+                    permutation_product_commitments: permutation_commitments,
+                    instance_evals,
+                    advice_evals,
+                    permutation_product_evals,
+                },
+            )
+            .collect();
+
+        Ok(Proof {
+            circuits,
+            vanishing_random_poly_commitment,
+            vanishing_h_commitments,
+            fixed_evals,
+            vanishing_random_eval,
+            permutation_common_evals,
+            multiopen: MultiopenProof {
+                f_commitment,
+                q_evals,
+                opening: OpeningProof {
+                    s_poly_commitment,
+                    rounds,
+                    c,
+                    f,
+                },
+            },
+        })
+    }
+
+    /// Writes this proof in the format produced by [`create_proof`].
+    ///
+    /// For any proof parsed with [`Proof::read`], this writes exactly the bytes that
+    /// were parsed.
+    ///
+    /// [`create_proof`]: crate::plonk::create_proof
+    pub fn write<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut result = Ok(());
+        self.visit(|_, element| {
+            if result.is_ok() {
+                result = match element {
+                    Element::Point(p) => writer.write_all(p.to_bytes().as_ref()),
+                    Element::Scalar(s) => writer.write_all(s.to_repr().as_ref()),
+                };
+            }
+        });
+        result
+    }
+
+    /// Returns the encoding of this proof in the format produced by [`create_proof`].
+    ///
+    /// [`create_proof`]: crate::plonk::create_proof
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![];
+        self.write(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        bytes
+    }
+
+    /// Calls `f` on every element of the proof, in the order they are encoded.
+    fn visit(&self, mut f: impl FnMut(&dyn Fn() -> String, Element<'_, C>)) {
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, c) in circuit.advice_commitments.iter().enumerate() {
+                f(
+                    &|| format!("circuits[{}].advice_commitments[{}]", i, j),
+                    Element::Point(c),
+                );
+            }
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, lookup) in circuit.lookups.iter().enumerate() {
+                f(
+                    &|| format!("circuits[{}].lookups[{}].permuted_input_commitment", i, j),
+                    Element::Point(&lookup.permuted_input_commitment),
+                );
+                f(
+                    &|| format!("circuits[{}].lookups[{}].permuted_table_commitment", i, j),
+                    Element::Point(&lookup.permuted_table_commitment),
+                );
+            }
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, c) in circuit.permutation_product_commitments.iter().enumerate() {
+                f(
+                    &|| format!("circuits[{}].permutation_product_commitments[{}]", i, j),
+                    Element::Point(c),
+                );
+            }
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, lookup) in circuit.lookups.iter().enumerate() {
+                f(
+                    &|| format!("circuits[{}].lookups[{}].product_commitment", i, j),
+                    Element::Point(&lookup.product_commitment),
+                );
+            }
+        }
+        f(
+            &|| "vanishing_random_poly_commitment".into(),
+            Element::Point(&self.vanishing_random_poly_commitment),
+        );
+        for (j, c) in self.vanishing_h_commitments.iter().enumerate() {
+            f(
+                &|| format!("vanishing_h_commitments[{}]", j),
+                Element::Point(c),
+            );
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, eval) in circuit.instance_evals.iter().enumerate() {
+                f(
+                    &|| format!("circuits[{}].instance_evals[{}]", i, j),
+                    Element::Scalar(eval),
+                );
+            }
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, eval) in circuit.advice_evals.iter().enumerate() {
+                f(
+                    &|| format!("circuits[{}].advice_evals[{}]", i, j),
+                    Element::Scalar(eval),
+                );
+            }
+        }
+        for (j, eval) in self.fixed_evals.iter().enumerate() {
+            f(&|| format!("fixed_evals[{}]", j), Element::Scalar(eval));
+        }
+        f(
+            &|| "vanishing_random_eval".into(),
+            Element::Scalar(&self.vanishing_random_eval),
+        );
+        for (j, eval) in self.permutation_common_evals.iter().enumerate() {
+            f(
+                &|| format!("permutation_common_evals[{}]", j),
+                Element::Scalar(eval),
+            );
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, set) in circuit.permutation_product_evals.iter().enumerate() {
+                let element = |name: &'static str| {
+                    move || format!("circuits[{}].permutation_product_evals[{}].{}", i, j, name)
+                };
+                f(&element("eval"), Element::Scalar(&set.eval));
+                f(&element("next_eval"), Element::Scalar(&set.next_eval));
+                if let Some(last_eval) = &set.last_eval {
+                    f(&element("last_eval"), Element::Scalar(last_eval));
+                }
+            }
+        }
+        for (i, circuit) in self.circuits.iter().enumerate() {
+            for (j, lookup) in circuit.lookups.iter().enumerate() {
+                let element =
+                    |name: &'static str| move || format!("circuits[{}].lookups[{}].{}", i, j, name);
+                f(
+                    &element("product_eval"),
+                    Element::Scalar(&lookup.product_eval),
+                );
+                f(
+                    &element("product_next_eval"),
+                    Element::Scalar(&lookup.product_next_eval),
+                );
+                f(
+                    &element("permuted_input_eval"),
+                    Element::Scalar(&lookup.permuted_input_eval),
+                );
+                f(
+                    &element("permuted_input_inv_eval"),
+                    Element::Scalar(&lookup.permuted_input_inv_eval),
+                );
+                f(
+                    &element("permuted_table_eval"),
+                    Element::Scalar(&lookup.permuted_table_eval),
+                );
+            }
+        }
+
+        let multiopen = &self.multiopen;
+        f(
+            &|| "multiopen.f_commitment".into(),
+            Element::Point(&multiopen.f_commitment),
+        );
+        for (j, eval) in multiopen.q_evals.iter().enumerate() {
+            f(
+                &|| format!("multiopen.q_evals[{}]", j),
+                Element::Scalar(eval),
+            );
+        }
+        let opening = &multiopen.opening;
+        f(
+            &|| "multiopen.opening.s_poly_commitment".into(),
+            Element::Point(&opening.s_poly_commitment),
+        );
+        for (j, (l, r)) in opening.rounds.iter().enumerate() {
+            f(
+                &|| format!("multiopen.opening.rounds[{}].L", j),
+                Element::Point(l),
+            );
+            f(
+                &|| format!("multiopen.opening.rounds[{}].R", j),
+                Element::Point(r),
+            );
+        }
+        f(
+            &|| "multiopen.opening.c".into(),
+            Element::Scalar(&opening.c),
+        );
+        f(
+            &|| "multiopen.opening.f".into(),
+            Element::Scalar(&opening.f),
+        );
+    }
+}
+
+/// Pretty-prints the proof, with one element per line in the order they are encoded.
+///
+/// Each line contains the byte offset of the element within the proof, the element's
+/// name, and the hex encoding of the element as it appears in the proof.
+impl<C: CurveAffine> fmt::Display for Proof<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut result = Ok(());
+        let mut offset = 0;
+        self.visit(|element, value| {
+            if result.is_err() {
+                return;
+            }
+            let bytes = match value {
+                Element::Point(p) => p.to_bytes().as_ref().to_vec(),
+                Element::Scalar(s) => s.to_repr().as_ref().to_vec(),
+            };
+            let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            result = writeln!(f, "{:#06x}: {} = {}", offset, element(), hex);
+            offset += bytes.len();
+        });
+        result
+    }
+}
+
+/// Returns the number of distinct sets of points at which the multi-point opening
+/// argument opens commitments, for a proof of `num_circuits` circuit instances.
+///
+/// This must match the point sets constructed by the verifier's queries, as the
+/// multi-point opening argument contains one evaluation per point set.
+fn num_point_sets<C: CurveAffine>(vk: &VerifyingKey<C>, num_circuits: usize) -> usize {
+    let cs = &vk.cs;
+    let mut point_sets: BTreeSet<BTreeSet<i32>> = BTreeSet::new();
+
+    // Each polynomial is identified by its column; columns of different types are
+    // committed to separately.
+    let mut column_rotations: HashMap<Column<Any>, BTreeSet<i32>> = HashMap::new();
+    if num_circuits > 0 {
+        for (column, at) in cs
+            .instance_queries
+            .iter()
+            .filter(|(column, _)| !cs.is_hashed_instance_column(*column))
+        {
+            column_rotations
+                .entry((*column).into())
+                .or_default()
+                .insert(at.0);
+        }
+        for (column, at) in cs.advice_queries.iter() {
+            column_rotations
+                .entry((*column).into())
+                .or_default()
+                .insert(at.0);
+        }
+    }
+    for (column, at) in cs.fixed_queries.iter() {
+        column_rotations
+            .entry((*column).into())
+            .or_default()
+            .insert(at.0);
+    }
+    point_sets.extend(column_rotations.into_values());
+
+    let cur = Rotation::cur().0;
+    let next = Rotation::next().0;
+    let prev = Rotation::prev().0;
+    let last = -((cs.blinding_factors() + 1) as i32);
+
+    if num_circuits > 0 {
+        // Permutation product polynomials; all but the last are also opened at
+        // \omega^{last} x.
+        let chunk_len = vk.cs_degree - 2;
+        let chunks = (cs.permutation.get_columns().len() + chunk_len - 1) / chunk_len;
+        if chunks > 0 {
+            point_sets.insert([cur, next].iter().cloned().collect());
+        }
+        if chunks > 1 {
+            point_sets.insert([cur, next, last].iter().cloned().collect());
+        }
+
+        // Lookup product, permuted input and permuted table polynomials.
+        if !cs.lookups.is_empty() {
+            point_sets.insert([cur, next].iter().cloned().collect());
+            point_sets.insert([prev, cur].iter().cloned().collect());
+            point_sets.insert([cur].iter().cloned().collect());
+        }
+    }
+
+    // The permutation argument's fixed polynomials, and the vanishing argument's
+    // polynomials, are all opened at x.
+    point_sets.insert([cur].iter().cloned().collect());
+
+    point_sets.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use ff::Field;
+    use pasta_curves::{EqAffine, Fp};
+    use rand_core::OsRng;
+
+    use super::{ParseError, Proof};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Error,
+            Fixed, TableColumn,
+        },
+        poly::{commitment::Params, Rotation},
+        transcript::{Blake2bWrite, Challenge255},
+    };
+
+    #[derive(Clone)]
+    struct MyConfig {
+        a: Column<Advice>,
+        b: Column<Advice>,
+        c: Column<Advice>,
+        f: Column<Fixed>,
+        table: TableColumn,
+    }
+
+    #[derive(Clone, Default)]
+    struct MyCircuit {
+        a: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> MyConfig {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let c = meta.advice_column();
+            let f = meta.fixed_column();
+            let table = meta.lookup_table_column();
+
+            // Enough columns to split the permutation argument into several chunks.
+            meta.enable_equality(a);
+            meta.enable_equality(b);
+            meta.enable_equality(c);
+            meta.enable_equality(f);
+
+            meta.create_gate("a' = a * b + f", |meta| {
+                let a_cur = meta.query_advice(a, Rotation::cur());
+                let b = meta.query_advice(b, Rotation::cur());
+                let a_next = meta.query_advice(a, Rotation::next());
+                let f = meta.query_fixed(f);
+                vec![f.clone() * (a_next - (a_cur * b + f))]
+            });
+            meta.lookup(|meta| {
+                let c = meta.query_advice(c, Rotation::cur());
+                vec![(c, table)]
+            });
+
+            MyConfig { a, b, c, f, table }
+        }
+
+        fn synthesize(
+            &self,
+            config: MyConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_table(
+                || "table",
+                |mut table| {
+                    for i in 0..4 {
+                        table.assign_cell(
+                            || "table",
+                            config.table,
+                            i,
+                            || Value::known(Fp::from(i as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+            layouter.assign_region(
+                || "region",
+                |mut region| {
+                    let a = region.assign_advice(|| "a", config.a, 0, || self.a)?;
+                    region.assign_advice(|| "b", config.b, 0, || Value::known(Fp::ONE))?;
+                    region.assign_advice(|| "c", config.c, 0, || Value::known(Fp::from(3)))?;
+                    region.assign_fixed(|| "f", config.f, 0, || Value::known(Fp::ONE))?;
+                    region.assign_advice(
+                        || "a'",
+                        config.a,
+                        1,
+                        || self.a + Value::known(Fp::ONE),
+                    )?;
+                    a.copy_advice(|| "c", &mut region, config.c, 1)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn proof_round_trip() {
+        const K: u32 = 5;
+        let params: Params<EqAffine> = Params::new(K);
+        let vk = keygen_vk(&params, &MyCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &MyCircuit::default()).unwrap();
+
+        let circuit = MyCircuit {
+            a: Value::known(Fp::from(2)),
+        };
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            &[circuit.clone(), circuit],
+            &[&[], &[]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let bytes = transcript.finalize();
+
+        let proof = Proof::read(pk.get_vk(), 2, &bytes).unwrap();
+        assert_eq!(proof.circuits.len(), 2);
+        assert_eq!(proof.circuits[0].advice_commitments.len(), 3);
+        assert_eq!(proof.circuits[0].lookups.len(), 1);
+        assert_eq!(proof.multiopen.opening.rounds.len(), K as usize);
+        assert_eq!(proof.to_bytes(), bytes);
+
+        // Every element is printed on its own line.
+        let printed = proof.to_string();
+        assert!(printed.starts_with("0x0000: circuits[0].advice_commitments[0] = "));
+        assert!(printed.ends_with(&format!(
+            "{:#06x}: multiopen.opening.f = {}\n",
+            bytes.len() - 32,
+            bytes[bytes.len() - 32..]
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+        )));
+
+        assert_matches!(
+            Proof::read(pk.get_vk(), 2, &bytes[..bytes.len() - 1]),
+            Err(ParseError::Truncated { offset, ref expected })
+                if offset == bytes.len() - 32 && expected == "multiopen.opening.f"
+        );
+
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert_eq!(
+            Proof::read(pk.get_vk(), 2, &extended),
+            Err(ParseError::TrailingData {
+                offset: bytes.len(),
+                len: 1
+            }),
+        );
+
+        // Parsing as the wrong number of circuit instances fails.
+        assert!(Proof::read(pk.get_vk(), 1, &bytes).is_err());
+
+        // Corrupting a scalar to a non-canonical encoding is detected.
+        let mut corrupted = bytes;
+        let c_offset = corrupted.len() - 64;
+        corrupted[c_offset..c_offset + 32].copy_from_slice(&[0xff; 32]);
+        assert_matches!(
+            Proof::read(pk.get_vk(), 2, &corrupted),
+            Err(ParseError::InvalidScalar { offset, ref element })
+                if offset == c_offset && element == "multiopen.opening.c"
+        );
+    }
+}
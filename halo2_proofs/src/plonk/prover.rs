@@ -9,8 +9,8 @@ use super::{
         Advice, Any, Assignment, Circuit, Column, ConstraintSystem, Fixed, FloorPlanner, Instance,
         Selector,
     },
-    lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
-    ChallengeY, Error, ProvingKey,
+    hash_instance_values, lookup, permutation, vanishing, ChallengeBeta, ChallengeGamma,
+    ChallengeTheta, ChallengeX, ChallengeY, Error, ProvingKey,
 };
 use crate::{
     arithmetic::{eval_polynomial, CurveAffine},
@@ -92,7 +92,9 @@ pub fn create_proof<
                 .collect::<Result<Vec<_>, _>>()?;
             let instance_commitments_projective: Vec<_> = instance_values
                 .iter()
-                .map(|poly| params.commit_lagrange(poly, Blind::default()))
+                .enumerate()
+                .filter(|(column_index, _)| !meta.is_hashed_instance_index(*column_index))
+                .map(|(_, poly)| params.commit_lagrange(poly, Blind::default()))
                 .collect();
             let mut instance_commitments =
                 vec![C::identity(); instance_commitments_projective.len()];
@@ -100,8 +102,15 @@ pub fn create_proof<
             let instance_commitments = instance_commitments;
             drop(instance_commitments_projective);
 
-            for commitment in &instance_commitments {
-                transcript.common_point(*commitment)?;
+            // Hash the values of hashed instance columns, and the commitments to the
+            // others, in column order.
+            let mut instance_commitments = instance_commitments.into_iter();
+            for (column_index, values) in instance.iter().enumerate() {
+                if meta.is_hashed_instance_index(column_index) {
+                    hash_instance_values(transcript, values)?;
+                } else {
+                    transcript.common_point(instance_commitments.next().unwrap())?;
+                }
             }
 
             let instance_polys: Vec<_> = instance_values
@@ -178,7 +187,7 @@ pub fn create_proof<
                     row: usize,
                 ) -> Result<Value<F>, Error> {
                     if !self.usable_rows.contains(&row) {
-                        return Err(Error::not_enough_rows_available(self.k));
+                        return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
                     }
 
                     self.instances
@@ -202,7 +211,7 @@ pub fn create_proof<
                     AR: Into<String>,
                 {
                     if !self.usable_rows.contains(&row) {
-                        return Err(Error::not_enough_rows_available(self.k));
+                        return Err(Error::row_not_usable(self.k, self.usable_rows.end, row));
                     }
 
                     *self
@@ -604,6 +613,8 @@ pub fn create_proof<
         let instance_evals: Vec<_> = meta
             .instance_queries
             .iter()
+            // The verifier evaluates hashed instance columns itself.
+            .filter(|&&(column, _)| !meta.is_hashed_instance_column(column))
             .map(|&(column, at)| {
                 eval_polynomial(
                     &instance.instance_polys[column.index()],
@@ -686,6 +697,7 @@ pub fn create_proof<
                         .cs
                         .instance_queries
                         .iter()
+                        .filter(|&&(column, _)| !pk.vk.cs.is_hashed_instance_column(column))
                         .map(move |&(column, at)| ProverQuery {
                             point: domain.rotate_omega(*x, at),
                             poly: &instance.instance_polys[column.index()],
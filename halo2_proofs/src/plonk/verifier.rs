@@ -1,17 +1,20 @@
-use ff::Field;
-use group::Curve;
+use ff::{Field, PrimeField, WithSmallOrderMulGroup};
+use std::io;
 use std::iter;
 
 use super::{
-    vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX, ChallengeY, Error,
-    VerifyingKey,
+    hash_instance_values, vanishing, ChallengeBeta, ChallengeGamma, ChallengeTheta, ChallengeX,
+    ChallengeY, Error, VerifierError, VerifyingKey,
 };
 use crate::arithmetic::CurveAffine;
 use crate::poly::{
-    commitment::{Blind, Guard, Params, MSM},
+    commitment::{Guard, Params, MSM},
     multiopen::{self, VerifierQuery},
+    EvaluationDomain, Rotation,
+};
+use crate::transcript::{
+    read_n_points, read_n_scalars, EncodedChallenge, Transcript, TranscriptRead,
 };
-use crate::transcript::{read_n_points, read_n_scalars, EncodedChallenge, TranscriptRead};
 
 #[cfg(feature = "batch")]
 mod batch;
@@ -63,7 +66,108 @@ impl<'params, C: CurveAffine> VerificationStrategy<'params, C> for SingleVerifie
     }
 }
 
+/// A [`TranscriptRead`] that tracks the offset of each read from the proof, so that
+/// read failures can be reported as a [`VerifierError`].
+struct OffsetTracker<'a, T> {
+    transcript: &'a mut T,
+    offset: usize,
+    failure: Option<VerifierError>,
+}
+
+impl<'a, T> OffsetTracker<'a, T> {
+    fn new(transcript: &'a mut T) -> Self {
+        OffsetTracker {
+            transcript,
+            offset: 0,
+            failure: None,
+        }
+    }
+
+    /// Records the outcome of a read of `len` bytes from the proof.
+    ///
+    /// Failures other than running out of proof are caused by an invalid encoding, which
+    /// is described by `invalid_encoding`.
+    fn track<R>(
+        &mut self,
+        result: io::Result<R>,
+        len: usize,
+        invalid_encoding: impl FnOnce(usize) -> VerifierError,
+    ) -> io::Result<R> {
+        let offset = self.offset;
+        match result {
+            Ok(value) => {
+                self.offset += len;
+                Ok(value)
+            }
+            Err(error) => {
+                let kind = error.kind();
+                self.failure = Some(match kind {
+                    io::ErrorKind::UnexpectedEof => {
+                        VerifierError::MalformedTranscript { offset, error }
+                    }
+                    _ => invalid_encoding(offset),
+                });
+                Err(kind.into())
+            }
+        }
+    }
+}
+
+impl<'a, C: CurveAffine, E: EncodedChallenge<C>, T: Transcript<C, E>> Transcript<C, E>
+    for OffsetTracker<'a, T>
+{
+    fn squeeze_challenge(&mut self) -> E {
+        self.transcript.squeeze_challenge()
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        self.transcript.common_point(point)
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.transcript.common_scalar(scalar)
+    }
+}
+
+impl<'a, C: CurveAffine, E: EncodedChallenge<C>, T: TranscriptRead<C, E>> TranscriptRead<C, E>
+    for OffsetTracker<'a, T>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let result = self.transcript.read_point();
+        let len = C::Repr::default().as_ref().len();
+        self.track(result, len, |offset| VerifierError::InvalidPointEncoding {
+            offset,
+        })
+    }
+
+    fn read_scalar(&mut self) -> io::Result<C::Scalar> {
+        let result = self.transcript.read_scalar();
+        let len = <C::Scalar as PrimeField>::Repr::default().as_ref().len();
+        self.track(result, len, |offset| VerifierError::InvalidScalarEncoding {
+            offset,
+        })
+    }
+}
+
+/// The value of an instance column, as provided to the verifier.
+#[derive(Clone, Copy, Debug)]
+pub enum InstanceInput<'a, C: CurveAffine> {
+    /// The values of the instance column.
+    Values(&'a [C::Scalar]),
+    /// A commitment to the values of the instance column, computed with
+    /// [`VerifyingKey::commit_instance`].
+    ///
+    /// This cannot be used for hashed instance columns (see
+    /// [`ConstraintSystem::hash_instance_column`]).
+    ///
+    /// [`ConstraintSystem::hash_instance_column`]: super::ConstraintSystem::hash_instance_column
+    Commitment(C),
+}
+
 /// Returns a boolean indicating whether or not the proof is valid
+///
+/// If the proof cannot be read from `transcript`, or is rejected by
+/// [`SingleVerifier`], the returned error is an [`Error::Verifier`] describing why.
 pub fn verify_proof<
     'params,
     C: CurveAffine,
@@ -76,6 +180,63 @@ pub fn verify_proof<
     strategy: V,
     instances: &[&[&[C::Scalar]]],
     transcript: &mut T,
+) -> Result<V::Output, Error> {
+    let instances: Vec<Vec<_>> = instances
+        .iter()
+        .map(|instances| {
+            instances
+                .iter()
+                .map(|values| InstanceInput::Values(values))
+                .collect()
+        })
+        .collect();
+    let instances: Vec<_> = instances.iter().map(|instances| &instances[..]).collect();
+
+    verify_proof_with_instance_inputs(params, vk, strategy, &instances, transcript)
+}
+
+/// Verifies a proof against instance columns that are each given either by their
+/// values, or by a commitment computed with [`VerifyingKey::commit_instance`].
+///
+/// Passing commitments lets a verifier that checks many proofs against the same
+/// instance columns commit to them once. The proofs accepted, and the errors
+/// returned, are the same as for [`verify_proof`].
+pub fn verify_proof_with_instance_inputs<
+    'params,
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptRead<C, E>,
+    V: VerificationStrategy<'params, C>,
+>(
+    params: &'params Params<C>,
+    vk: &VerifyingKey<C>,
+    strategy: V,
+    instances: &[&[InstanceInput<'_, C>]],
+    transcript: &mut T,
+) -> Result<V::Output, Error> {
+    let mut transcript = OffsetTracker::new(transcript);
+    verify_proof_inner(params, vk, strategy, instances, &mut transcript).map_err(|e| {
+        // Errors from reading the proof are propagated as transcript or opening errors;
+        // replace them with the more precise failure.
+        match transcript.failure.take() {
+            Some(failure) => Error::Verifier(failure),
+            None => e,
+        }
+    })
+}
+
+fn verify_proof_inner<
+    'params,
+    C: CurveAffine,
+    E: EncodedChallenge<C>,
+    T: TranscriptRead<C, E>,
+    V: VerificationStrategy<'params, C>,
+>(
+    params: &'params Params<C>,
+    vk: &VerifyingKey<C>,
+    strategy: V,
+    instances: &[&[InstanceInput<'_, C>]],
+    transcript: &mut T,
 ) -> Result<V::Output, Error> {
     // Check that instances matches the expected number of instance columns
     for instances in instances.iter() {
@@ -84,20 +245,27 @@ pub fn verify_proof<
         }
     }
 
+    // The commitments to the instance columns, or `None` for hashed instance columns.
     let instance_commitments = instances
         .iter()
         .map(|instance| {
             instance
                 .iter()
-                .map(|instance| {
-                    if instance.len() > params.n as usize - (vk.cs.blinding_factors() + 1) {
-                        return Err(Error::InstanceTooLarge);
+                .enumerate()
+                .map(|(column_index, instance)| {
+                    match (vk.cs.is_hashed_instance_index(column_index), instance) {
+                        (false, InstanceInput::Values(values)) => {
+                            vk.commit_instance(params, values).map(Some)
+                        }
+                        (false, InstanceInput::Commitment(commitment)) => Ok(Some(*commitment)),
+                        (true, InstanceInput::Values(values)) => {
+                            if values.len() > params.n as usize - (vk.cs.blinding_factors() + 1) {
+                                return Err(Error::InstanceTooLarge);
+                            }
+                            Ok(None)
+                        }
+                        (true, InstanceInput::Commitment(_)) => Err(Error::InvalidInstances),
                     }
-                    let mut poly = instance.to_vec();
-                    poly.resize(params.n as usize, C::Scalar::ZERO);
-                    let poly = vk.domain.lagrange_from_vec(poly);
-
-                    Ok(params.commit_lagrange(&poly, Blind::default()).to_affine())
                 })
                 .collect::<Result<Vec<_>, _>>()
         })
@@ -108,10 +276,15 @@ pub fn verify_proof<
     // Hash verification key into transcript
     vk.hash_into(transcript)?;
 
-    for instance_commitments in instance_commitments.iter() {
-        // Hash the instance (external) commitments into the transcript
-        for commitment in instance_commitments {
-            transcript.common_point(*commitment)?
+    for (instance, instance_commitments) in instances.iter().zip(instance_commitments.iter()) {
+        // Hash the instance (external) commitments into the transcript, or the values
+        // of hashed instance columns
+        for (instance, commitment) in instance.iter().zip(instance_commitments.iter()) {
+            match (instance, commitment) {
+                (_, Some(commitment)) => transcript.common_point(*commitment)?,
+                (InstanceInput::Values(values), None) => hash_instance_values(transcript, values)?,
+                (InstanceInput::Commitment(_), None) => unreachable!(),
+            }
         }
     }
 
@@ -170,8 +343,25 @@ pub fn verify_proof<
     // Sample x challenge, which is used to ensure the circuit is
     // satisfied with high probability.
     let x: ChallengeX<_> = transcript.squeeze_challenge_scalar();
-    let instance_evals = (0..num_proofs)
-        .map(|_| -> Result<Vec<_>, _> { read_n_scalars(transcript, vk.cs.instance_queries.len()) })
+    // x^n
+    let xn = x.pow(&[params.n, 0, 0, 0]);
+
+    let instance_evals = instances
+        .iter()
+        .map(|instance| -> Result<Vec<_>, _> {
+            // Read the evaluations of committed instance columns from the proof, and
+            // evaluate hashed instance columns ourselves.
+            vk.cs
+                .instance_queries
+                .iter()
+                .map(|&(column, at)| match instance[column.index()] {
+                    InstanceInput::Values(values) if vk.cs.is_hashed_instance_column(column) => {
+                        Ok(evaluate_instance_values(&vk.domain, values, *x, xn, at))
+                    }
+                    _ => transcript.read_scalar(),
+                })
+                .collect::<Result<Vec<_>, _>>()
+        })
         .collect::<Result<Vec<_>, _>>()?;
 
     let advice_evals = (0..num_proofs)
@@ -202,9 +392,6 @@ pub fn verify_proof<
     // This check ensures the circuit is satisfied so long as the polynomial
     // commitments open to the correct values.
     let vanishing = {
-        // x^n
-        let xn = x.pow(&[params.n, 0, 0, 0]);
-
         let blinding_factors = vk.cs.blinding_factors();
         let l_evals = vk
             .domain
@@ -296,13 +483,18 @@ pub fn verify_proof<
                 lookups,
             )| {
                 iter::empty()
-                    .chain(vk.cs.instance_queries.iter().enumerate().map(
+                    .chain(vk.cs.instance_queries.iter().enumerate().filter_map(
                         move |(query_index, &(column, at))| {
-                            VerifierQuery::new_commitment(
-                                &instance_commitments[column.index()],
-                                vk.domain.rotate_omega(*x, at),
-                                instance_evals[query_index],
-                            )
+                            // Hashed instance columns are not committed to.
+                            instance_commitments[column.index()]
+                                .as_ref()
+                                .map(|commitment| {
+                                    VerifierQuery::new_commitment(
+                                        commitment,
+                                        vk.domain.rotate_omega(*x, at),
+                                        instance_evals[query_index],
+                                    )
+                                })
                         },
                     ))
                     .chain(vk.cs.advice_queries.iter().enumerate().map(
@@ -345,3 +537,175 @@ pub fn verify_proof<
         multiopen::verify_proof(params, transcript, queries, msm).map_err(|_| Error::Opening)
     })
 }
+
+/// Evaluates the instance column with the given `values` at $\omega^{at} x$.
+fn evaluate_instance_values<F: WithSmallOrderMulGroup<3>>(
+    domain: &EvaluationDomain<F>,
+    values: &[F],
+    x: F,
+    xn: F,
+    at: Rotation,
+) -> F {
+    // The column is \sum_i values[i] L_i(X), and L_i(\omega^{at} x) = L_{i - at}(x).
+    let l_evals = domain.l_i_range(x, xn, (-at.0)..(values.len() as i32 - at.0));
+    values
+        .iter()
+        .zip(l_evals.iter())
+        .fold(F::ZERO, |acc, (value, l)| acc + *value * l)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_matches::assert_matches;
+    use ff::Field;
+    use pasta_curves::{EqAffine, Fp};
+    use rand_core::OsRng;
+
+    use super::{verify_proof_with_instance_inputs, InstanceInput, SingleVerifier};
+    use crate::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        plonk::{
+            create_proof, keygen_pk, keygen_vk, proof::Proof, Advice, Circuit, Column,
+            ConstraintSystem, Error, Instance, Selector,
+        },
+        poly::{commitment::Params, Rotation},
+        transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+    };
+
+    #[derive(Clone)]
+    struct MyConfig {
+        a: Column<Advice>,
+        hashed: Column<Instance>,
+        s: Selector,
+    }
+
+    #[derive(Clone, Default)]
+    struct MyCircuit {
+        a: [Value<Fp>; 2],
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> MyConfig {
+            let a = meta.advice_column();
+            let s = meta.selector();
+            let committed = meta.instance_column();
+            let hashed = meta.instance_column();
+            meta.hash_instance_column(hashed);
+            meta.enable_equality(a);
+            meta.enable_equality(hashed);
+
+            // Query both instance columns at several rotations.
+            meta.create_gate("a = committed + hashed'", |meta| {
+                let s = meta.query_selector(s);
+                let a = meta.query_advice(a, Rotation::cur());
+                let committed = meta.query_instance(committed, Rotation::cur());
+                let hashed_next = meta.query_instance(hashed, Rotation::next());
+                vec![s * (a - (committed + hashed_next))]
+            });
+
+            MyConfig { a, hashed, s }
+        }
+
+        fn synthesize(
+            &self,
+            config: MyConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let cells = layouter.assign_region(
+                || "region",
+                |mut region| {
+                    self.a
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, a)| {
+                            config.s.enable(&mut region, offset)?;
+                            region.assign_advice(|| "a", config.a, offset, || *a)
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+            layouter.constrain_instance(cells[0].cell(), config.hashed, 3)
+        }
+    }
+
+    #[test]
+    fn hashed_instance_columns() {
+        const K: u32 = 4;
+        let params: Params<EqAffine> = Params::new(K);
+        let vk = keygen_vk(&params, &MyCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk, &MyCircuit::default()).unwrap();
+
+        // a[0] = committed[0] + hashed[1], a[1] = committed[1] + hashed[2], hashed[3] = a[0]
+        let committed = [Fp::from(1), Fp::from(2)];
+        let hashed = [Fp::ZERO, Fp::from(4), Fp::from(5), Fp::from(5)];
+        let circuit = MyCircuit {
+            a: [Value::known(Fp::from(5)), Value::known(Fp::from(7))],
+        };
+
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            std::slice::from_ref(&circuit),
+            &[&[&committed, &hashed]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        // The hashed instance column's evaluations are not part of the proof.
+        let parsed = Proof::read(pk.get_vk(), 1, &proof).unwrap();
+        assert_eq!(parsed.circuits[0].instance_evals.len(), 1);
+        assert_eq!(parsed.to_bytes(), proof);
+
+        let verify = |instances: &[InstanceInput<'_, EqAffine>]| {
+            let strategy = SingleVerifier::new(&params);
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+            verify_proof_with_instance_inputs(
+                &params,
+                pk.get_vk(),
+                strategy,
+                &[instances],
+                &mut transcript,
+            )
+        };
+
+        let committed_commitment = pk.get_vk().commit_instance(&params, &committed).unwrap();
+        assert!(verify(&[
+            InstanceInput::Values(&committed),
+            InstanceInput::Values(&hashed)
+        ])
+        .is_ok());
+        assert!(verify(&[
+            InstanceInput::Commitment(committed_commitment),
+            InstanceInput::Values(&hashed)
+        ])
+        .is_ok());
+
+        // The proof is bound to the values of the hashed instance column.
+        let wrong = [Fp::ZERO, Fp::from(4), Fp::from(5), Fp::from(6)];
+        assert!(verify(&[
+            InstanceInput::Values(&committed),
+            InstanceInput::Values(&wrong)
+        ])
+        .is_err());
+
+        // Hashed instance columns cannot be provided as commitments.
+        let hashed_commitment = pk.get_vk().commit_instance(&params, &hashed).unwrap();
+        assert_matches!(
+            verify(&[
+                InstanceInput::Values(&committed),
+                InstanceInput::Commitment(hashed_commitment)
+            ]),
+            Err(Error::InvalidInstances)
+        );
+    }
+}
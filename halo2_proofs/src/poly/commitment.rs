@@ -7,8 +7,10 @@ use super::{Coeff, LagrangeCoeff, Polynomial};
 use crate::arithmetic::{best_fft, best_multiexp, parallelize, CurveAffine, CurveExt};
 use crate::helpers::CurveRead;
 
+use blake2b_simd::Params as Blake2bParams;
 use ff::{Field, PrimeField};
 use group::{prime::PrimeCurveAffine, Curve, Group};
+use once_cell::sync::OnceCell;
 use std::ops::{Add, AddAssign, Mul, MulAssign};
 
 mod msm;
@@ -30,6 +32,9 @@ pub struct Params<C: CurveAffine> {
     pub(crate) g_lagrange: Vec<C>,
     pub(crate) w: C,
     pub(crate) u: C,
+    /// A hash identifying these parameters, computed when it is first used as a cache
+    /// key.
+    key: OnceCell<[u8; 64]>,
 }
 
 impl<C: CurveAffine> Params<C> {
@@ -110,9 +115,27 @@ impl<C: CurveAffine> Params<C> {
             g_lagrange,
             w,
             u,
+            key: OnceCell::new(),
         }
     }
 
+    /// Returns the hash identifying these parameters, which covers `k`, the Lagrange
+    /// basis and the blinding generator.
+    pub(crate) fn key(&self) -> [u8; 64] {
+        *self.key.get_or_init(|| {
+            let mut hasher = Blake2bParams::new()
+                .hash_length(64)
+                .personal(b"Halo2-Params-Key")
+                .to_state();
+            hasher.update(&self.k.to_le_bytes());
+            for g in self.g_lagrange.iter() {
+                hasher.update(g.to_bytes().as_ref());
+            }
+            hasher.update(self.w.to_bytes().as_ref());
+            *hasher.finalize().as_array()
+        })
+    }
+
     /// This computes a commitment to a polynomial described by the provided
     /// slice of coefficients. The commitment will be blinded by the blinding
     /// factor `r`.
@@ -201,6 +224,7 @@ impl<C: CurveAffine> Params<C> {
             g_lagrange,
             w,
             u,
+            key: OnceCell::new(),
         })
     }
 }
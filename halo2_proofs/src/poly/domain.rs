@@ -471,6 +471,11 @@ impl<F: WithSmallOrderMulGroup<3>> EvaluationDomain<F> {
         results
     }
 
+    /// Gets the base-2 logarithm of the size of this domain.
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
     /// Gets the quotient polynomial's degree (as a multiple of n)
     pub fn get_quotient_poly_degree(&self) -> usize {
         self.quotient_poly_degree as usize
@@ -8,8 +8,9 @@ use halo2_proofs::circuit::{Cell, Layouter, SimpleFloorPlanner, Value};
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::pasta::{Eq, EqAffine, Fp};
 use halo2_proofs::plonk::{
-    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Assigned, BatchVerifier, Circuit,
-    Column, ConstraintSystem, Error, Fixed, SingleVerifier, TableColumn, VerificationStrategy,
+    create_proof, keygen_pk, keygen_vk, proof, verify_proof, verify_proof_with_instance_inputs,
+    Advice, Assigned, BatchVerifier, Circuit, Column, ConstraintSystem, Error, Fixed,
+    InstanceInput, SingleVerifier, TableColumn, VerificationStrategy, VerifierError,
 };
 use halo2_proofs::poly::commitment::{Guard, MSM};
 use halo2_proofs::poly::{commitment::Params, Rotation};
@@ -415,6 +416,7 @@ fn plonk_api() {
         keygen_vk(&much_too_small_params, &empty_circuit),
         Err(Error::NotEnoughRowsAvailable {
             current_k,
+            ..
         }) if current_k == 1
     );
 
@@ -425,6 +427,7 @@ fn plonk_api() {
         keygen_vk(&slightly_too_small_params, &empty_circuit),
         Err(Error::NotEnoughRowsAvailable {
             current_k,
+            ..
         }) if current_k == K - 1
     );
 
@@ -473,6 +476,72 @@ fn plonk_api() {
             &mut transcript,
         )
         .is_ok());
+
+        // Check that the hardcoded proof verifies against precommitted instance columns
+        let instance_commitment = pk
+            .get_vk()
+            .commit_instance(&params, &pubinputs)
+            .expect("instance should fit");
+        let strategy = SingleVerifier::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+        assert!(verify_proof_with_instance_inputs(
+            &params,
+            pk.get_vk(),
+            strategy,
+            &[
+                &[InstanceInput::Commitment(instance_commitment)],
+                &[InstanceInput::Values(&pubinputs)],
+            ],
+            &mut transcript,
+        )
+        .is_ok());
+
+        // Check that the hardcoded proof can be parsed and re-encoded
+        let parsed = proof::Proof::read(pk.get_vk(), 2, &proof).expect("proof should parse");
+        assert_eq!(parsed.to_bytes(), proof);
+
+        // Check that the verifier reports why modified proofs are rejected
+        let verify = |proof: &[u8]| {
+            let strategy = SingleVerifier::new(&params);
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+            verify_proof(
+                &params,
+                pk.get_vk(),
+                strategy,
+                &[&[&pubinputs[..]], &[&pubinputs[..]]],
+                &mut transcript,
+            )
+        };
+
+        // The final 32 bytes of the proof are the last scalar of the opening proof.
+        assert_matches!(
+            verify(&proof[..proof.len() - 1]),
+            Err(Error::Verifier(VerifierError::MalformedTranscript { offset, .. }))
+                if offset == proof.len() - 32
+        );
+
+        // The proof starts with the first advice commitment.
+        let mut invalid_point = proof.clone();
+        invalid_point[..32].copy_from_slice(&[0xff; 32]);
+        assert_matches!(
+            verify(&invalid_point),
+            Err(Error::Verifier(VerifierError::InvalidPointEncoding {
+                offset: 0
+            }))
+        );
+
+        let mut invalid_scalar = proof.clone();
+        let last = invalid_scalar.len() - 32;
+        invalid_scalar[last..].copy_from_slice(&[0xff; 32]);
+        assert_matches!(
+            verify(&invalid_scalar),
+            Err(Error::Verifier(VerifierError::InvalidScalarEncoding { offset }))
+                if offset == last
+        );
+
+        let mut wrong_scalar = proof.clone();
+        wrong_scalar[last] ^= 1;
+        assert_matches!(verify(&wrong_scalar), Err(Error::ConstraintSystemFailure));
     }
 
     for _ in 0..10 {